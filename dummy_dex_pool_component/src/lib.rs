@@ -0,0 +1,74 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod dummy_dex_pool_component {
+    pub struct DexPool {
+        vault_a: Vault,
+        vault_b: Vault,
+        pool_unit_manager: ResourceManager,
+    }
+
+    impl DexPool {
+        /// Instantiates a constant product pool from two initial deposits and returns the initial pool units.
+        pub fn instantiate_dex_pool(
+            token_a: Bucket,
+            token_b: Bucket,
+        ) -> (Global<DexPool>, Bucket) {
+            let (address_reservation, component_address) =
+                Runtime::allocate_component_address(DexPool::blueprint_id());
+
+            let initial_units = (token_a.amount() * token_b.amount()).checked_sqrt().unwrap();
+
+            let pool_units: Bucket = ResourceBuilder::new_fungible(OwnerRole::None)
+                .divisibility(DIVISIBILITY_MAXIMUM)
+                .mint_roles(mint_roles!(
+                    minter => rule!(require(global_caller(component_address)));
+                    minter_updater => rule!(deny_all);
+                ))
+                .mint_initial_supply(initial_units)
+                .into();
+
+            let pool = Self {
+                vault_a: Vault::with_bucket(token_a),
+                vault_b: Vault::with_bucket(token_b),
+                pool_unit_manager: pool_units.resource_manager(),
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .with_address(address_reservation)
+            .globalize();
+
+            (pool, pool_units)
+        }
+
+        pub fn get_vault_amounts(&self) -> IndexMap<ResourceAddress, Decimal> {
+            let mut amounts = IndexMap::new();
+            amounts.insert(self.vault_a.resource_address(), self.vault_a.amount());
+            amounts.insert(self.vault_b.resource_address(), self.vault_b.amount());
+            amounts
+        }
+
+        /// Returns the spot price of token A in token B.
+        pub fn get_spot_price(&self) -> Decimal {
+            self.vault_b.amount() / self.vault_a.amount()
+        }
+
+        /// Swaps against the constant product invariant without fees, used to move the spot price in tests.
+        pub fn swap(&mut self, input: Bucket) -> Bucket {
+            let (input_vault, output_vault) = if input.resource_address() == self.vault_a.resource_address() {
+                (&mut self.vault_a, &mut self.vault_b)
+            } else {
+                (&mut self.vault_b, &mut self.vault_a)
+            };
+
+            let output_amount =
+                output_vault.amount() * input.amount() / (input_vault.amount() + input.amount());
+            input_vault.put(input);
+            output_vault.take_advanced(output_amount, WithdrawStrategy::Rounded(RoundingMode::ToZero))
+        }
+
+        pub fn get_pool_unit_address(&self) -> ResourceAddress {
+            self.pool_unit_manager.address()
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use scrypto::prelude::*;
+
+#[blueprint]
+mod dummy_flash_borrower_component {
+    pub struct FlashBorrower {
+        vault: Vault,
+    }
+
+    impl FlashBorrower {
+        /// Instantiates the borrower with a bucket of fUSD used to pay flash loan fees.
+        pub fn instantiate_flash_borrower(fee_funds: Bucket) -> Global<FlashBorrower> {
+            Self {
+                vault: Vault::with_bucket(fee_funds),
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .globalize()
+        }
+
+        /// Flash loan callback. `args` is an SBOR encoded `Decimal` amount to return on top of the loan.
+        pub fn on_flash_loan(&mut self, loan: Bucket, args: Vec<u8>) -> Bucket {
+            let extra: Decimal = scrypto_decode(&args).unwrap();
+            let repay_amount = loan.amount() + extra;
+
+            self.vault.put(loan);
+            self.vault.take(repay_amount)
+        }
+    }
+}
@@ -2,22 +2,31 @@ use scrypto::prelude::*;
 
 #[blueprint]
 mod dummy_lsulp_component {
-    pub struct LsuPool {}
+    pub struct LsuPool {
+        dex_valuation_xrd: Decimal,
+    }
 
     impl LsuPool {
         pub fn instantiate_token_pool() -> Global<LsuPool> {
-            Self {}
-                .instantiate()
-                .prepare_to_globalize(OwnerRole::None)
-                .globalize()
+            Self {
+                dex_valuation_xrd: dec!(11000),
+            }
+            .instantiate()
+            .prepare_to_globalize(OwnerRole::None)
+            .globalize()
         }
 
         pub fn get_dex_valuation_xrd(&self) -> Decimal {
-            dec!(11000)
+            self.dex_valuation_xrd
         }
 
         pub fn get_liquidity_token_total_supply(&self) -> Decimal {
             dec!(10000)
         }
+
+        /// Changes the valuation, used to move the redemption rate in tests.
+        pub fn set_dex_valuation_xrd(&mut self, dex_valuation_xrd: Decimal) {
+            self.dex_valuation_xrd = dex_valuation_xrd;
+        }
     }
 }
@@ -0,0 +1,42 @@
+use scrypto::prelude::*;
+
+/// Mirrors the `PriceMessage` returned by the Morpher oracle.
+#[derive(ScryptoSbor, Clone)]
+pub struct PriceMessage {
+    pub market_id: String,
+    pub price: Decimal,
+    pub nonce: u64,
+    pub created_at: u64,
+}
+
+#[blueprint]
+mod dummy_morpher_component {
+    pub struct MorpherOracle {}
+
+    impl MorpherOracle {
+        pub fn instantiate_morpher_oracle() -> Global<MorpherOracle> {
+            Self {}
+                .instantiate()
+                .prepare_to_globalize(OwnerRole::None)
+                .globalize()
+        }
+
+        /// Decodes a `{market_id}-{price}-{nonce}-{created_at}` message. Any signature other than "invalid" is accepted.
+        pub fn check_price_input(&self, message: String, signature: String) -> PriceMessage {
+            assert!(signature != "invalid", "Invalid signature.");
+
+            let mut parts = message.rsplitn(4, '-');
+            let created_at = parts.next().unwrap().parse::<u64>().unwrap();
+            let nonce = parts.next().unwrap().parse::<u64>().unwrap();
+            let price = Decimal::try_from(parts.next().unwrap()).unwrap();
+            let market_id = parts.next().unwrap().to_string();
+
+            PriceMessage {
+                market_id,
+                price,
+                nonce,
+                created_at,
+            }
+        }
+    }
+}
@@ -17,7 +17,13 @@ mod oracle {
         methods {
             check_price_input => PUBLIC;
             check_price_inputs => PUBLIC;
+            get_twap_price => PUBLIC;
+            get_price => PUBLIC;
             set_price => restrict_to: [OWNER];
+            set_price_path => restrict_to: [OWNER];
+            set_random_walk_path => restrict_to: [OWNER];
+            set_crash_path => restrict_to: [OWNER];
+            clear_price_path => restrict_to: [OWNER];
             add_morpher_identifier => restrict_to: [OWNER];
             set_max_price_age => restrict_to: [OWNER];
         }
@@ -26,6 +32,8 @@ mod oracle {
     struct Oracle {
         morpher_identifiers: HashMap<ResourceAddress, String>,
         prices: HashMap<String, Decimal>,
+        /// Scheduled prices per market ID, taking precedence over `prices`.
+        price_paths: HashMap<String, PricePath>,
         max_price_age: i64,
     }
 
@@ -43,6 +51,7 @@ mod oracle {
             Self {
                 morpher_identifiers,
                 prices,
+                price_paths: HashMap::new(),
                 max_price_age: 120,
             }
             .instantiate()
@@ -67,8 +76,22 @@ mod oracle {
                 .get(&collateral)
                 .expect("Collateral not supported.");
             
+            self.get_price(market_id.clone())
+        }
+
+        /// Returns the current price of the collateral, standing in for the oracle's time-weighted average price.
+        pub fn get_twap_price(&self, collateral: ResourceAddress) -> Decimal {
+            self.check_price_input(collateral, String::new(), String::new())
+        }
+
+        /// Returns the current price of a market, following its price path if one is set.
+        pub fn get_price(&self, market_id: String) -> Decimal {
+            if let Some(path) = self.price_paths.get(&market_id) {
+                return path.price_at(Clock::current_time_rounded_to_seconds().seconds_since_unix_epoch);
+            }
+
             self.prices
-                .get(market_id)
+                .get(&market_id)
                 .cloned()
                 .expect("Price not set for this market")
         }
@@ -91,9 +114,100 @@ mod oracle {
             market_id: String,
             price: Decimal,
         ) {
+            self.price_paths.remove(&market_id);
             self.prices.insert(market_id, price);
         }
 
+        /// Schedules a price path for a market. Before the first point the first price is used,
+        /// after the last point the last price is used.
+        pub fn set_price_path(
+            &mut self,
+            market_id: String,
+            points: Vec<(i64, Decimal)>,
+            interpolation: Interpolation,
+        ) {
+            assert!(!points.is_empty(), "Price path needs at least one point.");
+            assert!(
+                points.windows(2).all(|pair| pair[0].0 < pair[1].0),
+                "Price path timestamps must be strictly increasing."
+            );
+            assert!(
+                points.iter().all(|(_, price)| *price > Decimal::ZERO),
+                "Prices must be positive."
+            );
+
+            self.price_paths.insert(
+                market_id,
+                PricePath {
+                    points,
+                    interpolation,
+                },
+            );
+        }
+
+        /// Schedules a deterministic random walk: every `step_seconds` the price moves by a pseudo-random
+        /// fraction between `-max_step_change` and `max_step_change`. The same `seed` always produces the same path.
+        pub fn set_random_walk_path(
+            &mut self,
+            market_id: String,
+            start_time: i64,
+            start_price: Decimal,
+            step_seconds: i64,
+            steps: u64,
+            max_step_change: Decimal,
+            seed: u64,
+        ) {
+            assert!(step_seconds > 0, "Step must be positive.");
+            assert!(
+                max_step_change >= Decimal::ZERO && max_step_change < Decimal::ONE,
+                "Step change must be between 0 and 1."
+            );
+
+            let mut state = seed.max(1);
+            let mut price = start_price;
+            let mut points = vec![(start_time, start_price)];
+
+            for step in 1..=steps {
+                // xorshift64
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+
+                let fraction = Decimal::from(state % 2001) / dec!(1000) - Decimal::ONE;
+                price = price * (Decimal::ONE + fraction * max_step_change);
+                points.push((start_time + step as i64 * step_seconds, price));
+            }
+
+            self.set_price_path(market_id, points, Interpolation::Linear);
+        }
+
+        /// Schedules a crash: the price stays at `start_price` until `crash_time`, then falls linearly
+        /// to `crash_price` over `duration_seconds` and stays there.
+        pub fn set_crash_path(
+            &mut self,
+            market_id: String,
+            start_price: Decimal,
+            crash_time: i64,
+            crash_price: Decimal,
+            duration_seconds: i64,
+        ) {
+            assert!(duration_seconds > 0, "Duration must be positive.");
+
+            self.set_price_path(
+                market_id,
+                vec![
+                    (crash_time, start_price),
+                    (crash_time + duration_seconds, crash_price),
+                ],
+                Interpolation::Linear,
+            );
+        }
+
+        /// Removes the price path of a market, falling back to the price set with `set_price`.
+        pub fn clear_price_path(&mut self, market_id: String) {
+            self.price_paths.remove(&market_id);
+        }
+
         pub fn add_morpher_identifier(
             &mut self,
             resource_address: ResourceAddress,
@@ -118,3 +232,43 @@ pub struct PriceEntry {
     pub changed_at: u64,
     pub identifier: String,
 }
+
+/// How the price between two points of a price path is determined.
+#[derive(ScryptoSbor, Clone, PartialEq, Debug)]
+pub enum Interpolation {
+    /// The price of the last passed point holds until the next point.
+    Stepped,
+    /// The price moves linearly between two points.
+    Linear,
+}
+
+/// A scheduled list of (unix timestamp in seconds, price) points.
+#[derive(ScryptoSbor, Clone)]
+pub struct PricePath {
+    pub points: Vec<(i64, Decimal)>,
+    pub interpolation: Interpolation,
+}
+
+impl PricePath {
+    pub fn price_at(&self, time: i64) -> Decimal {
+        let next_index = self.points.iter().position(|(timestamp, _)| *timestamp > time);
+
+        match next_index {
+            Some(0) => self.points[0].1,
+            None => self.points[self.points.len() - 1].1,
+            Some(index) => {
+                let (start_time, start_price) = self.points[index - 1];
+                let (end_time, end_price) = self.points[index];
+
+                match self.interpolation {
+                    Interpolation::Stepped => start_price,
+                    Interpolation::Linear => {
+                        start_price
+                            + (end_price - start_price) * Decimal::from(time - start_time)
+                                / Decimal::from(end_time - start_time)
+                    }
+                }
+            }
+        }
+    }
+}
@@ -3,6 +3,12 @@
 
 use scrypto::prelude::*;
 
+pub mod lp_pricing;
+pub mod verifier;
+
+use lp_pricing::*;
+use verifier::*;
+
 #[derive(ScryptoSbor, Clone)]
 pub struct PriceMessage {
     pub market_id: String,
@@ -14,13 +20,38 @@ pub struct PriceMessage {
 #[blueprint]
 mod oracle {
     enable_method_auth! {
+        roles {
+            price_pusher => updatable_by: [OWNER];
+        },
         methods {
             check_price_input => PUBLIC;
             check_price_inputs => PUBLIC;
+            update_redemption_rate => PUBLIC;
             update_lsu_multiplier => PUBLIC;
+            get_derivatives => PUBLIC;
+            get_lp_tokens => PUBLIC;
+            get_price_sources => PUBLIC;
+            get_twap_price => PUBLIC;
+            get_price_history => PUBLIC;
+            get_latest_price => PUBLIC;
+            get_price_verifier => PUBLIC;
+            push_price => restrict_to: [price_pusher];
             add_morpher_identifier => restrict_to: [OWNER];
+            set_price_verifier => restrict_to: [OWNER];
+            add_signer => restrict_to: [OWNER];
+            remove_signer => restrict_to: [OWNER];
+            set_price_sources => restrict_to: [OWNER];
+            set_max_price_spread => restrict_to: [OWNER];
+            set_max_pushed_price_age => restrict_to: [OWNER];
+            set_twap_parameters => restrict_to: [OWNER];
+            set_message_ordering => restrict_to: [OWNER];
             set_max_price_age => restrict_to: [OWNER];
+            register_derivative => restrict_to: [OWNER];
+            remove_derivative => restrict_to: [OWNER];
+            set_max_redemption_rate_age => restrict_to: [OWNER];
             set_max_lsu_multiplier_age => restrict_to: [OWNER];
+            register_lp_token => restrict_to: [OWNER];
+            remove_lp_token => restrict_to: [OWNER];
         }
     }
 
@@ -30,18 +61,6 @@ mod oracle {
         //"component_tdx_2_1cpdf8dsfslstthlvaa75kp652epw3pjn967dmf9kqhhzlger60mdn5" //stokenet dummy lsupool
     );
 
-    extern_blueprint! {
-        //"package_sim1pkgxxxxxxxxxpackgexxxxxxxxx000726633226xxxxxxxxxlk8hc9", //simulator package, uncomment to run tests
-        //"package_tdx_2_1phrthm8neequrhdg8jxvvwd8xazccuaa8u3ufyemysade0ckv88an2", //stokenet morpher package
-        "package_rdx1pka62r6e9754snp524ng3kfrkxma6qdxhzw86j7ka5nnl9m75nagmp", //mainnet morpher package
-        MorpherOracle {
-            fn check_price_input(&self, message: String, signature: String) -> PriceMessage;
-        }
-
-        // oracle address for stokenet: component_tdx_2_1cpt6kp3mqkds5uy858mqedwfglhsw25lhey59ev45ayce4yfsghf90
-        // oracle address for mainnet: component_rdx1cpuqchky58ualnunh485cqne7p6dkepuwq0us2t5n89mz32k6pfppz
-    }
-
     extern_blueprint! {
         "package_rdx1pkfrtmv980h85c9nvhxa7c9y0z4vxzt25c3gdzywz5l52g5t0hdeey", //mainnet lsu pool
         //"package_tdx_2_1ph6p4hk03a6p8f9mqzsfs9595jug6gmv2gxteggjtep3wv52t8g2ds",
@@ -55,38 +74,114 @@ mod oracle {
 
     struct Oracle {
         morpher_identifiers: HashMap<ResourceAddress, String>,
-        oracle_address: ComponentAddress,
+        /// Verifier used for signed price messages.
+        verifier: PriceVerifier,
         max_price_age: i64,
+        /// The LSULP, registered as a derivative at instantiation. Used by the LSU multiplier methods.
         lsu_lp_address: ResourceAddress,
-        lsu_multiplier: Decimal,
-        last_lsu_multiplier_update: Instant,
-        max_lsu_multiplier_age: i64,
+        /// Derivative collaterals (liquid staking tokens, pool shares) priced as their underlying market times a redemption rate.
+        derivatives: HashMap<ResourceAddress, DerivativeInfo>,
+        /// DEX LP tokens, priced from their pool's invariant and the oracle prices of both underlyings.
+        lp_tokens: HashMap<ResourceAddress, LpTokenInfo>,
+        /// Price sources per collateral. Collaterals without an entry only use the signed price message.
+        price_sources: HashMap<ResourceAddress, Vec<PriceSource>>,
+        /// Latest prices pushed by keepers, keyed by feed ID.
+        pushed_prices: HashMap<String, PushedPrice>,
+        /// Maximum age (seconds) of a pushed price before it is considered stale.
+        max_pushed_price_age: i64,
+        /// Maximum allowed spread between the highest and lowest source price, relative to the median.
+        max_price_spread: Decimal,
+        /// Ring buffers of verified signed prices, keyed by market ID.
+        price_histories: HashMap<String, PriceHistory>,
+        /// Maximum number of observations kept per market.
+        price_history_length: u64,
+        /// Time window (seconds) over which the time-weighted average price is calculated.
+        twap_window: i64,
+        /// Maximum age (seconds) of the newest observation for the TWAP to be usable.
+        max_twap_staleness: i64,
+        /// Newest accepted message per market ID.
+        latest_prices: HashMap<String, AcceptedPrice>,
+        /// Ordering rule per market ID. Markets without an entry use `default_message_ordering`.
+        message_orderings: HashMap<String, MessageOrdering>,
+        /// `Monotonic` by default, so an older message can't be replayed once a newer one was accepted.
+        default_message_ordering: MessageOrdering,
     }
 
     impl Oracle {
+        /// Instantiates the oracle with the mainnet LSU pool as the LSULP redemption rate source.
         pub fn instantiate_oracle(
             owner_role: OwnerRole,
             oracle_address: ComponentAddress,
             dapp_def_address: GlobalAddress,
             lsu_lp_address: ResourceAddress,
+        ) -> Global<Oracle> {
+            Self::instantiate_oracle_with_lsu_pool(
+                owner_role,
+                oracle_address,
+                dapp_def_address,
+                lsu_lp_address,
+                LSU_POOL.address(),
+            )
+        }
+
+        /// Instantiates the oracle with a custom LSU pool as the LSULP redemption rate source (e.g. a dummy pool on a test network).
+        pub fn instantiate_oracle_with_lsu_pool(
+            owner_role: OwnerRole,
+            oracle_address: ComponentAddress,
+            dapp_def_address: GlobalAddress,
+            lsu_lp_address: ResourceAddress,
+            lsu_pool_address: ComponentAddress,
         ) -> Global<Oracle> {
             let mut morpher_identifiers: HashMap<ResourceAddress, String> = HashMap::new();
 
             morpher_identifiers.insert(XRD, "GATEIO:XRD_USDT".to_string());
             morpher_identifiers.insert(lsu_lp_address, "GATEIO:XRD_USDT".to_string());
 
+            // lsu pool mainnet: component_rdx1cppy08xgra5tv5melsjtj79c0ngvrlmzl8hhs7vwtzknp9xxs63mfp
+            // lsu pool stokenet (dummy): component_tdx_2_1cpdf8dsfslstthlvaa75kp652epw3pjn967dmf9kqhhzlger60mdn5
+            let lsu_rate_source = RedemptionRateSource::ValuationPerSupply {
+                component: lsu_pool_address,
+                valuation_method: "get_dex_valuation_xrd".to_string(),
+            };
+
+            let mut derivatives: HashMap<ResourceAddress, DerivativeInfo> = HashMap::new();
+            derivatives.insert(
+                lsu_lp_address,
+                DerivativeInfo {
+                    underlying_market_id: "GATEIO:XRD_USDT".to_string(),
+                    redemption_rate: lsu_rate_source.fetch_rate(lsu_lp_address),
+                    rate_source: lsu_rate_source,
+                    last_rate_update: Clock::current_time_rounded_to_seconds(),
+                    max_rate_age: 86400,
+                },
+            );
+
             Self {
                 morpher_identifiers,
-                oracle_address,
+                // morpher oracle address for stokenet: component_tdx_2_1cpt6kp3mqkds5uy858mqedwfglhsw25lhey59ev45ayce4yfsghf90
+                // morpher oracle address for mainnet: component_rdx1cpuqchky58ualnunh485cqne7p6dkepuwq0us2t5n89mz32k6pfppz
+                verifier: PriceVerifier::Morpher { oracle_address },
                 max_price_age: 120,
                 lsu_lp_address,
-                lsu_multiplier: LSU_POOL.get_dex_valuation_xrd()
-                    / LSU_POOL.get_liquidity_token_total_supply(),
-                last_lsu_multiplier_update: Clock::current_time_rounded_to_seconds(),
-                max_lsu_multiplier_age: 1,
+                derivatives,
+                lp_tokens: HashMap::new(),
+                price_sources: HashMap::new(),
+                pushed_prices: HashMap::new(),
+                max_pushed_price_age: 120,
+                max_price_spread: dec!("0.05"),
+                price_histories: HashMap::new(),
+                price_history_length: 60,
+                twap_window: 3600,
+                max_twap_staleness: 3600,
+                latest_prices: HashMap::new(),
+                message_orderings: HashMap::new(),
+                default_message_ordering: MessageOrdering::Monotonic,
             }
             .instantiate()
             .prepare_to_globalize(owner_role)
+            .roles(roles! {
+                price_pusher => OWNER;
+            })
             .metadata(metadata! {
                 init {
                     "name" => "Flux Oracle".to_string(), updatable;
@@ -104,89 +199,568 @@ mod oracle {
             message: String,
             signature: String,
         ) -> Decimal {
-            let morpher_oracle = Global::<MorpherOracle>::from(self.oracle_address);
-            let price_message = morpher_oracle.check_price_input(message, signature);
-            self.check_message_validity(collateral, price_message.clone());
-
-            if collateral == self.lsu_lp_address {
-                self.check_for_lsu_multiplier_update();
-                return price_message.price * self.lsu_multiplier;
-            } else {
-                return price_message.price;
+            if self.lp_tokens.contains_key(&collateral) {
+                return self.get_lp_token_price(collateral, message, signature);
             }
+
+            let price = self.get_median_price(collateral, message, signature);
+            price * self.get_redemption_rate(collateral)
         }
 
         pub fn check_price_inputs(
             &mut self,
             collaterals: Vec<(ResourceAddress, String, String)>,
         ) -> Vec<(ResourceAddress, Decimal)> {
-            let morpher_oracle = Global::<MorpherOracle>::from(self.oracle_address);
             let mut price_return: Vec<(ResourceAddress, Decimal)> = vec![];
-            let mut xrd_price: Option<Decimal> = None;
+            // Collaterals sharing a market and price sources (e.g. XRD and its derivatives) only need one verified price.
+            let mut market_prices: HashMap<(String, Vec<PriceSource>), Decimal> = HashMap::new();
 
             for (collateral, message, signature) in collaterals {
-                if collateral == XRD {
-                    if xrd_price.is_none() {
-                        let price_message = morpher_oracle.check_price_input(message, signature);
-                        self.check_message_validity(collateral, price_message.clone());
-                        xrd_price = Some(price_message.price);
-                        price_return.push((collateral, price_message.price));
-                    } else {
-                        price_return.push((collateral, xrd_price.unwrap()));
-                    }
-                } else if collateral == self.lsu_lp_address {
-                    self.check_for_lsu_multiplier_update();
-                    if xrd_price.is_none() {
-                        let price_message = morpher_oracle.check_price_input(message, signature);
-                        self.check_message_validity(collateral, price_message.clone());
-                        xrd_price = Some(price_message.price);
-                        price_return.push((collateral, price_message.price * self.lsu_multiplier));
-                    } else {
-                        price_return.push((collateral, xrd_price.unwrap() * self.lsu_multiplier));
-                    }
-                } else {
-                    let price_message = morpher_oracle.check_price_input(message, signature);
-                    self.check_message_validity(collateral, price_message.clone());
-                    price_return.push((collateral, price_message.price));
+                if self.lp_tokens.contains_key(&collateral) {
+                    let price = self.get_lp_token_price(collateral, message, signature);
+                    price_return.push((collateral, price));
+                    continue;
                 }
+
+                let market_id = self
+                    .morpher_identifiers
+                    .get(&collateral)
+                    .expect("Collateral not supported.")
+                    .clone();
+                let cache_key = (market_id, self.get_price_sources(collateral));
+
+                let price = match market_prices.get(&cache_key) {
+                    Some(price) => *price,
+                    None => {
+                        let price = self.get_median_price(collateral, message, signature);
+                        market_prices.insert(cache_key, price);
+                        price
+                    }
+                };
+
+                price_return.push((collateral, price * self.get_redemption_rate(collateral)));
             }
 
             price_return
         }
 
+        /// Returns the time-weighted average price of a collateral, calculated from the recorded
+        /// history of verified messages. Meant as a fallback for non-critical actions when no fresh
+        /// signed price is available. Callers have to ask for it explicitly, `check_price_input` never
+        /// falls back to it.
+        ///
+        /// # Panics
+        /// * If the collateral is not supported or has no price history.
+        /// * If the newest observation is older than `max_twap_staleness`.
+        pub fn get_twap_price(&mut self, collateral: ResourceAddress) -> Decimal {
+            let market_id = self
+                .morpher_identifiers
+                .get(&collateral)
+                .expect("Collateral not supported.")
+                .clone();
+            let observations = self
+                .price_histories
+                .get(&market_id)
+                .expect("No price history for this collateral.")
+                .chronological();
+
+            let now = Clock::current_time_rounded_to_seconds().seconds_since_unix_epoch;
+            let newest = observations.last().unwrap();
+            assert!(
+                newest.timestamp + self.max_twap_staleness > now,
+                "Price history is stale."
+            );
+
+            let window_start = now - self.twap_window;
+            let mut weighted_price_sum = Decimal::ZERO;
+            let mut total_time: i64 = 0;
+
+            for (index, observation) in observations.iter().enumerate() {
+                let start = observation.timestamp.max(window_start);
+                let end = observations
+                    .get(index + 1)
+                    .map(|next| next.timestamp)
+                    .unwrap_or(now);
+                if end <= start {
+                    continue;
+                }
+                weighted_price_sum += observation.price * Decimal::from(end - start);
+                total_time += end - start;
+            }
+
+            let twap = if total_time > 0 {
+                weighted_price_sum / Decimal::from(total_time)
+            } else {
+                newest.price
+            };
+
+            twap * self.get_redemption_rate(collateral)
+        }
+
+        /// Returns the recorded price observations of a market, oldest first.
+        pub fn get_price_history(&self, market_id: String) -> Vec<PriceObservation> {
+            self.price_histories
+                .get(&market_id)
+                .map(|history| history.chronological())
+                .unwrap_or_default()
+        }
+
+        /// Sets the ring buffer length and the TWAP window / staleness limits (seconds).
+        /// Existing histories are trimmed to the newest `price_history_length` observations.
+        pub fn set_twap_parameters(
+            &mut self,
+            price_history_length: u64,
+            twap_window: i64,
+            max_twap_staleness: i64,
+        ) {
+            assert!(price_history_length > 0, "History length must be positive.");
+            assert!(twap_window > 0, "TWAP window must be positive.");
+
+            for history in self.price_histories.values_mut() {
+                history.resize(price_history_length);
+            }
+
+            self.price_history_length = price_history_length;
+            self.twap_window = twap_window;
+            self.max_twap_staleness = max_twap_staleness;
+        }
+
+        /// Returns the newest accepted price message of a market, if any.
+        pub fn get_latest_price(&self, market_id: String) -> Option<AcceptedPrice> {
+            self.latest_prices.get(&market_id).cloned()
+        }
+
+        /// Sets how strictly message ordering is enforced for a market.
+        /// If `market_id` is `None`, the default for all markets without their own setting is changed.
+        pub fn set_message_ordering(&mut self, market_id: Option<String>, ordering: MessageOrdering) {
+            match market_id {
+                Some(market_id) => {
+                    self.message_orderings.insert(market_id, ordering);
+                }
+                None => self.default_message_ordering = ordering,
+            }
+        }
+
+        /// Sets the signed market a resource is priced from.
+        ///
+        /// # Panics
+        /// * If a registered LP token would then depend on different signed markets.
         pub fn add_morpher_identifier(
             &mut self,
             resource_address: ResourceAddress,
             market_id: String,
         ) {
             self.morpher_identifiers.insert(resource_address, market_id);
+            self.check_lp_tokens_of_underlying(resource_address);
+        }
+
+        pub fn set_price_verifier(&mut self, verifier: PriceVerifier) {
+            self.verifier = verifier;
+        }
+
+        pub fn get_price_verifier(&self) -> PriceVerifier {
+            self.verifier.clone()
+        }
+
+        /// Adds a trusted signer key to the local signature verifier.
+        ///
+        /// # Panics
+        /// * If the Morpher verifier is in use, switching verifiers is only possible through `set_price_verifier`.
+        pub fn add_signer(&mut self, signer: SignerKey) {
+            match &mut self.verifier {
+                PriceVerifier::Signers { signers } => {
+                    if !signers.contains(&signer) {
+                        signers.push(signer);
+                    }
+                }
+                PriceVerifier::Morpher { .. } => panic!("Signers are not in use."),
+            }
+        }
+
+        pub fn remove_signer(&mut self, signer: SignerKey) {
+            match &mut self.verifier {
+                PriceVerifier::Signers { signers } => signers.retain(|key| *key != signer),
+                PriceVerifier::Morpher { .. } => panic!("Signers are not in use."),
+            }
+        }
+
+        /// Sets the price sources used for a collateral. The oracle returns the median of all sources.
+        ///
+        /// # Panics
+        /// * If `sources` is empty.
+        /// * If a registered LP token would then depend on different signed markets.
+        pub fn set_price_sources(
+            &mut self,
+            resource_address: ResourceAddress,
+            sources: Vec<PriceSource>,
+        ) {
+            assert!(!sources.is_empty(), "At least one price source is required.");
+            self.price_sources.insert(resource_address, sources);
+            self.check_lp_tokens_of_underlying(resource_address);
+        }
+
+        pub fn get_price_sources(&self, resource_address: ResourceAddress) -> Vec<PriceSource> {
+            self.price_sources
+                .get(&resource_address)
+                .cloned()
+                .unwrap_or(vec![PriceSource::SignedMessage])
+        }
+
+        /// Sets the maximum spread between the highest and lowest source price, relative to the median (e.g. 0.05 for 5%).
+        pub fn set_max_price_spread(&mut self, new_max_spread: Decimal) {
+            assert!(new_max_spread >= Decimal::ZERO, "Spread must be non-negative.");
+            self.max_price_spread = new_max_spread;
+        }
+
+        pub fn set_max_pushed_price_age(&mut self, new_max_age: i64) {
+            self.max_pushed_price_age = new_max_age;
+        }
+
+        /// Pushes a new price for a keeper-maintained feed.
+        pub fn push_price(&mut self, feed_id: String, price: Decimal) {
+            assert!(price > Decimal::ZERO, "Price must be positive.");
+            self.pushed_prices.insert(
+                feed_id,
+                PushedPrice {
+                    price,
+                    updated_at: Clock::current_time_rounded_to_seconds().seconds_since_unix_epoch,
+                },
+            );
         }
 
         pub fn set_max_price_age(&mut self, new_max_age: i64) {
             self.max_price_age = new_max_age;
         }
 
-        pub fn set_max_lsu_multiplier_age(&mut self, new_max_age: i64) {
-            self.max_lsu_multiplier_age = new_max_age;
+        /// Registers a derivative collateral (e.g. a liquid staking token or pool share), priced as
+        /// the price of `underlying_market_id` times the redemption rate returned by `rate_source`.
+        ///
+        /// # Arguments
+        /// * `derivative`: The `ResourceAddress` of the derivative token.
+        /// * `underlying_market_id`: The market ID of the underlying asset.
+        /// * `rate_source`: Where to fetch the derivative / underlying exchange rate from.
+        /// * `max_rate_age`: Maximum age (seconds) of the cached rate before it is fetched again.
+        pub fn register_derivative(
+            &mut self,
+            derivative: ResourceAddress,
+            underlying_market_id: String,
+            rate_source: RedemptionRateSource,
+            max_rate_age: i64,
+        ) {
+            self.morpher_identifiers
+                .insert(derivative, underlying_market_id.clone());
+            self.derivatives.insert(
+                derivative,
+                DerivativeInfo {
+                    underlying_market_id,
+                    redemption_rate: rate_source.fetch_rate(derivative),
+                    rate_source,
+                    last_rate_update: Clock::current_time_rounded_to_seconds(),
+                    max_rate_age,
+                },
+            );
+        }
+
+        pub fn remove_derivative(&mut self, derivative: ResourceAddress) {
+            self.derivatives.remove(&derivative);
+            self.morpher_identifiers.remove(&derivative);
+        }
+
+        pub fn set_max_redemption_rate_age(&mut self, derivative: ResourceAddress, new_max_age: i64) {
+            self.derivatives
+                .get_mut(&derivative)
+                .expect("Not a registered derivative.")
+                .max_rate_age = new_max_age;
+        }
+
+        pub fn get_derivatives(&self) -> Vec<(ResourceAddress, DerivativeInfo)> {
+            self.derivatives
+                .iter()
+                .map(|(address, info)| (*address, info.clone()))
+                .collect()
+        }
+
+        /// Registers a DEX LP token as a priceable collateral.
+        /// Underlyings without a fixed price must be priceable by this oracle themselves.
+        /// Only one message is passed to price the LP token, so signed-priced underlyings must share one market.
+        pub fn register_lp_token(&mut self, lp_token: ResourceAddress, info: LpTokenInfo) {
+            assert!(
+                info.underlying_a.resource != info.underlying_b.resource,
+                "Underlyings must differ."
+            );
+
+            self.check_lp_token_markets(&info);
+            self.lp_tokens.insert(lp_token, info);
+        }
+
+        pub fn remove_lp_token(&mut self, lp_token: ResourceAddress) {
+            self.lp_tokens.remove(&lp_token);
+        }
+
+        pub fn get_lp_tokens(&self) -> Vec<(ResourceAddress, LpTokenInfo)> {
+            self.lp_tokens
+                .iter()
+                .map(|(address, info)| (*address, info.clone()))
+                .collect()
+        }
+
+        /// Fetches a fresh redemption rate for a derivative from its rate source.
+        pub fn update_redemption_rate(&mut self, derivative: ResourceAddress) {
+            let info = self
+                .derivatives
+                .get_mut(&derivative)
+                .expect("Not a registered derivative.");
+            info.redemption_rate = info.rate_source.fetch_rate(derivative);
+            info.last_rate_update = Clock::current_time_rounded_to_seconds();
         }
 
+        /// Fetches a fresh LSULP redemption rate, same as `update_redemption_rate` for the LSULP.
         pub fn update_lsu_multiplier(&mut self) {
-            let lsu_lp_supply = ResourceManager::from_address(self.lsu_lp_address).total_supply().unwrap();
-            self.lsu_multiplier =
-                LSU_POOL.get_dex_valuation_xrd() / lsu_lp_supply;
-                
-            self.last_lsu_multiplier_update = Clock::current_time_rounded_to_seconds();
+            self.update_redemption_rate(self.lsu_lp_address);
+        }
+
+        /// Sets the maximum age of the LSULP redemption rate in days.
+        pub fn set_max_lsu_multiplier_age(&mut self, new_max_age: i64) {
+            self.set_max_redemption_rate_age(self.lsu_lp_address, new_max_age * 86400);
+        }
+
+        /// Checks that a single signed message can price both underlyings of an LP token.
+        ///
+        /// # Panics
+        /// * If the underlyings depend on different signed markets.
+        fn check_lp_token_markets(&self, info: &LpTokenInfo) {
+            let signed_markets: HashSet<Option<String>> = [&info.underlying_a, &info.underlying_b]
+                .into_iter()
+                .filter(|underlying| {
+                    underlying.fixed_price.is_none()
+                        && self
+                            .get_price_sources(underlying.resource)
+                            .contains(&PriceSource::SignedMessage)
+                })
+                .map(|underlying| self.morpher_identifiers.get(&underlying.resource).cloned())
+                .collect();
+            assert!(
+                signed_markets.len() <= 1,
+                "Underlyings can't depend on different signed markets."
+            );
+        }
+
+        /// Re-runs `check_lp_token_markets` for every registered LP token with the resource as an underlying,
+        /// after the resource's market or price sources changed.
+        fn check_lp_tokens_of_underlying(&self, resource_address: ResourceAddress) {
+            for info in self.lp_tokens.values() {
+                if info.underlying_a.resource == resource_address
+                    || info.underlying_b.resource == resource_address
+                {
+                    self.check_lp_token_markets(info);
+                }
+            }
+        }
+
+        /// Calculates the fair price of an LP token, see `lp_pricing::fair_lp_token_price`.
+        fn get_lp_token_price(
+            &mut self,
+            lp_token: ResourceAddress,
+            message: String,
+            signature: String,
+        ) -> Decimal {
+            let info = self.lp_tokens.get(&lp_token).unwrap().clone();
+            let (reserve_a, reserve_b) = info.get_reserves();
+
+            // Underlyings sharing a market and price sources (e.g. XRD and its derivatives) only verify the message once.
+            let mut market_prices: HashMap<(String, Vec<PriceSource>), Decimal> = HashMap::new();
+            let price_a =
+                self.get_underlying_price(&info.underlying_a, &message, &signature, &mut market_prices);
+            let price_b =
+                self.get_underlying_price(&info.underlying_b, &message, &signature, &mut market_prices);
+
+            let lp_supply = ResourceManager::from_address(lp_token)
+                .total_supply()
+                .unwrap();
+
+            fair_lp_token_price(reserve_a, price_a, reserve_b, price_b, lp_supply)
+        }
+
+        fn get_underlying_price(
+            &mut self,
+            underlying: &LpUnderlying,
+            message: &String,
+            signature: &String,
+            market_prices: &mut HashMap<(String, Vec<PriceSource>), Decimal>,
+        ) -> Decimal {
+            match underlying.fixed_price {
+                Some(price) => price,
+                None => {
+                    let cache_key = self
+                        .morpher_identifiers
+                        .get(&underlying.resource)
+                        .cloned()
+                        .map(|market_id| (market_id, self.get_price_sources(underlying.resource)));
+                    let price = match cache_key.as_ref().and_then(|key| market_prices.get(key)) {
+                        Some(price) => *price,
+                        None => {
+                            let price = self.get_median_price(
+                                underlying.resource,
+                                message.clone(),
+                                signature.clone(),
+                            );
+                            if let Some(cache_key) = cache_key {
+                                market_prices.insert(cache_key, price);
+                            }
+                            price
+                        }
+                    };
+                    price * self.get_redemption_rate(underlying.resource)
+                }
+            }
+        }
+
+        /// Returns the cached redemption rate of a derivative, updating it first if it is older than
+        /// its `max_rate_age`. Returns 1 for collaterals that are not derivatives.
+        fn get_redemption_rate(&mut self, collateral: ResourceAddress) -> Decimal {
+            let needs_update = match self.derivatives.get(&collateral) {
+                Some(info) => Clock::current_time_is_strictly_after(
+                    info.last_rate_update.add_seconds(info.max_rate_age).unwrap(),
+                    TimePrecision::Second,
+                ),
+                None => return Decimal::ONE,
+            };
+
+            if needs_update {
+                self.update_redemption_rate(collateral);
+            }
+
+            self.derivatives.get(&collateral).unwrap().redemption_rate
+        }
+
+        /// Gets the price from every source configured for the collateral and returns the median.
+        /// Panics if the spread between the sources exceeds `max_price_spread`.
+        fn get_median_price(
+            &mut self,
+            collateral: ResourceAddress,
+            message: String,
+            signature: String,
+        ) -> Decimal {
+            let mut prices: Vec<Decimal> = vec![];
+            for source in self.get_price_sources(collateral) {
+                prices.push(self.get_source_price(collateral, &source, &message, &signature));
+            }
+            prices.sort();
+
+            let length = prices.len();
+            let median = if length % 2 == 1 {
+                prices[length / 2]
+            } else {
+                (prices[length / 2 - 1] + prices[length / 2]) / dec!(2)
+            };
+
+            assert!(median > Decimal::ZERO, "Invalid price.");
+            assert!(
+                (prices[length - 1] - prices[0]) / median <= self.max_price_spread,
+                "Price sources disagree too much."
+            );
+
+            median
+        }
+
+        fn get_source_price(
+            &mut self,
+            collateral: ResourceAddress,
+            source: &PriceSource,
+            message: &String,
+            signature: &String,
+        ) -> Decimal {
+            match source {
+                PriceSource::SignedMessage => {
+                    let price_message = self.verifier.verify(message, signature);
+                    self.check_message_validity(collateral, price_message.clone());
+                    self.check_message_order(&price_message);
+                    self.record_price(&price_message);
+                    price_message.price
+                }
+                PriceSource::DexPool { component, method_name } => {
+                    let pool: Global<AnyComponent> = Global::from(*component);
+                    pool.call_raw::<Decimal>(method_name, scrypto_args!())
+                }
+                PriceSource::PushFeed { feed_id } => {
+                    let pushed_price = self
+                        .pushed_prices
+                        .get(feed_id)
+                        .expect("No price pushed for this feed.");
+                    assert!(
+                        (pushed_price.updated_at + self.max_pushed_price_age)
+                            > Clock::current_time_rounded_to_seconds().seconds_since_unix_epoch,
+                        "Pushed price is stale."
+                    );
+                    pushed_price.price
+                }
+            }
         }
 
-        fn check_for_lsu_multiplier_update(&mut self) {
-            if Clock::current_time_is_strictly_after(
-                self.last_lsu_multiplier_update
-                    .add_days(self.max_lsu_multiplier_age)
-                    .unwrap(),
-                TimePrecision::Second,
-            ) {
-                self.update_lsu_multiplier();
+        /// Checks the message against the newest accepted message of its market and stores it as the newest if it is.
+        ///
+        /// # Panics
+        /// * If the ordering is `Monotonic` or `SingleUse` and the message is older than the newest accepted message.
+        /// * If the ordering is `SingleUse` and the exact same message (nonce and timestamp) was already accepted.
+        fn check_message_order(&mut self, message: &PriceMessage) {
+            let ordering = self
+                .message_orderings
+                .get(&message.market_id)
+                .cloned()
+                .unwrap_or(self.default_message_ordering.clone());
+
+            if let Some(latest) = self.latest_prices.get(&message.market_id) {
+                match ordering {
+                    MessageOrdering::Unordered => {}
+                    MessageOrdering::Monotonic => {
+                        assert!(
+                            message.created_at >= latest.created_at,
+                            "Price message is older than the latest accepted message."
+                        );
+                    }
+                    MessageOrdering::SingleUse => {
+                        assert!(
+                            message.created_at >= latest.created_at,
+                            "Price message is older than the latest accepted message."
+                        );
+                        assert!(
+                            message.created_at > latest.created_at || message.nonce != latest.nonce,
+                            "Price message already used."
+                        );
+                    }
+                }
+
+                if message.created_at < latest.created_at {
+                    return;
+                }
             }
+
+            self.latest_prices.insert(
+                message.market_id.clone(),
+                AcceptedPrice {
+                    price: message.price,
+                    nonce: message.nonce,
+                    created_at: message.created_at,
+                },
+            );
+        }
+
+        /// Adds a verified message to the price history of its market, unless it isn't newer than the latest observation.
+        fn record_price(&mut self, message: &PriceMessage) {
+            let history_length = self.price_history_length;
+            self.price_histories
+                .entry(message.market_id.clone())
+                .or_insert(PriceHistory {
+                    observations: vec![],
+                    next_index: 0,
+                })
+                .record(
+                    PriceObservation {
+                        price: message.price,
+                        timestamp: message.created_at as i64,
+                    },
+                    history_length,
+                );
         }
 
         fn check_message_validity(&self, collateral: ResourceAddress, message: PriceMessage) {
@@ -211,3 +785,139 @@ pub struct PriceEntry {
     pub changed_at: u64,
     pub identifier: String,
 }
+
+/// A source of price information for a collateral.
+#[derive(ScryptoSbor, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum PriceSource {
+    /// The signed price message passed to `check_price_input`, checked by the oracle's `PriceVerifier`.
+    SignedMessage,
+    /// An on-ledger DEX pool. `method_name` is called on `component` and must return the USD price as a `Decimal`.
+    DexPool {
+        component: ComponentAddress,
+        method_name: String,
+    },
+    /// A price pushed by a keeper through `push_price`.
+    PushFeed { feed_id: String },
+}
+
+#[derive(ScryptoSbor, Clone)]
+pub struct PushedPrice {
+    pub price: Decimal,
+    pub updated_at: i64,
+}
+
+#[derive(ScryptoSbor, Clone)]
+pub struct PriceObservation {
+    pub price: Decimal,
+    pub timestamp: i64,
+}
+
+/// Fixed-size ring buffer of price observations for a single market.
+#[derive(ScryptoSbor, Clone)]
+pub struct PriceHistory {
+    pub observations: Vec<PriceObservation>,
+    /// Index the next observation is written to once the buffer is full.
+    pub next_index: u64,
+}
+
+impl PriceHistory {
+    pub fn record(&mut self, observation: PriceObservation, capacity: u64) {
+        if let Some(newest) = self.chronological().last() {
+            if observation.timestamp <= newest.timestamp {
+                return;
+            }
+        }
+
+        if (self.observations.len() as u64) < capacity {
+            self.observations.push(observation);
+        } else {
+            self.observations[self.next_index as usize] = observation;
+        }
+        self.next_index = (self.next_index + 1) % capacity;
+    }
+
+    pub fn chronological(&self) -> Vec<PriceObservation> {
+        let mut observations = self.observations.clone();
+        observations.sort_by_key(|observation| observation.timestamp);
+        observations
+    }
+
+    /// Keeps only the newest `capacity` observations.
+    pub fn resize(&mut self, capacity: u64) {
+        let mut observations = self.chronological();
+        if observations.len() as u64 > capacity {
+            observations.drain(0..observations.len() - capacity as usize);
+        }
+        self.next_index = observations.len() as u64 % capacity;
+        self.observations = observations;
+    }
+}
+
+/// Determines which signed price messages are accepted relative to the newest accepted message of a market.
+#[derive(ScryptoSbor, Clone, PartialEq, Debug)]
+pub enum MessageOrdering {
+    /// Any message within `max_price_age` is accepted.
+    Unordered,
+    /// Messages older than the newest accepted message are rejected.
+    Monotonic,
+    /// Like `Monotonic`, but the newest accepted message cannot be used again either.
+    SingleUse,
+}
+
+#[derive(ScryptoSbor, Clone)]
+pub struct AcceptedPrice {
+    pub price: Decimal,
+    pub nonce: u64,
+    pub created_at: u64,
+}
+
+/// Where the exchange rate between a derivative and its underlying asset is read from.
+#[derive(ScryptoSbor, Clone, PartialEq, Debug)]
+pub enum RedemptionRateSource {
+    /// `method_name` on `component` returns the exchange rate directly.
+    Rate {
+        component: ComponentAddress,
+        method_name: String,
+    },
+    /// `valuation_method` on `component` returns the total underlying value backing the derivative,
+    /// which is divided by the derivative's total supply (e.g. the LSU pool).
+    ValuationPerSupply {
+        component: ComponentAddress,
+        valuation_method: String,
+    },
+}
+
+impl RedemptionRateSource {
+    pub fn fetch_rate(&self, derivative: ResourceAddress) -> Decimal {
+        let rate: Decimal = match self {
+            RedemptionRateSource::Rate {
+                component,
+                method_name,
+            } => Global::<AnyComponent>::from(*component).call_raw(method_name, scrypto_args!()),
+            RedemptionRateSource::ValuationPerSupply {
+                component,
+                valuation_method,
+            } => {
+                let valuation: Decimal = Global::<AnyComponent>::from(*component)
+                    .call_raw(valuation_method, scrypto_args!());
+                valuation
+                    / ResourceManager::from_address(derivative)
+                        .total_supply()
+                        .unwrap()
+            }
+        };
+        assert!(rate > Decimal::ZERO, "Invalid redemption rate.");
+        rate
+    }
+}
+
+#[derive(ScryptoSbor, Clone)]
+pub struct DerivativeInfo {
+    pub underlying_market_id: String,
+    pub rate_source: RedemptionRateSource,
+    /// Cached exchange rate (underlying per derivative).
+    pub redemption_rate: Decimal,
+    pub last_rate_update: Instant,
+    /// Maximum age (seconds) of the cached rate.
+    pub max_rate_age: i64,
+}
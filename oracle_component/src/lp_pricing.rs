@@ -0,0 +1,77 @@
+//! # LP Token Pricing
+//! Fair pricing of constant product (x * y = k) DEX liquidity provider tokens.
+//!
+//! Valuing an LP token by its share of the spot reserves can be manipulated: a large swap moves the
+//! reserves (and thus the LP value) within a single transaction. Instead, the reserves are re-derived
+//! from the pool's invariant `k = x * y` and the oracle prices of both underlyings, which gives the
+//! reserves an arbitrage-free pool would hold: `x = sqrt(k * p_y / p_x)` and `y = sqrt(k * p_x / p_y)`.
+//! The fair pool value is then `x * p_x + y * p_y = 2 * sqrt(k * p_x * p_y)`, which only changes when
+//! `k` changes (fees, deposits, withdrawals), not when the spot price is pushed around.
+
+use scrypto::prelude::*;
+
+/// An underlying token of an LP token and how its price is obtained.
+#[derive(ScryptoSbor, Clone, PartialEq, Debug)]
+pub struct LpUnderlying {
+    /// The `ResourceAddress` of the underlying token.
+    pub resource: ResourceAddress,
+    /// If set, this price is used instead of querying the oracle (e.g. 1 for fUSD).
+    pub fixed_price: Option<Decimal>,
+}
+
+/// Configuration of an LP token accepted by the oracle.
+#[derive(ScryptoSbor, Clone, PartialEq, Debug)]
+pub struct LpTokenInfo {
+    /// The DEX pool the LP token is a share of.
+    pub pool: ComponentAddress,
+    /// Method on `pool` returning its reserves as an `IndexMap<ResourceAddress, Decimal>`.
+    pub reserves_method: String,
+    pub underlying_a: LpUnderlying,
+    pub underlying_b: LpUnderlying,
+}
+
+impl LpTokenInfo {
+    /// Reads the pool reserves of both underlyings, in the order `(a, b)`.
+    pub fn get_reserves(&self) -> (Decimal, Decimal) {
+        let reserves: IndexMap<ResourceAddress, Decimal> =
+            Global::<AnyComponent>::from(self.pool).call_raw(&self.reserves_method, scrypto_args!());
+
+        (
+            *reserves
+                .get(&self.underlying_a.resource)
+                .expect("Pool does not hold underlying A."),
+            *reserves
+                .get(&self.underlying_b.resource)
+                .expect("Pool does not hold underlying B."),
+        )
+    }
+}
+
+/// Calculates the fair price of a single LP token of a constant product pool.
+///
+/// # Arguments
+/// * `reserve_a`, `reserve_b`: The current pool reserves, only used to determine the invariant `k`.
+/// * `price_a`, `price_b`: The oracle prices of the underlyings.
+/// * `lp_supply`: The total supply of the LP token.
+///
+/// # Returns
+/// * `Decimal`: `2 * sqrt(k * price_a * price_b) / lp_supply`.
+pub fn fair_lp_token_price(
+    reserve_a: Decimal,
+    price_a: Decimal,
+    reserve_b: Decimal,
+    price_b: Decimal,
+    lp_supply: Decimal,
+) -> Decimal {
+    assert!(lp_supply > Decimal::ZERO, "LP token has no supply.");
+    assert!(
+        price_a > Decimal::ZERO && price_b > Decimal::ZERO,
+        "Invalid underlying price."
+    );
+
+    // sqrt(k * p_a * p_b) = sqrt(r_a * p_a) * sqrt(r_b * p_b), split up to avoid overflowing on k.
+    let value_root_a = (reserve_a * price_a).checked_sqrt().unwrap();
+    let value_root_b = (reserve_b * price_b).checked_sqrt().unwrap();
+
+    dec!(2) * value_root_a * value_root_b / lp_supply
+}
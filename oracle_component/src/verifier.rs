@@ -0,0 +1,107 @@
+//! # Price Verifier
+//! Verification of signed price messages.
+//!
+//! A price message is passed to the oracle as a `(message, signature)` pair of strings. The verifier turns this pair
+//! into a trusted `PriceMessage`, either by delegating to an external Morpher oracle component, or by checking the
+//! signature locally against a set of registered signer keys. The latter allows signers other than Morpher, and makes
+//! it possible to run the full verification path in tests with locally generated keys.
+//!
+//! Locally verified messages use the encoding `{market_id}-{price}-{nonce}-{created_at}` (see `PriceMessage::encode`).
+//! Signers sign the Keccak-256 hash of this encoding, the signature is passed as a hex string.
+
+use crate::PriceMessage;
+use scrypto::prelude::*;
+
+/// A public key of a trusted price signer.
+#[derive(ScryptoSbor, Clone, PartialEq, Debug)]
+pub enum SignerKey {
+    Ed25519(Ed25519PublicKey),
+    Secp256k1(Secp256k1PublicKey),
+}
+
+impl SignerKey {
+    /// Checks whether the hex encoded `signature` is a valid signature of `hash` by this key.
+    /// A signature that can't be parsed for this key type is treated as invalid.
+    pub fn verify(&self, hash: &Hash, signature: &str) -> bool {
+        match self {
+            SignerKey::Ed25519(public_key) => match Ed25519Signature::from_str(signature) {
+                Ok(signature) => CryptoUtils::ed25519_verify(hash.as_ref(), public_key, &signature),
+                Err(_) => false,
+            },
+            SignerKey::Secp256k1(public_key) => match Secp256k1Signature::from_str(signature) {
+                Ok(signature) => CryptoUtils::secp256k1_ecdsa_verify(*hash, public_key, &signature),
+                Err(_) => false,
+            },
+        }
+    }
+}
+
+/// How signed price messages are verified.
+#[derive(ScryptoSbor, Clone, PartialEq, Debug)]
+pub enum PriceVerifier {
+    /// Calls `check_price_input` on an external Morpher oracle component, which returns the verified `PriceMessage`.
+    Morpher { oracle_address: ComponentAddress },
+    /// Verifies the signature locally. A message is accepted if any of the signers signed it.
+    Signers { signers: Vec<SignerKey> },
+}
+
+impl PriceVerifier {
+    /// Verifies a signed price message.
+    ///
+    /// # Arguments
+    /// * `message`: The price message, as provided by the signer.
+    /// * `signature`: The signature of the message.
+    ///
+    /// # Returns
+    /// * `PriceMessage`: The verified price message.
+    ///
+    /// # Panics
+    /// * If the signature is invalid or the message can't be decoded.
+    pub fn verify(&self, message: &String, signature: &String) -> PriceMessage {
+        match self {
+            PriceVerifier::Morpher { oracle_address } => {
+                Global::<AnyComponent>::from(*oracle_address).call_raw::<PriceMessage>(
+                    "check_price_input",
+                    scrypto_args!(message.clone(), signature.clone()),
+                )
+            }
+            PriceVerifier::Signers { signers } => {
+                let hash = CryptoUtils::keccak256_hash(message.as_bytes());
+                assert!(
+                    signers.iter().any(|signer| signer.verify(&hash, signature)),
+                    "Invalid price signature."
+                );
+                PriceMessage::decode(message)
+            }
+        }
+    }
+}
+
+impl PriceMessage {
+    /// Encodes the message as `{market_id}-{price}-{nonce}-{created_at}`, the format signers sign.
+    pub fn encode(&self) -> String {
+        format!(
+            "{}-{}-{}-{}",
+            self.market_id, self.price, self.nonce, self.created_at
+        )
+    }
+
+    /// Decodes a message encoded by `encode`. The market ID may contain dashes, the other fields can't.
+    pub fn decode(message: &str) -> PriceMessage {
+        let mut parts = message.rsplitn(4, '-');
+        let created_at = parts.next().and_then(|part| part.parse::<u64>().ok());
+        let nonce = parts.next().and_then(|part| part.parse::<u64>().ok());
+        let price = parts.next().and_then(|part| Decimal::try_from(part).ok());
+        let market_id = parts.next();
+
+        match (market_id, price, nonce, created_at) {
+            (Some(market_id), Some(price), Some(nonce), Some(created_at)) => PriceMessage {
+                market_id: market_id.to_string(),
+                price,
+                nonce,
+                created_at,
+            },
+            _ => panic!("Invalid price message."),
+        }
+    }
+}
@@ -131,6 +131,108 @@ pub struct StabilityPoolWithdrawalEvent {
     pub collateral_received: Decimal,
 }
 
+/// Event emitted when a depositor claims the gains of a product-sum stability pool deposit
+#[derive(ScryptoSbor, ScryptoEvent, Clone)]
+pub struct StabilityPoolGainsClaimEvent {
+    /// The resource address of the collateral type for this pool
+    pub collateral: ResourceAddress,
+    /// The amount of collateral claimed
+    pub collateral_claimed: Decimal,
+    /// The amount of fUSD yield claimed
+    pub fusd_claimed: Decimal,
+}
+
+/// Event emitted when pool units are locked in a stability pool
+#[derive(ScryptoSbor, ScryptoEvent, Clone)]
+pub struct StabilityPoolLockEvent {
+    /// The resource address of the collateral type for this pool
+    pub collateral: ResourceAddress,
+    /// The ID of the lock receipt
+    pub lock_id: NonFungibleLocalId,
+    /// The amount of pool units locked
+    pub pool_units: Decimal,
+    /// The boosted weight of the lock
+    pub weight: Decimal,
+    /// The time after which the pool units can be unlocked without penalty
+    pub unlock_time: Instant,
+}
+
+/// Event emitted when locked pool units are unlocked
+#[derive(ScryptoSbor, ScryptoEvent, Clone)]
+pub struct StabilityPoolUnlockEvent {
+    /// The resource address of the collateral type for this pool
+    pub collateral: ResourceAddress,
+    /// The ID of the burned lock receipt
+    pub lock_id: NonFungibleLocalId,
+    /// The amount of pool units returned
+    pub pool_units_returned: Decimal,
+    /// The amount of pool units lost as an early unlock penalty
+    pub penalty_units: Decimal,
+    /// The amount of fUSD lock rewards paid out
+    pub rewards: Decimal,
+}
+
+/// Event emitted when a withdrawal from a stability pool is requested or cancelled
+#[derive(ScryptoSbor, ScryptoEvent, Clone)]
+pub struct StabilityPoolWithdrawalRequestEvent {
+    /// The resource address of the collateral type for this pool, `None` for the shared stability pool
+    pub collateral: Option<ResourceAddress>,
+    /// The ID of the withdrawal request
+    pub request_id: NonFungibleLocalId,
+    /// The amount of pool units (or deposit receipts) to withdraw
+    pub pool_units: Decimal,
+    /// The time after which the withdrawal can be claimed
+    pub claimable_at: Instant,
+    /// The change that occurred
+    pub change: WithdrawalRequestChange,
+}
+
+/// Event emitted when stability pool collateral is converted back to fUSD
+#[derive(ScryptoSbor, ScryptoEvent, Clone)]
+pub struct StabilityPoolConversionEvent {
+    /// The resource address of the collateral type for this pool
+    pub collateral: ResourceAddress,
+    /// The amount of collateral sold, or put up for auction
+    pub collateral_amount: Decimal,
+    /// The amount of fUSD received by the pool
+    pub fusd_amount: Decimal,
+    /// The price per unit of collateral (the starting price for a started auction)
+    pub price: Decimal,
+    /// The change that occurred
+    pub change: ConversionChange,
+}
+
+/// Event emitted when fUSD is contributed to the shared stability pool
+#[derive(ScryptoSbor, ScryptoEvent, Clone)]
+pub struct SharedPoolContributionEvent {
+    /// The amount of fUSD contributed
+    pub contribution_amount: Decimal,
+    /// The amount of shared pool tokens received
+    pub pool_tokens_received: Decimal,
+}
+
+/// Event emitted when shared stability pool tokens are redeemed
+#[derive(ScryptoSbor, ScryptoEvent, Clone)]
+pub struct SharedPoolWithdrawalEvent {
+    /// The amount of shared pool tokens burned
+    pub pool_tokens_burned: Decimal,
+    /// The amount of fUSD received
+    pub fusd_received: Decimal,
+}
+
+/// Event emitted when a CDP is liquidated using the shared stability pool
+#[derive(ScryptoSbor, ScryptoEvent, Clone)]
+pub struct SharedPoolLiquidationEvent {
+    /// The CDP ID being liquidated
+    pub cdp_id: NonFungibleLocalId,
+    /// The resource address of the CDP's collateral
+    pub collateral: ResourceAddress,
+    /// The amount of fUSD taken from the shared pool
+    pub fusd_paid: Decimal,
+    /// The amount of collateral added to the shared pool
+    pub collateral_received: Decimal,
+}
+
 /// Event emitted when collateral is bought directly from a stability pool
 #[derive(ScryptoSbor, ScryptoEvent, Clone)]
 pub struct StabilityPoolBuyEvent {
@@ -149,6 +251,8 @@ pub struct StabilityPoolBuyEvent {
 pub struct PanicModeChangeEvent {
     /// The CDP ID that triggered panic mode
     pub cdp_id: NonFungibleLocalId,
+    /// The collateral of the CDP, for which panic mode is tracked
+    pub collateral: ResourceAddress,
     /// The timestamp when panic mode was activated
     pub activation_time: Instant,
     /// The change that occurred
@@ -160,16 +264,62 @@ pub struct PanicModeChangeEvent {
 pub struct PanicModeLiquidationEvent {
     /// The CDP ID being liquidated
     pub cdp_id: NonFungibleLocalId,
+    /// The stablecoin paid with
+    pub stablecoin: ResourceAddress,
     /// The amount of stablecoin paid
     pub stablecoin_paid: Decimal,
+    /// The oracle price of the stablecoin, at which fUSD was minted
+    pub stablecoin_price: Decimal,
     /// The amount of collateral received
     pub collateral_received: Decimal,
 }
 
+/// Event emitted when panic mode stablecoin reserves are swapped back to fUSD, which is burned
+#[derive(ScryptoSbor, ScryptoEvent, Clone)]
+pub struct StablecoinUnwindEvent {
+    /// The stablecoin unwound
+    pub stablecoin: ResourceAddress,
+    /// The amount of stablecoin sold
+    pub stablecoin_amount: Decimal,
+    /// The amount of fUSD received and burned
+    pub fusd_burned: Decimal,
+    /// The oracle price of the stablecoin
+    pub stablecoin_price: Decimal,
+    /// The way the reserves were unwound
+    pub change: StablecoinUnwindChange,
+}
+
+/// Event emitted when a keeper is paid a bounty for a housekeeping action
+#[derive(ScryptoSbor, ScryptoEvent, Clone)]
+pub struct KeeperRewardEvent {
+    /// The action the bounty was paid for
+    pub action: KeeperAction,
+    /// The amount of fUSD paid
+    pub amount: Decimal,
+}
+
 /// Event emitted when rewards are claimed from the payout component
 #[derive(ScryptoSbor, ScryptoEvent)]
 pub struct PayoutClaimEvent {
+    /// The auction epoch the rewards were claimed in
+    pub epoch: u64,
+    /// The amount of fUSD claimed
     pub amount: Decimal,
+    /// The amount of payment tokens paid
+    pub payment_amount: Decimal,
+    /// The price paid, in payment tokens per fUSD
+    pub price: Decimal,
+}
+
+/// Event emitted when the payout component starts a new auction epoch
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct PayoutAuctionStartEvent {
+    /// The auction epoch started
+    pub epoch: u64,
+    /// The amount of fUSD auctioned in the epoch
+    pub lot: Decimal,
+    /// The starting price, in payment tokens per fUSD
+    pub start_price: Decimal,
 }
 
 /// Event emitted when the payout component fetches rewards from stability pools
@@ -178,16 +328,105 @@ pub struct PayoutFetchRewardsEvent {
     pub amount: Decimal,
 }
 
-/// Event emitted when the required payment amount for claiming rewards is updated
+/// Event emitted when the payout component sets aside part of the fetched revenue for a destination
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct PayoutRevenueSplitEvent {
+    /// The destination of the revenue
+    pub destination: RevenueDestination,
+    /// The amount of fUSD set aside for the destination
+    pub amount: Decimal,
+}
+
+/// Event emitted when the payout component pushes pending revenue to an external destination
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct PayoutRevenuePushEvent {
+    /// The destination of the revenue
+    pub destination: RevenueDestination,
+    /// The amount of fUSD sent to the destination
+    pub amount: Decimal,
+}
+
+/// Event emitted when the payout component deposits protocol-owned liquidity into a stability pool
+#[derive(ScryptoSbor, ScryptoEvent)]
+pub struct PayoutLiquidityDepositEvent {
+    /// The collateral whose stability pool was deposited into
+    pub collateral: ResourceAddress,
+    /// The amount of fUSD deposited
+    pub fusd_amount: Decimal,
+    /// The amount of pool units (or deposit receipts) received
+    pub pool_units: Decimal,
+}
+
+/// Event emitted when the auction parameters for claiming rewards are updated
 #[derive(ScryptoSbor, ScryptoEvent)]
 pub struct PayoutRequirementUpdateEvent {
-    pub new_requirement: Decimal,
+    pub start_premium: Decimal,
+    pub decay_per_minute: Decimal,
+    pub max_discount: Decimal,
+    pub reference_price: Decimal,
+    pub min_reference_price: Decimal,
     pub burn: bool,
 }
 
+/// Event emitted when LP tokens are staked in the liquidity rewards distributor
+#[derive(ScryptoSbor, ScryptoEvent, Clone)]
+pub struct LiquidityStakeEvent {
+    /// The ID of the stake receipt
+    pub stake_id: NonFungibleLocalId,
+    /// The amount of LP tokens staked
+    pub amount: Decimal,
+}
+
+/// Event emitted when LP tokens are unstaked from the liquidity rewards distributor
+#[derive(ScryptoSbor, ScryptoEvent, Clone)]
+pub struct LiquidityUnstakeEvent {
+    /// The ID of the burned stake receipt
+    pub stake_id: NonFungibleLocalId,
+    /// The amount of LP tokens returned
+    pub amount: Decimal,
+    /// The amount of fUSD rewards paid out
+    pub rewards: Decimal,
+}
+
+/// Event emitted when staking rewards are claimed from the liquidity rewards distributor
+#[derive(ScryptoSbor, ScryptoEvent, Clone)]
+pub struct LiquidityRewardsClaimEvent {
+    /// The ID of the stake receipt
+    pub stake_id: NonFungibleLocalId,
+    /// The amount of fUSD rewards claimed
+    pub amount: Decimal,
+}
+
+/// Event emitted when the liquidity rewards distributor fetches liquidity rewards from the stability pools
+#[derive(ScryptoSbor, ScryptoEvent, Clone)]
+pub struct LiquidityRewardsFetchEvent {
+    /// The amount of fUSD fetched
+    pub amount: Decimal,
+    /// The new amount of fUSD streamed to stakers per second
+    pub reward_rate: Decimal,
+    /// The time at which the current stream ends
+    pub stream_end: Instant,
+}
+
 #[derive(ScryptoSbor, PartialEq, Clone)]
 pub enum PanicModeEvent {
     Initiation,
     Activation,
     TooLateActivation,
-}
\ No newline at end of file
+}
+#[derive(ScryptoSbor, PartialEq, Clone)]
+pub enum WithdrawalRequestChange {
+    Requested,
+    Cancelled,
+}
+#[derive(ScryptoSbor, PartialEq, Clone)]
+pub enum ConversionChange {
+    DexSale,
+    AuctionStart,
+    AuctionSale,
+}
+#[derive(ScryptoSbor, PartialEq, Clone)]
+pub enum StablecoinUnwindChange {
+    DexSale,
+    BuyBack,
+}
@@ -3,7 +3,7 @@
 //! # fUSD Flash Loan Blueprint
 //!
 //! This blueprint defines a component that allows users to take out flash loans of fUSD tokens
-//! from the main Flux protocol component.
+//! from the main Flux protocol component, as well as flash loans of the collateral held by it.
 //! Flash loans are uncollateralized loans that must be borrowed and repaid within the same transaction.
 //!
 //! ## Functionality
@@ -12,7 +12,16 @@
 //! - The borrower *must* call the `pay_back` method within the same transaction, providing sufficient fUSD
 //!   (borrowed amount + interest) to repay the loan and allow the `LoanReceipt` to be burned.
 //! - Failure to repay within the same transaction results in the transaction failing.
-//! - Collected interest can be retrieved by an authorized party.
+//! - Collected interest is routed to the configured fee destinations on repayment (e.g. the payout component or a
+//!   stability pool). Without destinations it is kept as a surplus buffer, which can be retrieved by an authorized party.
+//! - Loans can be capped per loan and per transaction, and holders of configured badges (e.g. a `fusdPRIV` NFT or a
+//!   liquidation keeper badge) can borrow at a discounted interest rate by presenting a proof.
+//! - Alternatively, `flash_loan_with_callback` lends fUSD to a callback method on another component and checks
+//!   that the loan plus interest is returned by it, allowing other components to use flash loans in a single call.
+//! - Collateral flash loans work the same way, using a transient `CollateralLoanReceipt` NFT. The collateral is lent
+//!   from the idle collateral in the Flux vaults (`leftovers` first), leaving CDP accounting untouched. The fee is paid
+//!   in the borrowed collateral and is returned to the caller of `pay_back_collateral` to be sent to the stability pool
+//!   of that collateral.
 
 use crate::flux_component::flux_component::*;
 use scrypto::prelude::*;
@@ -28,15 +37,54 @@ pub struct LoanReceipt {
     pub interest: Decimal,
 }
 
+/// Represents the non-fungible data associated with a collateral flash loan receipt.
+/// This NFT is transient and must be returned (burned) in the same transaction it was minted.
+#[derive(ScryptoSbor, NonFungibleData)]
+pub struct CollateralLoanReceipt {
+    /// The collateral that was borrowed.
+    pub collateral: ResourceAddress,
+    /// The amount of collateral borrowed.
+    pub borrowed_amount: Decimal,
+    /// The part of `borrowed_amount` that was taken from the collateral's leftovers vault.
+    pub from_leftovers: Decimal,
+    /// The fee rate (e.g., 0.001 for 0.1%) applicable to this loan at the time of borrowing.
+    pub interest: Decimal,
+}
+
+/// A destination for the interest paid on fUSD flash loans.
+#[derive(ScryptoSbor, Clone, PartialEq, Debug)]
+pub enum FlashLoanFeeDestination {
+    /// Kept in the `interest_vault` of the `FlashLoans` component, retrievable through `retrieve_interest`.
+    SurplusBuffer,
+    /// Sent to `method_name(fee: Bucket)` on `component` (e.g. `receive_rewards` on the `PayoutComponent`).
+    /// The method must be public, it is called without the controller badge.
+    Component {
+        component: ComponentAddress,
+        method_name: String,
+    },
+    /// Deposited into the stability pool of `collateral` through `receive_fusd_fee` on the `StabilityPools` component.
+    StabilityPool {
+        stability_pools: ComponentAddress,
+        collateral: ResourceAddress,
+    },
+}
+
 #[blueprint]
-#[types(LoanReceipt)]
+#[types(LoanReceipt, CollateralLoanReceipt)]
 mod flash_loans {
     enable_method_auth! {
         methods {
             borrow => restrict_to: [OWNER];
             settings => restrict_to: [OWNER];
+            set_caps => restrict_to: [OWNER];
+            set_fee_tier => restrict_to: [OWNER];
+            set_fee_destinations => restrict_to: [OWNER];
             pay_back => restrict_to: [OWNER];
+            flash_loan_with_callback => restrict_to: [OWNER];
             retrieve_interest => restrict_to: [OWNER];
+            collateral_settings => restrict_to: [OWNER];
+            borrow_collateral => restrict_to: [OWNER];
+            pay_back_collateral => restrict_to: [OWNER];
         }
     }
 
@@ -59,6 +107,24 @@ mod flash_loans {
         /// Tracks the total amount of fUSD currently loaned out via active flash loans (within transactions).
         /// Note: This might not be strictly necessary given the transient nature but can be useful for monitoring.
         amount_loaned: Decimal,
+        /// Maximum amount of fUSD per single flash loan. `None` means uncapped.
+        max_loan_amount: Option<Decimal>,
+        /// Maximum aggregate amount of fUSD borrowed within a single transaction. `None` means uncapped.
+        max_transaction_amount: Option<Decimal>,
+        /// Hash of the last transaction a flash loan was taken out in.
+        last_transaction_hash: Hash,
+        /// Aggregate amount of fUSD borrowed within the transaction identified by `last_transaction_hash`.
+        transaction_loaned: Decimal,
+        /// Discounted interest rates, keyed by the resource address of the badge qualifying for them.
+        fee_tiers: HashMap<ResourceAddress, Decimal>,
+        /// Destinations the interest is routed to on repayment, with their relative weights.
+        fee_destinations: Vec<(FlashLoanFeeDestination, Decimal)>,
+        /// Manages the creation and burning of the transient `CollateralLoanReceipt` NFTs.
+        collateral_loan_receipt_manager: ResourceManager,
+        /// The fee rate charged on new collateral flash loans, paid in the borrowed collateral.
+        collateral_interest: Decimal,
+        /// A flag indicating whether collateral flash loans are currently enabled.
+        collateral_loans_enabled: bool,
     }
 
     impl FlashLoans {
@@ -126,6 +192,33 @@ mod flash_loans {
                 .create_with_no_initial_supply()
                 .into();
 
+            let collateral_loan_receipt_manager: ResourceManager =
+                <scrypto::prelude::ResourceBuilder as flash_loans::flash_loans::FlashLoansResourceBuilder>::new_integer_non_fungible_with_registered_type::<CollateralLoanReceipt>(OwnerRole::Fixed(rule!(
+                    require_amount(dec!("0.75"), controller_badge.resource_address())
+                )))
+                .metadata(metadata!(
+                    init {
+                        "name" => "Collateral Flash Loan Receipt", locked;
+                        "symbol" => "fusdCFLASH", locked;
+                        "description" => "A receipt for your collateral flash loan", locked;
+                        "info_url" => "https://flux.ilikeitstable.com", updatable;
+                    }
+                ))
+                .mint_roles(mint_roles!(
+                    minter => rule!(require(global_caller(component_address)));
+                    minter_updater => rule!(deny_all);
+                ))
+                .burn_roles(burn_roles!(
+                    burner => rule!(require(global_caller(component_address)));
+                    burner_updater => rule!(deny_all);
+                ))
+                .deposit_roles(deposit_roles!(
+                    depositor => rule!(deny_all);
+                    depositor_updater => rule!(deny_all);
+                ))
+                .create_with_no_initial_supply()
+                .into();
+
             let controller_address: ResourceAddress = controller_badge.resource_address();
 
             //create the flash loan component
@@ -138,6 +231,15 @@ mod flash_loans {
                 loan_receipt_counter: 0,
                 enabled: true,
                 amount_loaned: dec!(0),
+                max_loan_amount: None,
+                max_transaction_amount: None,
+                last_transaction_hash: Runtime::transaction_hash(),
+                transaction_loaned: dec!(0),
+                fee_tiers: HashMap::new(),
+                fee_destinations: vec![],
+                collateral_loan_receipt_manager,
+                collateral_interest: dec!(0),
+                collateral_loans_enabled: true,
             }
             .instantiate()
             .prepare_to_globalize(OwnerRole::Fixed(rule!(require(controller_address))))
@@ -165,6 +267,53 @@ mod flash_loans {
             self.enabled = enabled;
         }
 
+        /// Sets the flash loan caps.
+        ///
+        /// # Arguments
+        /// * `max_loan_amount`: Maximum amount of fUSD per single flash loan, `None` for no cap.
+        /// * `max_transaction_amount`: Maximum aggregate amount of fUSD borrowed within one transaction, `None` for no cap.
+        pub fn set_caps(&mut self, max_loan_amount: Option<Decimal>, max_transaction_amount: Option<Decimal>) {
+            self.max_loan_amount = max_loan_amount;
+            self.max_transaction_amount = max_transaction_amount;
+        }
+
+        /// Sets or removes a discounted interest rate for holders of a badge.
+        ///
+        /// # Arguments
+        /// * `badge`: The `ResourceAddress` of the badge (e.g. the `fusdPRIV` NFT or a keeper badge).
+        /// * `interest`: The interest rate for holders of the badge, `None` to remove the fee tier.
+        pub fn set_fee_tier(&mut self, badge: ResourceAddress, interest: Option<Decimal>) {
+            match interest {
+                Some(interest) => {
+                    assert!(interest >= Decimal::ZERO, "Interest can't be negative.");
+                    self.fee_tiers.insert(badge, interest);
+                }
+                None => {
+                    self.fee_tiers.remove(&badge);
+                }
+            }
+        }
+
+        /// Sets the destinations flash loan interest is routed to on repayment.
+        ///
+        /// # Arguments
+        /// * `fee_destinations`: Destinations with their relative weights. An empty list keeps all interest as surplus buffer.
+        ///
+        /// # Panics
+        /// * If a weight is negative, or all weights are zero.
+        pub fn set_fee_destinations(&mut self, fee_destinations: Vec<(FlashLoanFeeDestination, Decimal)>) {
+            assert!(
+                fee_destinations.iter().all(|(_, weight)| *weight >= Decimal::ZERO),
+                "Weights can't be negative."
+            );
+            assert!(
+                fee_destinations.is_empty()
+                    || fee_destinations.iter().any(|(_, weight)| *weight > Decimal::ZERO),
+                "At least one weight must be positive."
+            );
+            self.fee_destinations = fee_destinations;
+        }
+
         /// Takes out a flash loan of fUSD tokens.
         ///
         /// This method allows a user (authorized via OWNER role) to borrow fUSD. It mints the requested fUSD
@@ -172,6 +321,7 @@ mod flash_loans {
         ///
         /// # Arguments
         /// * `amount`: The `Decimal` amount of fUSD tokens to borrow.
+        /// * `fee_tier_proof`: Optional proof of a badge qualifying for a discounted interest rate.
         ///
         /// # Returns
         /// * `(Bucket, Bucket)`: A tuple containing:
@@ -180,21 +330,22 @@ mod flash_loans {
         ///
         /// # Panics
         /// * If flash loans are currently disabled (`self.enabled == false`).
+        /// * If the loan exceeds the per loan or per transaction cap.
+        /// * If the `fee_tier_proof` is not of a badge with a fee tier.
         ///
         /// # Logic
-        /// 1. Asserts that flash loans are enabled.
+        /// 1. Asserts that flash loans are enabled and the caps aren't exceeded.
         /// 2. Increments `amount_loaned` (for tracking).
-        /// 3. Creates the `LoanReceipt` data with the borrowed amount and current interest rate.
+        /// 3. Creates the `LoanReceipt` data with the borrowed amount and the applicable interest rate.
         /// 4. Mints a new `LoanReceipt` NFT using the `loan_receipt_manager` and the current `loan_receipt_counter`.
         /// 5. Increments the `loan_receipt_counter`.
         /// 6. Authorizes the `Flux` component (using the controller badge) to mint the requested `amount` of fUSD via `free_fusd`.
         /// 7. Returns the bucket of borrowed fUSD and the bucket containing the `LoanReceipt` NFT.
-        pub fn borrow(&mut self, amount: Decimal) -> (Bucket, Bucket) {
-            assert!(self.enabled, "Flash loans are disabled.");
-            self.amount_loaned += amount;
+        pub fn borrow(&mut self, amount: Decimal, fee_tier_proof: Option<Proof>) -> (Bucket, Bucket) {
+            self.register_loan(amount);
             let loan_receipt = LoanReceipt {
                 borrowed_amount: amount,
-                interest: self.interest,
+                interest: self.get_interest(fee_tier_proof),
             };
 
             let receipt: Bucket = self.loan_receipt_manager.mint_non_fungible(
@@ -248,31 +399,155 @@ mod flash_loans {
                 .loan_receipt_manager
                 .get_non_fungible_data(&receipt_bucket.as_non_fungible().non_fungible_local_id());
 
+            let payment = self.settle_loan(payment, receipt.borrowed_amount, receipt.interest);
+
+            receipt_bucket.burn();
+
+            payment
+        }
+
+        /// Takes out a flash loan of fUSD and hands it to a callback method on another component, within a single call.
+        ///
+        /// This is an alternative to the `borrow` / `pay_back` receipt flow for components that want to use flash loans
+        /// programmatically. The callback is called as `method_name(loan: Bucket, args: Vec<u8>) -> Bucket` and must
+        /// return at least the borrowed amount plus interest in fUSD. The callback can't call back into this component
+        /// (or the component calling this method), as it is still locked by this call.
+        ///
+        /// # Arguments
+        /// * `amount`: The `Decimal` amount of fUSD tokens to borrow.
+        /// * `target_component`: The `ComponentAddress` of the component to call with the borrowed fUSD.
+        /// * `method_name`: The name of the callback method on `target_component`.
+        /// * `args`: Opaque arguments passed to the callback, e.g. SBOR encoded with `scrypto_encode`.
+        /// * `fee_tier_proof`: Optional proof of a badge qualifying for a discounted interest rate.
+        ///
+        /// # Returns
+        /// * `Bucket`: Any fUSD returned by the callback in excess of the borrowed amount plus interest.
+        ///
+        /// # Panics
+        /// * If flash loans are currently disabled (`self.enabled == false`).
+        /// * If the loan exceeds the per loan or per transaction cap.
+        /// * If the callback fails or doesn't return a `Bucket`.
+        /// * If the returned bucket is not fUSD or holds less than the borrowed amount plus interest.
+        pub fn flash_loan_with_callback(
+            &mut self,
+            amount: Decimal,
+            target_component: ComponentAddress,
+            method_name: String,
+            args: Vec<u8>,
+            fee_tier_proof: Option<Proof>,
+        ) -> Bucket {
+            self.register_loan(amount);
+            let interest = self.get_interest(fee_tier_proof);
+
+            let loan_bucket: Bucket = self
+                .badge_vault
+                .authorize_with_amount(dec!("0.75"), || self.flux.free_fusd(amount));
+
+            let target: Global<AnyComponent> = Global::from(target_component);
+            let payment: Bucket = target.call_raw(&method_name, scrypto_args!(loan_bucket, args));
+
+            self.settle_loan(payment, amount, interest)
+        }
+
+        /// Alters the settings for collateral flash loans.
+        ///
+        /// # Arguments
+        /// * `interest`: The new fee rate to be charged on collateral flash loans (e.g., 0.001 for 0.1%).
+        /// * `enabled`: A boolean flag to enable (`true`) or disable (`false`) collateral flash loans.
+        pub fn collateral_settings(&mut self, interest: Decimal, enabled: bool) {
+            assert!(interest >= Decimal::ZERO, "Interest can't be negative.");
+            self.collateral_interest = interest;
+            self.collateral_loans_enabled = enabled;
+        }
+
+        /// Takes out a flash loan of collateral held by the `Flux` component.
+        ///
+        /// The collateral is taken from the collateral's `leftovers` vault first and then from its main vault,
+        /// without altering any CDP accounting. A transient `CollateralLoanReceipt` NFT is minted, which must be
+        /// returned to `pay_back_collateral` in the same transaction.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral to borrow.
+        /// * `amount`: The `Decimal` amount of collateral to borrow.
+        ///
+        /// # Returns
+        /// * `(Bucket, Bucket)`: A tuple containing:
+        ///     1. The borrowed collateral in a `Bucket`.
+        ///     2. The transient `CollateralLoanReceipt` NFT in a `Bucket`.
+        ///
+        /// # Panics
+        /// * If collateral flash loans are currently disabled.
+        /// * If the `Flux` component doesn't hold enough of the collateral.
+        pub fn borrow_collateral(&mut self, collateral: ResourceAddress, amount: Decimal) -> (Bucket, Bucket) {
+            assert!(self.collateral_loans_enabled, "Collateral flash loans are disabled.");
+            assert!(amount > Decimal::ZERO, "Amount must be positive.");
+
+            let (loan_bucket, from_leftovers): (Bucket, Decimal) = self
+                .badge_vault
+                .authorize_with_amount(dec!("0.75"), || self.flux.flash_borrow_collateral(collateral, amount));
+
+            let loan_receipt = CollateralLoanReceipt {
+                collateral,
+                borrowed_amount: amount,
+                from_leftovers,
+                interest: self.collateral_interest,
+            };
+
+            let receipt: Bucket = self.collateral_loan_receipt_manager.mint_non_fungible(
+                &NonFungibleLocalId::integer(self.loan_receipt_counter),
+                loan_receipt,
+            );
+            self.loan_receipt_counter += 1;
+
+            (loan_bucket, receipt)
+        }
+
+        /// Pays back a collateral flash loan.
+        ///
+        /// This method **must** be called in the same transaction as the `borrow_collateral` method that issued the
+        /// `receipt_bucket`. The principal is returned to the `Flux` vaults it was taken from and the fee is split off.
+        ///
+        /// # Arguments
+        /// * `receipt_bucket`: The `Bucket` containing the transient `CollateralLoanReceipt` NFT.
+        /// * `payment`: A `Bucket` containing the borrowed collateral plus the fee.
+        ///
+        /// # Returns
+        /// * `(Bucket, Bucket)`: A tuple containing:
+        ///     1. Any remaining collateral from the `payment` bucket after the loan and fee have been settled.
+        ///     2. The fee, to be deposited into the stability pool of the collateral.
+        ///
+        /// # Panics
+        /// * If the `receipt_bucket` does not contain a valid `CollateralLoanReceipt` managed by this component.
+        /// * If the `payment` is not the borrowed collateral, or is less than the borrowed amount plus fee.
+        pub fn pay_back_collateral(&mut self, receipt_bucket: Bucket, mut payment: Bucket) -> (Bucket, Bucket) {
+            assert!(
+                receipt_bucket.resource_address() == self.collateral_loan_receipt_manager.address(),
+                "Invalid receipt"
+            );
+
+            let receipt: CollateralLoanReceipt = self
+                .collateral_loan_receipt_manager
+                .get_non_fungible_data(&receipt_bucket.as_non_fungible().non_fungible_local_id());
+
+            assert!(
+                payment.resource_address() == receipt.collateral,
+                "Wrong collateral paid back."
+            );
             assert!(
                 payment.amount() >= receipt.borrowed_amount * (dec!(1) + receipt.interest),
-                "Not enough fUSD paid back."
+                "Not enough collateral paid back."
             );
 
+            let principal = payment.take(receipt.borrowed_amount);
             self.badge_vault.authorize_with_amount(dec!("0.75"), || {
-                self.flux.burn_fusd(payment.take(receipt.borrowed_amount))
+                self.flux.flash_return_collateral(principal, receipt.from_leftovers)
             });
 
-            if receipt.interest > dec!(0) {
-                if self.interest_vault.is_none() {
-                    self.interest_vault = Some(Vault::with_bucket(
-                        payment.take(receipt.interest * receipt.borrowed_amount),
-                    ));
-                } else {
-                    self.interest_vault
-                        .as_mut()
-                        .unwrap()
-                        .put(payment.take(receipt.interest * receipt.borrowed_amount));
-                }
-            }
+            let fee = payment.take(receipt.interest * receipt.borrowed_amount);
 
             receipt_bucket.burn();
 
-            payment
+            (payment, fee)
         }
 
         /// Allows an authorized user (OWNER) to retrieve the accumulated interest from the `interest_vault`.
@@ -285,5 +560,145 @@ mod flash_loans {
         pub fn retrieve_interest(&mut self) -> Bucket {
             self.interest_vault.as_mut().expect("Interest vault not initialized.").take_all()
         }
+
+        /// Checks the flash loan caps for a new loan and tracks the loaned amounts.
+        ///
+        /// # Panics
+        /// * If flash loans are currently disabled.
+        /// * If `amount` exceeds `max_loan_amount`, or the aggregate amount borrowed in this transaction exceeds `max_transaction_amount`.
+        fn register_loan(&mut self, amount: Decimal) {
+            assert!(self.enabled, "Flash loans are disabled.");
+
+            if let Some(max_loan_amount) = self.max_loan_amount {
+                assert!(amount <= max_loan_amount, "Flash loan exceeds the maximum loan amount.");
+            }
+
+            let transaction_hash = Runtime::transaction_hash();
+            if transaction_hash != self.last_transaction_hash {
+                self.last_transaction_hash = transaction_hash;
+                self.transaction_loaned = Decimal::ZERO;
+            }
+            self.transaction_loaned += amount;
+
+            if let Some(max_transaction_amount) = self.max_transaction_amount {
+                assert!(
+                    self.transaction_loaned <= max_transaction_amount,
+                    "Flash loans exceed the maximum amount per transaction."
+                );
+            }
+
+            self.amount_loaned += amount;
+        }
+
+        /// Determines the interest rate for a new loan, applying the fee tier of the presented badge if any.
+        /// The fee tier never increases the interest rate above the default one.
+        ///
+        /// # Panics
+        /// * If the proof is not of a badge with a fee tier, or is empty.
+        fn get_interest(&self, fee_tier_proof: Option<Proof>) -> Decimal {
+            match fee_tier_proof {
+                None => self.interest,
+                Some(proof) => {
+                    let badge = proof.resource_address();
+                    let tier_interest = *self
+                        .fee_tiers
+                        .get(&badge)
+                        .expect("No fee tier for this badge.");
+                    let checked_proof = proof.check(badge);
+                    assert!(checked_proof.amount() > Decimal::ZERO, "Empty fee tier proof.");
+
+                    tier_interest.min(self.interest)
+                }
+            }
+        }
+
+        /// Routes flash loan interest to the configured fee destinations, split by their weights.
+        /// Without destinations, the interest is kept in the `interest_vault` as surplus buffer.
+        fn route_fees(&mut self, mut fees: Bucket) {
+            if self.fee_destinations.is_empty() {
+                self.put_in_interest_vault(fees);
+                return;
+            }
+
+            let total_fees = fees.amount();
+            let total_weight = self
+                .fee_destinations
+                .iter()
+                .fold(Decimal::ZERO, |total, (_, weight)| total + *weight);
+            let destinations = self.fee_destinations.clone();
+            let last_index = destinations.len() - 1;
+
+            for (index, (destination, weight)) in destinations.into_iter().enumerate() {
+                let fee = if index == last_index {
+                    fees.take(fees.amount())
+                } else {
+                    fees.take_advanced(
+                        total_fees * weight / total_weight,
+                        WithdrawStrategy::Rounded(RoundingMode::ToZero),
+                    )
+                };
+
+                if fee.amount() == Decimal::ZERO {
+                    fee.drop_empty();
+                    continue;
+                }
+
+                match destination {
+                    FlashLoanFeeDestination::SurplusBuffer => self.put_in_interest_vault(fee),
+                    FlashLoanFeeDestination::Component { component, method_name } => {
+                        let receiver: Global<AnyComponent> = Global::from(component);
+                        receiver.call_raw::<()>(&method_name, scrypto_args!(fee));
+                    }
+                    FlashLoanFeeDestination::StabilityPool { stability_pools, collateral } => {
+                        let receiver: Global<AnyComponent> = Global::from(stability_pools);
+                        self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                            receiver.call_raw::<()>("receive_fusd_fee", scrypto_args!(collateral, fee))
+                        });
+                    }
+                }
+            }
+
+            fees.drop_empty();
+        }
+
+        fn put_in_interest_vault(&mut self, bucket: Bucket) {
+            if self.interest_vault.is_none() {
+                self.interest_vault = Some(Vault::with_bucket(bucket));
+            } else {
+                self.interest_vault.as_mut().unwrap().put(bucket);
+            }
+        }
+
+        /// Settles an fUSD flash loan: burns the principal and routes the interest to the fee destinations.
+        ///
+        /// # Arguments
+        /// * `payment`: A `Bucket` containing the fUSD repayment (principal + interest).
+        /// * `borrowed_amount`: The borrowed principal.
+        /// * `interest`: The interest rate of the loan.
+        ///
+        /// # Returns
+        /// * `Bucket`: Any remaining fUSD from the `payment` bucket.
+        ///
+        /// # Panics
+        /// * If the `payment` amount is less than the required amount (borrowed amount + interest).
+        /// * If the `payment` is not fUSD.
+        fn settle_loan(&mut self, mut payment: Bucket, borrowed_amount: Decimal, interest: Decimal) -> Bucket {
+            assert!(
+                payment.amount() >= borrowed_amount * (dec!(1) + interest),
+                "Not enough fUSD paid back."
+            );
+
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                self.flux.burn_fusd(payment.take(borrowed_amount))
+            });
+            self.amount_loaned -= borrowed_amount;
+
+            if interest > dec!(0) {
+                let fees = payment.take(interest * borrowed_amount);
+                self.route_fees(fees);
+            }
+
+            payment
+        }
     }
 }
@@ -40,7 +40,7 @@
 //! - **`Proxy`:** Usually the main entry point for users, handling oracle price feeds and authorization.
 //! - **`StabilityPools`:** Manages liquidations and redemptions, acting as the primary source of liquidity
 //!   for these operations and distributing rewards/losses.
-//! - **`FlashLoans`:** Borrows fUSD directly from this component for flash loan operations.
+//! - **`FlashLoans`:** Borrows fUSD and idle collateral directly from this component for flash loan operations.
 //! - **Oracle:** Provides price feeds for collateral assets.
 
 use crate::events::*;
@@ -76,6 +76,7 @@ mod flux_component {
             liquidate_cdp => restrict_to: [OWNER];
             change_collateral_price => restrict_to: [OWNER];
             edit_collateral => restrict_to: [OWNER];
+            set_debt_ceiling => restrict_to: [OWNER];
             mint_controller_badge => restrict_to: [OWNER];
             set_stops => restrict_to: [OWNER];
             set_max_vector_length => restrict_to: [OWNER];
@@ -89,6 +90,8 @@ mod flux_component {
             optimal_batch_redemption => restrict_to: [OWNER];
             free_fusd => restrict_to: [OWNER];
             burn_fusd => restrict_to: [OWNER];
+            flash_borrow_collateral => restrict_to: [OWNER];
+            flash_return_collateral => restrict_to: [OWNER];
             burn_loan_receipt => restrict_to: [OWNER];
             charge_interest => restrict_to: [OWNER];
             set_redemption_parameters => restrict_to: [OWNER];
@@ -105,9 +108,11 @@ mod flux_component {
             get_collateral_infos => PUBLIC;
             get_crs => PUBLIC;
             get_next_liquidations => PUBLIC;
+            get_liquidatable_cdps => PUBLIC;
             get_next_redemptions => PUBLIC;
             get_debt_in_front => PUBLIC;
             get_interest_infos => PUBLIC;
+            get_interest_infos_limited => PUBLIC;
             get_total_debt => PUBLIC;
             get_marked_liquidation_date => PUBLIC;
             check_liquidate_cdp => PUBLIC;
@@ -516,6 +521,7 @@ mod flux_component {
 
             let mut fusd_tokens = self.mint_fusd(collateral_address, interest, pool_debt);
             self.add_debt_to_collateral(collateral_address, fusd_tokens.amount());
+            self.check_debt_ceiling(collateral_address);
 
             assert!(
                 fusd_tokens.amount() >= self.parameters.minimum_mint,
@@ -1229,6 +1235,7 @@ mod flux_component {
             );
 
             self.add_debt_to_collateral(receipt_data.collateral_address, tokens.amount());
+            self.check_debt_ceiling(receipt_data.collateral_address);
 
             self.collaterals
                 .get_mut(&receipt_data.collateral_address)
@@ -2555,6 +2562,7 @@ mod flux_component {
                 ratios_by_interest: KeyValueStore::new_with_registered_type(),
                 interests: AvlTree::new(),
                 marked_cdps: KeyValueStore::new_with_registered_type(),
+                debt_ceiling: None,
             };
 
             self.collaterals.insert(address, info);
@@ -2588,6 +2596,27 @@ mod flux_component {
             });
         }
 
+        /// Sets the maximum total debt that can be minted against a collateral.
+        /// Only new borrowing is restricted, interest can still push the total debt above the ceiling.
+        ///
+        /// # Arguments
+        /// * `address`: The ResourceAddress of the collateral.
+        /// * `debt_ceiling`: The maximum total debt, or `None` for no limit.
+        ///
+        /// # Panics
+        /// * If `address` is not a collateral.
+        /// * If `debt_ceiling` is negative.
+        pub fn set_debt_ceiling(&mut self, address: ResourceAddress, debt_ceiling: Option<Decimal>) {
+            if let Some(ceiling) = debt_ceiling {
+                assert!(ceiling >= Decimal::ZERO, "Debt ceiling can't be negative.");
+            }
+
+            self.collaterals
+                .get_mut(&address)
+                .expect("Collateral not found.")
+                .debt_ceiling = debt_ceiling;
+        }
+
         /// Sets parameters related to the dynamic redemption fee calculation.
         ///
         /// # Arguments
@@ -2706,6 +2735,65 @@ mod flux_component {
             bucket.burn();
         }
 
+        /// Lends out collateral for a flash loan, without touching any CDP accounting.
+        ///
+        /// Collateral is taken from the `leftovers` vault first, and then from the main collateral vault.
+        /// The tracked `collateral_amount`s are not altered, so the loaned collateral must be returned
+        /// via `flash_return_collateral` within the same transaction (enforced by the `FlashLoans` component).
+        ///
+        /// # Arguments
+        /// * `collateral_address`: The `ResourceAddress` of the collateral to borrow.
+        /// * `amount`: The `Decimal` amount of collateral to borrow.
+        ///
+        /// # Returns
+        /// * `(Bucket, Decimal)`: A tuple containing:
+        ///     1. The borrowed collateral.
+        ///     2. The part of the borrowed amount that was taken from the `leftovers` vault.
+        ///
+        /// # Panics
+        /// * If the collateral is not accepted by the protocol.
+        /// * If the vaults don't hold enough collateral.
+        pub fn flash_borrow_collateral(
+            &mut self,
+            collateral_address: ResourceAddress,
+            amount: Decimal,
+        ) -> (Bucket, Decimal) {
+            let mut collateral_info = self
+                .collaterals
+                .get_mut(&collateral_address)
+                .expect("Collateral not accepted.");
+
+            assert!(
+                collateral_info.leftovers.amount() + collateral_info.vault.amount() >= amount,
+                "Not enough collateral available."
+            );
+
+            let from_leftovers = amount.min(collateral_info.leftovers.amount());
+            let mut loan = collateral_info.leftovers.take(from_leftovers);
+            loan.put(collateral_info.vault.take(amount - from_leftovers));
+
+            (loan, from_leftovers)
+        }
+
+        /// Returns collateral lent out by `flash_borrow_collateral` to the vaults it was taken from.
+        ///
+        /// # Arguments
+        /// * `collateral_bucket`: The returned collateral.
+        /// * `from_leftovers`: The part of the loan that was taken from the `leftovers` vault.
+        ///
+        /// # Panics
+        /// * If the collateral is not accepted by the protocol.
+        /// * If `collateral_bucket` holds less than `from_leftovers`.
+        pub fn flash_return_collateral(&mut self, mut collateral_bucket: Bucket, from_leftovers: Decimal) {
+            let mut collateral_info = self
+                .collaterals
+                .get_mut(&collateral_bucket.resource_address())
+                .expect("Collateral not accepted.");
+
+            collateral_info.leftovers.put(collateral_bucket.take(from_leftovers));
+            collateral_info.vault.put(collateral_bucket);
+        }
+
         /// Burns a CDP NFT (loan receipt) provided it meets the criteria for burning.
         ///
         /// A CDP NFT can only be burned if its associated loan is fully terminated
@@ -3089,6 +3177,98 @@ mod flux_component {
             Some(liquidations)
         }
 
+        /// Retrieves up to `max_count` CDPs of a collateral type that can be liquidated right away, together with
+        /// the fUSD needed to liquidate them.
+        ///
+        /// Walks the `ratios_by_interest` trees of all interest rates (including irredeemable loans) in ascending
+        /// order, and each tree from the lowest collateral ratio up, until the collateral ratio reaches the LCR or
+        /// `max_inspected` CDPs have been looked at, skipped ones included.
+        /// Skipped are:
+        /// - CDPs that are marked and still within their liquidation notice period.
+        /// - CDPs of privileged borrowers with a liquidation notice that aren't marked yet, as a liquidation
+        ///   would only mark them.
+        ///
+        /// # Arguments
+        /// * `collateral_address`: The `ResourceAddress` of the collateral to query.
+        /// * `max_count`: The maximum number (`u64`) of CDPs to return.
+        /// * `max_inspected`: The maximum number (`u64`) of CDPs to inspect, bounding the work of the call.
+        /// * `with_price`: The `Decimal` price of the collateral to use for LCR calculations.
+        ///
+        /// # Returns
+        /// * `Vec<(NonFungibleLocalId, Decimal)>`: The IDs of liquidatable CDPs and their real fUSD debt,
+        ///   ordered by interest rate, then by collateral ratio. Empty if the collateral address is invalid.
+        pub fn get_liquidatable_cdps(
+            &self,
+            collateral_address: ResourceAddress,
+            max_count: u64,
+            max_inspected: u64,
+            with_price: Decimal,
+        ) -> Vec<(NonFungibleLocalId, Decimal)> {
+            let mut liquidations: Vec<(NonFungibleLocalId, Decimal)> = vec![];
+
+            if max_count == 0 || max_inspected == 0 {
+                return liquidations;
+            }
+
+            let collateral = match self.collaterals.get(&collateral_address) {
+                Some(collateral) => collateral,
+                None => return liquidations,
+            };
+            let mut inspected: u64 = 0;
+
+            for (interest, interest_info, next_interest) in collateral.interests.range(dec!(-420)..) {
+                if let Some(collateral_ratios) = collateral.ratios_by_interest.get(&interest) {
+                    let debt_multiplier = interest_info.real_debt / interest_info.pool_debt;
+                    let lcr = collateral.mcr * (debt_multiplier / with_price);
+
+                    for (cr, cdp_ids, next_cr) in collateral_ratios.range(Decimal::ZERO..) {
+                        if cr >= lcr {
+                            break;
+                        }
+
+                        for cdp_id in cdp_ids {
+                            if inspected >= max_inspected {
+                                return liquidations;
+                            }
+                            inspected += 1;
+
+                            let cdp: Cdp = self.cdp_manager.get_non_fungible_data(&cdp_id);
+
+                            let skip = match collateral.marked_cdps.get(&cdp_id) {
+                                Some(liquidation_time) => !Clock::current_time_is_strictly_after(
+                                    *liquidation_time,
+                                    TimePrecision::Second,
+                                ),
+                                None => cdp.privileged_borrower.as_ref().is_some_and(|borrower| {
+                                    self.privileged_borrower_manager
+                                        .get_non_fungible_data::<PrivilegedBorrowerData>(borrower)
+                                        .liquidation_notice
+                                        .is_some()
+                                }),
+                            };
+
+                            if !skip {
+                                liquidations.push((cdp_id, cdp.pool_debt * debt_multiplier));
+                                if liquidations.len() as u64 >= max_count {
+                                    return liquidations;
+                                }
+                            }
+                        }
+
+                        if next_cr.is_none() {
+                            break;
+                        }
+                    }
+                }
+
+                if next_interest.is_none() {
+                    break;
+                }
+            }
+
+            liquidations
+        }
+
         /// Retrieves the `Cdp` data and current debt multiplier for a list of specified CDP IDs.
         ///
         /// The debt multiplier represents the current ratio of real fUSD debt to pool debt
@@ -3172,6 +3352,7 @@ mod flux_component {
                     leftovers: collateral_info.leftovers.amount(),
                     uncharged_interest: collateral_info.uncharged_interest.amount(),
                     accepted: collateral_info.accepted,
+                    debt_ceiling: collateral_info.debt_ceiling,
                 })
                 .collect()
         }
@@ -3223,6 +3404,41 @@ mod flux_component {
             interest_infos
         }
 
+        /// Same as `get_interest_infos`, but returns at most `limit` interest rate tiers, starting from the lowest.
+        /// Only the returned tiers are loaded, so this bounds the work of the call.
+        ///
+        /// # Arguments
+        /// * `collateral_address`: The `ResourceAddress` of the collateral to query.
+        /// * `start_interest`: Optional `Decimal` start of the interest rate range (inclusive, defaults to 0).
+        /// * `end_interest`: Optional `Decimal` end of the interest rate range (exclusive, defaults to max_interest + interval).
+        /// * `limit`: Maximum number of interest rate tiers to return.
+        ///
+        /// # Returns
+        /// * `Vec<InterestInfo>`: At most `limit` `InterestInfo` structs, ordered by interest rate.
+        ///
+        /// # Panics
+        /// * If the `collateral_address` is invalid.
+        pub fn get_interest_infos_limited(
+            &self,
+            collateral_address: ResourceAddress,
+            start_interest: Option<Decimal>,
+            end_interest: Option<Decimal>,
+            limit: u64,
+        ) -> Vec<InterestInfo> {
+            let start = start_interest.unwrap_or(dec!(-420));
+            let end = end_interest
+                .unwrap_or(self.parameters.max_interest + self.parameters.interest_interval);
+
+            self.collaterals
+                .get(&collateral_address)
+                .unwrap()
+                .interests
+                .range(start..end)
+                .take(limit as usize)
+                .map(|(_interest, interest_info, _next_interest)| interest_info)
+                .collect()
+        }
+
         /// Retrieves Collateral Ratio (CR) entries and associated CDP IDs within a specified range
         /// for a given collateral and interest rate.
         ///
@@ -3458,6 +3674,25 @@ mod flux_component {
                 .total_debt += amount;
         }
 
+        /// Checks that the total recorded debt of a collateral type doesn't exceed its debt ceiling.
+        /// Used internally after fUSD is borrowed against this collateral.
+        ///
+        /// # Arguments
+        /// * `collateral_address`: The ResourceAddress of the collateral.
+        ///
+        /// # Panics
+        /// * If the total debt exceeds the debt ceiling.
+        fn check_debt_ceiling(&self, collateral_address: ResourceAddress) {
+            let collateral_info = self.collaterals.get(&collateral_address).unwrap();
+
+            if let Some(debt_ceiling) = collateral_info.debt_ceiling {
+                assert!(
+                    collateral_info.total_debt <= debt_ceiling,
+                    "Debt ceiling of this collateral reached."
+                );
+            }
+        }
+
         /// Decreases the total recorded debt for a given collateral type.
         /// Used internally when fUSD debt backed by this collateral is repaid or redeemed.
         ///
@@ -4232,6 +4467,7 @@ pub struct CollateralInfo {
     pub ratios_by_interest: KeyValueStore<Decimal, AvlTree<Decimal, Vec<NonFungibleLocalId>>>,
     pub interests: AvlTree<Decimal, InterestInfo>,
     pub marked_cdps: KeyValueStore<NonFungibleLocalId, Instant>,
+    pub debt_ceiling: Option<Decimal>,
 }
 
 #[derive(ScryptoSbor, Clone)]
@@ -23,6 +23,11 @@
 //!   These pools act as the first line of defense in absorbing debt during liquidations, enhancing protocol stability
 //!   and providing yield opportunities for contributors. It also handles panic mode liquidations using centralized stablecoins.
 //! - `payout_component`: Implements the payout component for the protocol.
+//! - `product_sum_pool`: Implements the `ProductSumPool` component, a stability pool with product-sum accounting that
+//!   can be chosen per collateral instead of a `TwoResourcePool`. Deposits stay fUSD denominated and each depositor's
+//!   collateral gains and fUSD yield are tracked separately.
+//! - `liquidity_rewards_distributor`: Implements the `LiquidityRewardsDistributor` component, which streams the stability
+//!   pools' liquidity rewards on-ledger to stakers of a fUSD DEX LP token.
 
 pub mod flux_component;
 pub mod events;
@@ -31,3 +36,5 @@ pub mod proxy;
 pub mod shared_structs;
 pub mod stability_pools;
 pub mod payout_component;
+pub mod product_sum_pool;
+pub mod liquidity_rewards_distributor;
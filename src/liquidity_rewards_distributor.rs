@@ -0,0 +1,445 @@
+//! # Liquidity Rewards Distributor Blueprint
+//!
+//! This blueprint defines the `LiquidityRewardsDistributor` component, which distributes the `liquidity_rewards` of
+//! the stability pools on-ledger to stakers of a fUSD DEX LP token, replacing the manual airdrop of these rewards.
+//!
+//! ## Functionality
+//! - **Staking:** LP holders stake their LP tokens in exchange for a `LiquidityStake` NFT, which is required to
+//!   claim rewards and to unstake.
+//! - **Fetching:** Anyone can call `fetch_rewards`, which takes the `liquidity_rewards` of the configured stability
+//!   pools. The fetched rewards, together with what's left of the current stream, are streamed to stakers at a
+//!   constant rate over the next `stream_duration` seconds. A fetch never lowers the reward rate: rewards too small
+//!   to restart the stream at its current rate are added to the rate until the current `stream_end`.
+//! - **Accounting:** Streamed rewards are tracked by a reward-per-token accumulator. A stake's rewards are
+//!   `staked * (reward_per_token - reward_per_token_snapshot)`, so claims and stake changes are O(1).
+//!
+//! Rewards streamed while nothing is staked can't be distributed, so they're held back and added to the next stream.
+
+use crate::events::*;
+use crate::stability_pools::stability_pools::StabilityPools;
+use scrypto::prelude::*;
+
+/// The non-fungible data of a liquidity stake receipt.
+#[derive(ScryptoSbor, NonFungibleData, Clone)]
+pub struct LiquidityStake {
+    /// The amount of LP tokens staked.
+    pub staked: Decimal,
+    /// The distributor's `reward_per_token` at the last reward claim.
+    #[mutable]
+    pub reward_per_token_snapshot: PreciseDecimal,
+}
+
+/// A structure for returning the state of the distributor.
+#[derive(ScryptoSbor, Clone)]
+pub struct LiquidityRewardsDistributorInfo {
+    /// The collaterals whose stability pool liquidity rewards are distributed.
+    pub collaterals: Vec<ResourceAddress>,
+    /// The `ResourceAddress` of the staked LP token.
+    pub lp_token_address: ResourceAddress,
+    /// The total amount of LP tokens staked.
+    pub total_staked: Decimal,
+    /// The amount of fUSD streamed to stakers per second.
+    pub reward_rate: Decimal,
+    /// The time at which the current stream ends.
+    pub stream_end: Instant,
+    /// The duration (seconds) over which fetched rewards are streamed.
+    pub stream_duration: i64,
+    /// The amount of fUSD held for (current and future) rewards.
+    pub rewards: Decimal,
+}
+
+#[blueprint]
+#[types(LiquidityStake)]
+#[events(
+    LiquidityStakeEvent,
+    LiquidityUnstakeEvent,
+    LiquidityRewardsClaimEvent,
+    LiquidityRewardsFetchEvent,
+)]
+mod liquidity_rewards_distributor {
+    enable_method_auth! {
+        methods {
+            stake => PUBLIC;
+            unstake => PUBLIC;
+            claim_rewards => PUBLIC;
+            fetch_rewards => PUBLIC;
+            get_stake_rewards => PUBLIC;
+            get_distributor_info => PUBLIC;
+            set_parameters => restrict_to: [OWNER];
+        }
+    }
+
+    /// Streams stability pool liquidity rewards to stakers of a fUSD DEX LP token.
+    struct LiquidityRewardsDistributor {
+        /// Vault holding the controller badge, used to take the liquidity rewards from the stability pools.
+        controller_badge_vault: FungibleVault,
+        /// Global reference to the StabilityPools component.
+        stability_pools: Global<StabilityPools>,
+        /// The collaterals whose stability pool liquidity rewards are distributed.
+        collaterals: Vec<ResourceAddress>,
+        /// Vault holding the staked LP tokens.
+        lp_vault: Vault,
+        /// Vault holding the fetched fUSD rewards, until they are claimed.
+        rewards_vault: Vault,
+        /// `ResourceManager` for the `LiquidityStake` NFTs.
+        stake_manager: ResourceManager,
+        /// Counter used to generate the IDs of stake receipts.
+        stake_counter: u64,
+        /// The total amount of LP tokens staked.
+        total_staked: Decimal,
+        /// The accumulated fUSD rewards per staked LP token.
+        reward_per_token: PreciseDecimal,
+        /// The amount of fUSD streamed to stakers per second.
+        reward_rate: Decimal,
+        /// The duration (seconds) over which fetched rewards are streamed.
+        stream_duration: i64,
+        /// The time (seconds since the Unix epoch) at which the current stream ends.
+        stream_end: i64,
+        /// The time (seconds since the Unix epoch) up to which `reward_per_token` is updated.
+        last_update: i64,
+        /// fUSD streamed while nothing was staked, added to the next stream.
+        undistributed: Decimal,
+    }
+
+    impl LiquidityRewardsDistributor {
+        /// Instantiates the LiquidityRewardsDistributor.
+        ///
+        /// # Arguments
+        /// * `controller_badge`: A bucket containing a controller badge, used to take the liquidity rewards.
+        /// * `stability_pools_address`: The `ComponentAddress` of the StabilityPools component.
+        /// * `fusd_address`: The resource address of the fUSD token.
+        /// * `lp_token_address`: The resource address of the LP token that can be staked.
+        /// * `collaterals`: The collaterals whose stability pool liquidity rewards are distributed.
+        /// * `stream_duration`: The duration (seconds) over which fetched rewards are streamed.
+        /// * `owner_role`: The OwnerRole for the component and the stake receipt resource.
+        /// * `dapp_def_address`: The DApp definition address for metadata.
+        ///
+        /// # Returns
+        /// * `Global<LiquidityRewardsDistributor>`: A global reference to the new component.
+        ///
+        /// # Panics
+        /// * If `stream_duration` is not positive.
+        pub fn instantiate(
+            controller_badge: Bucket,
+            stability_pools_address: ComponentAddress,
+            fusd_address: ResourceAddress,
+            lp_token_address: ResourceAddress,
+            collaterals: Vec<ResourceAddress>,
+            stream_duration: i64,
+            owner_role: OwnerRole,
+            dapp_def_address: GlobalAddress,
+        ) -> Global<LiquidityRewardsDistributor> {
+            assert!(stream_duration > 0, "Stream duration must be positive.");
+
+            let (address_reservation, component_address) =
+                Runtime::allocate_component_address(LiquidityRewardsDistributor::blueprint_id());
+
+            let stake_manager: ResourceManager =
+                <scrypto::prelude::ResourceBuilder as liquidity_rewards_distributor::liquidity_rewards_distributor::LiquidityRewardsDistributorResourceBuilder>::new_integer_non_fungible_with_registered_type::<LiquidityStake>(owner_role.clone())
+                .metadata(metadata!(
+                    init {
+                        "name" => "Flux Liquidity Stake", updatable;
+                        "symbol" => "fusdSTAKE", updatable;
+                        "description" => "A receipt for LP tokens staked for Flux liquidity rewards", updatable;
+                        "info_url" => Url::of("https://flux.ilikeitstable.com"), updatable;
+                        "dapp_definitions" => vec![dapp_def_address], updatable;
+                    }
+                ))
+                .non_fungible_data_update_roles(non_fungible_data_update_roles!(
+                    non_fungible_data_updater => rule!(require(global_caller(component_address)));
+                    non_fungible_data_updater_updater => rule!(deny_all);
+                ))
+                .mint_roles(mint_roles!(
+                    minter => rule!(require(global_caller(component_address)));
+                    minter_updater => rule!(deny_all);
+                ))
+                .burn_roles(burn_roles!(
+                    burner => rule!(require(global_caller(component_address)));
+                    burner_updater => rule!(deny_all);
+                ))
+                .create_with_no_initial_supply()
+                .into();
+
+            let now = Clock::current_time_rounded_to_seconds().seconds_since_unix_epoch;
+
+            Self {
+                controller_badge_vault: FungibleVault::with_bucket(controller_badge.as_fungible()),
+                stability_pools: Global::from(stability_pools_address),
+                collaterals,
+                lp_vault: Vault::new(lp_token_address),
+                rewards_vault: Vault::new(fusd_address),
+                stake_manager,
+                stake_counter: 0,
+                total_staked: Decimal::ZERO,
+                reward_per_token: PreciseDecimal::ZERO,
+                reward_rate: Decimal::ZERO,
+                stream_duration,
+                stream_end: now,
+                last_update: now,
+                undistributed: Decimal::ZERO,
+            }
+            .instantiate()
+            .prepare_to_globalize(owner_role)
+            .with_address(address_reservation)
+            .metadata(metadata! {
+                init {
+                    "name" => "Flux Liquidity Rewards Distributor".to_string(), updatable;
+                    "description" => "Streams Flux liquidity rewards to staked fUSD LP tokens.".to_string(), updatable;
+                    "dapp_definition" => dapp_def_address, updatable;
+                    "info_url" => Url::of("https://flux.ilikeitstable.com"), updatable;
+                    "icon_url" => Url::of("https://flux.ilikeitstable.com/flux-logo.png"), updatable;
+                }
+            })
+            .globalize()
+        }
+
+        /// Stakes LP tokens.
+        ///
+        /// # Arguments
+        /// * `lp_tokens`: A `Bucket` containing the LP tokens to stake.
+        ///
+        /// # Returns
+        /// * `Bucket`: A `LiquidityStake` NFT, required to claim rewards and unstake.
+        ///
+        /// # Panics
+        /// * If the bucket doesn't contain the staked LP token, or is empty.
+        pub fn stake(&mut self, lp_tokens: Bucket) -> Bucket {
+            assert!(
+                lp_tokens.resource_address() == self.lp_vault.resource_address(),
+                "Invalid LP token."
+            );
+            assert!(lp_tokens.amount() > Decimal::ZERO, "Stake must be positive.");
+
+            self.update_reward_per_token();
+
+            let amount = lp_tokens.amount();
+            self.lp_vault.put(lp_tokens);
+            self.total_staked += amount;
+
+            self.stake_counter += 1;
+            let stake_id = NonFungibleLocalId::integer(self.stake_counter);
+
+            Runtime::emit_event(LiquidityStakeEvent {
+                stake_id: stake_id.clone(),
+                amount,
+            });
+
+            self.stake_manager.mint_non_fungible(
+                &stake_id,
+                LiquidityStake {
+                    staked: amount,
+                    reward_per_token_snapshot: self.reward_per_token,
+                },
+            )
+        }
+
+        /// Unstakes the LP tokens of a stake, burning the stake receipt and paying out its outstanding rewards.
+        ///
+        /// # Arguments
+        /// * `stake_receipt`: A `Bucket` containing a single `LiquidityStake` NFT.
+        ///
+        /// # Returns
+        /// * `(Bucket, Bucket)`: The staked LP tokens and the fUSD rewards.
+        ///
+        /// # Panics
+        /// * If the bucket doesn't contain a single stake receipt.
+        pub fn unstake(&mut self, stake_receipt: Bucket) -> (Bucket, Bucket) {
+            assert!(
+                stake_receipt.resource_address() == self.stake_manager.address(),
+                "Invalid stake receipt."
+            );
+            let stake_id = stake_receipt.as_non_fungible().non_fungible_local_id();
+            let stake: LiquidityStake = self.stake_manager.get_non_fungible_data(&stake_id);
+
+            self.update_reward_per_token();
+            let rewards = self.take_stake_rewards(&stake);
+
+            self.total_staked -= stake.staked;
+            stake_receipt.burn();
+
+            Runtime::emit_event(LiquidityUnstakeEvent {
+                stake_id,
+                amount: stake.staked,
+                rewards: rewards.amount(),
+            });
+
+            (self.lp_vault.take(stake.staked), rewards)
+        }
+
+        /// Claims the fUSD rewards earned by a stake.
+        ///
+        /// # Arguments
+        /// * `stake_proof`: A `Proof` of a single `LiquidityStake` NFT.
+        ///
+        /// # Returns
+        /// * `Bucket`: The claimed fUSD rewards.
+        ///
+        /// # Panics
+        /// * If the proof isn't of a single stake receipt.
+        pub fn claim_rewards(&mut self, stake_proof: Proof) -> Bucket {
+            let stake_id = stake_proof
+                .check_with_message(self.stake_manager.address(), "Invalid stake receipt.")
+                .as_non_fungible()
+                .non_fungible_local_id();
+            let stake: LiquidityStake = self.stake_manager.get_non_fungible_data(&stake_id);
+
+            self.update_reward_per_token();
+            let rewards = self.take_stake_rewards(&stake);
+
+            self.stake_manager.update_non_fungible_data(
+                &stake_id,
+                "reward_per_token_snapshot",
+                self.reward_per_token,
+            );
+
+            Runtime::emit_event(LiquidityRewardsClaimEvent {
+                stake_id,
+                amount: rewards.amount(),
+            });
+
+            rewards
+        }
+
+        /// Takes the liquidity rewards of the configured stability pools and streams them to stakers.
+        ///
+        /// Nothing changes if no rewards were fetched. Otherwise the fetched rewards, the part of the current stream
+        /// that hasn't been streamed yet and any undistributed rewards are streamed at a constant rate over the next
+        /// `stream_duration` seconds, unless that would lower the current reward rate. In that case the current
+        /// `stream_end` is kept and the fetched rewards are added to the rate, so small fetches can't stretch a stream.
+        /// Can be called by anyone.
+        pub fn fetch_rewards(&mut self) {
+            self.update_reward_per_token();
+
+            let mut fetched = Decimal::ZERO;
+            for collateral in self.collaterals.clone() {
+                let rewards: Bucket = self.controller_badge_vault.authorize_with_amount(Decimal::ONE, || {
+                    self.stability_pools.take_liquidity_rewards(collateral)
+                });
+                fetched += rewards.amount();
+                self.rewards_vault.put(rewards);
+            }
+
+            if fetched == Decimal::ZERO {
+                return;
+            }
+
+            let now = Clock::current_time_rounded_to_seconds().seconds_since_unix_epoch;
+            let remaining = if now < self.stream_end {
+                self.reward_rate * Decimal::from(self.stream_end - now)
+            } else {
+                Decimal::ZERO
+            };
+            let to_stream = fetched + remaining + self.undistributed;
+            let restarted_rate = to_stream / Decimal::from(self.stream_duration);
+
+            if now < self.stream_end && restarted_rate < self.reward_rate {
+                self.reward_rate = to_stream / Decimal::from(self.stream_end - now);
+            } else {
+                self.reward_rate = restarted_rate;
+                self.stream_end = now + self.stream_duration;
+            }
+            self.undistributed = Decimal::ZERO;
+
+            Runtime::emit_event(LiquidityRewardsFetchEvent {
+                amount: fetched,
+                reward_rate: self.reward_rate,
+                stream_end: Instant::new(self.stream_end),
+            });
+        }
+
+        /// Returns the fUSD rewards a stake can currently claim.
+        ///
+        /// # Arguments
+        /// * `stake_id`: The `NonFungibleLocalId` of the stake receipt.
+        ///
+        /// # Returns
+        /// * `Decimal`: The claimable fUSD rewards.
+        pub fn get_stake_rewards(&self, stake_id: NonFungibleLocalId) -> Decimal {
+            let stake: LiquidityStake = self.stake_manager.get_non_fungible_data(&stake_id);
+            let (reward_per_token, _) = self.get_current_reward_per_token();
+
+            Self::get_rewards(&stake, reward_per_token).min(self.rewards_vault.amount())
+        }
+
+        /// Returns the state of the distributor.
+        ///
+        /// # Returns
+        /// * `LiquidityRewardsDistributorInfo`: The configuration, total stake and current stream.
+        pub fn get_distributor_info(&self) -> LiquidityRewardsDistributorInfo {
+            LiquidityRewardsDistributorInfo {
+                collaterals: self.collaterals.clone(),
+                lp_token_address: self.lp_vault.resource_address(),
+                total_staked: self.total_staked,
+                reward_rate: self.reward_rate,
+                stream_end: Instant::new(self.stream_end),
+                stream_duration: self.stream_duration,
+                rewards: self.rewards_vault.amount(),
+            }
+        }
+
+        /// Sets the collaterals whose liquidity rewards are distributed and the stream duration.
+        /// The duration applies from the next `fetch_rewards` call.
+        /// Requires OWNER authorization (controller badge).
+        ///
+        /// # Arguments
+        /// * `collaterals`: The collaterals whose stability pool liquidity rewards are distributed.
+        /// * `stream_duration`: The duration (seconds) over which fetched rewards are streamed.
+        ///
+        /// # Panics
+        /// * If `stream_duration` is not positive.
+        pub fn set_parameters(&mut self, collaterals: Vec<ResourceAddress>, stream_duration: i64) {
+            assert!(stream_duration > 0, "Stream duration must be positive.");
+
+            self.collaterals = collaterals;
+            self.stream_duration = stream_duration;
+        }
+
+        /// Streams the rewards since the last update into `reward_per_token`.
+        fn update_reward_per_token(&mut self) {
+            let (reward_per_token, undistributed) = self.get_current_reward_per_token();
+
+            self.reward_per_token = reward_per_token;
+            self.undistributed += undistributed;
+            self.last_update = Clock::current_time_rounded_to_seconds().seconds_since_unix_epoch;
+        }
+
+        /// Calculates the current `reward_per_token`, including the rewards streamed since the last update.
+        ///
+        /// # Returns
+        /// * `(PreciseDecimal, Decimal)`: The current `reward_per_token`, and the rewards streamed since the last update
+        ///   that can't be distributed because nothing is staked.
+        fn get_current_reward_per_token(&self) -> (PreciseDecimal, Decimal) {
+            let now = Clock::current_time_rounded_to_seconds().seconds_since_unix_epoch;
+            let streamed_until = now.min(self.stream_end);
+
+            if streamed_until <= self.last_update {
+                return (self.reward_per_token, Decimal::ZERO);
+            }
+
+            let streamed = self.reward_rate * Decimal::from(streamed_until - self.last_update);
+
+            if self.total_staked == Decimal::ZERO {
+                return (self.reward_per_token, streamed);
+            }
+
+            (
+                self.reward_per_token + PreciseDecimal::from(streamed) / PreciseDecimal::from(self.total_staked),
+                Decimal::ZERO,
+            )
+        }
+
+        /// Takes the outstanding rewards of a stake from the rewards vault.
+        fn take_stake_rewards(&mut self, stake: &LiquidityStake) -> Bucket {
+            let rewards = Self::get_rewards(stake, self.reward_per_token);
+
+            self.rewards_vault.take(rewards.min(self.rewards_vault.amount()))
+        }
+
+        /// Calculates the outstanding rewards of a stake for a given `reward_per_token`.
+        fn get_rewards(stake: &LiquidityStake, reward_per_token: PreciseDecimal) -> Decimal {
+            (PreciseDecimal::from(stake.staked) * (reward_per_token - stake.reward_per_token_snapshot))
+                .checked_truncate(RoundingMode::ToZero)
+                .unwrap()
+        }
+    }
+}
@@ -1,12 +1,17 @@
 use scrypto::prelude::*;
 // Import StabilityPools if you need its type, otherwise AnyComponent might suffice for calling
 use crate::stability_pools::stability_pools::StabilityPools;
+use crate::shared_structs::*;
 use crate::events::*;
 
 #[blueprint]
 #[events(
     PayoutClaimEvent,
+    PayoutAuctionStartEvent,
     PayoutFetchRewardsEvent,
+    PayoutRevenueSplitEvent,
+    PayoutRevenuePushEvent,
+    PayoutLiquidityDepositEvent,
     PayoutRequirementUpdateEvent,
 )]
 mod payout_component {
@@ -14,6 +19,11 @@ mod payout_component {
         methods {
             // Public methods
             claim_rewards => PUBLIC;
+            get_auction_info => PUBLIC;
+            push_revenue => PUBLIC;
+            get_revenue_split => PUBLIC;
+            get_pending_revenue => PUBLIC;
+            get_protocol_owned_liquidity => PUBLIC;
             fetch_rewards_from_stability_pools => PUBLIC;
             receive_badges => PUBLIC;
             receive_rewards => PUBLIC;
@@ -22,11 +32,26 @@ mod payout_component {
             set_parameters => restrict_to: [OWNER];
             send_badges => restrict_to: [OWNER];
             take_payments => restrict_to: [OWNER];
+            set_revenue_split => restrict_to: [OWNER];
+            deposit_protocol_owned_liquidity => restrict_to: [OWNER];
+            take_protocol_owned_liquidity => restrict_to: [OWNER];
         }
     }
 
     /// Manages the distribution of accumulated fUSD rewards from the protocol.
-    /// Allows claiming rewards by paying a specified token amount or direct withdrawal via badge auth.
+    /// Sells the rewards for the payment token in periodic descending-price auctions, or allows direct
+    /// withdrawal via badge auth.
+    ///
+    /// Each auction epoch puts up a fixed lot of fUSD: the rewards available when the epoch starts. The price per
+    /// fUSD starts at the reference price increased by `start_premium`, and decreases by `decay_per_minute` (as a
+    /// fraction of the reference price) every minute, down to the reference price minus `max_discount`. Once the lot
+    /// is claimed, the price paid becomes the new reference price and the next epoch starts. The reference price
+    /// never falls below the owner-set `min_reference_price`, so repeated claims at the maximum discount can't
+    /// walk it down indefinitely.
+    ///
+    /// Fetched revenue is first split over the destinations of the revenue split table. Only the buy-back share
+    /// is auctioned. The shares of external destinations are held until they are pushed with `push_revenue`, so a
+    /// failing destination can't block fetching and claiming.
     struct PayoutComponent {
         /// Vault holding the controller badge, granting admin privileges.
         controller_badge_vault: FungibleVault,
@@ -34,12 +59,30 @@ mod payout_component {
         fusd_vault: FungibleVault,
         /// The resource address of the token required as payment to claim rewards.
         payment_token_vault: FungibleVault,
-        /// The amount of the payment token required to claim rewards.
-        required_payment_amount: Decimal,
+        /// The price the auction is based on, in payment tokens per fUSD.
+        reference_price: Decimal,
+        /// The lowest the reference price can fall to after a claim, in payment tokens per fUSD.
+        min_reference_price: Decimal,
+        /// The fraction above the reference price at which each auction epoch starts.
+        start_premium: Decimal,
+        /// The fraction of the reference price the auction price decreases by every minute.
+        decay_per_minute: Decimal,
+        /// The maximum fraction below the reference price the auction price can decrease to.
+        max_discount: Decimal,
+        /// The running auction epoch, `None` if there were no rewards to auction when the last epoch ended.
+        epoch: Option<PayoutEpoch>,
+        /// Counter used to number the auction epochs.
+        epoch_counter: u64,
         /// The resource address of the stability pools component.
         stability_pools_address: ComponentAddress,
         /// Whether to burn the payment token after claiming rewards.
         burn: bool,
+        /// The destinations of fetched revenue and the share each receives.
+        revenue_split: Vec<(RevenueDestination, Decimal)>,
+        /// The fUSD revenue waiting to be pushed to the DAO treasury and savings rate destinations.
+        pending_revenue: HashMap<RevenueDestination, Vault>,
+        /// The protocol-owned stability pool liquidity, per collateral.
+        protocol_owned_liquidity: HashMap<ResourceAddress, ProtocolOwnedLiquidity>,
     }
 
     impl PayoutComponent {
@@ -48,7 +91,8 @@ mod payout_component {
         /// # Arguments
         /// * `controller_badge`: A bucket containing the controller badge for authorization.
         /// * `payment_token_address`: The resource address of the token required for payment.
-        /// * `initial_required_payment_amount`: The initial amount of the payment token required.
+        /// * `initial_reference_price`: The initial reference price of the auction, in payment tokens per fUSD.
+        ///   Also used as the initial minimum reference price.
         /// * `fusd_address`: The resource address of the fUSD token.
         /// * `owner_role`: The OwnerRole for the component (should be rule!(require(controller_badge.resource_address()))).
         /// * `dapp_def_address`: The DApp definition address for metadata.
@@ -58,7 +102,7 @@ mod payout_component {
         pub fn instantiate(
             controller_badge: Bucket,
             payment_token_address: ResourceAddress,
-            initial_required_payment_amount: Decimal,
+            initial_reference_price: Decimal,
             fusd_address: ResourceAddress,
             stability_pools_address: ComponentAddress,
             owner_role: OwnerRole, // Expecting rule!(require(controller_badge.resource_address()))
@@ -69,17 +113,26 @@ mod payout_component {
             
 
             assert!(
-                initial_required_payment_amount > Decimal::ZERO,
-                "Required payment amount must be positive"
+                initial_reference_price > Decimal::ZERO,
+                "Reference price must be positive"
             );
 
             Self {
                 controller_badge_vault: FungibleVault::with_bucket(controller_badge.as_fungible()),
                 fusd_vault: FungibleVault::new(fusd_address),
                 payment_token_vault: FungibleVault::new(payment_token_address),
-                required_payment_amount: initial_required_payment_amount,
+                reference_price: initial_reference_price,
+                min_reference_price: initial_reference_price,
+                start_premium: dec!("0.1"),
+                decay_per_minute: dec!("0.0005"),
+                max_discount: dec!("0.2"),
+                epoch: None,
+                epoch_counter: 0,
                 stability_pools_address,
                 burn: true,
+                revenue_split: vec![(RevenueDestination::BuyBackAuction, Decimal::ONE)],
+                pending_revenue: HashMap::new(),
+                protocol_owned_liquidity: HashMap::new(),
             }
             .instantiate()
             .prepare_to_globalize(owner_role)
@@ -96,52 +149,109 @@ mod payout_component {
             .globalize()
         }
 
-        /// Allows a user to claim all available fUSD rewards by providing the required payment token amount.
+        /// Claims the fUSD lot of the running auction epoch by paying its current price in the payment token.
         ///
-        /// The provided payment tokens are burned.
+        /// Fetches new rewards first, starting an epoch if none is running. The payment is burned or stored,
+        /// depending on the `burn` setting. The price paid becomes the new reference price (but no less than the
+        /// minimum reference price), and the next epoch is started with the rewards that arrived during this one.
         ///
         /// # Arguments
         /// * `payment_bucket`: A bucket containing the payment tokens.
         ///
         /// # Returns
         /// * `(Bucket, Bucket)`: A tuple containing:
-        ///     1. A bucket with all the claimed fUSD rewards.
+        ///     1. A bucket with the claimed fUSD lot.
         ///     2. A bucket with any remaining payment tokens (if more than required was provided).
         ///
         /// # Panics
-        /// * If the `payment_bucket` resource does not match the configured `payment_token_address`.
-        /// * If the `payment_bucket` amount is less than `required_payment_amount`.
+        /// * If the `payment_bucket` resource does not match the payment token.
+        /// * If there are no rewards to claim.
+        /// * If the `payment_bucket` amount is less than the required payment for the lot.
         pub fn claim_rewards(&mut self, mut payment_bucket: Bucket) -> (Bucket, Bucket) {
             assert!(
-                payment_bucket.amount() >= self.required_payment_amount,
+                payment_bucket.resource_address() == self.payment_token_vault.resource_address(),
+                "Invalid payment token"
+            );
+
+            self.fetch_rewards();
+            if self.epoch.is_none() {
+                self.start_epoch();
+            }
+
+            let epoch = self.epoch.clone().expect("No rewards to claim");
+            let price = self.get_auction_price(&epoch);
+            let required_payment_amount = epoch.lot * price;
+
+            assert!(
+                payment_bucket.amount() >= required_payment_amount,
                 "Insufficient payment amount provided. Required: {}",
-                self.required_payment_amount
+                required_payment_amount
             );
 
+            let payment = payment_bucket.take_advanced(
+                required_payment_amount,
+                WithdrawStrategy::Rounded(RoundingMode::ToPositiveInfinity),
+            );
+            let payment_amount = payment.amount();
             if self.burn {
-                payment_bucket.take(self.required_payment_amount).burn();
+                payment.burn();
             } else {
-                self.payment_token_vault.put(payment_bucket.take(self.required_payment_amount).as_fungible());
+                self.payment_token_vault.put(payment.as_fungible());
             }
 
-            self.fetch_rewards_from_stability_pools();
-
-            // Take all accumulated fUSD rewards
-            let fusd_rewards = self.fusd_vault.take_all();
+            // Take the epoch's fUSD lot
+            let fusd_rewards = self.fusd_vault.take(epoch.lot);
 
             // Emit claim event
             Runtime::emit_event(PayoutClaimEvent {
+                epoch: epoch.number,
                 amount: fusd_rewards.amount(),
+                payment_amount,
+                price,
             });
 
+            self.reference_price = price.max(self.min_reference_price);
+            self.start_epoch();
+
             (fusd_rewards.into(), payment_bucket)
         }
 
+        /// Retrieves the state of the rewards auction.
+        ///
+        /// # Returns
+        /// * `PayoutAuctionInfo`: The running epoch with its current price and required payment, the auction
+        ///   parameters, and the rewards waiting for the next epoch.
+        pub fn get_auction_info(&self) -> PayoutAuctionInfo {
+            let (current_price, required_payment_amount) = match &self.epoch {
+                Some(epoch) => {
+                    let price = self.get_auction_price(epoch);
+                    (Some(price), Some(epoch.lot * price))
+                }
+                None => (None, None),
+            };
+            let lot = self.epoch.as_ref().map(|epoch| epoch.lot).unwrap_or(Decimal::ZERO);
+
+            PayoutAuctionInfo {
+                epoch: self.epoch.clone(),
+                current_price,
+                required_payment_amount,
+                pending_rewards: self.fusd_vault.amount() - lot,
+                reference_price: self.reference_price,
+                min_reference_price: self.min_reference_price,
+                start_premium: self.start_premium,
+                decay_per_minute: self.decay_per_minute,
+                max_discount: self.max_discount,
+                burn: self.burn,
+            }
+        }
+
         /// Allows the component owner (holding the badge) to withdraw all accumulated fUSD rewards directly.
+        /// Ends the running auction epoch.
         ///
         /// # Returns
         /// * `Bucket`: A bucket containing all the fUSD rewards from the vault.
         pub fn take_payout_component_rewards(&mut self) -> Bucket {
+            self.epoch = None;
             self.fusd_vault.take_all().into()
         }
 
@@ -150,33 +260,240 @@ mod payout_component {
             self.payment_token_vault.take_all().into()
         }
 
-        /// Sets the required payment token and amount needed to claim rewards.
+        /// Sets the parameters of the rewards auction. The new parameters apply to the running epoch as well.
         /// Requires OWNER authorization (controller badge).
         ///
         /// # Arguments
-        /// * `new_payment_token_address`: The new `ResourceAddress` of the required payment token.
-        /// * `new_required_payment_amount`: The new `Decimal` amount required.
+        /// * `start_premium`: The fraction above the reference price at which each auction epoch starts.
+        /// * `decay_per_minute`: The fraction of the reference price the auction price decreases by every minute.
+        /// * `max_discount`: The maximum fraction below the reference price the auction price can decrease to.
+        /// * `reference_price`: Optional new reference price, in payment tokens per fUSD.
+        /// * `min_reference_price`: Optional new minimum reference price, in payment tokens per fUSD.
+        /// * `burn`: Whether to burn the payment token after claiming rewards.
         ///
         /// # Panics
-        /// * If `new_required_payment_amount` is not positive.
+        /// * If `start_premium` or `decay_per_minute` are negative.
+        /// * If `max_discount` is not between 0 (inclusive) and 1 (exclusive).
+        /// * If `reference_price` or `min_reference_price` is not positive.
+        /// * If the resulting reference price is below the resulting minimum reference price.
         pub fn set_parameters(
             &mut self,
-            new_required_payment_amount: Decimal,
+            start_premium: Decimal,
+            decay_per_minute: Decimal,
+            max_discount: Decimal,
+            reference_price: Option<Decimal>,
+            min_reference_price: Option<Decimal>,
             burn: bool,
         ) {
+            assert!(start_premium >= Decimal::ZERO, "Start premium can't be negative");
+            assert!(decay_per_minute >= Decimal::ZERO, "Decay per minute can't be negative");
             assert!(
-                new_required_payment_amount > Decimal::ZERO,
-                "Required payment amount must be positive"
+                max_discount >= Decimal::ZERO && max_discount < Decimal::ONE,
+                "Max discount must be between 0 and 1"
             );
-            self.required_payment_amount = new_required_payment_amount;
+            if let Some(reference_price) = reference_price {
+                assert!(reference_price > Decimal::ZERO, "Reference price must be positive");
+                self.reference_price = reference_price;
+            }
+            if let Some(min_reference_price) = min_reference_price {
+                assert!(min_reference_price > Decimal::ZERO, "Minimum reference price must be positive");
+                self.min_reference_price = min_reference_price;
+            }
+            assert!(
+                self.reference_price >= self.min_reference_price,
+                "Reference price can't be below the minimum reference price"
+            );
+
+            self.start_premium = start_premium;
+            self.decay_per_minute = decay_per_minute;
+            self.max_discount = max_discount;
             self.burn = burn;
             // Emit requirement update event
             Runtime::emit_event(PayoutRequirementUpdateEvent {
-                new_requirement: new_required_payment_amount,
+                start_premium,
+                decay_per_minute,
+                max_discount,
+                reference_price: self.reference_price,
+                min_reference_price: self.min_reference_price,
                 burn,
             });
         }
 
+        /// Sets the revenue split table, dividing the fUSD revenue fetched from the StabilityPools component over
+        /// the buy-back auction, the DAO treasury, the savings rate and protocol-owned stability pool liquidity.
+        /// Requires OWNER authorization (controller badge).
+        ///
+        /// # Arguments
+        /// * `revenue_split`: The destinations of the revenue and the share each receives. Shares must add up to 1.
+        ///
+        /// # Panics
+        /// * If `revenue_split` is empty or contains a destination twice.
+        /// * If a share is not positive, or the shares don't add up to 1.
+        pub fn set_revenue_split(&mut self, revenue_split: Vec<(RevenueDestination, Decimal)>) {
+            assert!(!revenue_split.is_empty(), "Revenue split can't be empty");
+
+            let mut total_share = Decimal::ZERO;
+            for (index, (destination, share)) in revenue_split.iter().enumerate() {
+                assert!(*share > Decimal::ZERO, "Shares must be positive");
+                assert!(
+                    !revenue_split[..index].iter().any(|(other, _)| other == destination),
+                    "Duplicate revenue destination"
+                );
+                total_share += *share;
+            }
+            assert!(total_share == Decimal::ONE, "Shares must add up to 1");
+
+            self.revenue_split = revenue_split;
+        }
+
+        /// Retrieves the revenue split table.
+        ///
+        /// # Returns
+        /// * `Vec<(RevenueDestination, Decimal)>`: The destinations of the revenue and the share each receives.
+        pub fn get_revenue_split(&self) -> Vec<(RevenueDestination, Decimal)> {
+            self.revenue_split.clone()
+        }
+
+        /// Sends the revenue set aside for a DAO treasury or savings rate destination to it.
+        /// Can be called by anyone. If the destination fails, only this call fails and the revenue stays pending.
+        ///
+        /// # Arguments
+        /// * `destination`: The `RevenueDestination` to push the pending revenue to.
+        ///
+        /// # Panics
+        /// * If there is no revenue pending for the destination.
+        /// * If the destination rejects the deposit or its method call fails.
+        pub fn push_revenue(&mut self, destination: RevenueDestination) {
+            let revenue = self
+                .pending_revenue
+                .get_mut(&destination)
+                .expect("No revenue pending for this destination")
+                .take_all();
+            assert!(!revenue.is_empty(), "No revenue pending for this destination");
+            let amount = revenue.amount();
+
+            match &destination {
+                RevenueDestination::DaoTreasury { account } => {
+                    let mut treasury: Global<Account> = Global::from(*account);
+                    treasury.try_deposit_or_abort(revenue, None);
+                }
+                RevenueDestination::SavingsRate { component, method_name } => {
+                    let savings_rate: Global<AnyComponent> = Global::from(*component);
+                    savings_rate.call_raw::<()>(method_name, scrypto_args!(revenue));
+                }
+                // Only external destinations have pending revenue
+                RevenueDestination::BuyBackAuction | RevenueDestination::StabilityPoolDeposit { .. } => {
+                    panic!("Revenue for this destination isn't pushed")
+                }
+            }
+
+            Runtime::emit_event(PayoutRevenuePushEvent { destination, amount });
+        }
+
+        /// Retrieves the revenue waiting to be pushed to a DAO treasury or savings rate destination.
+        ///
+        /// # Arguments
+        /// * `destination`: The `RevenueDestination` to check.
+        ///
+        /// # Returns
+        /// * `Decimal`: The amount of fUSD pending for the destination.
+        pub fn get_pending_revenue(&self, destination: RevenueDestination) -> Decimal {
+            self.pending_revenue
+                .get(&destination)
+                .map(|vault| vault.amount())
+                .unwrap_or(Decimal::ZERO)
+        }
+
+        /// Deposits the fUSD set aside for protocol-owned liquidity into a collateral's stability pool.
+        /// The pool units (or deposit receipt) received are held by this component.
+        /// Requires OWNER authorization (controller badge), as the caller chooses the oracle data.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral whose stability pool is deposited into.
+        /// * `message`: Oracle message for price verification.
+        /// * `signature`: Oracle signature for price verification.
+        ///
+        /// # Panics
+        /// * If there is no fUSD set aside for the collateral's stability pool.
+        /// * If the `StabilityPools::contribute_to_pool` call fails, e.g. because the pool requires a contributor
+        ///   badge or the deposit cap is reached.
+        pub fn deposit_protocol_owned_liquidity(
+            &mut self,
+            collateral: ResourceAddress,
+            message: String,
+            signature: String,
+        ) {
+            let fusd = self
+                .protocol_owned_liquidity
+                .get_mut(&collateral)
+                .expect("No protocol-owned liquidity for this collateral")
+                .pending_fusd
+                .take_all();
+            assert!(!fusd.is_empty(), "No fUSD to deposit");
+            let fusd_amount = fusd.amount();
+
+            let stability_pools: Global<StabilityPools> = Global::from(self.stability_pools_address);
+            let (pool_units, leftover_units, leftover_fusd) =
+                stability_pools.contribute_to_pool(collateral, fusd, true, message, signature);
+            let pool_units_amount = pool_units.amount();
+
+            let liquidity = self.protocol_owned_liquidity.get_mut(&collateral).unwrap();
+            let pool_units_vault = liquidity
+                .pool_units
+                .get_or_insert_with(|| Vault::new(pool_units.resource_address()));
+            pool_units_vault.put(pool_units);
+            if let Some(leftover_units) = leftover_units {
+                pool_units_vault.put(leftover_units.into());
+            }
+            if let Some(leftover_fusd) = leftover_fusd {
+                liquidity.pending_fusd.put(leftover_fusd);
+            }
+
+            Runtime::emit_event(PayoutLiquidityDepositEvent {
+                collateral,
+                fusd_amount,
+                pool_units: pool_units_amount,
+            });
+        }
+
+        /// Retrieves the protocol-owned liquidity of a collateral's stability pool.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral.
+        ///
+        /// # Returns
+        /// * `(Decimal, Decimal)`: The fUSD waiting to be deposited, and the pool units (or deposit receipts) held.
+        pub fn get_protocol_owned_liquidity(&self, collateral: ResourceAddress) -> (Decimal, Decimal) {
+            self.protocol_owned_liquidity
+                .get(&collateral)
+                .map(|liquidity| {
+                    (
+                        liquidity.pending_fusd.amount(),
+                        liquidity.pool_units.as_ref().map(|vault| vault.amount()).unwrap_or(Decimal::ZERO),
+                    )
+                })
+                .unwrap_or((Decimal::ZERO, Decimal::ZERO))
+        }
+
+        /// Allows the component owner (holding the badge) to withdraw the protocol-owned pool units (or deposit
+        /// receipts) of a collateral's stability pool.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral.
+        ///
+        /// # Returns
+        /// * `Bucket`: A bucket containing all pool units (or deposit receipts) held for the collateral.
+        ///
+        /// # Panics
+        /// * If no liquidity has been deposited into the collateral's stability pool.
+        pub fn take_protocol_owned_liquidity(&mut self, collateral: ResourceAddress) -> Bucket {
+            self.protocol_owned_liquidity
+                .get_mut(&collateral)
+                .and_then(|liquidity| liquidity.pool_units.as_mut())
+                .expect("No protocol-owned liquidity for this collateral")
+                .take_all()
+        }
+
         /// Sends controller badges to another component.
         ///
         /// # Arguments
@@ -200,24 +517,27 @@ mod payout_component {
         }
 
 
-        /// Fetches accumulated fUSD rewards from the StabilityPools component.
-        /// Requires OWNER authorization (controller badge).
+        /// Fetches accumulated fUSD rewards from the StabilityPools component, and starts a new auction epoch
+        /// if none is running.
+        /// If any rewards were fetched, the caller is paid the `PayoutFetch` keeper bounty from the StabilityPools
+        /// component, if configured and not on cooldown.
         ///
-        /// # Arguments
-        /// * `stability_pools_address`: The `ComponentAddress` of the StabilityPools component.
-        pub fn fetch_rewards_from_stability_pools(&mut self) {
-            let stability_pools: Global<StabilityPools> = Global::from(self.stability_pools_address);
-
-            let rewards_bucket: Bucket = self.controller_badge_vault.authorize_with_amount(Decimal::ONE, || {
-                stability_pools.claim_payout_rewards()
-            });
+        /// # Returns
+        /// * `Bucket`: The keeper bounty (fUSD), empty if none is paid.
+        pub fn fetch_rewards_from_stability_pools(&mut self) -> Bucket {
+            let fetched = self.fetch_rewards();
+            if self.epoch.is_none() {
+                self.start_epoch();
+            }
 
-            // Emit fetch rewards event
-            Runtime::emit_event(PayoutFetchRewardsEvent {
-                amount: rewards_bucket.amount(),
-            });
+            if fetched == Decimal::ZERO {
+                return Bucket::new(self.fusd_vault.resource_address());
+            }
 
-            self.fusd_vault.put(rewards_bucket.as_fungible());
+            let stability_pools: Global<StabilityPools> = Global::from(self.stability_pools_address);
+            self.controller_badge_vault.authorize_with_amount(Decimal::ONE, || {
+                stability_pools.claim_keeper_bounty(KeeperAction::PayoutFetch)
+            })
         }
 
         /// Receives fUSD rewards directly. Used primarily for testing.
@@ -231,5 +551,156 @@ mod payout_component {
             );
             self.fusd_vault.put(rewards.as_fungible());
         }
+
+        /// Fetches accumulated fUSD rewards from the StabilityPools component and splits them according to the
+        /// revenue split table. The buy-back share, and any rounding remainder, is put in the fUSD vault. Other
+        /// shares are set aside, without calling any external destination.
+        ///
+        /// # Returns
+        /// * `Decimal`: The amount of fUSD rewards fetched.
+        fn fetch_rewards(&mut self) -> Decimal {
+            let stability_pools: Global<StabilityPools> = Global::from(self.stability_pools_address);
+
+            let mut rewards_bucket: Bucket = self.controller_badge_vault.authorize_with_amount(Decimal::ONE, || {
+                stability_pools.claim_payout_rewards()
+            });
+
+            // Emit fetch rewards event
+            let total_rewards = rewards_bucket.amount();
+            Runtime::emit_event(PayoutFetchRewardsEvent {
+                amount: total_rewards,
+            });
+
+            if total_rewards > Decimal::ZERO {
+                for (destination, share) in self.revenue_split.clone() {
+                    let revenue_amount = (total_rewards * share).min(rewards_bucket.amount());
+                    let revenue = rewards_bucket.take_advanced(
+                        revenue_amount,
+                        WithdrawStrategy::Rounded(RoundingMode::ToZero),
+                    );
+                    let amount = revenue.amount();
+
+                    match &destination {
+                        // The buy-back share stays with the rest of the rewards, and is emitted below
+                        RevenueDestination::BuyBackAuction => {
+                            rewards_bucket.put(revenue);
+                            continue;
+                        }
+                        RevenueDestination::DaoTreasury { .. } | RevenueDestination::SavingsRate { .. } => {
+                            let fusd_address = self.fusd_vault.resource_address();
+                            self.pending_revenue
+                                .entry(destination.clone())
+                                .or_insert_with(|| Vault::new(fusd_address))
+                                .put(revenue);
+                        }
+                        RevenueDestination::StabilityPoolDeposit { collateral } => {
+                            let fusd_address = self.fusd_vault.resource_address();
+                            self.protocol_owned_liquidity
+                                .entry(*collateral)
+                                .or_insert_with(|| ProtocolOwnedLiquidity {
+                                    pending_fusd: Vault::new(fusd_address),
+                                    pool_units: None,
+                                })
+                                .pending_fusd
+                                .put(revenue);
+                        }
+                    }
+
+                    Runtime::emit_event(PayoutRevenueSplitEvent { destination, amount });
+                }
+
+                Runtime::emit_event(PayoutRevenueSplitEvent {
+                    destination: RevenueDestination::BuyBackAuction,
+                    amount: rewards_bucket.amount(),
+                });
+            }
+
+            self.fusd_vault.put(rewards_bucket.as_fungible());
+
+            total_rewards
+        }
+
+        /// Starts a new auction epoch, putting up all fUSD rewards in the vault as the epoch's lot.
+        /// No epoch is started if there are no rewards.
+        fn start_epoch(&mut self) {
+            let lot = self.fusd_vault.amount();
+            if lot == Decimal::ZERO {
+                self.epoch = None;
+                return;
+            }
+
+            self.epoch_counter += 1;
+            let start_price = self.reference_price * (Decimal::ONE + self.start_premium);
+            self.epoch = Some(PayoutEpoch {
+                number: self.epoch_counter,
+                start: Clock::current_time_rounded_to_seconds(),
+                lot,
+                start_price,
+            });
+
+            Runtime::emit_event(PayoutAuctionStartEvent {
+                epoch: self.epoch_counter,
+                lot,
+                start_price,
+            });
+        }
+
+        /// Calculates the current auction price of an epoch, in payment tokens per fUSD.
+        fn get_auction_price(&self, epoch: &PayoutEpoch) -> Decimal {
+            let elapsed_seconds = Clock::current_time_rounded_to_seconds().seconds_since_unix_epoch
+                - epoch.start.seconds_since_unix_epoch;
+            let price_modifier = (Decimal::ONE + self.start_premium
+                - self.decay_per_minute * Decimal::from(elapsed_seconds) / dec!(60))
+                .max(Decimal::ONE - self.max_discount);
+
+            self.reference_price * price_modifier
+        }
     }
+}
+
+/// The protocol-owned liquidity of a collateral's stability pool.
+#[derive(ScryptoSbor)]
+pub struct ProtocolOwnedLiquidity {
+    /// Vault holding the fUSD waiting to be deposited into the stability pool.
+    pub pending_fusd: Vault,
+    /// Vault holding the pool units (or deposit receipts) received, `None` until the first deposit.
+    pub pool_units: Option<Vault>,
+}
+
+/// A rewards auction epoch of the payout component.
+#[derive(ScryptoSbor, Clone, Debug)]
+pub struct PayoutEpoch {
+    /// The number of the epoch.
+    pub number: u64,
+    /// The time at which the epoch started.
+    pub start: Instant,
+    /// The amount of fUSD auctioned in the epoch.
+    pub lot: Decimal,
+    /// The price at which the epoch started, in payment tokens per fUSD.
+    pub start_price: Decimal,
+}
+
+/// A structure for returning the state of the payout component's rewards auction.
+#[derive(ScryptoSbor, Clone, Debug)]
+pub struct PayoutAuctionInfo {
+    /// The running auction epoch, `None` if no epoch is running.
+    pub epoch: Option<PayoutEpoch>,
+    /// The current price of the running epoch, in payment tokens per fUSD.
+    pub current_price: Option<Decimal>,
+    /// The amount of payment tokens currently required to claim the running epoch's lot.
+    pub required_payment_amount: Option<Decimal>,
+    /// The fUSD rewards received during the running epoch, to be auctioned in the next one.
+    pub pending_rewards: Decimal,
+    /// The price the auction is based on, in payment tokens per fUSD.
+    pub reference_price: Decimal,
+    /// The lowest the reference price can fall to after a claim, in payment tokens per fUSD.
+    pub min_reference_price: Decimal,
+    /// The fraction above the reference price at which each auction epoch starts.
+    pub start_premium: Decimal,
+    /// The fraction of the reference price the auction price decreases by every minute.
+    pub decay_per_minute: Decimal,
+    /// The maximum fraction below the reference price the auction price can decrease to.
+    pub max_discount: Decimal,
+    /// Whether the payment token is burned after claiming rewards.
+    pub burn: bool,
 } 
\ No newline at end of file
@@ -0,0 +1,607 @@
+#![allow(deprecated)]
+
+//! # Product-Sum Stability Pool Blueprint
+//!
+//! This blueprint defines the `ProductSumPool` component, an alternative to the `TwoResourcePool` for a
+//! collateral's stability pool. It follows the product-sum accounting used by Liquity's stability pool.
+//!
+//! ## Functionality
+//! - **Deposits:** Deposits stay denominated in fUSD. Every deposit is represented by a `StabilityDeposit` NFT,
+//!   which stores the initial deposit and snapshots of the pool's accumulators at the time of depositing.
+//! - **Liquidations:** Liquidations consume deposits pro-rata. The running product `P` tracks how much of a deposit
+//!   is left, so a deposit's current (compounded) value is `initial_deposit * P / P_snapshot`.
+//! - **Collateral gains:** Collateral received from liquidations is tracked by the running sum `S`. A deposit's
+//!   gain is `initial_deposit * (S - S_snapshot) / P_snapshot`. Gains can be claimed without withdrawing.
+//! - **fUSD yield:** Interest and fees paid to the pool are tracked separately by the running sum `G`, so yield is
+//!   never consumed by liquidations and can be claimed separately from the deposit itself.
+//! - **Scales:** Each liquidation that consumes most of the pool makes `P` smaller, until it would lose its precision.
+//!   When `P` drops below `1 / SCALE_FACTOR` it is multiplied by `SCALE_FACTOR` and a new scale is started. A deposit
+//!   made one scale earlier is divided by `SCALE_FACTOR` once, deposits made two or more scales earlier are worth zero.
+//! - **Epochs:** When a liquidation fully depletes the pool, all deposits are worth zero. Instead of letting `P`
+//!   reach zero, a new epoch is started with `P = 1`. Deposits made in an earlier epoch only keep the gains of their epoch.
+//!   fUSD dust left in the pool at that point is distributed to the next depositors as yield.
+//!
+//! Rewards received while the pool holds no deposits can't be distributed, so they're held back and added to the
+//! next distribution.
+//!
+//! All mutating methods are restricted to the pool manager (the `StabilityPools` component), which is responsible
+//! for user facing checks and events.
+
+use scrypto::prelude::*;
+
+/// The factor `P` is multiplied by when it drops below `1 / SCALE_FACTOR`.
+pub const SCALE_FACTOR: u64 = 1_000_000_000;
+
+/// The non-fungible data of a stability pool deposit receipt.
+#[derive(ScryptoSbor, NonFungibleData, Clone)]
+pub struct StabilityDeposit {
+    /// The `ResourceAddress` of the collateral of the pool this deposit was made into.
+    pub collateral: ResourceAddress,
+    /// The fUSD deposit at the time of the last snapshot.
+    #[mutable]
+    pub initial_deposit: Decimal,
+    /// Snapshot of the running product `P`.
+    #[mutable]
+    pub product_snapshot: PreciseDecimal,
+    /// Snapshot of the collateral gain sum `S` of the deposit's epoch.
+    #[mutable]
+    pub collateral_sum_snapshot: PreciseDecimal,
+    /// Snapshot of the fUSD yield sum `G` of the deposit's epoch.
+    #[mutable]
+    pub yield_sum_snapshot: PreciseDecimal,
+    /// The epoch the snapshots were taken in.
+    #[mutable]
+    pub epoch: u64,
+    /// The scale the snapshots were taken in.
+    #[mutable]
+    pub scale: u64,
+}
+
+/// Identifies the running sums of a scale within an epoch.
+#[derive(ScryptoSbor, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct EpochScale {
+    pub epoch: u64,
+    pub scale: u64,
+}
+
+/// The running sums of a scale within an epoch.
+#[derive(ScryptoSbor, Clone)]
+pub struct EpochSums {
+    /// Collateral gain per unit of deposit, scaled by the running product.
+    pub collateral_sum: PreciseDecimal,
+    /// fUSD yield per unit of deposit, scaled by the running product.
+    pub yield_sum: PreciseDecimal,
+}
+
+/// The current state of a stability pool deposit.
+#[derive(ScryptoSbor, Clone)]
+pub struct StabilityDepositInfo {
+    /// The fUSD deposit remaining after liquidations.
+    pub compounded_deposit: Decimal,
+    /// The collateral gained from liquidations, claimable.
+    pub collateral_gain: Decimal,
+    /// The fUSD yield earned, claimable.
+    pub fusd_yield: Decimal,
+}
+
+#[blueprint]
+#[types(StabilityDeposit, EpochScale, EpochSums)]
+mod product_sum_pool {
+    enable_method_auth! {
+        roles {
+            pool_manager => updatable_by: [OWNER];
+        },
+        methods {
+            get_vault_amounts => PUBLIC;
+            get_total_deposits => PUBLIC;
+            get_deposit_info => PUBLIC;
+            get_deposit_receipt_address => PUBLIC;
+            deposit => restrict_to: [pool_manager];
+            withdraw => restrict_to: [pool_manager];
+            claim_gains => restrict_to: [pool_manager];
+            withdraw_for_offset => restrict_to: [pool_manager];
+            offset => restrict_to: [pool_manager];
+            deposit_yield => restrict_to: [pool_manager];
+            deposit_collateral => restrict_to: [pool_manager];
+        }
+    }
+
+    /// A stability pool using product-sum accounting, with per-depositor collateral gains and fUSD yield.
+    struct ProductSumPool {
+        /// The `ResourceAddress` of the collateral this pool absorbs liquidations for.
+        collateral: ResourceAddress,
+        /// The `ResourceAddress` of the fUSD token.
+        fusd_address: ResourceAddress,
+        /// Vault holding the deposited fUSD, available for liquidations.
+        fusd_vault: Vault,
+        /// Vault holding the fUSD yield, which can't be used for liquidations.
+        yield_vault: Vault,
+        /// Vault holding the collateral gained from liquidations.
+        collateral_vault: Vault,
+        /// `ResourceManager` for the `StabilityDeposit` NFTs.
+        deposit_manager: ResourceManager,
+        /// Counter used to generate the IDs of deposit receipts.
+        deposit_counter: u64,
+        /// Sum of all compounded deposits.
+        total_deposits: Decimal,
+        /// The running product `P`.
+        product: PreciseDecimal,
+        /// The current epoch, incremented each time the pool is fully depleted.
+        epoch: u64,
+        /// The current scale, incremented each time `P` is multiplied by `SCALE_FACTOR`. Reset every epoch.
+        scale: u64,
+        /// The running sums of each scale of each epoch.
+        epoch_sums: KeyValueStore<EpochScale, EpochSums>,
+        /// fUSD currently withdrawn for a liquidation, which is settled by `offset`.
+        offset_amount: Decimal,
+        /// Collateral received while there were no deposits, added to the next distribution.
+        undistributed_collateral: Decimal,
+        /// fUSD yield received while there were no deposits, added to the next distribution.
+        undistributed_yield: Decimal,
+    }
+
+    impl ProductSumPool {
+        /// Instantiates a new `ProductSumPool` component.
+        ///
+        /// # Arguments
+        /// * `owner_role`: The `OwnerRole` of the component and the deposit receipt resource.
+        /// * `pool_manager_rule`: The `AccessRule` for the pool manager, which is allowed to deposit, withdraw and liquidate.
+        /// * `collateral`: The `ResourceAddress` of the collateral of the pool.
+        /// * `fusd_address`: The `ResourceAddress` of the fUSD token.
+        /// * `receipt_name`: The name of the deposit receipt NFT.
+        /// * `receipt_description`: The description of the deposit receipt NFT.
+        /// * `receipt_icon_url`: The icon URL of the deposit receipt NFT.
+        /// * `receipt_symbol`: The symbol of the deposit receipt NFT.
+        /// * `dapp_definition`: The DApp definition address to link in the metadata.
+        ///
+        /// # Returns
+        /// * `Global<ProductSumPool>`: A global reference to the new component.
+        pub fn instantiate(
+            owner_role: OwnerRole,
+            pool_manager_rule: AccessRule,
+            collateral: ResourceAddress,
+            fusd_address: ResourceAddress,
+            receipt_name: String,
+            receipt_description: String,
+            receipt_icon_url: Url,
+            receipt_symbol: String,
+            dapp_definition: GlobalAddress,
+        ) -> Global<ProductSumPool> {
+            let (address_reservation, component_address) =
+                Runtime::allocate_component_address(ProductSumPool::blueprint_id());
+
+            let deposit_manager: ResourceManager =
+                <scrypto::prelude::ResourceBuilder as product_sum_pool::product_sum_pool::ProductSumPoolResourceBuilder>::new_integer_non_fungible_with_registered_type::<StabilityDeposit>(owner_role.clone())
+                .metadata(metadata!(
+                    init {
+                        "name" => receipt_name, updatable;
+                        "symbol" => receipt_symbol, updatable;
+                        "description" => receipt_description, updatable;
+                        "icon_url" => receipt_icon_url, updatable;
+                        "dapp_definitions" => vec![dapp_definition], updatable;
+                    }
+                ))
+                .non_fungible_data_update_roles(non_fungible_data_update_roles!(
+                    non_fungible_data_updater => rule!(require(global_caller(component_address)));
+                    non_fungible_data_updater_updater => rule!(deny_all);
+                ))
+                .mint_roles(mint_roles!(
+                    minter => rule!(require(global_caller(component_address)));
+                    minter_updater => rule!(deny_all);
+                ))
+                .burn_roles(burn_roles!(
+                    burner => rule!(require(global_caller(component_address)));
+                    burner_updater => rule!(deny_all);
+                ))
+                .create_with_no_initial_supply()
+                .into();
+
+            let epoch_sums = <scrypto::component::KeyValueStore<_, _> as product_sum_pool::product_sum_pool::ProductSumPoolKeyValueStore>::new_with_registered_type();
+            epoch_sums.insert(
+                EpochScale { epoch: 0, scale: 0 },
+                EpochSums {
+                    collateral_sum: PreciseDecimal::ZERO,
+                    yield_sum: PreciseDecimal::ZERO,
+                },
+            );
+
+            Self {
+                collateral,
+                fusd_address,
+                fusd_vault: Vault::new(fusd_address),
+                yield_vault: Vault::new(fusd_address),
+                collateral_vault: Vault::new(collateral),
+                deposit_manager,
+                deposit_counter: 0,
+                total_deposits: Decimal::ZERO,
+                product: PreciseDecimal::ONE,
+                epoch: 0,
+                scale: 0,
+                epoch_sums,
+                offset_amount: Decimal::ZERO,
+                undistributed_collateral: Decimal::ZERO,
+                undistributed_yield: Decimal::ZERO,
+            }
+            .instantiate()
+            .prepare_to_globalize(owner_role)
+            .roles(roles! {
+                pool_manager => pool_manager_rule;
+            })
+            .with_address(address_reservation)
+            .globalize()
+        }
+
+        /// Returns the amounts of collateral and deposited fUSD held by the pool.
+        /// Mirrors `TwoResourcePool::get_vault_amounts`. The fUSD yield is not included, as it can't absorb liquidations.
+        ///
+        /// # Returns
+        /// * `IndexMap<ResourceAddress, Decimal>`: The collateral and fUSD amounts, keyed by resource.
+        pub fn get_vault_amounts(&self) -> IndexMap<ResourceAddress, Decimal> {
+            let mut amounts = IndexMap::new();
+            amounts.insert(self.collateral, self.collateral_vault.amount());
+            amounts.insert(self.fusd_address, self.fusd_vault.amount());
+            amounts
+        }
+
+        /// Returns the sum of all compounded deposits.
+        pub fn get_total_deposits(&self) -> Decimal {
+            self.total_deposits
+        }
+
+        /// Returns the `ResourceAddress` of the deposit receipt NFTs.
+        pub fn get_deposit_receipt_address(&self) -> ResourceAddress {
+            self.deposit_manager.address()
+        }
+
+        /// Returns the current state of a deposit.
+        ///
+        /// # Arguments
+        /// * `deposit_id`: The `NonFungibleLocalId` of the deposit receipt.
+        ///
+        /// # Returns
+        /// * `StabilityDepositInfo`: The compounded deposit, collateral gain and fUSD yield of the deposit.
+        pub fn get_deposit_info(&self, deposit_id: NonFungibleLocalId) -> StabilityDepositInfo {
+            let deposit: StabilityDeposit = self.deposit_manager.get_non_fungible_data(&deposit_id);
+            self.get_deposit_state(&deposit)
+        }
+
+        /// Deposits fUSD into the pool.
+        ///
+        /// # Arguments
+        /// * `fusd`: A `Bucket` containing the fUSD to deposit.
+        ///
+        /// # Returns
+        /// * `Bucket`: A `StabilityDeposit` NFT representing the deposit.
+        ///
+        /// # Panics
+        /// * If the bucket is not fUSD or is empty.
+        pub fn deposit(&mut self, fusd: Bucket) -> Bucket {
+            assert!(fusd.resource_address() == self.fusd_address, "Invalid input.");
+            assert!(fusd.amount() > Decimal::ZERO, "Deposit must be positive.");
+
+            let deposit = self.new_snapshot(fusd.amount());
+            self.total_deposits += fusd.amount();
+            self.fusd_vault.put(fusd);
+            self.deposit_counter += 1;
+
+            self.deposit_manager
+                .mint_non_fungible(&NonFungibleLocalId::integer(self.deposit_counter), deposit)
+        }
+
+        /// Withdraws deposits, including their collateral gains and fUSD yield, and burns the receipts.
+        ///
+        /// # Arguments
+        /// * `deposits`: A `Bucket` containing one or more `StabilityDeposit` NFTs.
+        ///
+        /// # Returns
+        /// * `(Bucket, Bucket)`: The collateral gains, and the compounded deposits plus the fUSD yield.
+        ///
+        /// # Panics
+        /// * If the bucket doesn't contain deposit receipts of this pool.
+        pub fn withdraw(&mut self, deposits: Bucket) -> (Bucket, Bucket) {
+            assert!(
+                deposits.resource_address() == self.deposit_manager.address(),
+                "Invalid deposit receipt."
+            );
+
+            let mut state = StabilityDepositInfo {
+                compounded_deposit: Decimal::ZERO,
+                collateral_gain: Decimal::ZERO,
+                fusd_yield: Decimal::ZERO,
+            };
+
+            for deposit_id in deposits.as_non_fungible().non_fungible_local_ids() {
+                let deposit: StabilityDeposit = self.deposit_manager.get_non_fungible_data(&deposit_id);
+                let deposit_state = self.get_deposit_state(&deposit);
+                state.compounded_deposit += deposit_state.compounded_deposit;
+                state.collateral_gain += deposit_state.collateral_gain;
+                state.fusd_yield += deposit_state.fusd_yield;
+            }
+
+            self.total_deposits = (self.total_deposits - state.compounded_deposit).max(Decimal::ZERO);
+            deposits.burn();
+
+            let collateral = self
+                .collateral_vault
+                .take(state.collateral_gain.min(self.collateral_vault.amount()));
+            let mut fusd = self
+                .fusd_vault
+                .take(state.compounded_deposit.min(self.fusd_vault.amount()));
+            fusd.put(self.yield_vault.take(state.fusd_yield.min(self.yield_vault.amount())));
+
+            (collateral, fusd)
+        }
+
+        /// Claims the collateral gains and fUSD yield of deposits, without withdrawing the deposits.
+        /// The deposits are reset to their compounded value with fresh snapshots.
+        ///
+        /// # Arguments
+        /// * `deposits_proof`: A `Proof` of one or more `StabilityDeposit` NFTs.
+        ///
+        /// # Returns
+        /// * `(Bucket, Bucket)`: The claimed collateral gains and fUSD yield.
+        ///
+        /// # Panics
+        /// * If the proof isn't of deposit receipts of this pool.
+        pub fn claim_gains(&mut self, deposits_proof: Proof) -> (Bucket, Bucket) {
+            let deposit_ids = deposits_proof
+                .check_with_message(self.deposit_manager.address(), "Invalid deposit receipt.")
+                .as_non_fungible()
+                .non_fungible_local_ids();
+
+            let mut collateral_gain = Decimal::ZERO;
+            let mut fusd_yield = Decimal::ZERO;
+
+            for deposit_id in deposit_ids {
+                let deposit: StabilityDeposit = self.deposit_manager.get_non_fungible_data(&deposit_id);
+                let state = self.get_deposit_state(&deposit);
+                collateral_gain += state.collateral_gain;
+                fusd_yield += state.fusd_yield;
+
+                let snapshot = self.new_snapshot(state.compounded_deposit);
+                self.deposit_manager
+                    .update_non_fungible_data(&deposit_id, "initial_deposit", snapshot.initial_deposit);
+                self.deposit_manager
+                    .update_non_fungible_data(&deposit_id, "product_snapshot", snapshot.product_snapshot);
+                self.deposit_manager.update_non_fungible_data(
+                    &deposit_id,
+                    "collateral_sum_snapshot",
+                    snapshot.collateral_sum_snapshot,
+                );
+                self.deposit_manager
+                    .update_non_fungible_data(&deposit_id, "yield_sum_snapshot", snapshot.yield_sum_snapshot);
+                self.deposit_manager
+                    .update_non_fungible_data(&deposit_id, "epoch", snapshot.epoch);
+                self.deposit_manager
+                    .update_non_fungible_data(&deposit_id, "scale", snapshot.scale);
+            }
+
+            (
+                self.collateral_vault
+                    .take(collateral_gain.min(self.collateral_vault.amount())),
+                self.yield_vault.take(fusd_yield.min(self.yield_vault.amount())),
+            )
+        }
+
+        /// Withdraws deposited fUSD to pay off the debt of a liquidated CDP.
+        /// Must be followed by a call to `offset` in the same transaction, which settles the liquidation.
+        ///
+        /// # Arguments
+        /// * `amount`: The amount of fUSD to withdraw.
+        ///
+        /// # Returns
+        /// * `Bucket`: The withdrawn fUSD.
+        ///
+        /// # Panics
+        /// * If a previous withdrawal hasn't been settled yet.
+        /// * If the pool holds less than `amount` fUSD.
+        pub fn withdraw_for_offset(&mut self, amount: Decimal) -> Bucket {
+            assert!(self.offset_amount == Decimal::ZERO, "Offset already in progress.");
+            assert!(amount <= self.fusd_vault.amount(), "Not enough fUSD in pool.");
+
+            self.offset_amount = amount;
+            self.fusd_vault.take(amount)
+        }
+
+        /// Settles a liquidation started with `withdraw_for_offset`.
+        /// The collateral gain is distributed over the deposits, after which the used fUSD is deducted from them.
+        ///
+        /// # Arguments
+        /// * `leftover_fusd`: A `Bucket` containing the withdrawn fUSD that wasn't used by the liquidation.
+        /// * `collateral_gain`: A `Bucket` containing the collateral received from the liquidation.
+        ///
+        /// # Panics
+        /// * If the buckets contain the wrong resources, or more fUSD is returned than was withdrawn.
+        pub fn offset(&mut self, leftover_fusd: Bucket, collateral_gain: Bucket) {
+            assert!(leftover_fusd.resource_address() == self.fusd_address, "Invalid input.");
+            assert!(collateral_gain.resource_address() == self.collateral, "Invalid input.");
+            assert!(
+                leftover_fusd.amount() <= self.offset_amount,
+                "More fUSD returned than withdrawn."
+            );
+
+            let debt_offset = self.offset_amount - leftover_fusd.amount();
+            self.offset_amount = Decimal::ZERO;
+            self.fusd_vault.put(leftover_fusd);
+
+            self.deposit_collateral(collateral_gain);
+
+            if debt_offset == Decimal::ZERO || self.total_deposits == Decimal::ZERO {
+                return;
+            }
+
+            if debt_offset >= self.total_deposits {
+                // Whatever fUSD is left belongs to no deposit anymore, so it goes to the next depositors.
+                let dust = self.fusd_vault.take(self.fusd_vault.amount());
+                self.undistributed_yield += dust.amount();
+                self.yield_vault.put(dust);
+
+                self.epoch += 1;
+                self.scale = 0;
+                self.product = PreciseDecimal::ONE;
+                self.total_deposits = Decimal::ZERO;
+                self.start_scale();
+            } else {
+                let product_factor = PreciseDecimal::from(self.total_deposits - debt_offset)
+                    / PreciseDecimal::from(self.total_deposits);
+                let scale_factor = PreciseDecimal::from(SCALE_FACTOR);
+                let minimum_product = PreciseDecimal::ONE / scale_factor;
+
+                let mut product = self.product;
+                while product * product_factor < minimum_product {
+                    product = product * scale_factor;
+                    self.scale += 1;
+                    self.start_scale();
+                }
+
+                self.product = product * product_factor;
+                self.total_deposits -= debt_offset;
+            }
+        }
+
+        /// Distributes fUSD yield over the current deposits.
+        ///
+        /// # Arguments
+        /// * `fusd`: A `Bucket` containing the fUSD yield.
+        ///
+        /// # Panics
+        /// * If the bucket is not fUSD.
+        pub fn deposit_yield(&mut self, fusd: Bucket) {
+            assert!(fusd.resource_address() == self.fusd_address, "Invalid input.");
+
+            let amount = fusd.amount() + self.undistributed_yield;
+            self.yield_vault.put(fusd);
+
+            match self.get_sum_increment(amount) {
+                Some(increment) => {
+                    self.epoch_sums.get_mut(&self.current_epoch_scale()).unwrap().yield_sum += increment;
+                    self.undistributed_yield = Decimal::ZERO;
+                }
+                None => self.undistributed_yield = amount,
+            }
+        }
+
+        /// Distributes collateral over the current deposits.
+        ///
+        /// # Arguments
+        /// * `collateral`: A `Bucket` containing the collateral.
+        ///
+        /// # Panics
+        /// * If the bucket is not the pool's collateral.
+        pub fn deposit_collateral(&mut self, collateral: Bucket) {
+            assert!(collateral.resource_address() == self.collateral, "Invalid input.");
+
+            let amount = collateral.amount() + self.undistributed_collateral;
+            self.collateral_vault.put(collateral);
+
+            match self.get_sum_increment(amount) {
+                Some(increment) => {
+                    self.epoch_sums.get_mut(&self.current_epoch_scale()).unwrap().collateral_sum += increment;
+                    self.undistributed_collateral = Decimal::ZERO;
+                }
+                None => self.undistributed_collateral = amount,
+            }
+        }
+
+        //helpers
+
+        fn current_epoch_scale(&self) -> EpochScale {
+            EpochScale {
+                epoch: self.epoch,
+                scale: self.scale,
+            }
+        }
+
+        /// Creates empty running sums for the current scale of the current epoch.
+        fn start_scale(&mut self) {
+            self.epoch_sums.insert(
+                self.current_epoch_scale(),
+                EpochSums {
+                    collateral_sum: PreciseDecimal::ZERO,
+                    yield_sum: PreciseDecimal::ZERO,
+                },
+            );
+        }
+
+        /// Returns the running sums of a scale, or zero sums if the scale hasn't been reached yet.
+        fn get_sums(&self, epoch_scale: EpochScale) -> EpochSums {
+            match self.epoch_sums.get(&epoch_scale) {
+                Some(sums) => sums.clone(),
+                None => EpochSums {
+                    collateral_sum: PreciseDecimal::ZERO,
+                    yield_sum: PreciseDecimal::ZERO,
+                },
+            }
+        }
+
+        /// Calculates the increase of a running sum when distributing `amount` over the current deposits.
+        /// Returns `None` if there are no deposits to distribute over.
+        fn get_sum_increment(&self, amount: Decimal) -> Option<PreciseDecimal> {
+            if self.total_deposits == Decimal::ZERO || amount == Decimal::ZERO {
+                return None;
+            }
+
+            Some(PreciseDecimal::from(amount) * self.product / PreciseDecimal::from(self.total_deposits))
+        }
+
+        /// Creates deposit data for a deposit of `amount`, with snapshots of the current accumulators.
+        fn new_snapshot(&self, amount: Decimal) -> StabilityDeposit {
+            let sums = self.get_sums(self.current_epoch_scale());
+
+            StabilityDeposit {
+                collateral: self.collateral,
+                initial_deposit: amount,
+                product_snapshot: self.product,
+                collateral_sum_snapshot: sums.collateral_sum,
+                yield_sum_snapshot: sums.yield_sum,
+                epoch: self.epoch,
+                scale: self.scale,
+            }
+        }
+
+        /// Calculates the compounded deposit, collateral gain and fUSD yield of a deposit.
+        /// Gains made in the scale after the deposit's scale are divided by `SCALE_FACTOR`, later scales are ignored.
+        fn get_deposit_state(&self, deposit: &StabilityDeposit) -> StabilityDepositInfo {
+            let sums = self.get_sums(EpochScale {
+                epoch: deposit.epoch,
+                scale: deposit.scale,
+            });
+            let next_sums = self.get_sums(EpochScale {
+                epoch: deposit.epoch,
+                scale: deposit.scale + 1,
+            });
+            let scale_factor = PreciseDecimal::from(SCALE_FACTOR);
+            let initial_deposit = PreciseDecimal::from(deposit.initial_deposit);
+
+            let mut compounded_deposit = if deposit.epoch != self.epoch {
+                PreciseDecimal::ZERO
+            } else if self.scale == deposit.scale {
+                initial_deposit * self.product / deposit.product_snapshot
+            } else if self.scale == deposit.scale + 1 {
+                initial_deposit * self.product / deposit.product_snapshot / scale_factor
+            } else {
+                PreciseDecimal::ZERO
+            };
+            // Anything below a billionth of the initial deposit is precision noise.
+            if compounded_deposit < initial_deposit / scale_factor {
+                compounded_deposit = PreciseDecimal::ZERO;
+            }
+
+            let collateral_gain = initial_deposit
+                * (sums.collateral_sum - deposit.collateral_sum_snapshot + next_sums.collateral_sum / scale_factor)
+                / deposit.product_snapshot;
+            let fusd_yield = initial_deposit
+                * (sums.yield_sum - deposit.yield_sum_snapshot + next_sums.yield_sum / scale_factor)
+                / deposit.product_snapshot;
+
+            StabilityDepositInfo {
+                compounded_deposit: compounded_deposit
+                    .checked_truncate(RoundingMode::ToZero)
+                    .unwrap()
+                    .min(deposit.initial_deposit),
+                collateral_gain: collateral_gain.checked_truncate(RoundingMode::ToZero).unwrap(),
+                fusd_yield: fusd_yield.checked_truncate(RoundingMode::ToZero).unwrap(),
+            }
+        }
+    }
+}
@@ -26,6 +26,7 @@ use crate::flash_loans::flash_loans::*;
 use crate::shared_structs::*;
 use crate::stability_pools::stability_pools::*;
 use crate::payout_component::payout_component::*;
+use crate::liquidity_rewards_distributor::liquidity_rewards_distributor::*;
 use scrypto::prelude::*;
 
 #[blueprint]
@@ -37,13 +38,18 @@ mod proxy {
             open_cdp => PUBLIC;
             close_cdp => PUBLIC;
             top_up_cdp => PUBLIC;
+            top_up_cdp_with_twap => PUBLIC;
             remove_collateral => PUBLIC;
             change_cdp_interest => PUBLIC;
             partial_close_cdp => PUBLIC;
             retrieve_leftover_collateral => PUBLIC;
             borrow_more => PUBLIC;
             flash_borrow => PUBLIC;
+            flash_borrow_with_tier => PUBLIC;
             flash_pay_back => PUBLIC;
+            flash_loan_with_callback => PUBLIC;
+            flash_borrow_collateral => PUBLIC;
+            flash_pay_back_collateral => PUBLIC;
             burn_loan_receipt => PUBLIC; // Allows burning finalized CDP NFTs
             tag_irredeemable => PUBLIC;
             unmark => PUBLIC;
@@ -72,7 +78,20 @@ mod proxy {
             create_privileged_borrower => restrict_to: [OWNER]; // Create Flux privileged borrower NFT
             edit_privileged_borrower => restrict_to: [OWNER]; // Edit Flux privileged borrower NFT data
             payout_set_parameters => restrict_to: [OWNER]; // Set PayoutComponent parameters
+            payout_set_revenue_split => restrict_to: [OWNER]; // Set PayoutComponent revenue split table
+            payout_deposit_protocol_owned_liquidity => restrict_to: [OWNER]; // Deposit PayoutComponent protocol-owned liquidity
             set_panic_mode_parameters => restrict_to: [OWNER]; // Set StabilityPools panic mode parameters
+            set_panic_mode_stablecoins => restrict_to: [OWNER]; // Set StabilityPools panic mode stablecoins
+            set_stablecoin_unwind => restrict_to: [OWNER]; // Set StabilityPools stablecoin reserve unwinding
+            set_stability_pool_lock_parameters => restrict_to: [OWNER]; // Set StabilityPools lock-up parameters
+            set_stability_pool_withdrawal_parameters => restrict_to: [OWNER]; // Set StabilityPools withdrawal delay
+            set_stability_pool_conversion => restrict_to: [OWNER]; // Set StabilityPools collateral conversion
+            new_shared_stability_pool => restrict_to: [OWNER]; // Create the shared StabilityPools pool
+            set_shared_stability_pool_opt_in => restrict_to: [OWNER]; // Opt a collateral in/out of the shared pool
+            set_keeper_bounty => restrict_to: [OWNER]; // Set a StabilityPools keeper bounty
+            set_keeper_funding_share => restrict_to: [OWNER]; // Set the payout share funding keeper bounties
+            new_liquidity_rewards_distributor => restrict_to: [OWNER]; // Create a LiquidityRewardsDistributor
+            set_liquidity_rewards_distributor_parameters => restrict_to: [OWNER]; // Set LiquidityRewardsDistributor params
         }
     }
 
@@ -135,7 +154,7 @@ mod proxy {
             oracle_address: ComponentAddress,
             centralized_stablecoin_address: ResourceAddress,
             payout_token_address: ResourceAddress,
-            payout_initial_required_amount: Decimal,
+            payout_initial_reference_price: Decimal,
             manual_liquidity_airdropper_address: ResourceAddress,
         ) -> (Global<Proxy>, Global<Flux>, Global<FlashLoans>, Global<StabilityPools>, Global<PayoutComponent>, Bucket) {
             let (address_reservation, component_address) =
@@ -181,7 +200,7 @@ mod proxy {
             let payout_component = PayoutComponent::instantiate(
                 controller_badge.take(1),
                 payout_token_address,
-                payout_initial_required_amount,
+                payout_initial_reference_price,
                 fusd_address,
                 stability_pools.address(),
                 owner_role.clone(),
@@ -534,6 +553,38 @@ mod proxy {
             })
         }
 
+        /// Adds more collateral to an existing CDP when no fresh signed price is available.
+        /// Calls `Flux::top_up_cdp` without a price, so the CDP's health is checked at the collateral's stored
+        /// price and no price is written. The oracle's time-weighted average price can lag the market, so it is
+        /// never used to update the stored price or to unmark a CDP: marked CDPs need a signed price to be topped up.
+        /// Closing a CDP needs no price at all.
+        /// Requires proof of ownership of the CDP NFT.
+        ///
+        /// # Arguments
+        /// * `receipt_proof`: A `NonFungibleProof` of the CDP NFT.
+        /// * `collateral`: A `Bucket` containing the additional collateral.
+        ///
+        /// # Panics
+        /// * If the CDP is marked for liquidation.
+        /// * If the CDP is below its MCR at the stored price after the top up.
+        pub fn top_up_cdp_with_twap(&mut self, receipt_proof: NonFungibleProof, collateral: Bucket) {
+            let receipt_proof = receipt_proof.check_with_message(
+                self.cdp_receipt_manager.address(),
+                "Incorrect proof! Are you sure this loan is yours?",
+            );
+            let receipt = receipt_proof.non_fungible::<Cdp>();
+            let receipt_id: NonFungibleLocalId = receipt.local_id().clone();
+
+            assert!(
+                receipt.data().status != CdpStatus::Marked,
+                "Marked loans can only be topped up with a signed price."
+            );
+
+            self.badge_vault.authorize_with_amount(dec!(0.75), || {
+                self.flux.top_up_cdp(receipt_id, collateral, None)
+            })
+        }
+
         /// Removes collateral from an existing CDP, provided it remains sufficiently collateralized.
         /// Fetches the collateral price from the oracle and calls `Flux::remove_collateral`.
         /// Requires proof of ownership of the CDP NFT.
@@ -941,7 +992,22 @@ mod proxy {
         /// * `(Bucket, Bucket)`: Tuple containing the borrowed fUSD and the transient LoanReceipt NFT.
         pub fn flash_borrow(&mut self, amount: Decimal) -> (Bucket, Bucket) {
             self.badge_vault
-                .authorize_with_amount(dec!("0.75"), || self.flash_loans.borrow(amount))
+                .authorize_with_amount(dec!("0.75"), || self.flash_loans.borrow(amount, None))
+        }
+
+        /// Borrows fUSD via the FlashLoans component at the discounted interest rate of a fee tier.
+        /// Calls `FlashLoans::borrow`.
+        ///
+        /// # Arguments
+        /// * `amount`: The `Decimal` amount of fUSD to borrow.
+        /// * `fee_tier_proof`: Proof of a badge qualifying for a discounted interest rate (e.g. a `fusdPRIV` NFT).
+        ///
+        /// # Returns
+        /// * `(Bucket, Bucket)`: Tuple containing the borrowed fUSD and the transient LoanReceipt NFT.
+        pub fn flash_borrow_with_tier(&mut self, amount: Decimal, fee_tier_proof: Proof) -> (Bucket, Bucket) {
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                self.flash_loans.borrow(amount, Some(fee_tier_proof))
+            })
         }
 
         /// Pays back a flash loan.
@@ -959,6 +1025,75 @@ mod proxy {
             })
         }
 
+        /// Takes out an fUSD flash loan that is handed to a callback method on another component.
+        /// Calls `FlashLoans::flash_loan_with_callback`.
+        ///
+        /// # Arguments
+        /// * `amount`: The `Decimal` amount of fUSD to borrow.
+        /// * `target_component`: The `ComponentAddress` of the component to call with the borrowed fUSD.
+        /// * `method_name`: The callback method, called as `method_name(loan: Bucket, args: Vec<u8>) -> Bucket`.
+        /// * `args`: Opaque arguments passed to the callback.
+        /// * `fee_tier_proof`: Optional proof of a badge qualifying for a discounted interest rate.
+        ///
+        /// # Returns
+        /// * `Bucket`: Any fUSD returned by the callback in excess of the borrowed amount plus interest.
+        pub fn flash_loan_with_callback(
+            &self,
+            amount: Decimal,
+            target_component: ComponentAddress,
+            method_name: String,
+            args: Vec<u8>,
+            fee_tier_proof: Option<Proof>,
+        ) -> Bucket {
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                self.flash_loans.flash_loan_with_callback(
+                    amount,
+                    target_component,
+                    method_name,
+                    args,
+                    fee_tier_proof,
+                )
+            })
+        }
+
+        /// Borrows collateral via the FlashLoans component.
+        /// Calls `FlashLoans::borrow_collateral`.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral to borrow.
+        /// * `amount`: The `Decimal` amount of collateral to borrow.
+        ///
+        /// # Returns
+        /// * `(Bucket, Bucket)`: Tuple containing the borrowed collateral and the transient CollateralLoanReceipt NFT.
+        pub fn flash_borrow_collateral(&mut self, collateral: ResourceAddress, amount: Decimal) -> (Bucket, Bucket) {
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                self.flash_loans.borrow_collateral(collateral, amount)
+            })
+        }
+
+        /// Pays back a collateral flash loan.
+        /// Calls `FlashLoans::pay_back_collateral` and deposits the fee into the stability pool of the collateral.
+        ///
+        /// # Arguments
+        /// * `receipt_bucket`: The `Bucket` containing the transient CollateralLoanReceipt NFT.
+        /// * `payment_bucket`: A `Bucket` containing the collateral repayment (principal + fee).
+        ///
+        /// # Returns
+        /// * `Bucket`: Any leftover collateral from the `payment_bucket`.
+        pub fn flash_pay_back_collateral(&mut self, receipt_bucket: Bucket, payment_bucket: Bucket) -> Bucket {
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                let (leftover, fee) = self.flash_loans.pay_back_collateral(receipt_bucket, payment_bucket);
+
+                if fee.amount() > Decimal::ZERO {
+                    self.stability_pools.receive_collateral_fee(fee);
+                } else {
+                    fee.drop_empty();
+                }
+
+                leftover
+            })
+        }
+
         /// ADMIN: Retrieves accumulated interest from the FlashLoans component.
         /// Calls `FlashLoans::retrieve_interest`.
         /// Requires OWNER authorization on the Proxy.
@@ -1002,6 +1137,9 @@ mod proxy {
         /// * `stability_pool_split`: Optional new stability pool split ratio.
         /// * `allow_pool_buys`: New boolean value for allowing direct pool buys.
         /// * `pool_buy_price_modifier`: Optional new price modifier for direct pool buys.
+        /// * `deposit_cap`: Optional maximum amount of fUSD the pool accepts through contributions.
+        /// * `contributor_badge`: Optional badge required to contribute to the pool.
+        /// * `guarded_launch`: Optional guarded-launch settings, tying the collateral's debt ceiling to the pool's depth.
         pub fn edit_stability_pool(
             &self,
             collateral: ResourceAddress,
@@ -1010,6 +1148,9 @@ mod proxy {
             stability_pool_split: Option<Decimal>,
             allow_pool_buys: bool,
             pool_buy_price_modifier: Option<Decimal>,
+            deposit_cap: Option<Decimal>,
+            contributor_badge: Option<ResourceAddress>,
+            guarded_launch: Option<GuardedLaunch>,
         ) {
             self.badge_vault.authorize_with_amount(dec!("0.75"), || {
                 self.stability_pools.edit_pool(
@@ -1019,6 +1160,9 @@ mod proxy {
                     stability_pool_split,
                     allow_pool_buys,
                     pool_buy_price_modifier,
+                    deposit_cap,
+                    contributor_badge,
+                    guarded_launch,
                 );
             })
         }
@@ -1082,28 +1226,184 @@ mod proxy {
             })
         }
 
+        /// ADMIN: Sets the stablecoins accepted for panic mode liquidations, and the order in which redemptions
+        /// drain their reserves.
+        /// Requires OWNER authorization on the Proxy.
+        /// Calls `StabilityPools::set_panic_mode_stablecoins`.
+        ///
+        /// # Arguments
+        /// * `stablecoins`: The accepted stablecoins with their depeg tolerance.
+        /// * `redemption_order`: The order in which redemptions drain the stablecoin vaults.
+        pub fn set_panic_mode_stablecoins(
+            &self,
+            stablecoins: Vec<PanicModeStablecoin>,
+            redemption_order: Vec<ResourceAddress>,
+        ) {
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                self.stability_pools.set_panic_mode_stablecoins(stablecoins, redemption_order);
+            })
+        }
+
+        /// ADMIN: Enables, edits or disables the unwinding of panic mode stablecoin reserves back into fUSD.
+        /// Requires OWNER authorization on the Proxy.
+        /// Calls `StabilityPools::set_stablecoin_unwind`.
+        ///
+        /// # Arguments
+        /// * `method`: The `StablecoinUnwindMethod` to use, `None` to disable unwinding.
+        /// * `max_slippage`: The maximum fraction below the oracle value reserves can be sold for through a DEX adapter.
+        /// * `max_fusd_per_period`: The maximum amount of fUSD bought back and burned per period.
+        /// * `period`: The length of a rate limit period, in minutes.
+        /// * `min_pool_coverage`: The minimum stability pool fUSD, as a fraction of the collateral's debt, of every
+        ///   collateral that has been (pending) panic mode before reserves can be unwound.
+        pub fn set_stablecoin_unwind(
+            &self,
+            method: Option<StablecoinUnwindMethod>,
+            max_slippage: Decimal,
+            max_fusd_per_period: Decimal,
+            period: i64,
+            min_pool_coverage: Decimal,
+        ) {
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                self.stability_pools.set_stablecoin_unwind(method, max_slippage, max_fusd_per_period, period, min_pool_coverage);
+            })
+        }
+
+        /// ADMIN: Sets the parameters for locking stability pool units.
+        /// Requires OWNER authorization on the Proxy.
+        /// Calls `StabilityPools::set_lock_parameters`.
+        ///
+        /// # Arguments
+        /// * `max_lock_duration`: The maximum lock duration (minutes), which receives the full boost.
+        /// * `max_lock_boost`: The extra weight of pool units locked for the maximum duration.
+        /// * `early_unlock_penalty`: The fraction of pool units lost when unlocking right after locking.
+        pub fn set_stability_pool_lock_parameters(
+            &self,
+            max_lock_duration: i64,
+            max_lock_boost: Decimal,
+            early_unlock_penalty: Decimal,
+        ) {
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                self.stability_pools.set_lock_parameters(
+                    max_lock_duration,
+                    max_lock_boost,
+                    early_unlock_penalty,
+                );
+            })
+        }
+
+        /// ADMIN: Sets the two-step withdrawal parameters for the StabilityPools component.
+        /// Requires OWNER authorization on the Proxy.
+        /// Calls `StabilityPools::set_withdrawal_parameters`.
+        ///
+        /// # Arguments
+        /// * `withdrawal_delay`: The delay (minutes) between requesting and claiming a withdrawal. 0 allows direct withdrawals.
+        /// * `withdrawal_claim_window`: The duration (minutes) after the delay in which a withdrawal can be claimed.
+        pub fn set_stability_pool_withdrawal_parameters(
+            &self,
+            withdrawal_delay: i64,
+            withdrawal_claim_window: i64,
+        ) {
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                self.stability_pools.set_withdrawal_parameters(
+                    withdrawal_delay,
+                    withdrawal_claim_window,
+                );
+            })
+        }
+
+        /// ADMIN: Enables, edits or disables the conversion of a stability pool's collateral back to fUSD.
+        /// Requires OWNER authorization on the Proxy.
+        /// Calls `StabilityPools::set_collateral_conversion`.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` identifying the pool.
+        /// * `method`: The `ConversionMethod` to use, `None` to disable conversions.
+        /// * `threshold`: The share of the pool's value held in collateral above which a conversion can be triggered.
+        /// * `max_slippage`: The maximum fraction below the oracle price collateral can be sold for.
+        /// * `max_sale_amount`: Optional cap on the amount of collateral sold per conversion.
+        pub fn set_stability_pool_conversion(
+            &self,
+            collateral: ResourceAddress,
+            method: Option<ConversionMethod>,
+            threshold: Decimal,
+            max_slippage: Decimal,
+            max_sale_amount: Option<Decimal>,
+        ) {
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                self.stability_pools.set_collateral_conversion(
+                    collateral,
+                    method,
+                    threshold,
+                    max_slippage,
+                    max_sale_amount,
+                );
+            })
+        }
+
         //==================================================================
         //                    Payout Component
         //==================================================================
 
-        /// Sets the payment details (token address and required amount) on the PayoutComponent.
+        /// Sets the rewards auction parameters on the PayoutComponent.
         /// Requires OWNER authorization (Proxy's admin badge).
         ///
         /// # Arguments
-        /// * `new_payment_token_address`: The new `ResourceAddress` for payment.
-        /// * `new_required_payment_amount`: The new `Decimal` amount required for payment.
+        /// * `start_premium`: The fraction above the reference price at which each auction epoch starts.
+        /// * `decay_per_minute`: The fraction of the reference price the auction price decreases by every minute.
+        /// * `max_discount`: The maximum fraction below the reference price the auction price can decrease to.
+        /// * `reference_price`: Optional new reference price, in payment tokens per fUSD.
+        /// * `min_reference_price`: Optional new minimum reference price, in payment tokens per fUSD.
+        /// * `burn`: Whether to burn the payment token after claiming rewards.
         pub fn payout_set_parameters(
             &mut self,
-            new_required_payment_amount: Decimal,
+            start_premium: Decimal,
+            decay_per_minute: Decimal,
+            max_discount: Decimal,
+            reference_price: Option<Decimal>,
+            min_reference_price: Option<Decimal>,
             burn: bool,
         ) {
             self.badge_vault.authorize_with_amount(dec!(1.0), || {
                  self.payout_component.set_parameters(
-                    new_required_payment_amount,
+                    start_premium,
+                    decay_per_minute,
+                    max_discount,
+                    reference_price,
+                    min_reference_price,
                     burn
                 )
             });
         }
+
+        /// Sets the revenue split table on the PayoutComponent.
+        /// Requires OWNER authorization (Proxy's admin badge).
+        ///
+        /// # Arguments
+        /// * `revenue_split`: The destinations of the revenue and the share each receives. Shares must add up to 1.
+        pub fn payout_set_revenue_split(&mut self, revenue_split: Vec<(RevenueDestination, Decimal)>) {
+            self.badge_vault.authorize_with_amount(dec!(1.0), || {
+                self.payout_component.set_revenue_split(revenue_split)
+            });
+        }
+
+        /// Deposits the fUSD the PayoutComponent set aside for protocol-owned liquidity into a collateral's
+        /// stability pool.
+        /// Requires OWNER authorization (Proxy's admin badge).
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral whose stability pool is deposited into.
+        /// * `message`: Oracle message for price verification.
+        /// * `signature`: Oracle signature for price verification.
+        pub fn payout_deposit_protocol_owned_liquidity(
+            &mut self,
+            collateral: ResourceAddress,
+            message: String,
+            signature: String,
+        ) {
+            self.badge_vault.authorize_with_amount(dec!(1.0), || {
+                self.payout_component.deposit_protocol_owned_liquidity(collateral, message, signature)
+            });
+        }
         //                    COMBINATIONS OF COMPONENTS
         //==================================================================
 
@@ -1120,6 +1420,7 @@ mod proxy {
         /// * `stability_pool_split`: Optional stability pool split override for the new stability pool.
         /// * `allow_pool_buys`: Whether to allow direct buys from the new stability pool.
         /// * `pool_buy_price_modifier`: Optional price modifier for direct buys from the new stability pool.
+        /// * `pool_type`: The accounting used by the new stability pool (`TwoResource` or `ProductSum`).
         /// * `pool_name`: Name for the new stability pool's unit token.
         /// * `pool_description`: Description for the new stability pool's unit token.
         /// * `pool_icon_url`: Icon URL for the new stability pool's unit token.
@@ -1134,6 +1435,7 @@ mod proxy {
             stability_pool_split: Option<Decimal>,
             allow_pool_buys: bool,
             pool_buy_price_modifier: Option<Decimal>,
+            pool_type: StabilityPoolType,
             pool_name: String,
             pool_description: String,
             pool_icon_url: Url,
@@ -1152,6 +1454,7 @@ mod proxy {
                     stability_pool_split,
                     allow_pool_buys,
                     pool_buy_price_modifier,
+                    pool_type,
                     pool_name,
                     pool_description,
                     pool_icon_url,
@@ -1167,5 +1470,149 @@ mod proxy {
                 });
             }
         }
+
+        /// ADMIN: Creates the shared stability pool, covering liquidations of opted-in collaterals.
+        /// Requires OWNER authorization on the Proxy.
+        /// Calls `StabilityPools::new_shared_pool` and claims the shared pool unit custody receipt in the DApp definition.
+        ///
+        /// # Arguments
+        /// * `collaterals`: The collaterals the shared pool can cover.
+        /// * `seed`: Buckets of fUSD and every collateral, used as the first contribution.
+        /// * `pool_name`: Name metadata for the shared pool unit token.
+        /// * `pool_description`: Description metadata for the shared pool unit token.
+        /// * `pool_icon_url`: Icon URL metadata for the shared pool unit token.
+        /// * `pool_token_symbol`: Symbol metadata for the shared pool unit token.
+        pub fn new_shared_stability_pool(
+            &mut self,
+            collaterals: Vec<ResourceAddress>,
+            seed: Vec<Bucket>,
+            pool_name: String,
+            pool_description: String,
+            pool_icon_url: Url,
+            pool_token_symbol: String,
+        ) {
+            let pool_unit_resource_address = self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                self.stability_pools.new_shared_pool(
+                    collaterals,
+                    seed,
+                    pool_name,
+                    pool_description,
+                    pool_icon_url,
+                    pool_token_symbol,
+                    GlobalAddress::from(self.dapp_def_account.address()),
+                )
+            });
+
+            if let Ok(Some(mut claimed_entities)) = self.dapp_def_account.get_metadata::<&str, Vec<GlobalAddress>>("claimed_entities") {
+                claimed_entities.push(GlobalAddress::from(pool_unit_resource_address));
+                self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                    let _ = self.dapp_def_account.set_metadata("claimed_entities", claimed_entities);
+                });
+            }
+        }
+
+        /// ADMIN: Sets whether liquidations of a collateral fall back to the shared stability pool.
+        /// Requires OWNER authorization on the Proxy.
+        /// Calls `StabilityPools::set_shared_pool_opt_in`.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` identifying the collateral's stability pool.
+        /// * `use_shared_pool`: `true` to opt in, `false` to opt out.
+        pub fn set_shared_stability_pool_opt_in(&self, collateral: ResourceAddress, use_shared_pool: bool) {
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                self.stability_pools.set_shared_pool_opt_in(collateral, use_shared_pool);
+            })
+        }
+
+        /// ADMIN: Sets the bounty paid to keepers for a housekeeping action.
+        /// Requires OWNER authorization on the Proxy.
+        /// Calls `StabilityPools::set_keeper_bounty`.
+        ///
+        /// # Arguments
+        /// * `action`: The `KeeperAction` to set the bounty for.
+        /// * `amount`: The fUSD bounty paid per call. 0 disables the bounty.
+        /// * `cooldown`: The duration (minutes) after a paid bounty during which no bounty is paid for this action.
+        pub fn set_keeper_bounty(&self, action: KeeperAction, amount: Decimal, cooldown: i64) {
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                self.stability_pools.set_keeper_bounty(action, amount, cooldown);
+            })
+        }
+
+        /// ADMIN: Sets the share of the payout split that is set aside to fund keeper bounties.
+        /// Requires OWNER authorization on the Proxy.
+        /// Calls `StabilityPools::set_keeper_funding_share`.
+        ///
+        /// # Arguments
+        /// * `funding_share`: The fraction of the payout split set aside for keeper bounties.
+        pub fn set_keeper_funding_share(&self, funding_share: Decimal) {
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                self.stability_pools.set_keeper_funding_share(funding_share);
+            })
+        }
+
+        /// ADMIN: Creates a distributor that streams the liquidity rewards of stability pools to stakers of an LP token.
+        /// Requires OWNER authorization on the Proxy.
+        /// Mints a controller badge for the distributor, instantiates it and claims it in the DApp definition.
+        ///
+        /// # Arguments
+        /// * `lp_token_address`: The `ResourceAddress` of the fUSD DEX LP token that can be staked.
+        /// * `collaterals`: The collaterals whose stability pool liquidity rewards are distributed.
+        /// * `stream_duration`: The duration (seconds) over which fetched rewards are streamed.
+        ///
+        /// # Returns
+        /// * `Global<LiquidityRewardsDistributor>`: A global reference to the new distributor.
+        pub fn new_liquidity_rewards_distributor(
+            &mut self,
+            lp_token_address: ResourceAddress,
+            collaterals: Vec<ResourceAddress>,
+            stream_duration: i64,
+        ) -> Global<LiquidityRewardsDistributor> {
+            let controller_badge = self
+                .badge_vault
+                .authorize_with_amount(dec!("0.75"), || self.flux.mint_controller_badge(Decimal::ONE));
+
+            let distributor = LiquidityRewardsDistributor::instantiate(
+                controller_badge,
+                self.stability_pools.address(),
+                self.flux.get_fusd_address(),
+                lp_token_address,
+                collaterals,
+                stream_duration,
+                OwnerRole::Fixed(rule!(require_amount(
+                    dec!("0.75"),
+                    self.badge_vault.resource_address(),
+                ))),
+                GlobalAddress::from(self.dapp_def_account.address()),
+            );
+
+            if let Ok(Some(mut claimed_entities)) = self.dapp_def_account.get_metadata::<&str, Vec<GlobalAddress>>("claimed_entities") {
+                claimed_entities.push(GlobalAddress::from(distributor.address()));
+                self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                    let _ = self.dapp_def_account.set_metadata("claimed_entities", claimed_entities);
+                });
+            }
+
+            distributor
+        }
+
+        /// ADMIN: Sets the collaterals and stream duration of a liquidity rewards distributor.
+        /// Requires OWNER authorization on the Proxy.
+        /// Calls `LiquidityRewardsDistributor::set_parameters`.
+        ///
+        /// # Arguments
+        /// * `distributor_address`: The `ComponentAddress` of the distributor.
+        /// * `collaterals`: The collaterals whose stability pool liquidity rewards are distributed.
+        /// * `stream_duration`: The duration (seconds) over which fetched rewards are streamed.
+        pub fn set_liquidity_rewards_distributor_parameters(
+            &self,
+            distributor_address: ComponentAddress,
+            collaterals: Vec<ResourceAddress>,
+            stream_duration: i64,
+        ) {
+            let distributor: Global<LiquidityRewardsDistributor> = Global::from(distributor_address);
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                distributor.set_parameters(collaterals, stream_duration);
+            })
+        }
     }
 }
@@ -91,4 +91,108 @@ pub struct CollateralInfoReturn {
     pub uncharged_interest: Decimal,
     /// Indicates if this collateral type is currently accepted for opening new CDPs.
     pub accepted: bool,
+    /// The maximum total debt that can be borrowed against this collateral, if any.
+    pub debt_ceiling: Option<Decimal>,
+}
+
+/// The accounting used by a collateral's stability pool, chosen when the pool is created.
+#[derive(ScryptoSbor, PartialEq, Clone, Copy, Debug)]
+pub enum StabilityPoolType {
+    /// A Radix `TwoResourcePool`. Contributors hold fungible pool units, representing a share of the pooled
+    /// collateral and fUSD, and rewards are added to the pool itself.
+    TwoResource,
+    /// A `ProductSumPool`. Deposits stay fUSD denominated and are represented by NFTs, collateral gains and fUSD
+    /// yield are tracked and claimable per deposit.
+    ProductSum,
+}
+
+/// How a stability pool converts accumulated collateral back into fUSD (see `StabilityPools::set_collateral_conversion`).
+#[derive(ScryptoSbor, PartialEq, Clone, Debug)]
+pub enum ConversionMethod {
+    /// Sells the collateral through an external DEX adapter. The adapter method is called with a single `Bucket` of
+    /// collateral and must return a single `Bucket` of fUSD. After a sale, no new sale can be made for `cooldown` minutes.
+    DexAdapter {
+        component: ComponentAddress,
+        method_name: String,
+        cooldown: i64,
+    },
+    /// Sells the collateral in a descending-price auction. The price starts at the oracle price increased by
+    /// `start_premium`, and decreases by `decay_per_minute` (as a fraction of the oracle price) every minute.
+    /// The auction expires `duration` minutes after it started.
+    Auction {
+        start_premium: Decimal,
+        decay_per_minute: Decimal,
+        duration: i64,
+    },
+}
+
+/// A housekeeping action keepers can earn a bounty for (see `StabilityPools::set_keeper_bounty`).
+#[derive(ScryptoSbor, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum KeeperAction {
+    /// Charging interest, through `StabilityPools::charge_interest` or `StabilityPools::charge_all_interest`.
+    ChargeInterest,
+    /// Initiating or activating panic mode, through `StabilityPools::check_and_initiate_panic_mode`.
+    PanicModeCheck,
+    /// Updating a derivative's redemption rate in the oracle, through `StabilityPools::update_oracle_redemption_rate`.
+    OracleRateUpdate,
+    /// Fetching rewards into the payout component, through `PayoutComponent::fetch_rewards_from_stability_pools`.
+    PayoutFetch,
+}
+
+/// A stablecoin accepted for panic mode liquidations (see `StabilityPools::set_panic_mode_stablecoins`).
+#[derive(ScryptoSbor, PartialEq, Clone, Copy, Debug)]
+pub struct PanicModeStablecoin {
+    /// The `ResourceAddress` of the stablecoin.
+    pub address: ResourceAddress,
+    /// The maximum deviation of the stablecoin's oracle price from 1 USD for it to be accepted.
+    pub depeg_tolerance: Decimal,
+}
+
+/// How panic mode stablecoin reserves are unwound back into fUSD once panic mode has ended
+/// (see `StabilityPools::set_stablecoin_unwind`). The fUSD received is burned.
+#[derive(ScryptoSbor, PartialEq, Clone, Debug)]
+pub enum StablecoinUnwindMethod {
+    /// Sells the reserves through an external DEX adapter. The adapter method is called with a single `Bucket` of
+    /// stablecoin and must return a single `Bucket` of fUSD.
+    DexAdapter {
+        component: ComponentAddress,
+        method_name: String,
+    },
+    /// Opens a buy-back window in which anyone can sell fUSD for the reserves, receiving stablecoin worth
+    /// `1 - discount` USD per fUSD at the stablecoin's oracle price.
+    BuyBackWindow {
+        discount: Decimal,
+    },
+}
+
+/// A destination of the revenue fetched by the payout component (see `PayoutComponent::set_revenue_split`).
+#[derive(ScryptoSbor, PartialEq, Eq, Hash, Clone, Debug)]
+pub enum RevenueDestination {
+    /// Sold for the payment token in the payout component's rewards auction.
+    BuyBackAuction,
+    /// Deposited into the DAO treasury account, through `PayoutComponent::push_revenue`.
+    DaoTreasury {
+        account: ComponentAddress,
+    },
+    /// Contributed to the savings rate through an external component, through `PayoutComponent::push_revenue`.
+    /// The method is called with a single `Bucket` of fUSD and must not return anything.
+    SavingsRate {
+        component: ComponentAddress,
+        method_name: String,
+    },
+    /// Deposited into a collateral's stability pool as protocol-owned liquidity, through
+    /// `PayoutComponent::deposit_protocol_owned_liquidity`.
+    StabilityPoolDeposit {
+        collateral: ResourceAddress,
+    },
+}
+
+/// Guarded-launch settings of a stability pool, under which the collateral's debt ceiling grows with pool depth.
+/// The debt ceiling is `base_debt_ceiling + debt_per_pool_fusd * fUSD in the pool`.
+#[derive(ScryptoSbor, PartialEq, Clone, Copy, Debug)]
+pub struct GuardedLaunch {
+    /// The debt ceiling of an empty pool, so the first fUSD can be borrowed.
+    pub base_debt_ceiling: Decimal,
+    /// The increase of the debt ceiling per fUSD held by the pool.
+    pub debt_per_pool_fusd: Decimal,
 }
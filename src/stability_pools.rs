@@ -19,7 +19,35 @@
 //! - **Reward Distribution:** Manages the distribution of rewards (e.g., liquidation profits, interest income)
 //!   among stability pool contributors, liquidity providers (future feature?), and a designated payout component.
 //! - **Panic Mode:** Implements a panic mode mechanism using centralized stablecoins to handle liquidations
-//!   when stability pools lack sufficient fUSD.
+//!   when stability pools lack sufficient fUSD. Panic mode is tracked per collateral, so a shortage in one pool
+//!   doesn't enable panic mode liquidations of other collaterals' CDPs. Multiple stablecoins can be accepted. Each is
+//!   checked against its oracle price and a depeg tolerance, and fUSD is minted at the stablecoin's oracle value.
+//!   Redemptions drain the stablecoin reserves in a configurable order before redeeming against CDPs, paying
+//!   stablecoins out at their oracle value and skipping those outside their depeg tolerance.
+//! - **Stablecoin Unwinding:** Once panic mode has ended for all collaterals and their stability pools again hold a
+//!   configured share of their debt, the stablecoin reserves can be swapped back into fUSD, either through a DEX
+//!   adapter or through an open buy-back window. The fUSD received is burned, limited to a maximum amount per period.
+//! - **Lock-ups:** Pool units can be locked for a chosen duration in exchange for a `StabilityPoolLock` NFT.
+//!   Locked units are weighted by a boost proportional to the lock length, and earn a boosted share of the
+//!   liquidity rewards and of the stability pool's share of charged interest. Unlocking early costs a penalty,
+//!   which is returned to the pool.
+//! - **Withdrawal Delay:** If a withdrawal delay is configured, withdrawing takes two steps. Pool units (or deposit
+//!   receipts) are escrowed with `request_withdrawal`, and can be redeemed with `claim_withdrawal` once the delay has
+//!   passed, within a claim window. Escrowed units stay in the pool and keep absorbing liquidations, so depositors
+//!   can't exit right before a liquidation they see coming.
+//! - **Collateral Conversion:** Once collateral makes up more than a configured share of a pool's value, anyone can
+//!   trigger `convert_pool_collateral` to convert part of it back into fUSD, either by selling it through a DEX
+//!   adapter or by starting a descending-price auction. Both are bounded by a maximum slippage relative to the
+//!   oracle price, restoring the pool's capacity to absorb liquidations.
+//! - **Shared Pool:** An optional shared stability pool holds fUSD for all collaterals that opt in. If a collateral's
+//!   own pool can't cover a liquidation, `liquidate` falls back to the shared pool, which then receives the seized
+//!   collateral. Panic mode is only considered when neither pool can cover the liquidation.
+//! - **Keeper Rewards:** A configurable share of the payout split is set aside to pay fUSD bounties to keepers for
+//!   housekeeping calls (charging interest, panic mode checks, oracle redemption rate updates and payout fetches).
+//!   Each action has its own bounty and cooldown, so bounties can't be farmed by calling repeatedly.
+//! - **Guarded Launch:** A pool can have a deposit cap and an allowlist badge required to contribute. In guarded-launch
+//!   mode, the collateral's debt ceiling in `Flux` follows the pool's depth, so borrowing can only grow as fast as the
+//!   fUSD available to liquidate it.
 //!
 //! ## Interaction with Other Components
 //! - **`Flux` (Core):** Calls methods for liquidation (`liquidate_cdp`, `check_liquidate_cdp`), redemption
@@ -30,8 +58,14 @@
 //! - **`Payout Component`:** Receives a share of protocol fees/rewards for further distribution or protocol use.
 //! - **`TwoResourcePool` (Radix Pool Blueprint):** Uses instances of this blueprint to manage the liquidity
 //!   (collateral/fUSD) within each stability pool.
+//! - **`ProductSumPool`:** Alternatively, a pool can be created as a `ProductSumPool` (see `StabilityPoolType`).
+//!   Contributions then stay fUSD denominated and are consumed by liquidations, while collateral gains and fUSD
+//!   yield are tracked per deposit and can be claimed separately through `claim_pool_gains`.
+//! - **`MultiResourcePool` (Radix Pool Blueprint):** Backs the shared stability pool, holding fUSD and the seized
+//!   collaterals of all opted-in collateral types.
 
 use crate::flux_component::flux_component::*;
+use crate::product_sum_pool::product_sum_pool::*;
 use crate::shared_structs::*;
 use crate::events::*;
 use scrypto::prelude::*;
@@ -39,13 +73,23 @@ use scrypto::prelude::*;
 pub type Unit = ();
 
 #[blueprint]
-#[types(NonFungibleLocalId, Instant, Unit, Hash, ResourceAddress, Vault)]
+#[types(NonFungibleLocalId, Instant, Unit, Hash, ResourceAddress, Vault, StabilityPoolLock, WithdrawalRequest)]
 #[events(
     StabilityPoolContributionEvent,
     StabilityPoolWithdrawalEvent,
     StabilityPoolBuyEvent,
+    StabilityPoolGainsClaimEvent,
+    StabilityPoolLockEvent,
+    StabilityPoolUnlockEvent,
+    StabilityPoolWithdrawalRequestEvent,
+    StabilityPoolConversionEvent,
+    SharedPoolContributionEvent,
+    SharedPoolWithdrawalEvent,
+    SharedPoolLiquidationEvent,
+    KeeperRewardEvent,
     PanicModeChangeEvent,
     PanicModeLiquidationEvent,
+    StablecoinUnwindEvent,
 )]
 mod stability_pools {
     enable_method_auth! {
@@ -57,24 +101,57 @@ mod stability_pools {
             receive_badges => PUBLIC;
             contribute_to_pool => PUBLIC;
             withdraw_from_pool => PUBLIC;
+            contribute_to_shared_pool => PUBLIC;
+            withdraw_from_shared_pool => PUBLIC;
+            request_shared_pool_withdrawal => PUBLIC;
+            claim_shared_pool_withdrawal => PUBLIC;
+            get_shared_pool_info => PUBLIC;
+            request_withdrawal => PUBLIC;
+            claim_withdrawal => PUBLIC;
+            cancel_withdrawal => PUBLIC;
+            claim_pool_gains => PUBLIC;
+            lock_pool_units => PUBLIC;
+            claim_lock_rewards => PUBLIC;
+            unlock_pool_units => PUBLIC;
             buy_collateral_from_pool => PUBLIC;
+            convert_pool_collateral => PUBLIC;
+            buy_from_conversion_auction => PUBLIC;
             charge_interest => PUBLIC;
+            charge_all_interest => PUBLIC;
             liquidate => PUBLIC;
+            batch_liquidate => PUBLIC;
             redemptions => PUBLIC;
             get_stability_pool_infos => PUBLIC;
             check_and_initiate_panic_mode => PUBLIC;
+            update_oracle_redemption_rate => PUBLIC;
+            get_keeper_rewards_info => PUBLIC;
             panic_mode_liquidate => PUBLIC;
             check_panic_mode_status => PUBLIC;
+            get_panic_mode_stablecoins => PUBLIC;
+            unwind_stablecoin_reserves => PUBLIC;
+            sell_fusd_for_stablecoin => PUBLIC;
+            get_stablecoin_unwind => PUBLIC;
             set_oracle => restrict_to: [flux];
             send_badges => restrict_to: [flux];
             new_pool => restrict_to: [flux];
             edit_pool => restrict_to: [flux];
+            new_shared_pool => restrict_to: [flux];
+            set_shared_pool_opt_in => restrict_to: [flux];
             take_liquidity_rewards => restrict_to: [flux, airdropper];
+            receive_collateral_fee => restrict_to: [flux];
+            receive_fusd_fee => restrict_to: [flux];
             set_parameters => restrict_to: [flux];
-            set_centralized_stablecoin => restrict_to: [flux];
+            set_panic_mode_stablecoins => restrict_to: [flux];
+            set_stablecoin_unwind => restrict_to: [flux];
             set_panic_mode_parameters => restrict_to: [flux];
+            set_lock_parameters => restrict_to: [flux];
+            set_withdrawal_parameters => restrict_to: [flux];
+            set_collateral_conversion => restrict_to: [flux];
             set_allow_multiple_actions => restrict_to: [flux];
             claim_payout_rewards => restrict_to: [flux];
+            claim_keeper_bounty => restrict_to: [flux];
+            set_keeper_bounty => restrict_to: [flux];
+            set_keeper_funding_share => restrict_to: [flux];
         }
     }
 
@@ -112,6 +189,20 @@ mod stability_pools {
         panic_mode: PanicModeInfo,
         /// Flag to allow/disallow multiple pool contributions or interest charges in a single transaction.
         allow_multiple_actions: bool,
+        /// `ResourceManager` for the `StabilityPoolLock` NFTs, received when locking pool units.
+        lock_receipt_manager: ResourceManager,
+        /// Counter used to generate the IDs of lock receipts.
+        lock_counter: u64,
+        /// `ResourceManager` for the `WithdrawalRequest` NFTs, received when requesting a withdrawal.
+        withdrawal_request_manager: ResourceManager,
+        /// Counter used to generate the IDs of withdrawal requests.
+        withdrawal_request_counter: u64,
+        /// The shared stability pool, covering liquidations of all opted-in collaterals. `None` until created.
+        shared_pool: Option<SharedStabilityPool>,
+        /// Where the next `charge_all_interest` call resumes. `None` to start at the first collateral.
+        interest_charge_cursor: Option<InterestChargeCursor>,
+        /// The funds, bounties and cooldowns used to reward keepers for housekeeping calls.
+        keeper_rewards: KeeperRewards,
     }
 
     impl StabilityPools {
@@ -166,8 +257,61 @@ mod stability_pools {
                 lowest_interest_interval: 30,
                 panic_mode_wait_period: 3, // use 1440 for prod
                 panic_mode_cooldown_period: 3, // use 1440 for prod
+                max_lock_duration: 525600,
+                max_lock_boost: dec!(1),
+                early_unlock_penalty: dec!(0.1),
+                withdrawal_delay: 0,
+                withdrawal_claim_window: 1440,
             };
 
+            let lock_receipt_manager: ResourceManager =
+                <scrypto::prelude::ResourceBuilder as stability_pools::stability_pools::StabilityPoolsResourceBuilder>::new_integer_non_fungible_with_registered_type::<StabilityPoolLock>(owner_role.clone())
+                .metadata(metadata!(
+                    init {
+                        "name" => "Flux Stability Pool Lock", updatable;
+                        "symbol" => "fusdLOCK", updatable;
+                        "description" => "A receipt for pool units locked in a Flux stability pool", updatable;
+                        "info_url" => Url::of("https://flux.ilikeitstable.com"), updatable;
+                        "dapp_definitions" => vec![dapp_def_address], updatable;
+                    }
+                ))
+                .non_fungible_data_update_roles(non_fungible_data_update_roles!(
+                    non_fungible_data_updater => rule!(require(global_caller(component_address)));
+                    non_fungible_data_updater_updater => rule!(deny_all);
+                ))
+                .mint_roles(mint_roles!(
+                    minter => rule!(require(global_caller(component_address)));
+                    minter_updater => rule!(deny_all);
+                ))
+                .burn_roles(burn_roles!(
+                    burner => rule!(require(global_caller(component_address)));
+                    burner_updater => rule!(deny_all);
+                ))
+                .create_with_no_initial_supply()
+                .into();
+
+            let withdrawal_request_manager: ResourceManager =
+                <scrypto::prelude::ResourceBuilder as stability_pools::stability_pools::StabilityPoolsResourceBuilder>::new_integer_non_fungible_with_registered_type::<WithdrawalRequest>(owner_role.clone())
+                .metadata(metadata!(
+                    init {
+                        "name" => "Flux Stability Pool Withdrawal Request", updatable;
+                        "symbol" => "fusdWITHDRAW", updatable;
+                        "description" => "A pending withdrawal from a Flux stability pool", updatable;
+                        "info_url" => Url::of("https://flux.ilikeitstable.com"), updatable;
+                        "dapp_definitions" => vec![dapp_def_address], updatable;
+                    }
+                ))
+                .mint_roles(mint_roles!(
+                    minter => rule!(require(global_caller(component_address)));
+                    minter_updater => rule!(deny_all);
+                ))
+                .burn_roles(burn_roles!(
+                    burner => rule!(require(global_caller(component_address)));
+                    burner_updater => rule!(deny_all);
+                ))
+                .create_with_no_initial_supply()
+                .into();
+
             let centralized_stablecoin_vaults = <scrypto::component::KeyValueStore<_, _> as stability_pools::stability_pools::StabilityPoolsKeyValueStore>::new_with_registered_type();
             centralized_stablecoin_vaults.insert(initial_centralized_stablecoin, Vault::new(initial_centralized_stablecoin));
 
@@ -186,13 +330,28 @@ mod stability_pools {
                 component_address,
                 transactions: <scrypto::component::KeyValueStore<_, _> as stability_pools::stability_pools::StabilityPoolsKeyValueStore>::new_with_registered_type(),
                 panic_mode: PanicModeInfo {
-                    is_active: false,
-                    last_liquidation_time: None,
-                    pending_cdps: <scrypto::component::KeyValueStore<_, _> as stability_pools::stability_pools::StabilityPoolsKeyValueStore>::new_with_registered_type(),
+                    collaterals: HashMap::new(),
                     centralized_stablecoin_vaults,
-                    current_centralized_stablecoin: initial_centralized_stablecoin,
+                    stablecoins: vec![PanicModeStablecoin {
+                        address: initial_centralized_stablecoin,
+                        depeg_tolerance: dec!("0.02"),
+                    }],
+                    redemption_order: vec![initial_centralized_stablecoin],
+                    depeg_tolerances: HashMap::from([(initial_centralized_stablecoin, dec!("0.02"))]),
+                    unwind: None,
                 },
                 allow_multiple_actions: false,
+                lock_receipt_manager,
+                lock_counter: 0,
+                withdrawal_request_manager,
+                withdrawal_request_counter: 0,
+                shared_pool: None,
+                interest_charge_cursor: None,
+                keeper_rewards: KeeperRewards {
+                    funds: Vault::new(fusd_address),
+                    funding_share: Decimal::ZERO,
+                    bounties: HashMap::new(),
+                },
             }
             .instantiate()
             .prepare_to_globalize(owner_role)
@@ -256,8 +415,8 @@ mod stability_pools {
 
         /// Creates and initializes a new stability pool for a specific collateral type.
         ///
-        /// Instantiates a `TwoResourcePool` blueprint instance to manage the collateral/fUSD pool,
-        /// sets up metadata for the pool unit token, and stores the pool information.
+        /// Depending on `pool_type`, instantiates a `TwoResourcePool` blueprint instance to manage the collateral/fUSD pool
+        /// or a `ProductSumPool` component, sets up metadata for the pool unit token (or deposit receipt), and stores the pool information.
         /// Requires OWNER authorization (controller badge).
         ///
         /// # Arguments
@@ -267,14 +426,19 @@ mod stability_pools {
         /// * `stability_pool_split`: Optional `Decimal` override for the share remaining in the stability pool itself.
         /// * `allow_pool_buys`: `bool` - Whether users are allowed to directly buy collateral from this pool using fUSD.
         /// * `pool_buy_price_modifier`: Optional `Decimal` price modifier applied when buying collateral directly from the pool.
+        /// * `pool_type`: The `StabilityPoolType` of the pool, determining its accounting.
         /// * `pool_name`: The name for the pool unit token metadata.
         /// * `pool_description`: The description for the pool unit token metadata.
         /// * `pool_icon_url`: The icon URL for the pool unit token metadata.
         /// * `pool_token_symbol`: The symbol for the pool unit token metadata.
         /// * `pool_dapp_definition`: The DApp definition address to link in the pool unit token metadata.
         ///
+        /// # Returns
+        /// * `ResourceAddress`: The address of the pool unit custody receipt, or of the deposit receipt for product-sum pools.
+        ///
         /// # Panics
         /// * If a pool for the given `collateral` already exists.
+        /// * If pool buys are allowed for a product-sum pool.
         pub fn new_pool(
             &mut self,
             collateral: ResourceAddress,
@@ -283,12 +447,117 @@ mod stability_pools {
             stability_pool_split: Option<Decimal>,
             allow_pool_buys: bool,
             pool_buy_price_modifier: Option<Decimal>,
+            pool_type: StabilityPoolType,
             pool_name: String,
             pool_description: String,
             pool_icon_url: Url,
             pool_token_symbol: String,
             pool_dapp_definition: GlobalAddress,
         ) -> ResourceAddress{
+            assert!(
+                !self.stability_pools.contains_key(&collateral),
+                "Pool already exists."
+            );
+
+            let (pool, resource_address, locks, pool_unit_custody) = match pool_type {
+                StabilityPoolType::TwoResource => {
+                    let (pool_component, pool_unit_resource_address, custody) = self.new_two_resource_pool(
+                        collateral,
+                        pool_name,
+                        pool_description,
+                        pool_icon_url,
+                        pool_token_symbol,
+                        pool_dapp_definition,
+                    );
+                    let locks = PoolLocks {
+                        locked_units: Vault::new(pool_unit_resource_address),
+                        total_weight: Decimal::ZERO,
+                        reward_per_weight: PreciseDecimal::ZERO,
+                        rewards: Vault::new(self.fusd_address),
+                    };
+                    (
+                        StabilityPoolComponent::TwoResource(pool_component),
+                        pool_unit_resource_address,
+                        Some(locks),
+                        Some(custody),
+                    )
+                }
+                StabilityPoolType::ProductSum => {
+                    assert!(!allow_pool_buys, "Pool buys are not supported by product-sum pools.");
+
+                    let pool_component = ProductSumPool::instantiate(
+                        OwnerRole::Fixed(rule!(require_amount(
+                            dec!("0.75"),
+                            self.badge_vault.resource_address(),
+                        ))),
+                        rule!(require(global_caller(self.component_address)) ||
+                            require_amount(
+                                dec!("0.75"),
+                                self.badge_vault.resource_address(),
+                            )
+                        ),
+                        collateral,
+                        self.fusd_address,
+                        pool_name,
+                        pool_description,
+                        pool_icon_url,
+                        pool_token_symbol,
+                        pool_dapp_definition,
+                    );
+                    let deposit_receipt_address = pool_component.get_deposit_receipt_address();
+                    (StabilityPoolComponent::ProductSum(pool_component), deposit_receipt_address, None, None)
+                }
+            };
+
+            self.collaterals.push(collateral);
+
+            let user_resource_address = pool_unit_custody
+                .as_ref()
+                .map(|custody| custody.receipt_manager.address())
+                .unwrap_or(resource_address);
+
+            self.stability_pools.insert(
+                collateral,
+                StabilityPoolInfo {
+                    collateral,
+                    payout_split,
+                    liquidity_rewards_split,
+                    stability_pool_split,
+                    allow_pool_buys,
+                    pool_buy_price_modifier,
+                    liquidity_rewards: Vault::new(self.fusd_address),
+                    pool,
+                    locks,
+                    pending_withdrawals: Vault::new(resource_address),
+                    pool_unit_custody,
+                    conversion: None,
+                    use_shared_pool: false,
+                    deposit_cap: None,
+                    contributor_badge: None,
+                    guarded_launch: None,
+                    latest_lowest_interests: vec![],
+                    last_lowest_interests_update: Clock::current_time_rounded_to_seconds(),
+                },
+            );
+
+            user_resource_address
+        }
+
+        /// Instantiates a `TwoResourcePool` for a new stability pool, sets the metadata of its pool unit token and
+        /// creates the custody receipt handed out instead of the pool units.
+        ///
+        /// # Returns
+        /// * `(Global<TwoResourcePool>, ResourceAddress, PoolUnitCustody)`: The pool component, the address of its
+        ///   pool unit token and the pool unit custody.
+        fn new_two_resource_pool(
+            &mut self,
+            collateral: ResourceAddress,
+            pool_name: String,
+            pool_description: String,
+            pool_icon_url: Url,
+            pool_token_symbol: String,
+            pool_dapp_definition: GlobalAddress,
+        ) -> (Global<TwoResourcePool>, ResourceAddress, PoolUnitCustody) {
             let pool_component = Blueprint::<TwoResourcePool>::instantiate(
                 OwnerRole::Fixed(rule!(require_amount(
                     dec!("0.75"),
@@ -311,32 +580,70 @@ mod stability_pools {
             let pool_unit_manager = ResourceManager::from(pool_unit_resource_address);
 
             self.badge_vault.authorize_with_amount(dec!("0.75"), || {
-                pool_unit_manager.set_metadata("name", pool_name);
-                pool_unit_manager.set_metadata("description", pool_description);
-                pool_unit_manager.set_metadata("symbol", pool_token_symbol);
-                pool_unit_manager.set_metadata("icon_url", pool_icon_url);
+                pool_unit_manager.set_metadata("name", pool_name.clone());
+                pool_unit_manager.set_metadata("description", pool_description.clone());
+                pool_unit_manager.set_metadata("symbol", pool_token_symbol.clone());
+                pool_unit_manager.set_metadata("icon_url", pool_icon_url.clone());
                 pool_unit_manager.set_metadata("dapp_definitions", pool_dapp_definition);
             });
 
-            self.collaterals.push(collateral);
-
-            self.stability_pools.insert(
-                collateral,
-                StabilityPoolInfo {
-                    collateral,
-                    payout_split,
-                    liquidity_rewards_split,
-                    stability_pool_split,
-                    allow_pool_buys,
-                    pool_buy_price_modifier,
-                    liquidity_rewards: Vault::new(self.fusd_address),
-                    pool: pool_component,
-                    latest_lowest_interests: vec![],
-                    last_lowest_interests_update: Clock::current_time_rounded_to_seconds(),
-                },
+            let custody = self.new_pool_unit_custody(
+                pool_unit_resource_address,
+                pool_name,
+                pool_description,
+                pool_icon_url,
+                pool_token_symbol,
+                pool_dapp_definition,
             );
 
-            pool_unit_resource_address
+            (pool_component, pool_unit_resource_address, custody)
+        }
+
+        /// Creates the custody for the pool units of a Radix pool, together with its custody receipt token.
+        ///
+        /// The `redeem` method of the Radix pools is public, so pool units held by users could be redeemed directly,
+        /// bypassing the withdrawal delay. Instead, the pool units are kept in custody by this component, and custody
+        /// receipts are handed out 1:1. The receipt token gets the metadata the pool unit token would have had.
+        ///
+        /// # Returns
+        /// * `PoolUnitCustody`: The (empty) pool unit custody.
+        fn new_pool_unit_custody(
+            &self,
+            pool_unit_address: ResourceAddress,
+            name: String,
+            description: String,
+            icon_url: Url,
+            symbol: String,
+            dapp_definition: GlobalAddress,
+        ) -> PoolUnitCustody {
+            let receipt_manager: ResourceManager = ResourceBuilder::new_fungible(OwnerRole::Fixed(rule!(
+                require_amount(dec!("0.75"), self.badge_vault.resource_address())
+            )))
+            .divisibility(DIVISIBILITY_MAXIMUM)
+            .metadata(metadata!(
+                init {
+                    "name" => name, updatable;
+                    "description" => description, updatable;
+                    "symbol" => symbol, updatable;
+                    "icon_url" => icon_url, updatable;
+                    "dapp_definitions" => vec![dapp_definition], updatable;
+                }
+            ))
+            .mint_roles(mint_roles!(
+                minter => rule!(require(global_caller(self.component_address)));
+                minter_updater => rule!(deny_all);
+            ))
+            .burn_roles(burn_roles!(
+                burner => rule!(require(global_caller(self.component_address)));
+                burner_updater => rule!(deny_all);
+            ))
+            .create_with_no_initial_supply()
+            .into();
+
+            PoolUnitCustody {
+                pool_units: Vault::new(pool_unit_address),
+                receipt_manager,
+            }
         }
 
         /// Edits the parameters of an existing stability pool.
@@ -350,9 +657,14 @@ mod stability_pools {
         /// * `stability_pool_split`: New optional `Decimal` override for the stability pool split.
         /// * `allow_pool_buys`: New `bool` value for allowing direct pool buys.
         /// * `pool_buy_price_modifier`: New optional `Decimal` override for the buy price modifier.
+        /// * `deposit_cap`: New optional maximum amount of fUSD the pool accepts through contributions.
+        /// * `contributor_badge`: New optional badge required to contribute to the pool.
+        /// * `guarded_launch`: New optional guarded-launch settings. Disabling guarded launch removes the debt ceiling.
         ///
         /// # Panics
         /// * If no pool exists for the given `collateral` address.
+        /// * If pool buys are allowed for a product-sum pool.
+        /// * If `deposit_cap` or a guarded-launch setting is negative.
         pub fn edit_pool(
             &mut self,
             collateral: ResourceAddress,
@@ -361,7 +673,24 @@ mod stability_pools {
             stability_pool_split: Option<Decimal>,
             allow_pool_buys: bool,
             pool_buy_price_modifier: Option<Decimal>,
+            deposit_cap: Option<Decimal>,
+            contributor_badge: Option<ResourceAddress>,
+            guarded_launch: Option<GuardedLaunch>,
         ) {
+            if let StabilityPoolComponent::ProductSum(_) = self.stability_pools.get(&collateral).unwrap().pool {
+                assert!(!allow_pool_buys, "Pool buys are not supported by product-sum pools.");
+            }
+            if let Some(cap) = deposit_cap {
+                assert!(cap >= Decimal::ZERO, "Deposit cap can't be negative.");
+            }
+            if let Some(launch) = guarded_launch {
+                assert!(
+                    launch.base_debt_ceiling >= Decimal::ZERO && launch.debt_per_pool_fusd >= Decimal::ZERO,
+                    "Guarded-launch settings can't be negative."
+                );
+            }
+            let was_guarded = self.stability_pools.get(&collateral).unwrap().guarded_launch.is_some();
+
             self.stability_pools
                 .get_mut(&collateral)
                 .unwrap()
@@ -382,6 +711,154 @@ mod stability_pools {
                 .get_mut(&collateral)
                 .unwrap()
                 .pool_buy_price_modifier = pool_buy_price_modifier;
+            self.stability_pools
+                .get_mut(&collateral)
+                .unwrap()
+                .deposit_cap = deposit_cap;
+            self.stability_pools
+                .get_mut(&collateral)
+                .unwrap()
+                .contributor_badge = contributor_badge;
+            self.stability_pools
+                .get_mut(&collateral)
+                .unwrap()
+                .guarded_launch = guarded_launch;
+
+            if guarded_launch.is_some() {
+                self.update_debt_ceiling(collateral);
+            } else if was_guarded {
+                self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                    self.flux.set_debt_ceiling(collateral, None);
+                });
+            }
+        }
+
+        /// Creates the shared stability pool, a `MultiResourcePool` holding fUSD and the given collaterals.
+        ///
+        /// The pool's resources are fixed at creation, so only the given collaterals can later opt in through
+        /// `set_shared_pool_opt_in`. The pool is seeded with a small amount of every resource, as the first contribution
+        /// to a `MultiResourcePool` has to contain all of its resources. The pool units of the seed are kept by this
+        /// component, so the pool can never be fully redeemed. Like for the stability pools, contributors receive
+        /// custody receipts instead of the pool units.
+        ///
+        /// Requires OWNER authorization (controller badge).
+        ///
+        /// # Arguments
+        /// * `collaterals`: The collaterals the shared pool can cover. Each must have a stability pool.
+        /// * `seed`: Buckets of fUSD and every collateral, used as the first contribution.
+        /// * `pool_name`: Name metadata for the shared pool unit token.
+        /// * `pool_description`: Description metadata for the shared pool unit token.
+        /// * `pool_icon_url`: Icon URL metadata for the shared pool unit token.
+        /// * `pool_token_symbol`: Symbol metadata for the shared pool unit token.
+        /// * `pool_dapp_definition`: DApp definition address for the shared pool unit token's metadata.
+        ///
+        /// # Returns
+        /// * `ResourceAddress`: The address of the shared pool unit custody receipt.
+        ///
+        /// # Panics
+        /// * If the shared pool already exists.
+        /// * If a collateral has no stability pool.
+        /// * If the seed doesn't contain a non-empty bucket of fUSD and every collateral.
+        pub fn new_shared_pool(
+            &mut self,
+            collaterals: Vec<ResourceAddress>,
+            seed: Vec<Bucket>,
+            pool_name: String,
+            pool_description: String,
+            pool_icon_url: Url,
+            pool_token_symbol: String,
+            pool_dapp_definition: GlobalAddress,
+        ) -> ResourceAddress {
+            assert!(self.shared_pool.is_none(), "Shared pool already exists.");
+            for collateral in collaterals.iter() {
+                assert!(
+                    self.stability_pools.contains_key(collateral),
+                    "No stability pool for this collateral."
+                );
+            }
+
+            let mut resources = vec![self.fusd_address];
+            resources.extend(collaterals.iter().cloned());
+
+            let mut pool = Blueprint::<MultiResourcePool>::instantiate(
+                OwnerRole::Fixed(rule!(require_amount(
+                    dec!("0.75"),
+                    self.badge_vault.resource_address(),
+                ))),
+                rule!(require(global_caller(self.component_address)) ||
+                    require_amount(
+                        dec!("0.75"),
+                        self.badge_vault.resource_address(),
+                    )
+                ),
+                resources,
+                None,
+            );
+
+            let pool_unit_global_address: GlobalAddress =
+                pool.get_metadata("pool_unit").unwrap().unwrap();
+            let pool_unit_address = ResourceAddress::try_from(pool_unit_global_address).unwrap();
+            let pool_unit_manager = ResourceManager::from(pool_unit_address);
+
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                pool_unit_manager.set_metadata("name", pool_name.clone());
+                pool_unit_manager.set_metadata("description", pool_description.clone());
+                pool_unit_manager.set_metadata("symbol", pool_token_symbol.clone());
+                pool_unit_manager.set_metadata("icon_url", pool_icon_url.clone());
+                pool_unit_manager.set_metadata("dapp_definitions", pool_dapp_definition);
+            });
+
+            let custody = self.new_pool_unit_custody(
+                pool_unit_address,
+                pool_name,
+                pool_description,
+                pool_icon_url,
+                pool_token_symbol,
+                pool_dapp_definition,
+            );
+            let custody_receipt_address = custody.receipt_manager.address();
+
+            let (seed_units, change) = pool.contribute(
+                seed.into_iter().map(|bucket| bucket.as_fungible()).collect(),
+            );
+            for bucket in change {
+                pool.protected_deposit(bucket);
+            }
+
+            self.shared_pool = Some(SharedStabilityPool {
+                pool,
+                collaterals,
+                pending_withdrawals: Vault::new(seed_units.resource_address()),
+                seed_units: Vault::with_bucket(seed_units.into()),
+                custody,
+            });
+
+            custody_receipt_address
+        }
+
+        /// Sets whether liquidations of a collateral fall back to the shared stability pool.
+        ///
+        /// Requires OWNER authorization (controller badge).
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` identifying the collateral's stability pool.
+        /// * `use_shared_pool`: `true` to opt in, `false` to opt out.
+        ///
+        /// # Panics
+        /// * If no pool exists for the given `collateral`.
+        /// * If opting in while there is no shared pool, or the shared pool can't hold the collateral.
+        pub fn set_shared_pool_opt_in(&mut self, collateral: ResourceAddress, use_shared_pool: bool) {
+            if use_shared_pool {
+                assert!(
+                    self.get_shared_pool().collaterals.contains(&collateral),
+                    "Collateral not supported by the shared pool."
+                );
+            }
+
+            self.stability_pools
+                .get_mut(&collateral)
+                .expect("No stability pool for this collateral.")
+                .use_shared_pool = use_shared_pool;
         }
 
         /// Contributes fUSD to a specific collateral's stability pool.
@@ -390,7 +867,9 @@ mod stability_pools {
         /// amount to maintain the pool's balance based on the current price and a buy-in modifier,
         /// buys that collateral from the pool using a portion of the contribution, and then contributes
         /// the bought collateral and remaining fUSD to the underlying `TwoResourcePool`.
-        /// Returns the pool units representing the user's share and any leftover tokens if not deposited.
+        /// The pool units are kept in custody, and custody receipts representing the user's share are returned,
+        /// together with any leftover tokens if not deposited.
+        /// For product-sum pools, the contribution (after fees) is deposited as is, and a deposit receipt NFT is returned instead.
         ///
         /// # Arguments
         /// * `collateral`: The `ResourceAddress` of the collateral whose pool the user is contributing to.
@@ -401,7 +880,7 @@ mod stability_pools {
         ///
         /// # Returns
         /// * `(Bucket, Option<FungibleBucket>, Option<Bucket>)`: A tuple containing:
-        ///     1. Pool unit tokens (`Bucket`) representing the contribution.
+        ///     1. Pool unit custody receipts (`Bucket`) representing the contribution, or the deposit receipt for product-sum pools.
         ///     2. Optional leftover pool tokens (`Option<FungibleBucket>`) if `deposit_leftover` is `false` and the pool returned leftovers.
         ///     3. Optional leftover fUSD (`Option<Bucket>`) if `deposit_leftover` is `false` and there was fUSD remaining after internal collateral buying.
         ///
@@ -410,6 +889,8 @@ mod stability_pools {
         /// * If the `contribution` bucket is not fUSD.
         /// * If the oracle call fails or provides an invalid price.
         /// * If no pool exists for the given `collateral`.
+        /// * If the pool requires a contributor badge and no proof of it is in the auth zone.
+        /// * If the contribution would take the pool's fUSD above its deposit cap.
         pub fn contribute_to_pool(
             &mut self,
             collateral: ResourceAddress,
@@ -426,31 +907,30 @@ mod stability_pools {
                 "Invalid input."
             );
 
-            let collateral_price: Decimal = self.oracle.call_raw(
-                &self.oracle_single_method_name,
-                scrypto_args!(collateral, message.clone(), signature.clone()),
-            );
+            self.take_contribution_fees(&mut contribution);
+            self.check_contribution_allowed(collateral, contribution.amount());
 
-            if self.parameters.pool_contribution_percentage_fee > Decimal::ZERO {
-                self.payout_vault.put(contribution.take(
-                    fusd_input * self.parameters.pool_contribution_percentage_fee,
-                ));
-            }
+            if let StabilityPoolComponent::ProductSum(pool) = self.stability_pools.get(&collateral).unwrap().pool {
+                let deposit_receipt = pool.deposit(contribution);
+                self.update_debt_ceiling(collateral);
 
-            if self.parameters.pool_contribution_flat_fee > Decimal::ZERO {
-                self.payout_vault
-                    .put(contribution.take(self.parameters.pool_contribution_flat_fee));
+                // Emit contribution event
+                Runtime::emit_event(StabilityPoolContributionEvent {
+                    collateral,
+                    contribution_amount: fusd_input,
+                    pool_tokens_received: deposit_receipt.amount(),
+                });
+
+                return (deposit_receipt, None, None);
             }
 
-            let vault_amounts = self
-                .stability_pools
-                .get(&collateral)
-                .unwrap()
-                .pool
-                .get_vault_amounts();
+            let collateral_price: Decimal = self.oracle.call_raw(
+                &self.oracle_single_method_name,
+                scrypto_args!(collateral, message.clone(), signature.clone()),
+            );
 
-            let pool_fusd_value = *vault_amounts.get(&self.fusd_address).unwrap();
-            let pool_collateral_value = *vault_amounts.get(&collateral).unwrap() * collateral_price;
+            let (pool_collateral_amount, pool_fusd_value) = self.get_pool_amounts(collateral);
+            let pool_collateral_value = pool_collateral_amount * collateral_price;
             let buy_in_modifier = self
                 .stability_pools
                 .get(&collateral)
@@ -470,26 +950,18 @@ mod stability_pools {
                 );
 
                 let (pool_units, leftover) = self
-                    .stability_pools
-                    .get_mut(&collateral)
-                    .unwrap()
-                    .pool
+                    .get_two_resource_pool(collateral)
                     .contribute((bought_collateral.as_fungible(), contribution.as_fungible()));
+                self.update_debt_ceiling(collateral);
+
+                let pool_units = self.hand_out_pool_units(collateral, pool_units.into());
 
                 if deposit_leftover {
                     if let Some(leftover_bucket) = leftover {
-                        self.stability_pools
-                            .get_mut(&collateral)
-                            .unwrap()
-                            .pool
-                            .protected_deposit(leftover_bucket);
+                        self.deposit_rewards(collateral, leftover_bucket.into());
                     }
 
-                    self.stability_pools
-                        .get_mut(&collateral)
-                        .unwrap()
-                        .pool
-                        .protected_deposit(leftover_fusd.as_fungible());
+                    self.deposit_rewards(collateral, leftover_fusd);
 
                     // Emit contribution event
                     Runtime::emit_event(StabilityPoolContributionEvent {
@@ -498,7 +970,7 @@ mod stability_pools {
                         pool_tokens_received: pool_units.amount(),
                     });
 
-                    return (pool_units.into(), None, None);
+                    return (pool_units, None, None);
                 }
 
                 // Emit contribution event
@@ -508,13 +980,13 @@ mod stability_pools {
                     pool_tokens_received: pool_units.amount(),
                 });
 
-                (pool_units.into(), leftover, Some(leftover_fusd))
+                (pool_units, leftover, Some(leftover_fusd))
             } else {
-                let (pool_units, leftover) = self.stability_pools
-                    .get_mut(&collateral)
-                    .unwrap()
-                    .pool
+                let (pool_units, leftover) = self
+                    .get_two_resource_pool(collateral)
                     .contribute((FungibleBucket::new(collateral), contribution.as_fungible()));
+                self.update_debt_ceiling(collateral);
+                let pool_units = self.hand_out_pool_units(collateral, pool_units.into());
 
                 // Emit contribution event
                 Runtime::emit_event(StabilityPoolContributionEvent {
@@ -523,295 +995,1731 @@ mod stability_pools {
                     pool_tokens_received: pool_units.amount(),
                 });
 
-                (pool_units.into(), leftover, None)
+                (pool_units, leftover, None)
             }
         }
 
         /// Withdraws a user's contribution (collateral and fUSD) from a stability pool.
         ///
-        /// Redeems the provided pool unit tokens from the underlying `TwoResourcePool`
-        /// and returns the corresponding pro-rata share of collateral and fUSD held by the pool.
+        /// Burns the provided pool unit custody receipts, redeems the pool units they represent from the underlying
+        /// `TwoResourcePool` and returns the corresponding pro-rata share of collateral and fUSD held by the pool.
+        /// For product-sum pools, the deposit receipts are burned and the compounded deposits are returned,
+        /// together with their collateral gains and fUSD yield.
+        /// Only possible if no withdrawal delay is set, otherwise `request_withdrawal` and `claim_withdrawal` have to be used.
         ///
         /// # Arguments
         /// * `collateral`: The `ResourceAddress` of the collateral whose pool the user is withdrawing from.
-        /// * `tokens`: A `Bucket` containing the pool unit tokens to be redeemed.
+        /// * `tokens`: A `Bucket` containing the pool unit custody receipts (or deposit receipts) to be redeemed.
         ///
         /// # Returns
         /// * `(Bucket, Bucket)`: A tuple containing the withdrawn collateral and fUSD buckets.
         ///
         /// # Panics
+        /// * If a withdrawal delay is set.
         /// * If no pool exists for the given `collateral`.
-        /// * If the `tokens` bucket resource address does not match the pool's unit token address.
+        /// * If the `tokens` bucket resource address does not match the pool's custody receipt (or deposit receipt) address.
         pub fn withdraw_from_pool(
             &mut self,
             collateral: ResourceAddress,
             tokens: Bucket,
         ) -> (Bucket, Bucket) {
-            let input_amount = tokens.amount();
-
-            let (bucket1, bucket2) = self
-                .stability_pools
-                .get_mut(&collateral)
-                .unwrap()
-                .pool
-                .redeem(tokens.as_fungible());
-
-            let fusd_amount = bucket2.amount();
-            let collateral_amount = bucket1.amount();
-
-            // Emit withdrawal event
-            Runtime::emit_event(StabilityPoolWithdrawalEvent {
-                collateral,
-                pool_tokens_burned: input_amount,
-                fusd_received: fusd_amount,
-                collateral_received: collateral_amount,
-            });
+            assert!(
+                self.parameters.withdrawal_delay == 0,
+                "Withdrawal delay active, request a withdrawal instead."
+            );
 
-            (bucket1.into(), bucket2.into())
+            let tokens = self.take_pool_units(collateral, tokens);
+            self.redeem_from_pool(collateral, tokens)
         }
 
-        /// Allows a user to buy collateral directly from a stability pool using fUSD.
+        /// Requests a withdrawal from a stability pool, starting the withdrawal delay.
         ///
-        /// This is only allowed if the pool's `allow_pool_buys` flag is set to `true`.
-        /// Applies the pool's `pool_buy_price_modifier` (or the default one) to the oracle price.
+        /// The pool units (or deposit receipts) are escrowed by this component until the withdrawal is claimed or
+        /// cancelled. While escrowed, they remain part of the pool and keep absorbing liquidations.
         ///
         /// # Arguments
-        /// * `collateral`: The `ResourceAddress` of the collateral being bought.
-        /// * `fusd`: A `Bucket` containing the fUSD payment.
-        /// * `message`: Oracle message for price verification.
-        /// * `signature`: Oracle signature for price verification.
+        /// * `collateral`: The `ResourceAddress` of the collateral whose pool the user is withdrawing from.
+        /// * `tokens`: A `Bucket` containing the pool unit custody receipts (or deposit receipts) to withdraw.
         ///
         /// # Returns
-        /// * `(Bucket, Bucket)`: A tuple containing:
-        ///     1. The `Bucket` of bought collateral.
-        ///     2. The `Bucket` of remaining fUSD payment (if any).
+        /// * `Bucket`: A `WithdrawalRequest` NFT, required to claim or cancel the withdrawal.
         ///
         /// # Panics
-        /// * If the specified `collateral` pool does not allow direct buys (`allow_pool_buys == false`).
-        /// * If the oracle call fails.
         /// * If no pool exists for the given `collateral`.
-        pub fn buy_collateral_from_pool(
-            &mut self,
-            collateral: ResourceAddress,
-            fusd: Bucket,
-            message: String,
-            signature: String,
-        ) -> (Bucket, Bucket) {
-            let collateral_price: Decimal = self.oracle.call_raw(
-                &self.oracle_single_method_name,
-                scrypto_args!(collateral, message.clone(), signature.clone()),
-            );
+        /// * If the `tokens` bucket resource address does not match the pool's custody receipt (or deposit receipt) address.
+        pub fn request_withdrawal(&mut self, collateral: ResourceAddress, tokens: Bucket) -> Bucket {
+            let tokens = self.take_pool_units(collateral, tokens);
+            let pool_info = self.stability_pools.get_mut(&collateral).unwrap();
+
+            let pool_units = tokens.amount();
+            let deposit_ids: Vec<NonFungibleLocalId> = match pool_info.pool {
+                StabilityPoolComponent::TwoResource(_) => vec![],
+                StabilityPoolComponent::ProductSum(_) => {
+                    tokens.as_non_fungible().non_fungible_local_ids().into_iter().collect()
+                }
+            };
+            pool_info.pending_withdrawals.put(tokens);
 
-            let fusd_amount = fusd.amount();
+            let requested_at = Clock::current_time_rounded_to_seconds();
+            let claimable_at = requested_at.add_minutes(self.parameters.withdrawal_delay).unwrap();
 
-            assert!(
-                self.stability_pools
-                    .get(&collateral)
-                    .unwrap()
-                    .allow_pool_buys,
-                "Buying from the pool not allowed."
-            );
-            let (collateral_bucket, leftover_fusd) = self.buy_collateral_from_pool_internal(collateral, fusd, collateral_price);
+            self.withdrawal_request_counter += 1;
+            let request_id = NonFungibleLocalId::integer(self.withdrawal_request_counter);
 
-            // Emit buy event
-            Runtime::emit_event(StabilityPoolBuyEvent {
-                collateral,
-                fusd_paid: fusd_amount - leftover_fusd.amount(),
-                collateral_received: collateral_bucket.amount(),
-                effective_price: (fusd_amount - leftover_fusd.amount()) / collateral_bucket.amount(),
+            Runtime::emit_event(StabilityPoolWithdrawalRequestEvent {
+                collateral: Some(collateral),
+                request_id: request_id.clone(),
+                pool_units,
+                claimable_at,
+                change: WithdrawalRequestChange::Requested,
             });
 
-            (collateral_bucket, leftover_fusd)
+            self.withdrawal_request_manager.mint_non_fungible(
+                &request_id,
+                WithdrawalRequest {
+                    collateral: Some(collateral),
+                    pool_units,
+                    deposit_ids,
+                    requested_at,
+                    claimable_at,
+                },
+            )
         }
 
-        /// Allows an authorized user (OWNER) to withdraw accumulated liquidity rewards for a specific pool.
-        ///
-        /// Requires OWNER authorization (controller badge).
+        /// Claims a requested withdrawal once its delay has passed, redeeming the escrowed pool units.
+        ///
+        /// # Arguments
+        /// * `request`: A `Bucket` containing a single `WithdrawalRequest` NFT.
+        ///
+        /// # Returns
+        /// * `(Bucket, Bucket)`: A tuple containing the withdrawn collateral and fUSD buckets.
+        ///
+        /// # Panics
+        /// * If the bucket doesn't contain a single withdrawal request.
+        /// * If the request is a shared pool withdrawal, which is claimed through `claim_shared_pool_withdrawal`.
+        /// * If the withdrawal delay hasn't passed yet, or the claim window has passed.
+        pub fn claim_withdrawal(&mut self, request: Bucket) -> (Bucket, Bucket) {
+            let (withdrawal, tokens) = self.take_withdrawal_request(request);
+            let collateral = withdrawal
+                .collateral
+                .expect("Shared pool withdrawal, use claim_shared_pool_withdrawal.");
+            self.check_withdrawal_claimable(&withdrawal);
+
+            self.redeem_from_pool(collateral, tokens)
+        }
+
+        /// Cancels a requested withdrawal, returning the escrowed pool units (or deposit receipts).
+        /// Also used to retrieve the pool units of an expired request. Works for shared pool withdrawals as well.
+        ///
+        /// # Arguments
+        /// * `request`: A `Bucket` containing a single `WithdrawalRequest` NFT.
+        ///
+        /// # Returns
+        /// * `Bucket`: Custody receipts for the escrowed pool units (or the deposit receipts).
+        ///
+        /// # Panics
+        /// * If the bucket doesn't contain a single withdrawal request.
+        pub fn cancel_withdrawal(&mut self, request: Bucket) -> Bucket {
+            let request_id = request.as_non_fungible().non_fungible_local_id();
+            let (withdrawal, tokens) = self.take_withdrawal_request(request);
+
+            Runtime::emit_event(StabilityPoolWithdrawalRequestEvent {
+                collateral: withdrawal.collateral,
+                request_id,
+                pool_units: withdrawal.pool_units,
+                claimable_at: withdrawal.claimable_at,
+                change: WithdrawalRequestChange::Cancelled,
+            });
+
+            match withdrawal.collateral {
+                Some(collateral) => self.hand_out_pool_units(collateral, tokens),
+                None => Self::wrap_pool_units(&mut self.shared_pool.as_mut().unwrap().custody, tokens),
+            }
+        }
+
+        /// Contributes fUSD to the shared stability pool.
+        ///
+        /// Works like `contribute_to_pool`, but for every collateral held by the shared pool. Part of the contribution
+        /// is used to buy each collateral from the pool at the oracle price (with the default buy-in modifier), so the
+        /// contribution matches the pool's composition, after which everything is contributed to the pool.
+        ///
+        /// # Arguments
+        /// * `contribution`: A `Bucket` containing the fUSD to contribute.
+        /// * `oracle_info`: Oracle messages and signatures, as `(ResourceAddress, String, String)`, for every
+        ///   collateral currently held by the shared pool.
+        ///
+        /// # Returns
+        /// * `(Bucket, Vec<Bucket>)`: A tuple containing:
+        ///     1. The `Bucket` of shared pool unit custody receipts.
+        ///     2. Any tokens not accepted by the pool.
+        ///
+        /// # Panics
+        /// * If `allow_multiple_actions` is false and `charge_interest` has already occurred in the same transaction.
+        /// * If the contribution is not fUSD.
+        /// * If there is no shared pool.
+        /// * If a price is missing for a collateral held by the shared pool.
+        pub fn contribute_to_shared_pool(
+            &mut self,
+            mut contribution: Bucket,
+            oracle_info: Vec<(ResourceAddress, String, String)>,
+        ) -> (Bucket, Vec<Bucket>) {
+            self.create_hash();
+            let fusd_input = contribution.amount();
+
+            assert!(
+                contribution.resource_address() == self.fusd_address,
+                "Invalid input."
+            );
+
+            self.take_contribution_fees(&mut contribution);
+
+            let shared_pool = self.get_shared_pool();
+            let mut pool = shared_pool.pool;
+            let collaterals = shared_pool.collaterals.clone();
+            let vault_amounts = pool.get_vault_amounts();
+
+            let prices: Vec<(ResourceAddress, Decimal)> = self
+                .oracle
+                .call_raw(&self.oracle_batch_method_name, scrypto_args!(oracle_info));
+
+            let collateral_amounts: Vec<(ResourceAddress, Decimal)> = collaterals
+                .iter()
+                .map(|collateral| (*collateral, *vault_amounts.get(collateral).unwrap_or(&Decimal::ZERO)))
+                .collect();
+            let collateral_value = collateral_amounts
+                .iter()
+                .filter(|(_, amount)| *amount > Decimal::ZERO)
+                .fold(Decimal::ZERO, |value, (collateral, amount)| {
+                    let (_, price) = prices
+                        .iter()
+                        .find(|(address, _)| address == collateral)
+                        .expect("Missing collateral price.");
+                    value + *amount * *price * self.parameters.default_pool_buy_price_modifier
+                });
+            let pool_fusd = *vault_amounts.get(&self.fusd_address).unwrap_or(&Decimal::ZERO);
+
+            // Buy the same fraction of every collateral, leaving the contribution in the pool's ratio
+            let fusd_amount = contribution.amount();
+            let buy_fraction = fusd_amount / (pool_fusd + fusd_amount + collateral_value);
+
+            let mut buckets: Vec<FungibleBucket> = collateral_amounts
+                .into_iter()
+                .map(|(collateral, amount)| {
+                    if amount > Decimal::ZERO {
+                        pool.protected_withdraw(
+                            collateral,
+                            amount * buy_fraction,
+                            WithdrawStrategy::Rounded(RoundingMode::ToNegativeInfinity),
+                        )
+                    } else {
+                        FungibleBucket::new(collateral)
+                    }
+                })
+                .collect();
+            pool.protected_deposit(contribution.take(collateral_value * buy_fraction).as_fungible());
+            buckets.push(contribution.as_fungible());
+
+            let (pool_units, change) = pool.contribute(buckets);
+            let pool_units = Self::wrap_pool_units(
+                &mut self.shared_pool.as_mut().unwrap().custody,
+                pool_units.into(),
+            );
+
+            Runtime::emit_event(SharedPoolContributionEvent {
+                contribution_amount: fusd_input,
+                pool_tokens_received: pool_units.amount(),
+            });
+
+            (
+                pool_units,
+                change.into_iter().map(|bucket| bucket.into()).collect(),
+            )
+        }
+
+        /// Withdraws from the shared stability pool, burning shared pool unit custody receipts and redeeming the pool
+        /// units they represent for fUSD and collaterals.
+        /// Only possible if no withdrawal delay is set, otherwise `request_shared_pool_withdrawal` and
+        /// `claim_shared_pool_withdrawal` have to be used.
+        ///
+        /// # Arguments
+        /// * `pool_units`: A `Bucket` containing the shared pool unit custody receipts to redeem.
+        ///
+        /// # Returns
+        /// * `Vec<Bucket>`: The pro-rata share of fUSD and each collateral held by the shared pool.
+        ///
+        /// # Panics
+        /// * If a withdrawal delay is set.
+        /// * If there is no shared pool, or the bucket doesn't contain its custody receipts.
+        pub fn withdraw_from_shared_pool(&mut self, pool_units: Bucket) -> Vec<Bucket> {
+            assert!(
+                self.parameters.withdrawal_delay == 0,
+                "Withdrawal delay active, request a withdrawal instead."
+            );
+
+            let shared_pool = self.shared_pool.as_mut().expect("No shared pool.");
+            let pool_units = Self::unwrap_pool_units(&mut shared_pool.custody, pool_units);
+            self.redeem_from_shared_pool(pool_units)
+        }
+
+        /// Requests a withdrawal from the shared stability pool, starting the withdrawal delay.
+        ///
+        /// Works like `request_withdrawal`: the pool units are escrowed by this component until the withdrawal is
+        /// claimed through `claim_shared_pool_withdrawal` or cancelled through `cancel_withdrawal`, and keep absorbing
+        /// liquidations in the meantime.
+        ///
+        /// # Arguments
+        /// * `pool_units`: A `Bucket` containing the shared pool unit custody receipts to withdraw.
+        ///
+        /// # Returns
+        /// * `Bucket`: A `WithdrawalRequest` NFT, required to claim or cancel the withdrawal.
+        ///
+        /// # Panics
+        /// * If there is no shared pool, or the bucket doesn't contain its custody receipts.
+        pub fn request_shared_pool_withdrawal(&mut self, pool_units: Bucket) -> Bucket {
+            let shared_pool = self.shared_pool.as_mut().expect("No shared pool.");
+            let pool_units = Self::unwrap_pool_units(&mut shared_pool.custody, pool_units);
+            let pool_unit_amount = pool_units.amount();
+            shared_pool.pending_withdrawals.put(pool_units);
+
+            let requested_at = Clock::current_time_rounded_to_seconds();
+            let claimable_at = requested_at.add_minutes(self.parameters.withdrawal_delay).unwrap();
+
+            self.withdrawal_request_counter += 1;
+            let request_id = NonFungibleLocalId::integer(self.withdrawal_request_counter);
+
+            Runtime::emit_event(StabilityPoolWithdrawalRequestEvent {
+                collateral: None,
+                request_id: request_id.clone(),
+                pool_units: pool_unit_amount,
+                claimable_at,
+                change: WithdrawalRequestChange::Requested,
+            });
+
+            self.withdrawal_request_manager.mint_non_fungible(
+                &request_id,
+                WithdrawalRequest {
+                    collateral: None,
+                    pool_units: pool_unit_amount,
+                    deposit_ids: vec![],
+                    requested_at,
+                    claimable_at,
+                },
+            )
+        }
+
+        /// Claims a requested shared pool withdrawal once its delay has passed, redeeming the escrowed pool units
+        /// at the shared pool.
+        ///
+        /// # Arguments
+        /// * `request`: A `Bucket` containing a single `WithdrawalRequest` NFT of a shared pool withdrawal.
+        ///
+        /// # Returns
+        /// * `Vec<Bucket>`: The pro-rata share of fUSD and each collateral held by the shared pool.
+        ///
+        /// # Panics
+        /// * If the bucket doesn't contain a single withdrawal request.
+        /// * If the request is not a shared pool withdrawal.
+        /// * If the withdrawal delay hasn't passed yet, or the claim window has passed.
+        pub fn claim_shared_pool_withdrawal(&mut self, request: Bucket) -> Vec<Bucket> {
+            let (withdrawal, pool_units) = self.take_withdrawal_request(request);
+            assert!(
+                withdrawal.collateral.is_none(),
+                "Not a shared pool withdrawal, use claim_withdrawal."
+            );
+            self.check_withdrawal_claimable(&withdrawal);
+
+            self.redeem_from_shared_pool(pool_units)
+        }
+
+        /// Retrieves information about the shared stability pool.
+        ///
+        /// # Returns
+        /// * `Option<SharedStabilityPoolInfoReturn>`: The shared pool's information, `None` if it doesn't exist.
+        pub fn get_shared_pool_info(&self) -> Option<SharedStabilityPoolInfoReturn> {
+            self.shared_pool.as_ref().map(|shared_pool| SharedStabilityPoolInfoReturn {
+                pool: shared_pool.pool,
+                collaterals: shared_pool.collaterals.clone(),
+                opted_in: shared_pool
+                    .collaterals
+                    .iter()
+                    .filter(|collateral| self.stability_pools.get(collateral).unwrap().use_shared_pool)
+                    .cloned()
+                    .collect(),
+                vault_amounts: shared_pool.pool.get_vault_amounts(),
+                custody_receipt: shared_pool.custody.receipt_manager.address(),
+            })
+        }
+
+        /// Claims the collateral gains and fUSD yield of deposits in a product-sum pool, without withdrawing the deposits.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral whose pool the deposits were made into.
+        /// * `deposits_proof`: A `Proof` of the deposit receipts.
+        ///
+        /// # Returns
+        /// * `(Bucket, Bucket)`: A tuple containing the claimed collateral and fUSD yield.
+        ///
+        /// # Panics
+        /// * If no pool exists for the given `collateral`, or it isn't a product-sum pool.
+        /// * If the proof isn't of the pool's deposit receipts.
+        pub fn claim_pool_gains(
+            &mut self,
+            collateral: ResourceAddress,
+            deposits_proof: Proof,
+        ) -> (Bucket, Bucket) {
+            let (collateral_gain, fusd_yield) = match self.stability_pools.get(&collateral).unwrap().pool {
+                StabilityPoolComponent::ProductSum(pool) => pool.claim_gains(deposits_proof),
+                StabilityPoolComponent::TwoResource(_) => panic!("Not a product-sum pool."),
+            };
+
+            Runtime::emit_event(StabilityPoolGainsClaimEvent {
+                collateral,
+                collateral_claimed: collateral_gain.amount(),
+                fusd_claimed: fusd_yield.amount(),
+            });
+
+            (collateral_gain, fusd_yield)
+        }
+
+        /// Allows a user to buy collateral directly from a stability pool using fUSD.
+        ///
+        /// This is only allowed if the pool's `allow_pool_buys` flag is set to `true`.
+        /// Applies the pool's `pool_buy_price_modifier` (or the default one) to the oracle price.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral being bought.
+        /// * `fusd`: A `Bucket` containing the fUSD payment.
+        /// * `message`: Oracle message for price verification.
+        /// * `signature`: Oracle signature for price verification.
+        ///
+        /// # Returns
+        /// * `(Bucket, Bucket)`: A tuple containing:
+        ///     1. The `Bucket` of bought collateral.
+        ///     2. The `Bucket` of remaining fUSD payment (if any).
+        ///
+        /// # Panics
+        /// * If the specified `collateral` pool does not allow direct buys (`allow_pool_buys == false`).
+        /// * If the oracle call fails.
+        /// * If no pool exists for the given `collateral`.
+        pub fn buy_collateral_from_pool(
+            &mut self,
+            collateral: ResourceAddress,
+            fusd: Bucket,
+            message: String,
+            signature: String,
+        ) -> (Bucket, Bucket) {
+            let collateral_price: Decimal = self.oracle.call_raw(
+                &self.oracle_single_method_name,
+                scrypto_args!(collateral, message.clone(), signature.clone()),
+            );
+
+            let fusd_amount = fusd.amount();
+
+            assert!(
+                self.stability_pools
+                    .get(&collateral)
+                    .unwrap()
+                    .allow_pool_buys,
+                "Buying from the pool not allowed."
+            );
+            let (collateral_bucket, leftover_fusd) = self.buy_collateral_from_pool_internal(collateral, fusd, collateral_price);
+
+            // Emit buy event
+            Runtime::emit_event(StabilityPoolBuyEvent {
+                collateral,
+                fusd_paid: fusd_amount - leftover_fusd.amount(),
+                collateral_received: collateral_bucket.amount(),
+                effective_price: (fusd_amount - leftover_fusd.amount()) / collateral_bucket.amount(),
+            });
+
+            (collateral_bucket, leftover_fusd)
+        }
+
+        /// Converts part of a stability pool's collateral back into fUSD, once the collateral's share of the pool's
+        /// value exceeds the configured threshold.
+        ///
+        /// With a `DexAdapter` conversion the collateral is sold right away and the fUSD received is added to the pool.
+        /// No more collateral is sold than keeps the collateral's share of the pool's value above the threshold at the
+        /// oracle price, and a new sale can only be made once the adapter's cooldown has passed.
+        /// With an `Auction` conversion a descending-price auction is started, in which the collateral can be bought
+        /// through `buy_from_conversion_auction`. At most `max_sale_amount` collateral is sold per conversion.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral to convert.
+        /// * `message`: Oracle message for price verification.
+        /// * `signature`: Oracle signature for price verification.
+        ///
+        /// # Panics
+        /// * If no pool exists for the given `collateral`, or collateral conversion is not enabled for it.
+        /// * If the collateral's share of the pool's value doesn't exceed the threshold.
+        /// * If the DEX adapter's cooldown since the last sale hasn't passed yet.
+        /// * If the DEX adapter doesn't return fUSD, or returns less than the oracle value minus the maximum slippage.
+        /// * If an auction is already running. An expired auction is replaced by the new one.
+        pub fn convert_pool_collateral(
+            &mut self,
+            collateral: ResourceAddress,
+            message: String,
+            signature: String,
+        ) {
+            let collateral_price: Decimal = self.oracle.call_raw(
+                &self.oracle_single_method_name,
+                scrypto_args!(collateral, message, signature),
+            );
+
+            let conversion = self.get_collateral_conversion(collateral).clone();
+            let (collateral_amount, fusd_amount) = self.get_pool_amounts(collateral);
+            let collateral_value = collateral_amount * collateral_price;
+
+            assert!(
+                collateral_value > Decimal::ZERO
+                    && collateral_value / (collateral_value + fusd_amount) > conversion.threshold,
+                "Collateral share below conversion threshold."
+            );
+
+            let sale_amount = conversion
+                .max_sale_amount
+                .map_or(collateral_amount, |max_sale_amount| max_sale_amount.min(collateral_amount));
+
+            match conversion.method {
+                ConversionMethod::DexAdapter { component, method_name, cooldown } => {
+                    if let Some(last_sale) = conversion.last_sale {
+                        assert!(
+                            !Clock::current_time_is_strictly_before(
+                                last_sale.add_minutes(cooldown).unwrap(),
+                                TimePrecision::Second
+                            ),
+                            "Conversion cooldown not passed."
+                        );
+                    }
+
+                    // Selling x collateral at the oracle price keeps the share above the threshold t as long as
+                    // (collateral_amount - x) * (1 - t) > t * (fusd_amount / oracle_price + x)
+                    let threshold = conversion.threshold;
+                    let max_threshold_amount = collateral_amount * (Decimal::ONE - threshold)
+                        - threshold * fusd_amount / collateral_price;
+                    let sale_amount = sale_amount.min(max_threshold_amount);
+
+                    let mut pool = self.get_two_resource_pool(collateral);
+                    let sold_collateral: Bucket = pool
+                        .protected_withdraw(
+                            collateral,
+                            sale_amount,
+                            WithdrawStrategy::Rounded(RoundingMode::ToNegativeInfinity),
+                        )
+                        .into();
+                    let sold_amount = sold_collateral.amount();
+
+                    let adapter: Global<AnyComponent> = Global::from(component);
+                    let fusd: Bucket = adapter.call_raw(&method_name, scrypto_args!(sold_collateral));
+
+                    assert!(
+                        fusd.resource_address() == self.fusd_address,
+                        "Conversion must return fUSD."
+                    );
+                    let fusd_received = fusd.amount();
+                    assert!(
+                        fusd_received >= sold_amount * collateral_price * (Decimal::ONE - conversion.max_slippage),
+                        "Conversion slippage too high."
+                    );
+
+                    pool.protected_deposit(fusd.as_fungible());
+                    self.update_debt_ceiling(collateral);
+
+                    self.stability_pools.get_mut(&collateral).unwrap().conversion.as_mut().unwrap().last_sale =
+                        Some(Clock::current_time_rounded_to_seconds());
+
+                    Runtime::emit_event(StabilityPoolConversionEvent {
+                        collateral,
+                        collateral_amount: sold_amount,
+                        fusd_amount: fusd_received,
+                        price: fusd_received / sold_amount,
+                        change: ConversionChange::DexSale,
+                    });
+                }
+                ConversionMethod::Auction { start_premium, .. } => {
+                    assert!(
+                        !Self::is_conversion_auction_running(&conversion),
+                        "Conversion auction already running."
+                    );
+
+                    let conversion = self.stability_pools.get_mut(&collateral).unwrap().conversion.as_mut().unwrap();
+                    conversion.auction_start = Some(Clock::current_time_rounded_to_seconds());
+                    conversion.auction_lot = sale_amount;
+
+                    Runtime::emit_event(StabilityPoolConversionEvent {
+                        collateral,
+                        collateral_amount: sale_amount,
+                        fusd_amount: Decimal::ZERO,
+                        price: collateral_price * (Decimal::ONE + start_premium),
+                        change: ConversionChange::AuctionStart,
+                    });
+                }
+            }
+        }
+
+        /// Buys collateral from a running conversion auction.
+        ///
+        /// The price starts at the oracle price increased by the auction's `start_premium`, and decreases by
+        /// `decay_per_minute` every minute, down to the oracle price minus the maximum slippage. The fUSD paid is
+        /// added to the stability pool. The auction ends once its lot is sold, and expires after its duration.
+        /// No more collateral is sold than keeps the collateral's share of the pool's value above the threshold.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral being bought.
+        /// * `fusd`: A `Bucket` containing the fUSD payment.
+        /// * `message`: Oracle message for price verification.
+        /// * `signature`: Oracle signature for price verification.
+        ///
+        /// # Returns
+        /// * `(Bucket, Bucket)`: A tuple containing:
+        ///     1. The `Bucket` of bought collateral.
+        ///     2. The `Bucket` of remaining fUSD payment (if any).
+        ///
+        /// # Panics
+        /// * If no pool exists for the given `collateral`, or collateral conversion is not enabled for it.
+        /// * If no conversion auction is running, or it has expired.
+        /// * If the payment is not fUSD.
+        /// * If the collateral's share of the pool's value doesn't exceed the threshold.
+        /// * If the payment doesn't buy any collateral.
+        pub fn buy_from_conversion_auction(
+            &mut self,
+            collateral: ResourceAddress,
+            mut fusd: Bucket,
+            message: String,
+            signature: String,
+        ) -> (Bucket, Bucket) {
+            assert!(fusd.resource_address() == self.fusd_address, "Payment must be fUSD.");
+
+            let collateral_price: Decimal = self.oracle.call_raw(
+                &self.oracle_single_method_name,
+                scrypto_args!(collateral, message, signature),
+            );
+
+            let conversion = self.get_collateral_conversion(collateral).clone();
+            assert!(conversion.auction_start.is_some(), "No conversion auction running.");
+            assert!(Self::is_conversion_auction_running(&conversion), "Conversion auction expired.");
+
+            let price = Self::get_conversion_auction_price(&conversion, collateral_price);
+            let (collateral_amount, fusd_amount) = self.get_pool_amounts(collateral);
+
+            // Selling x collateral keeps the share above the threshold t as long as
+            // (collateral_amount - x) * oracle_price * (1 - t) > t * (fusd_amount + x * price)
+            let threshold = conversion.threshold;
+            let max_threshold_amount = (collateral_amount * collateral_price * (Decimal::ONE - threshold)
+                - threshold * fusd_amount)
+                / (collateral_price * (Decimal::ONE - threshold) + threshold * price);
+            assert!(
+                max_threshold_amount > Decimal::ZERO,
+                "Collateral share below conversion threshold."
+            );
+
+            let buy_amount = (fusd.amount() / price)
+                .min(conversion.auction_lot)
+                .min(collateral_amount)
+                .min(max_threshold_amount);
+            assert!(buy_amount > Decimal::ZERO, "Nothing to buy.");
+
+            let mut pool = self.get_two_resource_pool(collateral);
+            let bought_collateral: Bucket = pool
+                .protected_withdraw(
+                    collateral,
+                    buy_amount,
+                    WithdrawStrategy::Rounded(RoundingMode::ToNegativeInfinity),
+                )
+                .into();
+            let bought_amount = bought_collateral.amount();
+
+            let payment = fusd.take((bought_amount * price).min(fusd.amount()));
+            let fusd_paid = payment.amount();
+            pool.protected_deposit(payment.as_fungible());
+            self.update_debt_ceiling(collateral);
+
+            let conversion = self.stability_pools.get_mut(&collateral).unwrap().conversion.as_mut().unwrap();
+            conversion.auction_lot -= bought_amount;
+            if conversion.auction_lot <= Decimal::ZERO || bought_amount >= collateral_amount {
+                conversion.auction_start = None;
+                conversion.auction_lot = Decimal::ZERO;
+            }
+
+            Runtime::emit_event(StabilityPoolConversionEvent {
+                collateral,
+                collateral_amount: bought_amount,
+                fusd_amount: fusd_paid,
+                price,
+                change: ConversionChange::AuctionSale,
+            });
+
+            (bought_collateral, fusd)
+        }
+
+        /// Locks pool units for a chosen duration.
+        ///
+        /// The locked units are weighted by `1 + max_lock_boost * duration / max_lock_duration`. Lockers share in the
+        /// liquidity rewards and the stability pool's share of charged interest by weight, instead of by pool units,
+        /// with the boost paid out as fUSD rewards claimable through `claim_lock_rewards`.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral whose pool units are locked.
+        /// * `pool_units`: A `Bucket` containing the pool unit custody receipts to lock.
+        /// * `duration`: The lock duration in minutes.
+        ///
+        /// # Returns
+        /// * `Bucket`: A `StabilityPoolLock` NFT, required to claim rewards and unlock the pool units.
+        ///
+        /// # Panics
+        /// * If `allow_multiple_actions` is false and `charge_interest` has already occurred in the same transaction.
+        /// * If no pool exists for the given `collateral`, or it is a product-sum pool.
+        /// * If the bucket doesn't contain the pool's custody receipts.
+        /// * If the duration is not positive or exceeds the maximum lock duration.
+        pub fn lock_pool_units(
+            &mut self,
+            collateral: ResourceAddress,
+            pool_units: Bucket,
+            duration: i64,
+        ) -> Bucket {
+            self.create_hash();
+
+            assert!(
+                duration > 0 && duration <= self.parameters.max_lock_duration,
+                "Invalid lock duration."
+            );
+            let pool_units = self.take_pool_units(collateral, pool_units);
+
+            let boost = Decimal::ONE
+                + self.parameters.max_lock_boost * Decimal::from(duration)
+                    / Decimal::from(self.parameters.max_lock_duration);
+            let pool_unit_amount = pool_units.amount();
+            let weight = pool_unit_amount * boost;
+
+            let locks = self.get_pool_locks(collateral);
+            let reward_per_weight = locks.reward_per_weight;
+            locks.locked_units.put(pool_units);
+            locks.total_weight += weight;
+
+            let now = Clock::current_time_rounded_to_seconds();
+            let unlock_time = now.add_minutes(duration).unwrap();
+
+            self.lock_counter += 1;
+            let lock_receipt = self.lock_receipt_manager.mint_non_fungible(
+                &NonFungibleLocalId::integer(self.lock_counter),
+                StabilityPoolLock {
+                    collateral,
+                    pool_units: pool_unit_amount,
+                    weight,
+                    lock_start: now,
+                    unlock_time,
+                    reward_per_weight_snapshot: reward_per_weight,
+                },
+            );
+
+            Runtime::emit_event(StabilityPoolLockEvent {
+                collateral,
+                lock_id: NonFungibleLocalId::integer(self.lock_counter),
+                pool_units: pool_unit_amount,
+                weight,
+                unlock_time,
+            });
+
+            lock_receipt
+        }
+
+        /// Claims the fUSD rewards earned by a lock.
+        ///
+        /// # Arguments
+        /// * `lock_proof`: A `Proof` of a single `StabilityPoolLock` NFT.
+        ///
+        /// # Returns
+        /// * `Bucket`: The claimed fUSD rewards.
+        ///
+        /// # Panics
+        /// * If the proof isn't of a single lock receipt.
+        pub fn claim_lock_rewards(&mut self, lock_proof: Proof) -> Bucket {
+            let lock_id = lock_proof
+                .check_with_message(self.lock_receipt_manager.address(), "Invalid lock receipt.")
+                .as_non_fungible()
+                .non_fungible_local_id();
+            let lock: StabilityPoolLock = self.lock_receipt_manager.get_non_fungible_data(&lock_id);
+
+            let locks = self.get_pool_locks(lock.collateral);
+            let reward_per_weight = locks.reward_per_weight;
+            let rewards = Self::take_lock_rewards(locks, &lock);
+
+            self.lock_receipt_manager.update_non_fungible_data(
+                &lock_id,
+                "reward_per_weight_snapshot",
+                reward_per_weight,
+            );
+
+            rewards
+        }
+
+        /// Unlocks the pool units of a lock, burning the lock receipt and paying out its outstanding rewards.
+        ///
+        /// Unlocking before the unlock time is allowed, but a penalty is deducted from the pool units. The penalty is
+        /// `early_unlock_penalty` scaled by the remaining fraction of the lock duration. The penalized pool units are
+        /// redeemed and their collateral and fUSD are returned to the pool, benefiting the remaining contributors.
+        ///
+        /// # Arguments
+        /// * `lock_receipt`: A `Bucket` containing a single `StabilityPoolLock` NFT.
+        ///
+        /// # Returns
+        /// * `(Bucket, Bucket)`: A tuple containing custody receipts for the unlocked pool units and the fUSD rewards.
+        ///
+        /// # Panics
+        /// * If the bucket doesn't contain a single lock receipt.
+        pub fn unlock_pool_units(&mut self, lock_receipt: Bucket) -> (Bucket, Bucket) {
+            assert!(
+                lock_receipt.resource_address() == self.lock_receipt_manager.address(),
+                "Invalid lock receipt."
+            );
+            let lock_id = lock_receipt.as_non_fungible().non_fungible_local_id();
+            let lock: StabilityPoolLock = self.lock_receipt_manager.get_non_fungible_data(&lock_id);
+            let collateral = lock.collateral;
+
+            let now = Clock::current_time_rounded_to_seconds();
+            let penalty = if Clock::current_time_is_strictly_before(lock.unlock_time, TimePrecision::Second) {
+                let remaining = lock.unlock_time.seconds_since_unix_epoch - now.seconds_since_unix_epoch;
+                let duration = lock.unlock_time.seconds_since_unix_epoch - lock.lock_start.seconds_since_unix_epoch;
+                self.parameters.early_unlock_penalty * lock.pool_units * Decimal::from(remaining)
+                    / Decimal::from(duration)
+            } else {
+                Decimal::ZERO
+            };
+
+            let locks = self.get_pool_locks(collateral);
+            let rewards = Self::take_lock_rewards(locks, &lock);
+            locks.total_weight = (locks.total_weight - lock.weight).max(Decimal::ZERO);
+            let mut pool_units = locks.locked_units.take(lock.pool_units.min(locks.locked_units.amount()));
+            let penalty_units = pool_units.take(penalty);
+
+            lock_receipt.burn();
+
+            if !penalty_units.is_empty() {
+                let (penalty_collateral, penalty_fusd) = self
+                    .get_two_resource_pool(collateral)
+                    .redeem(penalty_units.as_fungible());
+                self.deposit_rewards(collateral, penalty_collateral.into());
+                self.deposit_rewards(collateral, penalty_fusd.into());
+            } else {
+                penalty_units.drop_empty();
+            }
+
+            Runtime::emit_event(StabilityPoolUnlockEvent {
+                collateral,
+                lock_id,
+                pool_units_returned: pool_units.amount(),
+                penalty_units: penalty,
+                rewards: rewards.amount(),
+            });
+
+            (self.hand_out_pool_units(collateral, pool_units), rewards)
+        }
+
+        /// Allows an authorized user (OWNER) to withdraw accumulated liquidity rewards for a specific pool.
+        ///
+        /// Requires OWNER authorization (controller badge).
         ///
         /// # Arguments
         /// * `collateral`: The `ResourceAddress` identifying the pool whose rewards are being withdrawn.
         ///
         /// # Returns
-        /// * `Bucket`: A bucket containing the withdrawn fUSD rewards.
+        /// * `Bucket`: A bucket containing the withdrawn fUSD rewards.
+        ///
+        /// # Panics
+        /// * If no pool exists for the given `collateral`.
+        pub fn take_liquidity_rewards(&mut self, collateral: ResourceAddress) -> Bucket {
+            self.stability_pools
+                .get_mut(&collateral)
+                .unwrap()
+                .liquidity_rewards
+                .take_all()
+        }
+
+        /// Deposits a fee paid in collateral (e.g. a collateral flash loan fee) into the stability pool of that collateral,
+        /// distributing it pro-rata among the pool contributors.
+        ///
+        /// # Arguments
+        /// * `fee`: A `Bucket` containing the collateral fee.
+        ///
+        /// # Panics
+        /// * If no pool exists for the fee's collateral.
+        pub fn receive_collateral_fee(&mut self, fee: Bucket) {
+            assert!(
+                self.stability_pools.contains_key(&fee.resource_address()),
+                "No stability pool for this collateral."
+            );
+            self.deposit_rewards(fee.resource_address(), fee);
+        }
+
+        /// Deposits a fee paid in fUSD (e.g. flash loan interest) into the stability pool of a collateral,
+        /// distributing it pro-rata among the pool contributors.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` identifying the pool.
+        /// * `fee`: A `Bucket` containing the fUSD fee.
+        ///
+        /// # Panics
+        /// * If the `fee` is not fUSD.
+        /// * If no pool exists for the given `collateral`.
+        pub fn receive_fusd_fee(&mut self, collateral: ResourceAddress, fee: Bucket) {
+            assert!(fee.resource_address() == self.fusd_address, "Fee must be fUSD.");
+            assert!(
+                self.stability_pools.contains_key(&collateral),
+                "No stability pool for this collateral."
+            );
+            self.deposit_rewards(collateral, fee);
+        }
+
+        /// Triggers the charging of accrued interest on CDPs for a specific collateral type.
+        ///
+        /// Delegates the call to the core `Flux` component's `charge_interest` method.
+        /// The collected interest (fUSD) is then split according to the pool's configured or default
+        /// `payout_split`, `liquidity_rewards_split`, and `stability_pool_split` ratios.
+        /// If pool units are locked, the lockers' boosted share of the liquidity rewards and stability pool split is
+        /// paid to them as lock rewards (see `get_lock_reward_shares`).
+        /// Also updates the record of the lowest interest rate seen for this collateral.
+        /// If any interest was charged, the caller is paid the `ChargeInterest` keeper bounty, if configured and not on cooldown.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral whose CDPs should be charged interest.
+        /// * `start_interest`: Optional start interest rate for the range to charge (passed to `Flux`).
+        /// * `end_interest`: Optional end interest rate for the range to charge (passed to `Flux`).
+        ///
+        /// # Returns
+        /// * `Bucket`: The keeper bounty (fUSD), empty if none is paid.
+        ///
+        /// # Panics
+        /// * If `allow_multiple_actions` is false and this action (or `contribute_to_pool`) has already occurred in the same transaction.
+        /// * If the underlying `Flux::charge_interest` call fails.
+        /// * If no pool exists for the given `collateral`.
+        pub fn charge_interest(
+            &mut self,
+            collateral: ResourceAddress,
+            start_interest: Option<Decimal>,
+            end_interest: Option<Decimal>,
+        ) -> Bucket {
+            self.check_hash();
+
+            let fusd_charged = self.charge_interest_internal(collateral, start_interest, end_interest);
+
+            self.pay_keeper_bounty_for_charge(fusd_charged)
+        }
+
+        /// Charges interest across all collaterals, resuming where the previous call stopped.
+        ///
+        /// Walks `collaterals` starting from the stored cursor, charging the interest of each collateral's interest
+        /// tiers like `charge_interest` does, including the split of the charged interest. Work is capped at `max_tiers`
+        /// interest tiers per call (a collateral without tiers counts as one). If the cap is reached in the middle of a
+        /// collateral, the cursor is set to the first uncharged tier, so the next call continues from there.
+        /// Each collateral is visited at most once per call.
+        /// If any interest was charged, the caller is paid the `ChargeInterest` keeper bounty, if configured and not on cooldown.
+        ///
+        /// # Arguments
+        /// * `max_tiers`: The maximum number of interest tiers to charge.
+        ///
+        /// # Returns
+        /// * `(Vec<InterestChargeSummary>, Bucket)`: A tuple containing:
+        ///     1. A summary of the interest charged per collateral.
+        ///     2. The keeper bounty (fUSD), empty if none is paid.
+        ///
+        /// # Panics
+        /// * If `allow_multiple_actions` is false and this action (or `contribute_to_pool`) has already occurred in the same transaction.
+        /// * If the underlying `Flux::charge_interest` call fails.
+        pub fn charge_all_interest(&mut self, max_tiers: u64) -> (Vec<InterestChargeSummary>, Bucket) {
+            self.check_hash();
+
+            let mut summaries: Vec<InterestChargeSummary> = vec![];
+            if self.collaterals.is_empty() {
+                return (summaries, Bucket::new(self.fusd_address));
+            }
+
+            let (mut collateral_index, mut start_interest) = match &self.interest_charge_cursor {
+                Some(cursor) => match self.collaterals.iter().position(|collateral| *collateral == cursor.collateral) {
+                    Some(index) => (index, cursor.interest),
+                    None => (0, None),
+                },
+                None => (0, None),
+            };
+            let mut tiers_left = max_tiers;
+            let mut collaterals_visited = 0;
+
+            while tiers_left > 0 && collaterals_visited < self.collaterals.len() {
+                let collateral = self.collaterals[collateral_index];
+                // Load one tier more than is left, to find where to stop if the collateral has more tiers
+                let tiers: Vec<Decimal> = self
+                    .flux
+                    .get_interest_infos_limited(collateral, start_interest, None, tiers_left.saturating_add(1))
+                    .iter()
+                    .map(|interest_info| interest_info.interest)
+                    .collect();
+
+                // Stop in the middle of this collateral if it has more tiers than are left
+                if tiers.len() as u64 > tiers_left {
+                    let end_interest = tiers[tiers_left as usize];
+                    let fusd_charged = self.charge_interest_internal(collateral, start_interest, Some(end_interest));
+                    summaries.push(InterestChargeSummary {
+                        collateral,
+                        start_interest,
+                        end_interest: Some(end_interest),
+                        tiers_charged: tiers_left,
+                        fusd_charged,
+                    });
+                    self.interest_charge_cursor = Some(InterestChargeCursor {
+                        collateral,
+                        interest: Some(end_interest),
+                    });
+
+                    let bounty = self.pay_keeper_bounty_for_charge(Self::get_total_charged(&summaries));
+                    return (summaries, bounty);
+                }
+
+                let fusd_charged = self.charge_interest_internal(collateral, start_interest, None);
+                summaries.push(InterestChargeSummary {
+                    collateral,
+                    start_interest,
+                    end_interest: None,
+                    tiers_charged: tiers.len() as u64,
+                    fusd_charged,
+                });
+
+                tiers_left = tiers_left.saturating_sub((tiers.len() as u64).max(1));
+                collateral_index = (collateral_index + 1) % self.collaterals.len();
+                start_interest = None;
+                collaterals_visited += 1;
+            }
+
+            self.interest_charge_cursor = Some(InterestChargeCursor {
+                collateral: self.collaterals[collateral_index],
+                interest: start_interest,
+            });
+
+            let bounty = self.pay_keeper_bounty_for_charge(Self::get_total_charged(&summaries));
+            (summaries, bounty)
+        }
+
+        /// Sums the fUSD interest charged over interest charge summaries.
+        fn get_total_charged(summaries: &[InterestChargeSummary]) -> Decimal {
+            summaries
+                .iter()
+                .fold(Decimal::ZERO, |total, summary| total + summary.fusd_charged)
+        }
+
+        /// Pays the `ChargeInterest` keeper bounty, but only if interest was actually charged.
+        ///
+        /// # Arguments
+        /// * `fusd_charged`: The amount of fUSD interest charged by the call.
+        ///
+        /// # Returns
+        /// * `Bucket`: The keeper bounty (fUSD), empty if none is paid.
+        fn pay_keeper_bounty_for_charge(&mut self, fusd_charged: Decimal) -> Bucket {
+            if fusd_charged > Decimal::ZERO {
+                self.pay_keeper_bounty(KeeperAction::ChargeInterest)
+            } else {
+                Bucket::new(self.fusd_address)
+            }
+        }
+
+        /// Charges interest for a collateral's interest tiers in the given range and splits the charged interest,
+        /// see `charge_interest`.
+        ///
+        /// # Returns
+        /// * `Decimal`: The amount of fUSD interest charged.
+        fn charge_interest_internal(
+            &mut self,
+            collateral: ResourceAddress,
+            start_interest: Option<Decimal>,
+            end_interest: Option<Decimal>,
+        ) -> Decimal {
+            let (mut fusd, lowest_interest): (Bucket, Decimal) =
+                self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                    self.flux.charge_interest(
+                        collateral,
+                        start_interest,
+                        end_interest,
+                        self.get_highest_lowest_interest(collateral),
+                    )
+                });
+
+            let should_update_lowest_interest_history = {
+                let pool = self.stability_pools.get(&collateral).unwrap();
+                let last_update_time = pool.last_lowest_interests_update
+                    .add_minutes(self.parameters.lowest_interest_interval)
+                    .unwrap();
+                
+                let current_lowest_interest = pool.latest_lowest_interests
+                    .last()
+                    .unwrap_or(&Decimal::ZERO);
+
+                Clock::current_time_is_strictly_after(last_update_time, TimePrecision::Second) || 
+                lowest_interest > *current_lowest_interest
+            };
+
+            if should_update_lowest_interest_history {
+                self.stability_pools
+                    .get_mut(&collateral)
+                    .unwrap()
+                    .latest_lowest_interests
+                    .push(lowest_interest);
+
+                if self
+                    .stability_pools
+                    .get_mut(&collateral)
+                    .unwrap()
+                    .latest_lowest_interests
+                    .len()
+                    > self.parameters.lowest_interest_history_length as usize
+                {
+                    self.stability_pools
+                        .get_mut(&collateral)
+                        .unwrap()
+                        .latest_lowest_interests
+                        .remove(0);
+                }
+            }
+
+            let fusd_amount = fusd.amount();
+
+            let payout_split = self
+                .stability_pools
+                .get(&collateral)
+                .unwrap()
+                .payout_split
+                .unwrap_or(self.parameters.default_payout_split);
+            let liquidity_rewards_split = self
+                .stability_pools
+                .get(&collateral)
+                .unwrap()
+                .liquidity_rewards_split
+                .unwrap_or(self.parameters.default_liquidity_rewards_split);
+            let stability_pool_split = self
+                .stability_pools
+                .get(&collateral)
+                .unwrap()
+                .stability_pool_split
+                .unwrap_or(self.parameters.default_stability_pool_split);
+
+            let split_weight = payout_split + liquidity_rewards_split + stability_pool_split;
+
+            let mut payout = fusd.take(fusd_amount * payout_split / split_weight);
+            self.keeper_rewards
+                .funds
+                .put(payout.take(payout.amount() * self.keeper_rewards.funding_share));
+            self.payout_vault.put(payout);
+            let mut liquidity_rewards = fusd.take(fusd_amount * liquidity_rewards_split / split_weight);
+
+            let (liquidity_rewards_lock_share, stability_pool_lock_share) = self.get_lock_reward_shares(collateral);
+            let mut lock_rewards = liquidity_rewards.take(liquidity_rewards.amount() * liquidity_rewards_lock_share);
+            lock_rewards.put(fusd.take(fusd.amount() * stability_pool_lock_share));
+            self.distribute_lock_rewards(collateral, lock_rewards);
+
+            self.stability_pools
+                .get_mut(&collateral)
+                .unwrap()
+                .liquidity_rewards
+                .put(liquidity_rewards);
+            self.deposit_rewards(collateral, fusd);
+
+            fusd_amount
+        }
+
+        /// Initiates the liquidation of an undercollateralized CDP.
+        ///
+        /// Fetches the collateral price from the oracle, determines the amount of fUSD available in the
+        /// stability pool, withdraws it, and calls the core `Flux` component's `liquidate_cdp` method.
+        /// The returned collateral (debt coverage + profit) is processed: the profit portion (after deducting
+        /// a potential liquidator fee share) is deposited back into the stability pool, and the liquidator fee share
+        /// (if applicable) is returned to the caller.
+        /// For product-sum pools, the used fUSD is deducted from the deposits and all collateral that isn't paid to the
+        /// liquidator is distributed as collateral gains.
+        /// If the collateral opted in to the shared stability pool and its own pool can't cover the CDP's debt, the
+        /// shared pool pays the fUSD the collateral's own pool is missing. The seized collateral (and any leftover fUSD)
+        /// is split between both pools, pro rata to the fUSD each paid.
+        ///
+        /// # Arguments
+        /// * `cdp_id`: The `NonFungibleLocalId` of the CDP to liquidate.
+        /// * `message`: Oracle message for price verification.
+        /// * `signature`: Oracle signature for price verification.
+        ///
+        /// # Returns
+        /// * `Bucket`: A bucket containing the liquidator's share of the profit (collateral), if any.
+        ///
+        /// # Panics
+        /// * If the oracle call fails.
+        /// * If no pool exists for the CDP's collateral type.
+        /// * If the underlying `Flux::liquidate_cdp` call fails (e.g., CDP not liquidatable, insufficient pool fUSD for full liquidation initially provided).
+        pub fn liquidate(
+            &mut self,
+            cdp_id: NonFungibleLocalId,
+            message: String,
+            signature: String,
+        ) -> Bucket {
+            let cdp_data: Cdp = self.cdp_resource_manager.get_non_fungible_data(&cdp_id);
+            let collateral = cdp_data.collateral_address;
+
+            let price: Decimal = self.oracle.call_raw(
+                &self.oracle_single_method_name,
+                scrypto_args!(collateral, message, signature),
+            );
+
+            self.liquidate_with_price(cdp_id, collateral, price)
+        }
+
+        /// Liquidates up to `max_count` undercollateralized CDPs of a collateral type in a single call.
+        ///
+        /// The oracle is checked once, after which the `Flux` component's `ratios_by_interest` trees are walked for
+        /// liquidatable CDPs (see `Flux::get_liquidatable_cdps`). CDPs that are marked and still within their notice
+        /// period, or would only be marked by a liquidation, are skipped. Each CDP is liquidated like in `liquidate`,
+        /// stopping early once the stability pools can't cover the next CDP's debt.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral whose CDPs are liquidated.
+        /// * `max_count`: The maximum number of CDPs to liquidate.
+        /// * `max_inspected`: The maximum number of CDPs to inspect while looking for liquidatable ones.
+        /// * `message`: Oracle message for price verification.
+        /// * `signature`: Oracle signature for price verification.
+        ///
+        /// # Returns
+        /// * `Bucket`: A bucket containing the aggregated liquidator fees (collateral).
         ///
         /// # Panics
+        /// * If the oracle call fails.
         /// * If no pool exists for the given `collateral`.
-        pub fn take_liquidity_rewards(&mut self, collateral: ResourceAddress) -> Bucket {
-            self.stability_pools
-                .get_mut(&collateral)
-                .unwrap()
-                .liquidity_rewards
-                .take_all()
+        pub fn batch_liquidate(
+            &mut self,
+            collateral: ResourceAddress,
+            max_count: u64,
+            max_inspected: u64,
+            message: String,
+            signature: String,
+        ) -> Bucket {
+            let price: Decimal = self.oracle.call_raw(
+                &self.oracle_single_method_name,
+                scrypto_args!(collateral, message, signature),
+            );
+
+            let liquidatable_cdps = self.flux.get_liquidatable_cdps(collateral, max_count, max_inspected, price);
+
+            let mut liquidator_fee = Bucket::new(collateral);
+            for (cdp_id, debt) in liquidatable_cdps {
+                if !self.can_liquidate_from_pools(collateral, debt) {
+                    break;
+                }
+
+                liquidator_fee.put(self.liquidate_with_price(cdp_id, collateral, price));
+            }
+
+            liquidator_fee
         }
 
-        /// Triggers the charging of accrued interest on CDPs for a specific collateral type.
-        ///
-        /// Delegates the call to the core `Flux` component's `charge_interest` method.
-        /// The collected interest (fUSD) is then split according to the pool's configured or default
-        /// `payout_split`, `liquidity_rewards_split`, and `stability_pool_split` ratios.
-        /// Also updates the record of the lowest interest rate seen for this collateral.
+        /// Liquidates a CDP at an already verified price, see `liquidate`.
         ///
         /// # Arguments
-        /// * `collateral`: The `ResourceAddress` of the collateral whose CDPs should be charged interest.
-        /// * `start_interest`: Optional start interest rate for the range to charge (passed to `Flux`).
-        /// * `end_interest`: Optional end interest rate for the range to charge (passed to `Flux`).
+        /// * `cdp_id`: The `NonFungibleLocalId` of the CDP to liquidate.
+        /// * `collateral`: The `ResourceAddress` of the CDP's collateral.
+        /// * `price`: The verified oracle price of the collateral.
         ///
-        /// # Panics
-        /// * If `allow_multiple_actions` is false and this action (or `contribute_to_pool`) has already occurred in the same transaction.
-        /// * If the underlying `Flux::charge_interest` call fails.
-        /// * If no pool exists for the given `collateral`.
-        pub fn charge_interest(
+        /// # Returns
+        /// * `Bucket`: A bucket containing the liquidator's share of the profit (collateral), if any.
+        fn liquidate_with_price(
             &mut self,
+            cdp_id: NonFungibleLocalId,
             collateral: ResourceAddress,
-            start_interest: Option<Decimal>,
-            end_interest: Option<Decimal>,
-        ) {
-            self.check_hash();
+            price: Decimal,
+        ) -> Bucket {
+            let (_, fusd_amount_available) = self.get_pool_amounts(collateral);
 
-            let (mut fusd, lowest_interest): (Bucket, Decimal) =
-                self.badge_vault.authorize_with_amount(dec!("0.75"), || {
-                    self.flux.charge_interest(
+            if self.stability_pools.get(&collateral).unwrap().use_shared_pool {
+                let (_, required_fusd, _) = self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                    self.flux.check_liquidate_cdp(cdp_id.clone(), Some(price))
+                });
+
+                if fusd_amount_available < required_fusd {
+                    return self.liquidate_with_shared_pool(
+                        cdp_id,
                         collateral,
-                        start_interest,
-                        end_interest,
-                        self.get_highest_lowest_interest(collateral),
-                    )
+                        price,
+                        fusd_amount_available,
+                        required_fusd,
+                    );
+                }
+            }
+
+            let payment = self.withdraw_liquidation_payment(collateral, fusd_amount_available);
+
+            let (mut payout, collateral_equal_to_debt, leftover_payment) =
+                self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                    self.flux.liquidate_cdp(payment, cdp_id, Some(price))
                 });
 
-            let should_update_lowest_interest_history = {
-                let pool = self.stability_pools.get(&collateral).unwrap();
-                let last_update_time = pool.last_lowest_interests_update
-                    .add_minutes(self.parameters.lowest_interest_interval)
-                    .unwrap();
-                
-                let current_lowest_interest = pool.latest_lowest_interests
-                    .last()
-                    .unwrap_or(&Decimal::ZERO);
 
-                Clock::current_time_is_strictly_after(last_update_time, TimePrecision::Second) || 
-                lowest_interest > *current_lowest_interest
-            };
+            let liquidator_fee = self.take_liquidator_fee(&mut payout, collateral_equal_to_debt);
 
-            if should_update_lowest_interest_history {
-                self.stability_pools
-                    .get_mut(&collateral)
-                    .unwrap()
-                    .latest_lowest_interests
-                    .push(lowest_interest);
+            self.deposit_liquidation_proceeds(collateral, leftover_payment, payout);
+            self.update_debt_ceiling(collateral);
 
-                if self
-                    .stability_pools
-                    .get_mut(&collateral)
+            liquidator_fee
+        }
+
+        /// Withdraws fUSD from a stability pool to pay for a liquidation.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` identifying the pool.
+        /// * `amount`: The amount of fUSD to withdraw.
+        ///
+        /// # Returns
+        /// * `Bucket`: The withdrawn fUSD.
+        fn withdraw_liquidation_payment(&mut self, collateral: ResourceAddress, amount: Decimal) -> Bucket {
+            match self.stability_pools.get(&collateral).unwrap().pool {
+                StabilityPoolComponent::TwoResource(mut pool) => pool
+                    .protected_withdraw(
+                        self.fusd_address,
+                        amount,
+                        WithdrawStrategy::Rounded(RoundingMode::ToNegativeInfinity),
+                    )
+                    .into(),
+                StabilityPoolComponent::ProductSum(pool) => pool.withdraw_for_offset(amount),
+            }
+        }
+
+        /// Deposits the leftover fUSD payment and the seized collateral of a liquidation into a stability pool.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` identifying the pool.
+        /// * `leftover_payment`: The fUSD payment not used by the liquidation.
+        /// * `payout`: The seized collateral, after the liquidator fee.
+        fn deposit_liquidation_proceeds(&mut self, collateral: ResourceAddress, leftover_payment: Bucket, payout: Bucket) {
+            match self.stability_pools.get(&collateral).unwrap().pool {
+                StabilityPoolComponent::TwoResource(mut pool) => {
+                    pool.protected_deposit(leftover_payment.as_fungible());
+                    pool.protected_deposit(payout.as_fungible());
+                }
+                StabilityPoolComponent::ProductSum(pool) => pool.offset(leftover_payment, payout),
+            }
+        }
+
+        /// Performs fUSD redemptions against collateral held in the Flux protocol.
+        ///
+        /// First redeems against the centralized stablecoins received through panic mode liquidations,
+        /// draining their vaults in the configured redemption order. Stablecoins are paid out at their oracle value,
+        /// and stablecoins without a supplied price or whose oracle price deviates more than their (last configured)
+        /// depeg tolerance from 1 USD are skipped. If the fUSD payment is not fully covered by stablecoins, it proceeds to perform optimal batch
+        /// redemptions against regular collateral pools by calling the core `Flux` component.
+        ///
+        /// # Arguments
+        /// * `fusd`: A `Bucket` containing the fUSD to be redeemed.
+        /// * `oracle_info`: A `Vec` containing tuples of `(ResourceAddress, String, String)` needed by the oracle
+        ///                  to verify prices for all potential collateral types involved in the optimal redemption,
+        ///                  and for every panic mode stablecoin to redeem against.
+        /// * `max_redemptions`: The maximum number of individual CDP redemptions to perform across all collateral types.
+        ///
+        /// # Returns
+        /// * `(Vec<(ResourceAddress, Bucket)>, Bucket)`: A tuple containing:
+        ///     1. A `Vec` where each tuple holds the `ResourceAddress` of a redeemed collateral (or stablecoin)
+        ///        and a `Bucket` containing the corresponding payout.
+        ///     2. The `Bucket` containing any remaining fUSD from the initial `payment` bucket.
+        ///
+        /// # Panics
+        /// * If the oracle call for batch prices fails.
+        /// * If the underlying `Flux::optimal_batch_redemption` call fails.
+        pub fn redemptions(
+            &mut self,
+            mut fusd: Bucket,
+            oracle_info: Vec<(ResourceAddress, String, String)>,
+            max_redemptions: u64,
+        ) -> (Vec<(ResourceAddress, Bucket)>, Bucket) {
+            let prices: Vec<(ResourceAddress, Decimal)> = self
+                .oracle
+                .call_raw(&self.oracle_batch_method_name, scrypto_args!(oracle_info));
+
+            let mut stablecoin_redemptions: Vec<(ResourceAddress, Bucket)> = vec![];
+
+            // First drain the centralized stablecoin vaults, in redemption order
+            for stablecoin_address in self.panic_mode.redemption_order.clone() {
+                if fusd.is_empty() {
+                    break;
+                }
+
+                let reserves = self
+                    .panic_mode
+                    .centralized_stablecoin_vaults
+                    .get(&stablecoin_address)
                     .unwrap()
-                    .latest_lowest_interests
-                    .len()
-                    > self.parameters.lowest_interest_history_length as usize
+                    .amount();
+                if reserves == Decimal::ZERO {
+                    continue;
+                }
+
+                // Stablecoins without a supplied price are skipped, like depegged ones
+                let stablecoin_price = match prices
+                    .iter()
+                    .find(|&&(address, _)| address == stablecoin_address)
+                    .map(|&(_, price)| price)
                 {
-                    self.stability_pools
-                        .get_mut(&collateral)
-                        .unwrap()
-                        .latest_lowest_interests
-                        .remove(0);
+                    Some(price) => price,
+                    None => continue,
+                };
+                let depeg_tolerance = *self.panic_mode.depeg_tolerances.get(&stablecoin_address).unwrap();
+
+                // Depegged reserves are skipped, until their price recovers or they are unwound
+                if stablecoin_price < Decimal::ONE - depeg_tolerance
+                    || stablecoin_price > Decimal::ONE + depeg_tolerance
+                {
+                    continue;
+                }
+
+                let stablecoin = {
+                    let mut stablecoin_vault = self
+                        .panic_mode
+                        .centralized_stablecoin_vaults
+                        .get_mut(&stablecoin_address)
+                        .unwrap();
+                    stablecoin_vault.take_advanced(
+                        reserves.min(fusd.amount() / stablecoin_price),
+                        WithdrawStrategy::Rounded(RoundingMode::ToZero),
+                    )
+                }; // stablecoin_vault is dropped here
+
+                if stablecoin.is_empty() {
+                    stablecoin.drop_empty();
+                    continue;
+                }
+
+                let fusd_payment = fusd.take_advanced(
+                    (stablecoin.amount() * stablecoin_price).min(fusd.amount()),
+                    WithdrawStrategy::Rounded(RoundingMode::AwayFromZero),
+                );
+
+                // Burn the fUSD
+                self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                    fusd_payment.burn();
+                });
+
+                stablecoin_redemptions.push((stablecoin_address, stablecoin));
+            }
+
+            if fusd.is_empty() {
+                return (stablecoin_redemptions, fusd);
+            }
+
+            // Continue with normal redemptions and add the stablecoins to the results
+            let (mut results, remaining_fusd) = self.perform_normal_redemptions(fusd, prices, max_redemptions);
+            results.extend(stablecoin_redemptions);
+            (results, remaining_fusd)
+        }
+
+        // Helper method to perform normal redemptions
+        fn perform_normal_redemptions(
+            &mut self,
+            fusd: Bucket,
+            prices: Vec<(ResourceAddress, Decimal)>,
+            max_redemptions: u64,
+        ) -> (Vec<(ResourceAddress, Bucket)>, Bucket) {
+            let fusd_amount = fusd.amount();
+
+            // Get collateral infos
+            let collateral_infos_vec: Vec<CollateralInfoReturn> =
+                self.flux.get_collateral_infos(self.collaterals.clone());
+
+            // Pre-allocate vectors with known capacity
+            let mut optimal_redemption_input = Vec::with_capacity(collateral_infos_vec.len());
+            let mut total_debt_difference = Decimal::ZERO;
+
+            // Single pass through collateral infos to build input and calculate total
+            for collateral_info in collateral_infos_vec {
+                let (_, pool_amount) = self.get_pool_amounts(collateral_info.resource_address);
+
+                let individual_difference = collateral_info.total_debt - pool_amount;
+
+                if individual_difference > Decimal::ZERO {
+                    // Find price in single pass without additional allocations
+                    let price = prices
+                        .iter()
+                        .find(|&&(addr, _)| addr == collateral_info.resource_address)
+                        .map(|&(_, price)| price)
+                        .unwrap_or_else(|| panic!("No price info found."));
+
+                    total_debt_difference += individual_difference;
+
+                    // Store info for later processing
+                    optimal_redemption_input.push((
+                        collateral_info.resource_address,
+                        individual_difference,
+                        Some(price),
+                    ));
+                }
+            }
+
+            assert!(
+                total_debt_difference > Decimal::ZERO,
+                "All fUSD is used within Stability Pools"
+            );
+
+            // Adjust amounts in place
+            for (_, amount, _) in &mut optimal_redemption_input {
+                *amount = *amount / total_debt_difference * fusd_amount;
+            }
+
+            // Perform batch redemption
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                self.flux.optimal_batch_redemption(
+                    fusd,
+                    optimal_redemption_input,
+                    None,
+                    max_redemptions,
+                )
+            })
+        }
+        
+        //getters
+        
+        /// Retrieves detailed information about specified stability pools, or all pools if none are specified.
+        ///
+        /// # Arguments
+        /// * `resource_addresses`: An optional `Vec<ResourceAddress>` specifying which collateral pools to get info for.
+        ///                       If `None`, returns info for all existing pools.
+        ///
+        /// # Returns
+        /// * `Vec<StabilityPoolInfoReturn>`: A vector containing the information for each requested pool.
+        pub fn get_stability_pool_infos(&mut self, resource_addresses: Option<Vec<ResourceAddress>>) -> Vec<StabilityPoolInfoReturn> {
+            let mut info_return_vec: Vec<StabilityPoolInfoReturn> = vec![];
+            
+            let addresses_to_check = resource_addresses.as_ref().map(|v| v.as_slice()).unwrap_or_default();
+            let should_check_addresses = resource_addresses.is_some();
+
+            for (collateral, stability_pool) in &self.stability_pools {
+                if !should_check_addresses || addresses_to_check.contains(collateral) {
+                    let (collateral_amount, fusd_amount) = self.get_pool_amounts(*collateral);
+                    let (locked_pool_units, lock_weight) = stability_pool
+                        .locks
+                        .as_ref()
+                        .map(|locks| (locks.locked_units.amount(), locks.total_weight))
+                        .unwrap_or((Decimal::ZERO, Decimal::ZERO));
+
+                    let info_return = StabilityPoolInfoReturn {
+                        collateral: *collateral,
+                        payout_split: stability_pool.payout_split,
+                        liquidity_rewards_split: stability_pool.liquidity_rewards_split,
+                        stability_pool_split: stability_pool.stability_pool_split,
+                        allow_pool_buys: stability_pool.allow_pool_buys,
+                        pool_buy_price_modifier: stability_pool.pool_buy_price_modifier,
+                        liquidity_rewards: stability_pool.liquidity_rewards.amount(),
+                        pool: stability_pool.pool,
+                        collateral_amount,
+                        fusd_amount,
+                        locked_pool_units,
+                        lock_weight,
+                        custody_receipt: stability_pool
+                            .pool_unit_custody
+                            .as_ref()
+                            .map(|custody| custody.receipt_manager.address()),
+                        conversion: stability_pool.conversion.clone(),
+                        use_shared_pool: stability_pool.use_shared_pool,
+                        deposit_cap: stability_pool.deposit_cap,
+                        contributor_badge: stability_pool.contributor_badge,
+                        guarded_launch: stability_pool.guarded_launch,
+                        latest_lowest_interests: stability_pool.latest_lowest_interests.clone(),
+                        last_lowest_interests_update: stability_pool.last_lowest_interests_update,
+                    };
+
+                    info_return_vec.push(info_return);
                 }
             }
 
-            let fusd_amount = fusd.amount();
+            info_return_vec
+        }
 
-            let payout_split = self
-                .stability_pools
-                .get(&collateral)
-                .unwrap()
-                .payout_split
-                .unwrap_or(self.parameters.default_payout_split);
-            let liquidity_rewards_split = self
-                .stability_pools
-                .get(&collateral)
-                .unwrap()
-                .liquidity_rewards_split
-                .unwrap_or(self.parameters.default_liquidity_rewards_split);
-            let stability_pool_split = self
+        //helpers
+
+        /// Creates a transaction hash entry if `allow_multiple_actions` is false
+        /// Used to prevent contributing to a pool and immediately charging interest
+        ///
+        /// # Panics
+        /// * If `allow_multiple_actions` is `false` and an entry for the current transaction hash already exists.
+        fn create_hash(&mut self) {
+            let transaction_hash = Runtime::transaction_hash();
+            self.transactions.insert(transaction_hash, ());
+        }
+
+        fn check_hash(&mut self) {
+            let transaction_hash = Runtime::transaction_hash();
+            
+            if !self.allow_multiple_actions {
+                assert!(
+                    self.transactions.get(&transaction_hash).is_none(),
+                    "Trying to execute two actions that aren't allowed in one transaction!"
+                );
+            }
+        }
+
+        /// Internal helper function to handle buying collateral from a pool's underlying `TwoResourcePool` component.
+        /// Calculates the amount to buy based on the provided fUSD, price, and buy modifier.
+        /// Withdraws collateral from the pool and deposits the fUSD payment.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral being bought (and identifying the pool).
+        /// * `fusd`: A `Bucket` containing the fUSD payment.
+        /// * `collateral_price`: The current `Decimal` price of the collateral.
+        ///
+        /// # Returns
+        /// * `(Bucket, Bucket)`: A tuple containing the bought collateral and any remaining fUSD.
+        fn buy_collateral_from_pool_internal(
+            &mut self,
+            collateral: ResourceAddress,
+            mut fusd: Bucket,
+            collateral_price: Decimal,
+        ) -> (Bucket, Bucket) {
+            let buy_modifier = self
                 .stability_pools
                 .get(&collateral)
                 .unwrap()
-                .stability_pool_split
-                .unwrap_or(self.parameters.default_stability_pool_split);
+                .pool_buy_price_modifier
+                .unwrap_or(self.parameters.default_pool_buy_price_modifier);
 
-            let split_weight = payout_split + liquidity_rewards_split + stability_pool_split;
+            let can_buy = fusd.amount() / (collateral_price * buy_modifier);
+            let (max_available, _) = self.get_pool_amounts(collateral);
+            let can_buy_fraction = max_available / can_buy;
 
-            self.payout_vault
-                .put(fusd.take(fusd_amount * payout_split / split_weight));
-            self.stability_pools
-                .get_mut(&collateral)
-                .unwrap()
-                .liquidity_rewards
-                .put(fusd.take(fusd_amount * liquidity_rewards_split / split_weight));
-            self.stability_pools
-                .get_mut(&collateral)
-                .unwrap()
-                .pool
-                .protected_deposit(fusd.as_fungible());
+            let payment_bucket = if can_buy_fraction >= Decimal::ONE {
+                fusd.take(fusd.amount())
+            } else {
+                fusd.take(fusd.amount() * can_buy_fraction)
+            };
+
+            let mut pool = self.get_two_resource_pool(collateral);
+            let buy = pool.protected_withdraw(
+                collateral,
+                can_buy.min(max_available),
+                WithdrawStrategy::Rounded(RoundingMode::ToNegativeInfinity),
+            );
+            pool.protected_deposit(payment_bucket.as_fungible());
+            self.update_debt_ceiling(collateral);
+
+            (buy.into(), fusd)
         }
 
-        /// Initiates the liquidation of an undercollateralized CDP.
-        ///
-        /// Fetches the collateral price from the oracle, determines the amount of fUSD available in the
-        /// stability pool, withdraws it, and calls the core `Flux` component's `liquidate_cdp` method.
-        /// The returned collateral (debt coverage + profit) is processed: the profit portion (after deducting
-        /// a potential liquidator fee share) is deposited back into the stability pool, and the liquidator fee share
-        /// (if applicable) is returned to the caller.
+        /// Retrieves the amounts of collateral and fUSD held by a stability pool, regardless of its type.
+        /// For product-sum pools the fUSD amount only includes deposits, as the fUSD yield can't absorb liquidations.
         ///
         /// # Arguments
-        /// * `cdp_id`: The `NonFungibleLocalId` of the CDP to liquidate.
-        /// * `message`: Oracle message for price verification.
-        /// * `signature`: Oracle signature for price verification.
+        /// * `collateral`: The `ResourceAddress` identifying the pool.
         ///
         /// # Returns
-        /// * `Bucket`: A bucket containing the liquidator's share of the profit (collateral), if any.
+        /// * `(Decimal, Decimal)`: The collateral and fUSD amounts.
+        fn get_pool_amounts(&self, collateral: ResourceAddress) -> (Decimal, Decimal) {
+            let vault_amounts = match self.stability_pools.get(&collateral).unwrap().pool {
+                StabilityPoolComponent::TwoResource(pool) => pool.get_vault_amounts(),
+                StabilityPoolComponent::ProductSum(pool) => pool.get_vault_amounts(),
+            };
+
+            (
+                *vault_amounts.get(&collateral).unwrap_or(&Decimal::ZERO),
+                *vault_amounts.get(&self.fusd_address).unwrap_or(&Decimal::ZERO),
+            )
+        }
+
+        /// Checks whether a contribution to a stability pool is allowed by the pool's allowlist badge and deposit cap.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral whose pool is contributed to.
+        /// * `amount`: The amount of fUSD contributed, after fees.
         ///
         /// # Panics
-        /// * If the oracle call fails.
-        /// * If no pool exists for the CDP's collateral type.
-        /// * If the underlying `Flux::liquidate_cdp` call fails (e.g., CDP not liquidatable, insufficient pool fUSD for full liquidation initially provided).
-        pub fn liquidate(
-            &mut self,
-            cdp_id: NonFungibleLocalId,
-            message: String,
-            signature: String,
-        ) -> Bucket {
-            let cdp_data: Cdp = self.cdp_resource_manager.get_non_fungible_data(&cdp_id);
-            let collateral = cdp_data.collateral_address;
-
-            let price: Decimal = self.oracle.call_raw(
-                &self.oracle_single_method_name,
-                scrypto_args!(collateral, message, signature),
-            );
+        /// * If the pool requires a contributor badge and no proof of it is in the auth zone.
+        /// * If the contribution would take the pool's fUSD above its deposit cap.
+        fn check_contribution_allowed(&self, collateral: ResourceAddress, amount: Decimal) {
+            let pool_info = self.stability_pools.get(&collateral).unwrap();
 
-            let fusd_amount_available: Decimal = *self
-                .stability_pools
-                .get(&collateral)
-                .unwrap()
-                .pool
-                .get_vault_amounts()
-                .get(&self.fusd_address)
-                .unwrap();
+            if let Some(badge) = pool_info.contributor_badge {
+                Runtime::assert_access_rule(rule!(require(badge)));
+            }
 
-            let payment = self
-                .stability_pools
-                .get_mut(&collateral)
-                .unwrap()
-                .pool
-                .protected_withdraw(
-                    self.fusd_address,
-                    fusd_amount_available,
-                    WithdrawStrategy::Rounded(RoundingMode::ToNegativeInfinity),
+            if let Some(deposit_cap) = pool_info.deposit_cap {
+                let (_, pool_fusd) = self.get_pool_amounts(collateral);
+                assert!(
+                    pool_fusd + amount <= deposit_cap,
+                    "Contribution exceeds the deposit cap of this pool."
                 );
+            }
+        }
+
+        /// Sets the collateral's debt ceiling in `Flux` from the pool's current depth, if the pool is in guarded-launch
+        /// mode. Called whenever the fUSD held by the pool changes: contributions, withdrawals, liquidations, pool buys,
+        /// conversions and fUSD rewards. Pool units can only be redeemed through this component (see `PoolUnitCustody`),
+        /// so the pool's fUSD can't change without passing here. The shared pool's fUSD doesn't count towards the ceiling.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral whose debt ceiling is updated.
+        fn update_debt_ceiling(&self, collateral: ResourceAddress) {
+            if let Some(launch) = self.stability_pools.get(&collateral).unwrap().guarded_launch {
+                let (_, pool_fusd) = self.get_pool_amounts(collateral);
+                let debt_ceiling = launch.base_debt_ceiling + launch.debt_per_pool_fusd * pool_fusd;
 
-            let (mut payout, collateral_equal_to_debt, leftover_payment) =
                 self.badge_vault.authorize_with_amount(dec!("0.75"), || {
-                    self.flux.liquidate_cdp(payment.into(), cdp_id, Some(price))
+                    self.flux.set_debt_ceiling(collateral, Some(debt_ceiling));
                 });
+            }
+        }
+
+        /// Pays the keeper bounty for an action, if one is set, its cooldown has passed and funds are available.
+        /// Never panics, so a housekeeping call succeeds whether or not a bounty is paid.
+        ///
+        /// # Arguments
+        /// * `action`: The `KeeperAction` that was performed.
+        ///
+        /// # Returns
+        /// * `Bucket`: The bounty (fUSD), empty if none is paid.
+        fn pay_keeper_bounty(&mut self, action: KeeperAction) -> Bucket {
+            let available = self.keeper_rewards.funds.amount();
+            let amount = match self.keeper_rewards.bounties.get_mut(&action) {
+                Some(bounty) => {
+                    let on_cooldown = bounty.last_paid.is_some_and(|last_paid| {
+                        Clock::current_time_is_strictly_before(
+                            last_paid.add_minutes(bounty.cooldown).unwrap(),
+                            TimePrecision::Second,
+                        )
+                    });
+                    let amount = bounty.amount.min(available);
+
+                    if on_cooldown || amount == Decimal::ZERO {
+                        return Bucket::new(self.fusd_address);
+                    }
+
+                    bounty.last_paid = Some(Clock::current_time_rounded_to_seconds());
+                    amount
+                }
+                None => return Bucket::new(self.fusd_address),
+            };
+
+            Runtime::emit_event(KeeperRewardEvent { action, amount });
+
+            self.keeper_rewards.funds.take(amount)
+        }
+
+        /// Takes the percentage and flat contribution fees from a pool contribution, adding them to the payout vault.
+        fn take_contribution_fees(&mut self, contribution: &mut Bucket) {
+            let fusd_input = contribution.amount();
+
+            if self.parameters.pool_contribution_percentage_fee > Decimal::ZERO {
+                self.payout_vault.put(contribution.take(
+                    fusd_input * self.parameters.pool_contribution_percentage_fee,
+                ));
+            }
 
+            if self.parameters.pool_contribution_flat_fee > Decimal::ZERO {
+                self.payout_vault
+                    .put(contribution.take(self.parameters.pool_contribution_flat_fee));
+            }
+        }
 
+        /// Takes the liquidator's fee from a liquidation payout, as a flat and percentage share of the profit.
+        ///
+        /// # Arguments
+        /// * `payout`: The collateral paid out by the liquidation.
+        /// * `collateral_equal_to_debt`: The amount of collateral worth the repaid debt.
+        ///
+        /// # Returns
+        /// * `Bucket`: The liquidator's fee, in collateral.
+        fn take_liquidator_fee(&self, payout: &mut Bucket, collateral_equal_to_debt: Decimal) -> Bucket {
             let mut profit = payout.amount() - collateral_equal_to_debt;
 
             let mut liquidator_fee = Bucket::new(payout.resource_address());
@@ -836,290 +2744,457 @@ mod stability_pools {
                 }
             }
 
-            self.stability_pools
-                .get_mut(&collateral)
-                .unwrap()
-                .pool
-                .protected_deposit(leftover_payment.as_fungible());
-
-            self.stability_pools
-                .get_mut(&collateral)
-                .unwrap()
-                .pool
-                .protected_deposit(payout.as_fungible());
-
             liquidator_fee
         }
 
-        /// Performs fUSD redemptions against collateral held in the Flux protocol.
-        ///
-        /// First checks if any centralized stablecoin is available from panic mode redemptions.
-        /// If so, it redeems using the stablecoin first.
-        /// Otherwise, or if the fUSD payment is not fully covered by stablecoins, it proceeds
-        /// to perform optimal batch redemptions against regular collateral pools by calling
-        /// the core `Flux` component.
+        /// Liquidates a CDP using all fUSD of the collateral's own stability pool, topped up with fUSD from the shared
+        /// stability pool. The seized collateral and any leftover fUSD are split between both pools, pro rata to the
+        /// fUSD each paid.
         ///
         /// # Arguments
-        /// * `fusd`: A `Bucket` containing the fUSD to be redeemed.
-        /// * `oracle_info`: A `Vec` containing tuples of `(ResourceAddress, String, String)` needed by the oracle
-        ///                  to verify prices for all potential collateral types involved in the optimal redemption.
-        /// * `max_redemptions`: The maximum number of individual CDP redemptions to perform across all collateral types.
+        /// * `cdp_id`: The `NonFungibleLocalId` of the CDP to liquidate.
+        /// * `collateral`: The `ResourceAddress` of the CDP's collateral.
+        /// * `price`: The verified oracle price of the collateral.
+        /// * `own_fusd`: The amount of fUSD available in the collateral's own stability pool.
+        /// * `required_fusd`: The amount of fUSD needed to liquidate the CDP.
         ///
         /// # Returns
-        /// * `(Vec<(ResourceAddress, Bucket)>, Bucket)`: A tuple containing:
-        ///     1. A `Vec` where each tuple holds the `ResourceAddress` of a redeemed collateral (or stablecoin)
-        ///        and a `Bucket` containing the corresponding payout.
-        ///     2. The `Bucket` containing any remaining fUSD from the initial `payment` bucket.
+        /// * `Bucket`: The liquidator's fee, in collateral.
         ///
         /// # Panics
-        /// * If the oracle call for batch prices fails.
-        /// * If the underlying `Flux::optimal_batch_redemption` call fails.
-        pub fn redemptions(
+        /// * If the shared pool doesn't hold more than the fUSD missing from the collateral's own pool.
+        fn liquidate_with_shared_pool(
             &mut self,
-            mut fusd: Bucket,
-            oracle_info: Vec<(ResourceAddress, String, String)>,
-            max_redemptions: u64,
-        ) -> (Vec<(ResourceAddress, Bucket)>, Bucket) {
-            // First check if there's any centralized stablecoin to redeem
-            let current_stablecoin = self.panic_mode.current_centralized_stablecoin;
-            
-            // Create a temporary variable to store the stablecoin if we need it
-            let stablecoin_redemption = {
-                if let Some(mut stablecoin_vault) = self.panic_mode.centralized_stablecoin_vaults.get_mut(&current_stablecoin) {
-                    if !stablecoin_vault.is_empty() {
-                        let stablecoin_amount = stablecoin_vault.amount().min(fusd.amount());
-                        if stablecoin_amount > Decimal::ZERO {
-                            let stablecoin = stablecoin_vault.take(stablecoin_amount);
-                            let fusd_payment = fusd.take(stablecoin_amount);
-                            
-                            // Burn the fUSD
-                            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
-                                fusd_payment.burn();
-                            });
-
-                            // Return Some if we have a stablecoin redemption
-                            Some((stablecoin, fusd.is_empty()))
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                } else {
-                    None
-                }
-            }; // End of block scope, stablecoin_vault is dropped here
+            cdp_id: NonFungibleLocalId,
+            collateral: ResourceAddress,
+            price: Decimal,
+            own_fusd: Decimal,
+            required_fusd: Decimal,
+        ) -> Bucket {
+            assert!(
+                self.get_shared_pool_fusd() > required_fusd - own_fusd,
+                "Not enough fUSD in the stability pools."
+            );
 
-            // Handle the stablecoin redemption if it occurred
-            if let Some((stablecoin, is_empty)) = stablecoin_redemption {
-                if is_empty {
-                    return (vec![(current_stablecoin, stablecoin)], fusd);
-                }
+            let mut payment = if own_fusd > Decimal::ZERO {
+                self.withdraw_liquidation_payment(collateral, own_fusd)
+            } else {
+                Bucket::new(self.fusd_address)
+            };
+            let own_fusd_paid = payment.amount();
 
-                // Otherwise continue with normal redemptions and add stablecoin to results
-                let (mut results, remaining_fusd) = self.perform_normal_redemptions(fusd, oracle_info, max_redemptions);
-                results.push((current_stablecoin, stablecoin));
-                return (results, remaining_fusd);
-            }
+            let mut pool = self.get_shared_pool().pool;
+            payment.put(
+                pool.protected_withdraw(
+                    self.fusd_address,
+                    required_fusd - own_fusd_paid,
+                    WithdrawStrategy::Rounded(RoundingMode::ToPositiveInfinity),
+                )
+                .into(),
+            );
+            let fusd_paid = payment.amount();
 
-            // If no stablecoin redemption occurred, proceed with normal redemptions
-            self.perform_normal_redemptions(fusd, oracle_info, max_redemptions)
-        }
+            let (mut payout, collateral_equal_to_debt, mut leftover_payment) =
+                self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                    self.flux.liquidate_cdp(payment, cdp_id.clone(), Some(price))
+                });
 
-        // Helper method to perform normal redemptions
-        fn perform_normal_redemptions(
-            &mut self,
-            fusd: Bucket,
-            oracle_info: Vec<(ResourceAddress, String, String)>,
-            max_redemptions: u64,
-        ) -> (Vec<(ResourceAddress, Bucket)>, Bucket) {
-            // Get prices with pre-allocated vector
-            let prices: Vec<(ResourceAddress, Decimal)> = self
-                .oracle
-                .call_raw(&self.oracle_batch_method_name, scrypto_args!(oracle_info));
+            let liquidator_fee = self.take_liquidator_fee(&mut payout, collateral_equal_to_debt);
 
-            let fusd_amount = fusd.amount();
+            // Split the proceeds pro rata to the fUSD paid by the collateral's own pool and the shared pool
+            if own_fusd_paid > Decimal::ZERO {
+                let own_share = own_fusd_paid / fusd_paid;
+                let own_leftover = leftover_payment.take_advanced(
+                    leftover_payment.amount() * own_share,
+                    WithdrawStrategy::Rounded(RoundingMode::ToZero),
+                );
+                let own_payout = payout.take_advanced(
+                    payout.amount() * own_share,
+                    WithdrawStrategy::Rounded(RoundingMode::ToZero),
+                );
+                self.deposit_liquidation_proceeds(collateral, own_leftover, own_payout);
+                self.update_debt_ceiling(collateral);
+            }
 
-            // Get collateral infos
-            let collateral_infos_vec: Vec<CollateralInfoReturn> =
-                self.flux.get_collateral_infos(self.collaterals.clone());
+            Runtime::emit_event(SharedPoolLiquidationEvent {
+                cdp_id,
+                collateral,
+                fusd_paid: fusd_paid - own_fusd_paid - leftover_payment.amount(),
+                collateral_received: payout.amount(),
+            });
 
-            // Pre-allocate vectors with known capacity
-            let mut optimal_redemption_input = Vec::with_capacity(collateral_infos_vec.len());
-            let mut total_debt_difference = Decimal::ZERO;
+            pool.protected_deposit(leftover_payment.as_fungible());
+            pool.protected_deposit(payout.as_fungible());
 
-            // Single pass through collateral infos to build input and calculate total
-            for collateral_info in collateral_infos_vec {
-                let vault_amounts = self
-                    .stability_pools
-                    .get(&collateral_info.resource_address)
-                    .unwrap()
-                    .pool
-                    .get_vault_amounts();
+            liquidator_fee
+        }
 
-                let pool_amount = vault_amounts.get(&self.fusd_address).unwrap();
+        /// Checks whether a liquidation requiring `required_fusd` can be covered by the collateral's stability pool,
+        /// topped up by the shared stability pool if the collateral opted in. The shared pool always keeps some fUSD, so
+        /// contributions to it remain possible.
+        fn can_liquidate_from_pools(&self, collateral: ResourceAddress, required_fusd: Decimal) -> bool {
+            let (_, pool_fusd) = self.get_pool_amounts(collateral);
 
-                let individual_difference = collateral_info.total_debt - *pool_amount;
+            pool_fusd >= required_fusd
+                || (self.stability_pools.get(&collateral).unwrap().use_shared_pool
+                    && self.get_shared_pool_fusd() > required_fusd - pool_fusd)
+        }
 
-                if individual_difference > Decimal::ZERO {
-                    // Find price in single pass without additional allocations
-                    let price = prices
-                        .iter()
-                        .find(|&&(addr, _)| addr == collateral_info.resource_address)
-                        .map(|&(_, price)| price)
-                        .unwrap_or_else(|| panic!("No price info found."));
+        /// Retrieves the shared stability pool.
+        ///
+        /// # Panics
+        /// * If there is no shared pool.
+        fn get_shared_pool(&self) -> &SharedStabilityPool {
+            self.shared_pool.as_ref().expect("No shared pool.")
+        }
 
-                    total_debt_difference += individual_difference;
+        /// Retrieves the amount of fUSD held by the shared stability pool, zero if there is none.
+        fn get_shared_pool_fusd(&self) -> Decimal {
+            self.shared_pool
+                .as_ref()
+                .map(|shared_pool| {
+                    *shared_pool
+                        .pool
+                        .get_vault_amounts()
+                        .get(&self.fusd_address)
+                        .unwrap_or(&Decimal::ZERO)
+                })
+                .unwrap_or(Decimal::ZERO)
+        }
+
+        /// Retrieves the collateral conversion configuration of a stability pool.
+        ///
+        /// # Panics
+        /// * If collateral conversion is not enabled for the pool.
+        fn get_collateral_conversion(&self, collateral: ResourceAddress) -> &CollateralConversion {
+            self.stability_pools
+                .get(&collateral)
+                .unwrap()
+                .conversion
+                .as_ref()
+                .expect("Collateral conversion not enabled.")
+        }
 
-                    // Store info for later processing
-                    optimal_redemption_input.push((
-                        collateral_info.resource_address,
-                        individual_difference,
-                        Some(price),
-                    ));
+        /// Calculates the current price per unit of collateral in a running conversion auction.
+        ///
+        /// # Arguments
+        /// * `conversion`: The pool's `CollateralConversion`, with a running auction.
+        /// * `collateral_price`: The current oracle price of the collateral.
+        ///
+        /// # Returns
+        /// * `Decimal`: The auction price, never below the oracle price minus the maximum slippage.
+        fn get_conversion_auction_price(conversion: &CollateralConversion, collateral_price: Decimal) -> Decimal {
+            match conversion.method {
+                ConversionMethod::Auction { start_premium, decay_per_minute, .. } => {
+                    let elapsed_seconds = Clock::current_time_rounded_to_seconds().seconds_since_unix_epoch
+                        - conversion.auction_start.unwrap().seconds_since_unix_epoch;
+                    let price_modifier = (Decimal::ONE + start_premium
+                        - decay_per_minute * Decimal::from(elapsed_seconds) / dec!(60))
+                        .max(Decimal::ONE - conversion.max_slippage);
+
+                    collateral_price * price_modifier
                 }
+                ConversionMethod::DexAdapter { .. } => panic!("Not an auction conversion."),
             }
+        }
 
-            assert!(
-                total_debt_difference > Decimal::ZERO,
-                "All fUSD is used within Stability Pools"
-            );
+        /// Checks whether a conversion auction is running, i.e. it has been started and hasn't expired yet.
+        ///
+        /// # Arguments
+        /// * `conversion`: The pool's `CollateralConversion`.
+        ///
+        /// # Returns
+        /// * `bool`: `true` if an auction is running.
+        fn is_conversion_auction_running(conversion: &CollateralConversion) -> bool {
+            match (&conversion.method, conversion.auction_start) {
+                (ConversionMethod::Auction { duration, .. }, Some(auction_start)) => {
+                    Clock::current_time_is_strictly_before(
+                        auction_start.add_minutes(*duration).unwrap(),
+                        TimePrecision::Second,
+                    )
+                }
+                _ => false,
+            }
+        }
 
-            // Adjust amounts in place
-            for (_, amount, _) in &mut optimal_redemption_input {
-                *amount = *amount / total_debt_difference * fusd_amount;
+        /// Retrieves the `TwoResourcePool` of a stability pool, for operations only supported by that pool type.
+        ///
+        /// # Panics
+        /// * If the pool is a product-sum pool.
+        fn get_two_resource_pool(&self, collateral: ResourceAddress) -> Global<TwoResourcePool> {
+            match self.stability_pools.get(&collateral).unwrap().pool {
+                StabilityPoolComponent::TwoResource(pool) => pool,
+                StabilityPoolComponent::ProductSum(_) => panic!("Not supported by product-sum pools."),
             }
+        }
 
-            // Perform batch redemption
-            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
-                self.flux.optimal_batch_redemption(
-                    fusd,
-                    optimal_redemption_input,
-                    None,
-                    max_redemptions,
-                )
-            })
+        /// Deposits rewards (collateral or fUSD) into a stability pool, distributing them among its contributors.
+        /// A `TwoResourcePool` simply adds them to the pool, a `ProductSumPool` distributes fUSD as yield and
+        /// collateral as collateral gains. fUSD added to a `TwoResourcePool` deepens the pool, so its debt ceiling is updated.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` identifying the pool.
+        /// * `rewards`: A `Bucket` containing the collateral or fUSD rewards.
+        fn deposit_rewards(&mut self, collateral: ResourceAddress, rewards: Bucket) {
+            match self.stability_pools.get(&collateral).unwrap().pool {
+                StabilityPoolComponent::TwoResource(mut pool) => {
+                    let is_fusd = rewards.resource_address() == self.fusd_address;
+                    pool.protected_deposit(rewards.as_fungible());
+                    if is_fusd {
+                        self.update_debt_ceiling(collateral);
+                    }
+                }
+                StabilityPoolComponent::ProductSum(pool) => {
+                    if rewards.resource_address() == self.fusd_address {
+                        pool.deposit_yield(rewards);
+                    } else {
+                        pool.deposit_collateral(rewards);
+                    }
+                }
+            }
         }
-        
-        //getters
-        
-        /// Retrieves detailed information about specified stability pools, or all pools if none are specified.
+
+        /// Redeems pool units (or deposit receipts) from a stability pool and emits a withdrawal event.
         ///
         /// # Arguments
-        /// * `resource_addresses`: An optional `Vec<ResourceAddress>` specifying which collateral pools to get info for.
-        ///                       If `None`, returns info for all existing pools.
+        /// * `collateral`: The `ResourceAddress` identifying the pool.
+        /// * `tokens`: A `Bucket` containing the pool unit tokens (or deposit receipts) to be redeemed.
         ///
         /// # Returns
-        /// * `Vec<StabilityPoolInfoReturn>`: A vector containing the information for each requested pool.
-        pub fn get_stability_pool_infos(&mut self, resource_addresses: Option<Vec<ResourceAddress>>) -> Vec<StabilityPoolInfoReturn> {
-            let mut info_return_vec: Vec<StabilityPoolInfoReturn> = vec![];
-            
-            let addresses_to_check = resource_addresses.as_ref().map(|v| v.as_slice()).unwrap_or_default();
-            let should_check_addresses = resource_addresses.is_some();
+        /// * `(Bucket, Bucket)`: A tuple containing the withdrawn collateral and fUSD buckets.
+        fn redeem_from_pool(&mut self, collateral: ResourceAddress, tokens: Bucket) -> (Bucket, Bucket) {
+            let input_amount = tokens.amount();
 
-            for (collateral, stability_pool) in &self.stability_pools {
-                if !should_check_addresses || addresses_to_check.contains(collateral) {
-                    let amounts_binding = self.stability_pools.get(collateral).unwrap().pool.get_vault_amounts();
-                    let collateral_amount = amounts_binding.get(collateral).unwrap_or(&Decimal::ZERO);
-                    let fusd_amount = amounts_binding.get(&self.fusd_address).unwrap_or(&Decimal::ZERO);
+            let (bucket1, bucket2): (Bucket, Bucket) = match self.stability_pools.get(&collateral).unwrap().pool {
+                StabilityPoolComponent::TwoResource(mut pool) => {
+                    let (bucket1, bucket2) = pool.redeem(tokens.as_fungible());
+                    (bucket1.into(), bucket2.into())
+                }
+                StabilityPoolComponent::ProductSum(pool) => pool.withdraw(tokens),
+            };
+            self.update_debt_ceiling(collateral);
 
-                    let info_return = StabilityPoolInfoReturn {
-                        collateral: *collateral,
-                        payout_split: stability_pool.payout_split,
-                        liquidity_rewards_split: stability_pool.liquidity_rewards_split,
-                        stability_pool_split: stability_pool.stability_pool_split,
-                        allow_pool_buys: stability_pool.allow_pool_buys,
-                        pool_buy_price_modifier: stability_pool.pool_buy_price_modifier,
-                        liquidity_rewards: stability_pool.liquidity_rewards.amount(),
-                        pool: stability_pool.pool,
-                        collateral_amount: *collateral_amount,
-                        fusd_amount: *fusd_amount,
-                        latest_lowest_interests: stability_pool.latest_lowest_interests.clone(),
-                        last_lowest_interests_update: stability_pool.last_lowest_interests_update,
-                    };
+            let fusd_amount = bucket2.amount();
+            let collateral_amount = bucket1.amount();
 
-                    info_return_vec.push(info_return);
-                }
-            }
+            // Emit withdrawal event
+            Runtime::emit_event(StabilityPoolWithdrawalEvent {
+                collateral,
+                pool_tokens_burned: input_amount,
+                fusd_received: fusd_amount,
+                collateral_received: collateral_amount,
+            });
 
-            info_return_vec
+            (bucket1, bucket2)
         }
 
-        //helpers
+        /// Hands out pool units of a stability pool. The pool units of a `TwoResourcePool` are kept in custody, and
+        /// custody receipts are returned instead. Deposit receipts of product-sum pools are returned as is.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` identifying the pool.
+        /// * `pool_units`: A `Bucket` containing the pool units (or deposit receipts).
+        ///
+        /// # Returns
+        /// * `Bucket`: The custody receipts (or deposit receipts).
+        fn hand_out_pool_units(&mut self, collateral: ResourceAddress, pool_units: Bucket) -> Bucket {
+            match self.stability_pools.get_mut(&collateral).unwrap().pool_unit_custody.as_mut() {
+                Some(custody) => Self::wrap_pool_units(custody, pool_units),
+                None => pool_units,
+            }
+        }
 
-        /// Creates a transaction hash entry if `allow_multiple_actions` is false
-        /// Used to prevent contributing to a pool and immediately charging interest
+        /// Takes pool units of a stability pool from a user, burning the custody receipts and taking the pool units
+        /// they represent out of custody. Deposit receipts of product-sum pools are taken as is.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` identifying the pool.
+        /// * `tokens`: A `Bucket` containing the custody receipts (or deposit receipts).
+        ///
+        /// # Returns
+        /// * `Bucket`: The pool units (or deposit receipts).
         ///
         /// # Panics
-        /// * If `allow_multiple_actions` is `false` and an entry for the current transaction hash already exists.
-        fn create_hash(&mut self) {
-            let transaction_hash = Runtime::transaction_hash();
-            self.transactions.insert(transaction_hash, ());
+        /// * If no pool exists for the given `collateral`.
+        /// * If the bucket doesn't contain the pool's custody receipts (or deposit receipts).
+        fn take_pool_units(&mut self, collateral: ResourceAddress, tokens: Bucket) -> Bucket {
+            let pool_info = self
+                .stability_pools
+                .get_mut(&collateral)
+                .expect("No stability pool for this collateral.");
+
+            match pool_info.pool_unit_custody.as_mut() {
+                Some(custody) => Self::unwrap_pool_units(custody, tokens),
+                None => {
+                    assert!(
+                        tokens.resource_address() == pool_info.pending_withdrawals.resource_address(),
+                        "Invalid pool units."
+                    );
+                    tokens
+                }
+            }
         }
 
-        fn check_hash(&mut self) {
-            let transaction_hash = Runtime::transaction_hash();
-            
-            if !self.allow_multiple_actions {
-                assert!(
-                    self.transactions.get(&transaction_hash).is_none(),
-                    "Trying to execute two actions that aren't allowed in one transaction!"
-                );
-            }
+        /// Puts pool units in custody and mints the same amount of custody receipts.
+        fn wrap_pool_units(custody: &mut PoolUnitCustody, pool_units: Bucket) -> Bucket {
+            let receipts = custody.receipt_manager.mint(pool_units.amount());
+            custody.pool_units.put(pool_units);
+            receipts
         }
 
-        /// Internal helper function to handle buying collateral from a pool's underlying `TwoResourcePool` component.
-        /// Calculates the amount to buy based on the provided fUSD, price, and buy modifier.
-        /// Withdraws collateral from the pool and deposits the fUSD payment.
+        /// Burns custody receipts and takes the same amount of pool units out of custody.
         ///
-        /// # Arguments
-        /// * `collateral`: The `ResourceAddress` of the collateral being bought (and identifying the pool).
-        /// * `fusd`: A `Bucket` containing the fUSD payment.
-        /// * `collateral_price`: The current `Decimal` price of the collateral.
+        /// # Panics
+        /// * If the bucket doesn't contain the custody's receipts.
+        fn unwrap_pool_units(custody: &mut PoolUnitCustody, receipts: Bucket) -> Bucket {
+            assert!(
+                receipts.resource_address() == custody.receipt_manager.address(),
+                "Invalid pool units."
+            );
+            let pool_units = custody.pool_units.take(receipts.amount());
+            receipts.burn();
+            pool_units
+        }
+
+        /// Redeems shared pool units at the shared stability pool.
         ///
         /// # Returns
-        /// * `(Bucket, Bucket)`: A tuple containing the bought collateral and any remaining fUSD.
-        fn buy_collateral_from_pool_internal(
-            &mut self,
-            collateral: ResourceAddress,
-            mut fusd: Bucket,
-            collateral_price: Decimal,
-        ) -> (Bucket, Bucket) {
-            let buy_modifier = self
-                .stability_pools
-                .get(&collateral)
-                .unwrap()
-                .pool_buy_price_modifier
-                .unwrap_or(self.parameters.default_pool_buy_price_modifier);
+        /// * `Vec<Bucket>`: The pro-rata share of fUSD and each collateral held by the shared pool.
+        fn redeem_from_shared_pool(&mut self, pool_units: Bucket) -> Vec<Bucket> {
+            let mut pool = self.get_shared_pool().pool;
+            let pool_tokens_burned = pool_units.amount();
+            let withdrawn: Vec<Bucket> = pool
+                .redeem(pool_units.as_fungible())
+                .into_iter()
+                .map(|bucket| bucket.into())
+                .collect();
+
+            Runtime::emit_event(SharedPoolWithdrawalEvent {
+                pool_tokens_burned,
+                fusd_received: withdrawn
+                    .iter()
+                    .filter(|bucket| bucket.resource_address() == self.fusd_address)
+                    .fold(Decimal::ZERO, |amount, bucket| amount + bucket.amount()),
+            });
 
-            let can_buy = fusd.amount() / (collateral_price * buy_modifier);
-            let amount_binding = self.stability_pools.get(&collateral).unwrap().pool.get_vault_amounts();
-            let max_available = amount_binding.get(&collateral).unwrap_or(&Decimal::ZERO);
-            let can_buy_fraction = *max_available / can_buy;
+            withdrawn
+        }
 
-            let payment_bucket = if can_buy_fraction >= Decimal::ONE {
-                fusd.take(fusd.amount())
+        /// Checks whether a withdrawal request can be claimed.
+        ///
+        /// # Panics
+        /// * If the withdrawal delay hasn't passed yet, or the claim window has passed.
+        fn check_withdrawal_claimable(&self, withdrawal: &WithdrawalRequest) {
+            assert!(
+                !Clock::current_time_is_strictly_before(withdrawal.claimable_at, TimePrecision::Second),
+                "Withdrawal delay has not yet ended."
+            );
+            assert!(
+                !Clock::current_time_is_strictly_after(
+                    withdrawal
+                        .claimable_at
+                        .add_minutes(self.parameters.withdrawal_claim_window)
+                        .unwrap(),
+                    TimePrecision::Second
+                ),
+                "Withdrawal request expired, cancel it and request again."
+            );
+        }
+
+        /// Burns a withdrawal request and takes its escrowed pool units (or deposit receipts).
+        ///
+        /// # Panics
+        /// * If the bucket doesn't contain a single withdrawal request.
+        fn take_withdrawal_request(&mut self, request: Bucket) -> (WithdrawalRequest, Bucket) {
+            assert!(
+                request.resource_address() == self.withdrawal_request_manager.address(),
+                "Invalid withdrawal request."
+            );
+            let request_id = request.as_non_fungible().non_fungible_local_id();
+            let withdrawal: WithdrawalRequest = self.withdrawal_request_manager.get_non_fungible_data(&request_id);
+            request.burn();
+
+            let pending_withdrawals = match withdrawal.collateral {
+                Some(collateral) => &mut self.stability_pools.get_mut(&collateral).unwrap().pending_withdrawals,
+                None => &mut self.shared_pool.as_mut().unwrap().pending_withdrawals,
+            };
+            let tokens: Bucket = if withdrawal.deposit_ids.is_empty() {
+                pending_withdrawals.take(withdrawal.pool_units)
             } else {
-                fusd.take(fusd.amount() * can_buy_fraction)
+                pending_withdrawals
+                    .as_non_fungible()
+                    .take_non_fungibles(&withdrawal.deposit_ids.iter().cloned().collect())
+                    .into()
             };
 
-            let buy = self
-                .stability_pools
-                .get_mut(&collateral)
-                .unwrap()
-                .pool
-                .protected_withdraw(
-                    collateral,
-                    can_buy.min(*max_available),
-                    WithdrawStrategy::Rounded(RoundingMode::ToNegativeInfinity),
-                );
+            (withdrawal, tokens)
+        }
+
+        /// Retrieves the lock state of a stability pool.
+        ///
+        /// # Panics
+        /// * If no pool exists for the given `collateral`, or it is a product-sum pool.
+        fn get_pool_locks(&mut self, collateral: ResourceAddress) -> &mut PoolLocks {
             self.stability_pools
                 .get_mut(&collateral)
-                .unwrap()
-                .pool
-                .protected_deposit(payment_bucket.as_fungible());
+                .expect("No stability pool for this collateral.")
+                .locks
+                .as_mut()
+                .expect("Not supported by product-sum pools.")
+        }
 
-            (buy.into(), fusd)
+        /// Takes the outstanding rewards of a lock from the pool's lock rewards vault.
+        fn take_lock_rewards(locks: &mut PoolLocks, lock: &StabilityPoolLock) -> Bucket {
+            let rewards = (PreciseDecimal::from(lock.weight)
+                * (locks.reward_per_weight - lock.reward_per_weight_snapshot))
+                .checked_truncate(RoundingMode::ToZero)
+                .unwrap();
+
+            locks.rewards.take(rewards.min(locks.rewards.amount()))
+        }
+
+        /// Calculates which fractions of the liquidity rewards and of the stability pool split are paid to lockers.
+        ///
+        /// Unlocked pool units have weight 1, locked units have their boosted weight. Lockers receive the
+        /// liquidity rewards in proportion to their weight. They also receive enough of the stability pool split
+        /// as lock rewards that, together with the part of the remainder they earn through their pool units,
+        /// their share of the stability pool split is also in proportion to their weight.
+        ///
+        /// # Returns
+        /// * `(Decimal, Decimal)`: The lockers' fractions of the liquidity rewards and of the stability pool split.
+        fn get_lock_reward_shares(&self, collateral: ResourceAddress) -> (Decimal, Decimal) {
+            let locks = match &self.stability_pools.get(&collateral).unwrap().locks {
+                Some(locks) if locks.total_weight > Decimal::ZERO => locks,
+                _ => return (Decimal::ZERO, Decimal::ZERO),
+            };
+
+            let locked_units = locks.locked_units.amount();
+            let total_units = ResourceManager::from(locks.locked_units.resource_address())
+                .total_supply()
+                .unwrap_or(Decimal::ZERO);
+            let total_weight = total_units - locked_units + locks.total_weight;
+            let weight_share = locks.total_weight / total_weight;
+
+            if locked_units >= total_units {
+                return (weight_share, Decimal::ZERO);
+            }
+
+            let unit_share = locked_units / total_units;
+            let stability_pool_share = ((weight_share - unit_share) / (Decimal::ONE - unit_share)).max(Decimal::ZERO);
+
+            (weight_share, stability_pool_share)
+        }
+
+        /// Adds fUSD rewards to the lock rewards of a pool, distributing them by lock weight.
+        fn distribute_lock_rewards(&mut self, collateral: ResourceAddress, rewards: Bucket) {
+            if rewards.is_empty() {
+                rewards.drop_empty();
+                return;
+            }
+
+            let locks = self.get_pool_locks(collateral);
+            locks.reward_per_weight += PreciseDecimal::from(rewards.amount()) / PreciseDecimal::from(locks.total_weight);
+            locks.rewards.put(rewards);
         }
 
         /// Retrieves the highest interest rate recorded in the `latest_lowest_interests` history for a given collateral pool.
@@ -1141,40 +3216,178 @@ mod stability_pools {
                 .unwrap_or(&Decimal::ZERO)
         }
 
-        /// Sets the currently active centralized stablecoin used for panic mode liquidations and redemptions.
-        /// Creates a vault for the stablecoin if it doesn't exist yet.
+        /// Sets the centralized stablecoins accepted for panic mode liquidations, and the order in which
+        /// redemptions drain their vaults. Creates a vault for every stablecoin that doesn't have one yet.
+        ///
+        /// Stablecoins that are removed from the list are no longer accepted, but their reserves can still be
+        /// redeemed as long as they are part of the redemption order.
         ///
         /// Requires OWNER authorization (controller badge).
         ///
         /// # Arguments
-        /// * `stablecoin`: The `ResourceAddress` of the new centralized stablecoin.
-        pub fn set_centralized_stablecoin(&mut self, stablecoin: ResourceAddress) {
-            // Create new vault for the stablecoin if it doesn't exist
-            if !self.panic_mode.centralized_stablecoin_vaults.get(&stablecoin).is_some() {
-                self.panic_mode.centralized_stablecoin_vaults.insert(
-                    stablecoin,
-                    Vault::new(stablecoin)
+        /// * `stablecoins`: The accepted stablecoins with their depeg tolerance.
+        /// * `redemption_order`: The order in which redemptions drain the stablecoin vaults.
+        ///
+        /// # Panics
+        /// * If `stablecoins` is empty, contains fUSD or contains a stablecoin twice.
+        /// * If a depeg tolerance is not between 0 (inclusive) and 1 (exclusive).
+        /// * If `redemption_order` contains a stablecoin twice, contains a resource that never was a panic mode
+        ///   stablecoin, or misses one of the accepted `stablecoins`.
+        pub fn set_panic_mode_stablecoins(
+            &mut self,
+            stablecoins: Vec<PanicModeStablecoin>,
+            redemption_order: Vec<ResourceAddress>,
+        ) {
+            assert!(!stablecoins.is_empty(), "At least one panic mode stablecoin is required.");
+
+            let mut seen: HashSet<ResourceAddress> = HashSet::new();
+            for stablecoin in &stablecoins {
+                assert!(stablecoin.address != self.fusd_address, "fUSD can't be a panic mode stablecoin.");
+                assert!(seen.insert(stablecoin.address), "Duplicate panic mode stablecoin.");
+                assert!(
+                    stablecoin.depeg_tolerance >= Decimal::ZERO && stablecoin.depeg_tolerance < Decimal::ONE,
+                    "Depeg tolerance must be between 0 and 1."
                 );
+
+                // Create new vault for the stablecoin if it doesn't exist
+                if self.panic_mode.centralized_stablecoin_vaults.get(&stablecoin.address).is_none() {
+                    self.panic_mode.centralized_stablecoin_vaults.insert(
+                        stablecoin.address,
+                        Vault::new(stablecoin.address)
+                    );
+                }
             }
-            
-            self.panic_mode.current_centralized_stablecoin = stablecoin;
+
+            let mut seen_in_order: HashSet<ResourceAddress> = HashSet::new();
+            for stablecoin_address in &redemption_order {
+                assert!(seen_in_order.insert(*stablecoin_address), "Duplicate stablecoin in redemption order.");
+                assert!(
+                    self.panic_mode.centralized_stablecoin_vaults.get(stablecoin_address).is_some(),
+                    "Redemption order contains an unknown stablecoin."
+                );
+            }
+            assert!(
+                seen.iter().all(|address| seen_in_order.contains(address)),
+                "Redemption order must contain every panic mode stablecoin."
+            );
+
+            for stablecoin in &stablecoins {
+                self.panic_mode.depeg_tolerances.insert(stablecoin.address, stablecoin.depeg_tolerance);
+            }
+            self.panic_mode.stablecoins = stablecoins;
+            self.panic_mode.redemption_order = redemption_order;
+        }
+
+        /// Enables, edits or disables the unwinding of panic mode stablecoin reserves back into fUSD.
+        /// Editing the unwind configuration starts a new rate limit period.
+        ///
+        /// Requires OWNER authorization (controller badge).
+        ///
+        /// # Arguments
+        /// * `method`: The `StablecoinUnwindMethod` to use, `None` to disable unwinding.
+        /// * `max_slippage`: The maximum fraction below the oracle value reserves can be sold for through a DEX adapter.
+        /// * `max_fusd_per_period`: The maximum amount of fUSD bought back and burned per period.
+        /// * `period`: The length of a rate limit period, in minutes.
+        /// * `min_pool_coverage`: The minimum fUSD in the stability pool of every collateral that has been (pending)
+        ///   panic mode, as a fraction of the collateral's total debt, before reserves can be unwound.
+        ///
+        /// # Panics
+        /// * If `max_slippage` or the buy-back window's `discount` are not between 0 and 1.
+        /// * If `max_fusd_per_period` or `period` are not positive.
+        /// * If `min_pool_coverage` is negative.
+        pub fn set_stablecoin_unwind(
+            &mut self,
+            method: Option<StablecoinUnwindMethod>,
+            max_slippage: Decimal,
+            max_fusd_per_period: Decimal,
+            period: i64,
+            min_pool_coverage: Decimal,
+        ) {
+            assert!(
+                max_slippage >= Decimal::ZERO && max_slippage <= Decimal::ONE,
+                "Max slippage must be between 0 and 1"
+            );
+            if let Some(StablecoinUnwindMethod::BuyBackWindow { discount }) = &method {
+                assert!(
+                    *discount >= Decimal::ZERO && *discount < Decimal::ONE,
+                    "Discount must be between 0 and 1"
+                );
+            }
+            assert!(max_fusd_per_period > Decimal::ZERO, "Max fUSD per period must be positive");
+            assert!(period > 0, "Period must be positive");
+            assert!(min_pool_coverage >= Decimal::ZERO, "Min pool coverage can't be negative");
+
+            self.panic_mode.unwind = method.map(|method| StablecoinUnwind {
+                method,
+                max_slippage,
+                max_fusd_per_period,
+                period,
+                min_pool_coverage,
+                period_start: None,
+                unwound_in_period: Decimal::ZERO,
+            });
+        }
+
+        /// Retrieves the panic mode stablecoins and the protocol's reserves of each.
+        /// Accepted stablecoins come first, in the configured order, followed by stablecoins that are no longer
+        /// accepted but still part of the redemption order.
+        ///
+        /// # Returns
+        /// * `Vec<PanicModeStablecoinInfoReturn>`: The address, depeg tolerance and reserves of each stablecoin.
+        pub fn get_panic_mode_stablecoins(&self) -> Vec<PanicModeStablecoinInfoReturn> {
+            let reserves = |address: &ResourceAddress| {
+                self.panic_mode
+                    .centralized_stablecoin_vaults
+                    .get(address)
+                    .map(|vault| vault.amount())
+                    .unwrap_or(Decimal::ZERO)
+            };
+
+            let mut infos: Vec<PanicModeStablecoinInfoReturn> = self
+                .panic_mode
+                .stablecoins
+                .iter()
+                .map(|stablecoin| PanicModeStablecoinInfoReturn {
+                    address: stablecoin.address,
+                    depeg_tolerance: Some(stablecoin.depeg_tolerance),
+                    reserves: reserves(&stablecoin.address),
+                })
+                .collect();
+
+            for address in &self.panic_mode.redemption_order {
+                if !self.panic_mode.stablecoins.iter().any(|stablecoin| stablecoin.address == *address) {
+                    infos.push(PanicModeStablecoinInfoReturn {
+                        address: *address,
+                        depeg_tolerance: None,
+                        reserves: reserves(address),
+                    });
+                }
+            }
+
+            infos
         }
 
         /// Checks if a CDP is liquidatable and if the stability pool has insufficient fUSD to cover the debt.
         /// If both conditions are met and the CDP is not already pending panic mode, it marks the CDP
         /// as pending with a wait period.
-        /// If the CDP is already pending and the wait period has passed (but not exceeded by double), it activates panic mode.
+        /// If the CDP is already pending and the wait period has passed (but not exceeded by double), it activates
+        /// panic mode for the CDP's collateral only.
+        /// If the CDP was marked as pending or panic mode was activated, the caller is paid the `PanicModeCheck` keeper
+        /// bounty, if configured and not on cooldown. Restarting the wait period of a CDP checked too late pays nothing.
         ///
         /// # Arguments
         /// * `cdp_id`: The `NonFungibleLocalId` of the CDP to check.
         /// * `message`: Oracle message for price verification.
         /// * `signature`: Oracle signature for price verification.
         ///
+        /// # Returns
+        /// * `Bucket`: The keeper bounty (fUSD), empty if none is paid.
+        ///
         /// # Panics
         /// * If the oracle call fails.
         /// * If the `Flux::check_liquidate_cdp` call fails.
         /// * If the CDP is liquidatable but the stability pool *does* have enough fUSD.
-        pub fn check_and_initiate_panic_mode(&mut self, cdp_id: NonFungibleLocalId, message: String, signature: String) {
+        pub fn check_and_initiate_panic_mode(&mut self, cdp_id: NonFungibleLocalId, message: String, signature: String) -> Bucket {
             let cdp_data: Cdp = self.cdp_resource_manager.get_non_fungible_data(&cdp_id);
             let collateral = cdp_data.collateral_address;
 
@@ -1189,20 +3402,25 @@ mod stability_pools {
 
             assert!(liquidatable, "CDP not liquidatable");
 
-            // Check if stability pool has enough fUSD
-            let pool_fusd = *self.stability_pools
-                .get(&collateral_address)
-                .unwrap()
-                .pool
-                .get_vault_amounts()
-                .get(&self.fusd_address)
-                .unwrap();
+            // Check if the stability pools have enough fUSD
+            assert!(
+                !self.can_liquidate_from_pools(collateral_address, required_fusd),
+                "No need for panic mode, enough fUSD available"
+            );
 
-            assert!(pool_fusd < required_fusd, "No need for panic mode, enough fUSD available");
+            let panic_mode = self
+                .panic_mode
+                .collaterals
+                .entry(collateral_address)
+                .or_insert_with(|| CollateralPanicMode {
+                    is_active: false,
+                    last_liquidation_time: None,
+                    pending_cdps: <scrypto::component::KeyValueStore<_, _> as stability_pools::stability_pools::StabilityPoolsKeyValueStore>::new_with_registered_type(),
+                });
 
             // If CDP not already pending, add it with 1 day wait period
-            if !self.panic_mode.pending_cdps.get(&cdp_id).is_some() {
-                self.panic_mode.pending_cdps.insert(
+            if panic_mode.pending_cdps.get(&cdp_id).is_none() {
+                panic_mode.pending_cdps.insert(
                     cdp_id.clone(),
                     Clock::current_time_rounded_to_seconds()
                         .add_minutes(self.parameters.panic_mode_wait_period)
@@ -1210,73 +3428,78 @@ mod stability_pools {
                 );
                 Runtime::emit_event(PanicModeChangeEvent {
                     cdp_id,
+                    collateral: collateral_address,
                     activation_time: Clock::current_time_rounded_to_seconds(),
                     change: PanicModeEvent::Initiation,
                 });
-                return;
+                return self.pay_keeper_bounty(KeeperAction::PanicModeCheck);
             }
 
             let current_time = Clock::current_time_rounded_to_seconds();
-            let mut too_late = false;
+            let wait_until = *panic_mode.pending_cdps.get(&cdp_id).unwrap();
 
-            // If CDP is pending, check if it's within the valid window
-            if let Some(wait_until) = self.panic_mode.pending_cdps.get(&cdp_id) {
-                let final_deadline = wait_until.add_minutes(self.parameters.panic_mode_wait_period).unwrap();
-                
-                if Clock::current_time_is_strictly_after(*wait_until, TimePrecision::Second) {
-                    if Clock::current_time_is_strictly_before(final_deadline, TimePrecision::Second) {
-                        // Activate panic mode
-                        self.panic_mode.is_active = true;
-                        self.panic_mode.last_liquidation_time = Some(current_time);
-                        Runtime::emit_event(PanicModeChangeEvent {
-                            cdp_id,
-                            activation_time: Clock::current_time_rounded_to_seconds(),
-                            change: PanicModeEvent::Activation,
-                        });
-                        return;
-                    } else {
-                        too_late = true;
-                    }
-                } else {
-                    panic!("Wait period has not yet ended");
-                }
+            // The CDP is pending, check if it's within the valid window
+            let final_deadline = wait_until.add_minutes(self.parameters.panic_mode_wait_period).unwrap();
+
+            if !Clock::current_time_is_strictly_after(wait_until, TimePrecision::Second) {
+                panic!("Wait period has not yet ended");
             }
 
-            if too_late {
-                self.panic_mode.pending_cdps.remove(&cdp_id);
-                self.panic_mode.pending_cdps.insert(
+            if Clock::current_time_is_strictly_before(final_deadline, TimePrecision::Second) {
+                // Activate panic mode for the CDP's collateral
+                panic_mode.is_active = true;
+                panic_mode.last_liquidation_time = Some(current_time);
+                Runtime::emit_event(PanicModeChangeEvent {
+                    cdp_id,
+                    collateral: collateral_address,
+                    activation_time: Clock::current_time_rounded_to_seconds(),
+                    change: PanicModeEvent::Activation,
+                });
+            } else {
+                panic_mode.pending_cdps.remove(&cdp_id);
+                panic_mode.pending_cdps.insert(
                     cdp_id.clone(),
                     current_time.add_minutes(self.parameters.panic_mode_wait_period).unwrap()
                 );
                 Runtime::emit_event(PanicModeChangeEvent {
                     cdp_id,
+                    collateral: collateral_address,
                     activation_time: Clock::current_time_rounded_to_seconds(),
                     change: PanicModeEvent::TooLateActivation,
                 });
+
+                return Bucket::new(self.fusd_address);
             }
+
+            self.pay_keeper_bounty(KeeperAction::PanicModeCheck)
         }
 
         /// Performs a liquidation using a centralized stablecoin payment when panic mode is active.
         ///
-        /// Verifies panic mode status, stablecoin payment, and CDP liquidatability.
-        /// Mints fUSD 1:1 for the stablecoin payment, stores the stablecoin, and calls the core
-        /// `Flux::liquidate_cdp` method using the minted fUSD.
+        /// Verifies the panic mode status of the CDP's collateral, stablecoin payment, and CDP liquidatability.
+        /// The payment can be made in any accepted panic mode stablecoin whose oracle price is within its depeg
+        /// tolerance of 1 USD. fUSD is minted at the oracle value of the payment, the stablecoin is stored, and the
+        /// core `Flux::liquidate_cdp` method is called using the minted fUSD.
         /// Updates the last panic mode liquidation time.
         ///
         /// # Arguments
         /// * `cdp_id`: The `NonFungibleLocalId` of the CDP to liquidate.
-        /// * `stablecoin_payment`: A `Bucket` containing the payment in the currently active centralized stablecoin.
-        /// * `message`: Oracle message for price verification.
-        /// * `signature`: Oracle signature for price verification.
+        /// * `stablecoin_payment`: A `Bucket` containing the payment in one of the accepted panic mode stablecoins.
+        /// * `message`: Oracle message for price verification of the collateral.
+        /// * `signature`: Oracle signature for price verification of the collateral.
+        /// * `stablecoin_message`: Oracle message for price verification of the stablecoin.
+        /// * `stablecoin_signature`: Oracle signature for price verification of the stablecoin.
         ///
         /// # Returns
-        /// * `(Bucket, Bucket)`: A tuple containing the liquidation payout (collateral) and any leftover fUSD (from the 1:1 mint).
+        /// * `(Bucket, Bucket)`: A tuple containing the liquidation payout (collateral) and the part of the
+        ///   stablecoin payment that wasn't needed to cover the CDP's debt.
         ///
         /// # Panics
-        /// * If panic mode is not active.
-        /// * If the `stablecoin_payment` resource address doesn't match the active stablecoin.
-        /// * If the `stablecoin_payment` amount is less than the CDP's required fUSD debt.
-        /// * If the oracle call fails.
+        /// * If panic mode is not active for the CDP's collateral.
+        /// * If the `stablecoin_payment` resource address isn't an accepted panic mode stablecoin.
+        /// * If the stablecoin's oracle price deviates more than its depeg tolerance from 1 USD.
+        /// * If the oracle value of the `stablecoin_payment` is less than the CDP's required fUSD debt.
+        /// * If the oracle calls fail.
         /// * If the `Flux::check_liquidate_cdp` or `Flux::liquidate_cdp` calls fail.
         /// * If the stability pool actually *does* have enough fUSD (normal liquidation should be used).
         pub fn panic_mode_liquidate(
@@ -1285,6 +3508,8 @@ mod stability_pools {
             stablecoin_payment: Bucket,
             message: String,
             signature: String,
+            stablecoin_message: String,
+            stablecoin_signature: String,
         ) -> (Bucket, Bucket) {
             let cdp_data: Cdp = self.cdp_resource_manager.get_non_fungible_data(&cdp_id);
             let collateral = cdp_data.collateral_address;
@@ -1294,12 +3519,26 @@ mod stability_pools {
                 scrypto_args!(collateral, message, signature),
             );
 
-            assert!(self.check_panic_mode_status(), "Panic mode not active");
+            assert!(self.check_panic_mode_status(collateral), "Panic mode not active");
 
             // Verify stablecoin payment
+            let stablecoin_address = stablecoin_payment.resource_address();
+            let stablecoin = *self
+                .panic_mode
+                .stablecoins
+                .iter()
+                .find(|stablecoin| stablecoin.address == stablecoin_address)
+                .expect("Invalid stablecoin payment");
+
+            let stablecoin_price: Decimal = self.oracle.call_raw(
+                &self.oracle_single_method_name,
+                scrypto_args!(stablecoin_address, stablecoin_message, stablecoin_signature),
+            );
+
             assert!(
-                stablecoin_payment.resource_address() == self.panic_mode.current_centralized_stablecoin,
-                "Invalid stablecoin payment"
+                stablecoin_price >= Decimal::ONE - stablecoin.depeg_tolerance
+                    && stablecoin_price <= Decimal::ONE + stablecoin.depeg_tolerance,
+                "Stablecoin price outside of depeg tolerance"
             );
 
             // Check if CDP is liquidatable
@@ -1307,43 +3546,43 @@ mod stability_pools {
                 self.flux.check_liquidate_cdp(cdp_id.clone(), Some(price))
             });
 
-            assert!(stablecoin_payment.amount() >= required_fusd, "Stablecoin payment must be greater than or equal to fUSD debt");
+            let payment_value = stablecoin_payment.amount() * stablecoin_price;
 
-            assert!(liquidatable, "CDP not liquidatable");
+            assert!(payment_value >= required_fusd, "Stablecoin payment value must be greater than or equal to fUSD debt");
 
-            // Check if stability pool has enough fUSD - if so, must use normal liquidation
-            let pool_fusd = *self.stability_pools
-                .get(&collateral_address)
-                .unwrap()
-                .pool
-                .get_vault_amounts()
-                .get(&self.fusd_address)
-                .unwrap();
+            assert!(liquidatable, "CDP not liquidatable");
 
+            // Check if the stability pools have enough fUSD - if so, must use normal liquidation
             assert!(
-                pool_fusd < required_fusd,
+                !self.can_liquidate_from_pools(collateral_address, required_fusd),
                 "Must use normal liquidation - enough fUSD in stability pool"
             );
 
-            // Convert stablecoin to fUSD 1:1
+            // Convert stablecoin to fUSD at its oracle value
             let fusd = self.badge_vault.authorize_with_amount(dec!("0.75"), || {
-                self.flux.free_fusd(stablecoin_payment.amount())
+                self.flux.free_fusd(payment_value)
             });
 
+            let stablecoin_paid = stablecoin_payment.amount();
+
             // Store stablecoin
             self.panic_mode.centralized_stablecoin_vaults
-                .get_mut(&self.panic_mode.current_centralized_stablecoin)
+                .get_mut(&stablecoin_address)
                 .unwrap()
                 .put(stablecoin_payment);
 
             // Perform liquidation
             let (payout, leftover) = self.badge_vault.authorize_with_amount(dec!("0.75"), || {
                 let (payout, _, leftover_fusd) = self.flux.liquidate_cdp(fusd, cdp_id.clone(), Some(price));
-                
-                let leftover = self.panic_mode.centralized_stablecoin_vaults
-                    .get_mut(&self.panic_mode.current_centralized_stablecoin)
-                    .unwrap()
-                    .take(leftover_fusd.amount());
+
+                let mut stablecoin_vault = self.panic_mode.centralized_stablecoin_vaults
+                    .get_mut(&stablecoin_address)
+                    .unwrap();
+                let leftover_amount = (leftover_fusd.amount() / stablecoin_price).min(stablecoin_vault.amount());
+                let leftover = stablecoin_vault.take_advanced(
+                    leftover_amount,
+                    WithdrawStrategy::Rounded(RoundingMode::ToZero),
+                );
 
                 leftover_fusd.burn();
 
@@ -1351,42 +3590,274 @@ mod stability_pools {
             });
 
             // Update last liquidation time
-            self.panic_mode.last_liquidation_time = Some(Clock::current_time_rounded_to_seconds());
+            self.panic_mode.collaterals.get_mut(&collateral).unwrap().last_liquidation_time =
+                Some(Clock::current_time_rounded_to_seconds());
 
             // Emit panic mode liquidation event
             Runtime::emit_event(PanicModeLiquidationEvent {
                 cdp_id,
-                stablecoin_paid: required_fusd,
+                stablecoin: stablecoin_address,
+                stablecoin_paid: stablecoin_paid - leftover.amount(),
+                stablecoin_price,
                 collateral_received: payout.amount(),
             });
 
             (payout, leftover)
         }
 
-        /// Checks the current status of panic mode.
-        /// If active, it checks if the cooldown period since the last panic liquidation has passed.
-        /// If the cooldown has passed, it deactivates panic mode.
+        /// Checks the current status of panic mode for a collateral.
+        /// If active, it checks if the cooldown period since the collateral's last panic liquidation has passed.
+        /// If the cooldown has passed, it deactivates panic mode for the collateral.
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` of the collateral to check.
         ///
         /// # Returns
-        /// * `bool`: `true` if panic mode is currently active, `false` otherwise.
-        pub fn check_panic_mode_status(&mut self) -> bool{
-            if self.panic_mode.is_active {
-                if let Some(last_liquidation) = self.panic_mode.last_liquidation_time {
-                    if Clock::current_time_is_strictly_after(
-                        last_liquidation.add_minutes(self.parameters.panic_mode_cooldown_period).unwrap(),
-                        TimePrecision::Second
-                    ) {
-                        // Exit panic mode if no liquidations for a day
-                        self.panic_mode.is_active = false;
-                        self.panic_mode.last_liquidation_time = None;
-                        return false;
+        /// * `bool`: `true` if panic mode is currently active for the collateral, `false` otherwise.
+        pub fn check_panic_mode_status(&mut self, collateral: ResourceAddress) -> bool {
+            let cooldown_period = self.parameters.panic_mode_cooldown_period;
+
+            if let Some(panic_mode) = self.panic_mode.collaterals.get_mut(&collateral) {
+                if panic_mode.is_active {
+                    if let Some(last_liquidation) = panic_mode.last_liquidation_time {
+                        if Clock::current_time_is_strictly_after(
+                            last_liquidation.add_minutes(cooldown_period).unwrap(),
+                            TimePrecision::Second
+                        ) {
+                            // Exit panic mode if no liquidations for a day
+                            panic_mode.is_active = false;
+                            panic_mode.last_liquidation_time = None;
+                            return false;
+                        }
+                        return true;
                     }
-                    return true;
                 }
             }
             false
         }
 
+        /// Checks the panic mode status of every collateral, deactivating panic mode where the cooldown has passed.
+        ///
+        /// # Returns
+        /// * `bool`: `true` if panic mode is currently active for any collateral, `false` otherwise.
+        fn any_panic_mode_active(&mut self) -> bool {
+            let collaterals: Vec<ResourceAddress> = self.panic_mode.collaterals.keys().copied().collect();
+            let mut active = false;
+            for collateral in collaterals {
+                if self.check_panic_mode_status(collateral) {
+                    active = true;
+                }
+            }
+            active
+        }
+
+        /// Asserts that the protocol has recovered enough to unwind stablecoin reserves: panic mode isn't active for
+        /// any collateral, and the stability pool of every collateral that has been (pending) panic mode holds at
+        /// least `min_pool_coverage` of the collateral's total debt in fUSD.
+        ///
+        /// # Panics
+        /// * If panic mode is active for any collateral.
+        /// * If a stability pool holds less fUSD than the minimum coverage of its collateral's debt.
+        fn assert_recovered_from_panic_mode(&mut self, min_pool_coverage: Decimal) {
+            assert!(!self.any_panic_mode_active(), "Can't unwind reserves while panic mode is active.");
+
+            let collaterals: Vec<ResourceAddress> = self.panic_mode.collaterals.keys().copied().collect();
+            for collateral_info in self.flux.get_collateral_infos(collaterals) {
+                let (_, pool_fusd) = self.get_pool_amounts(collateral_info.resource_address);
+                assert!(
+                    pool_fusd >= collateral_info.total_debt * min_pool_coverage,
+                    "Stability pools have not recovered from panic mode."
+                );
+            }
+        }
+
+        /// Sells panic mode stablecoin reserves through the configured DEX adapter, and burns the fUSD received.
+        ///
+        /// Can only be called when panic mode isn't active for any collateral, and the stability pools of collaterals that
+        /// have been (pending) panic mode hold the configured minimum coverage of their debt in fUSD. Sells as much of the
+        /// stablecoin as the rate limit of the current period allows, valued at the stablecoin's oracle price.
+        ///
+        /// # Arguments
+        /// * `stablecoin`: The `ResourceAddress` of the stablecoin to unwind.
+        /// * `message`: Oracle message for price verification of the stablecoin.
+        /// * `signature`: Oracle signature for price verification of the stablecoin.
+        ///
+        /// # Panics
+        /// * If panic mode is active for any collateral, or a stability pool hasn't recovered the minimum coverage.
+        /// * If stablecoin unwinding is not enabled, or doesn't use a DEX adapter.
+        /// * If there are no reserves of the `stablecoin`, or the rate limit of the current period has been reached.
+        /// * If the DEX adapter doesn't return fUSD, or returns less than the oracle value minus the maximum slippage.
+        pub fn unwind_stablecoin_reserves(
+            &mut self,
+            stablecoin: ResourceAddress,
+            message: String,
+            signature: String,
+        ) {
+            let unwind = self.panic_mode.unwind.clone().expect("Stablecoin unwinding not enabled.");
+            self.assert_recovered_from_panic_mode(unwind.min_pool_coverage);
+            let (component, method_name) = match unwind.method {
+                StablecoinUnwindMethod::DexAdapter { component, method_name } => (component, method_name),
+                StablecoinUnwindMethod::BuyBackWindow { .. } => panic!("Not a DEX adapter unwind."),
+            };
+
+            let stablecoin_price: Decimal = self.oracle.call_raw(
+                &self.oracle_single_method_name,
+                scrypto_args!(stablecoin, message, signature),
+            );
+
+            let allowance = self.get_unwind_allowance();
+
+            let sold_stablecoin = {
+                let mut stablecoin_vault = self
+                    .panic_mode
+                    .centralized_stablecoin_vaults
+                    .get_mut(&stablecoin)
+                    .expect("Unknown stablecoin.");
+                let sale_amount = stablecoin_vault.amount().min(allowance / stablecoin_price);
+                assert!(sale_amount > Decimal::ZERO, "Nothing to unwind.");
+                stablecoin_vault.take_advanced(sale_amount, WithdrawStrategy::Rounded(RoundingMode::ToZero))
+            }; // stablecoin_vault is dropped here
+            let sold_amount = sold_stablecoin.amount();
+
+            let adapter: Global<AnyComponent> = Global::from(component);
+            let fusd: Bucket = adapter.call_raw(&method_name, scrypto_args!(sold_stablecoin));
+
+            assert!(
+                fusd.resource_address() == self.fusd_address,
+                "Unwinding must return fUSD."
+            );
+            let fusd_received = fusd.amount();
+            assert!(
+                fusd_received >= sold_amount * stablecoin_price * (Decimal::ONE - unwind.max_slippage),
+                "Unwind slippage too high."
+            );
+
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                fusd.burn();
+            });
+            self.panic_mode.unwind.as_mut().unwrap().unwound_in_period += fusd_received;
+
+            Runtime::emit_event(StablecoinUnwindEvent {
+                stablecoin,
+                stablecoin_amount: sold_amount,
+                fusd_burned: fusd_received,
+                stablecoin_price,
+                change: StablecoinUnwindChange::DexSale,
+            });
+        }
+
+        /// Sells fUSD to the protocol for panic mode stablecoin reserves, through the open buy-back window.
+        /// The fUSD is burned.
+        ///
+        /// Can only be called when panic mode isn't active for any collateral, and the stability pools of collaterals that
+        /// have been (pending) panic mode hold the configured minimum coverage of their debt in fUSD. For each fUSD, the
+        /// seller receives stablecoin worth `1 - discount` USD at the stablecoin's oracle price. At most the rate limit of
+        /// the current period is bought.
+        ///
+        /// # Arguments
+        /// * `stablecoin`: The `ResourceAddress` of the stablecoin to receive.
+        /// * `fusd`: A `Bucket` containing the fUSD to sell.
+        /// * `message`: Oracle message for price verification of the stablecoin.
+        /// * `signature`: Oracle signature for price verification of the stablecoin.
+        ///
+        /// # Returns
+        /// * `(Bucket, Bucket)`: A tuple containing:
+        ///     1. The `Bucket` of stablecoin received.
+        ///     2. The `Bucket` of fUSD that wasn't bought back.
+        ///
+        /// # Panics
+        /// * If the payment is not fUSD.
+        /// * If panic mode is active for any collateral, or a stability pool hasn't recovered the minimum coverage.
+        /// * If stablecoin unwinding is not enabled, or no buy-back window is open.
+        /// * If there are no reserves of the `stablecoin`, or the rate limit of the current period has been reached.
+        pub fn sell_fusd_for_stablecoin(
+            &mut self,
+            stablecoin: ResourceAddress,
+            mut fusd: Bucket,
+            message: String,
+            signature: String,
+        ) -> (Bucket, Bucket) {
+            assert!(fusd.resource_address() == self.fusd_address, "Payment must be fUSD.");
+            let unwind = self.panic_mode.unwind.clone().expect("Stablecoin unwinding not enabled.");
+            self.assert_recovered_from_panic_mode(unwind.min_pool_coverage);
+            let discount = match unwind.method {
+                StablecoinUnwindMethod::BuyBackWindow { discount } => discount,
+                StablecoinUnwindMethod::DexAdapter { .. } => panic!("No buy-back window open."),
+            };
+
+            let stablecoin_price: Decimal = self.oracle.call_raw(
+                &self.oracle_single_method_name,
+                scrypto_args!(stablecoin, message, signature),
+            );
+            let stablecoin_per_fusd = (Decimal::ONE - discount) / stablecoin_price;
+
+            let allowance = self.get_unwind_allowance();
+
+            let (bought_stablecoin, fusd_amount) = {
+                let mut stablecoin_vault = self
+                    .panic_mode
+                    .centralized_stablecoin_vaults
+                    .get_mut(&stablecoin)
+                    .expect("Unknown stablecoin.");
+                let fusd_amount = fusd
+                    .amount()
+                    .min(allowance)
+                    .min(stablecoin_vault.amount() / stablecoin_per_fusd);
+                assert!(fusd_amount > Decimal::ZERO, "Nothing to unwind.");
+                let stablecoin_amount = (fusd_amount * stablecoin_per_fusd).min(stablecoin_vault.amount());
+                let bought_stablecoin = stablecoin_vault.take_advanced(
+                    stablecoin_amount,
+                    WithdrawStrategy::Rounded(RoundingMode::ToZero),
+                );
+                (bought_stablecoin, fusd_amount)
+            }; // stablecoin_vault is dropped here
+
+            let payment = fusd.take(fusd_amount);
+            self.badge_vault.authorize_with_amount(dec!("0.75"), || {
+                payment.burn();
+            });
+            self.panic_mode.unwind.as_mut().unwrap().unwound_in_period += fusd_amount;
+
+            Runtime::emit_event(StablecoinUnwindEvent {
+                stablecoin,
+                stablecoin_amount: bought_stablecoin.amount(),
+                fusd_burned: fusd_amount,
+                stablecoin_price,
+                change: StablecoinUnwindChange::BuyBack,
+            });
+
+            (bought_stablecoin, fusd)
+        }
+
+        /// Retrieves the stablecoin unwind configuration and the state of its rate limit.
+        ///
+        /// # Returns
+        /// * `Option<StablecoinUnwind>`: The unwind configuration, `None` if unwinding is disabled.
+        pub fn get_stablecoin_unwind(&self) -> Option<StablecoinUnwind> {
+            self.panic_mode.unwind.clone()
+        }
+
+        /// Returns the amount of fUSD that can still be bought back in the current rate limit period,
+        /// starting a new period if the current one has ended.
+        ///
+        /// # Panics
+        /// * If stablecoin unwinding is not enabled.
+        fn get_unwind_allowance(&mut self) -> Decimal {
+            let unwind = self.panic_mode.unwind.as_mut().expect("Stablecoin unwinding not enabled.");
+            let now = Clock::current_time_rounded_to_seconds();
+
+            let period_ended = match unwind.period_start {
+                Some(period_start) => now >= period_start.add_minutes(unwind.period).unwrap(),
+                None => true,
+            };
+            if period_ended {
+                unwind.period_start = Some(now);
+                unwind.unwound_in_period = Decimal::ZERO;
+            }
+
+            (unwind.max_fusd_per_period - unwind.unwound_in_period).max(Decimal::ZERO)
+        }
+
         /// Sets the time periods (in minutes) related to panic mode activation and cooldown.
         ///
         /// Requires OWNER authorization (controller badge).
@@ -1414,6 +3885,113 @@ mod stability_pools {
             self.parameters.lowest_interest_interval = lowest_interest_interval;
         }
 
+        /// Sets the parameters for locking pool units.
+        ///
+        /// Requires OWNER authorization (controller badge).
+        ///
+        /// # Arguments
+        /// * `max_lock_duration`: The maximum lock duration (minutes), which receives the full boost.
+        /// * `max_lock_boost`: The extra weight of pool units locked for the maximum duration (e.g. 1 for a 2x weight).
+        /// * `early_unlock_penalty`: The fraction of the pool units lost when unlocking right after locking,
+        ///   decreasing linearly to zero at the unlock time.
+        ///
+        /// # Panics
+        /// * If `max_lock_duration` is not positive, `max_lock_boost` is negative or `early_unlock_penalty` is not between 0 and 1.
+        pub fn set_lock_parameters(
+            &mut self,
+            max_lock_duration: i64,
+            max_lock_boost: Decimal,
+            early_unlock_penalty: Decimal,
+        ) {
+            assert!(max_lock_duration > 0, "Max lock duration must be positive");
+            assert!(max_lock_boost >= Decimal::ZERO, "Max lock boost can't be negative");
+            assert!(
+                early_unlock_penalty >= Decimal::ZERO && early_unlock_penalty <= Decimal::ONE,
+                "Early unlock penalty must be between 0 and 1"
+            );
+
+            self.parameters.max_lock_duration = max_lock_duration;
+            self.parameters.max_lock_boost = max_lock_boost;
+            self.parameters.early_unlock_penalty = early_unlock_penalty;
+        }
+
+        /// Sets the parameters for two-step withdrawals.
+        ///
+        /// Requires OWNER authorization (controller badge).
+        ///
+        /// # Arguments
+        /// * `withdrawal_delay`: The delay (minutes) between requesting and claiming a withdrawal. 0 allows direct withdrawals.
+        /// * `withdrawal_claim_window`: The duration (minutes) after the delay in which a withdrawal can be claimed.
+        ///
+        /// # Panics
+        /// * If `withdrawal_delay` is negative or `withdrawal_claim_window` is not positive.
+        pub fn set_withdrawal_parameters(&mut self, withdrawal_delay: i64, withdrawal_claim_window: i64) {
+            assert!(withdrawal_delay >= 0, "Withdrawal delay can't be negative");
+            assert!(withdrawal_claim_window > 0, "Claim window must be positive");
+
+            self.parameters.withdrawal_delay = withdrawal_delay;
+            self.parameters.withdrawal_claim_window = withdrawal_claim_window;
+        }
+
+        /// Enables, edits or disables the conversion of a stability pool's collateral back to fUSD.
+        /// Editing the conversion ends a running auction.
+        ///
+        /// Requires OWNER authorization (controller badge).
+        ///
+        /// # Arguments
+        /// * `collateral`: The `ResourceAddress` identifying the pool.
+        /// * `method`: The `ConversionMethod` to use, `None` to disable conversions.
+        /// * `threshold`: The share of the pool's value held in collateral above which a conversion can be triggered.
+        /// * `max_slippage`: The maximum fraction below the oracle price collateral can be sold for.
+        /// * `max_sale_amount`: Optional cap on the amount of collateral sold per conversion.
+        ///
+        /// # Panics
+        /// * If no pool exists for the given `collateral`, or it is a product-sum pool.
+        /// * If `threshold` is not between 0 and 1, or `max_slippage` is not at least 0 and below 1.
+        /// * If `max_sale_amount` is not positive.
+        /// * If an auction's `duration` is not positive, or a DEX adapter's `cooldown` is negative.
+        pub fn set_collateral_conversion(
+            &mut self,
+            collateral: ResourceAddress,
+            method: Option<ConversionMethod>,
+            threshold: Decimal,
+            max_slippage: Decimal,
+            max_sale_amount: Option<Decimal>,
+        ) {
+            self.get_two_resource_pool(collateral);
+            assert!(
+                threshold >= Decimal::ZERO && threshold <= Decimal::ONE,
+                "Threshold must be between 0 and 1"
+            );
+            assert!(
+                max_slippage >= Decimal::ZERO && max_slippage < Decimal::ONE,
+                "Max slippage must be at least 0 and below 1"
+            );
+            if let Some(max_sale_amount) = max_sale_amount {
+                assert!(max_sale_amount > Decimal::ZERO, "Max sale amount must be positive");
+            }
+            match method {
+                Some(ConversionMethod::Auction { duration, .. }) => {
+                    assert!(duration > 0, "Auction duration must be positive");
+                }
+                Some(ConversionMethod::DexAdapter { cooldown, .. }) => {
+                    assert!(cooldown >= 0, "Conversion cooldown can't be negative");
+                }
+                None => {}
+            }
+
+            self.stability_pools.get_mut(&collateral).unwrap().conversion =
+                method.map(|method| CollateralConversion {
+                    method,
+                    threshold,
+                    max_slippage,
+                    max_sale_amount,
+                    auction_start: None,
+                    auction_lot: Decimal::ZERO,
+                    last_sale: None,
+                });
+        }
+
         /// Sets whether to allow multiple contribution/interest charging actions within a single transaction.
         ///
         /// Requires OWNER authorization (controller badge).
@@ -1433,6 +4011,100 @@ mod stability_pools {
             self.payout_vault.take_all()
         }
 
+        /// Updates the redemption rate of a derivative collateral in the oracle (see the oracle's `update_redemption_rate`).
+        /// The caller is paid the `OracleRateUpdate` keeper bounty, if configured and not on cooldown.
+        ///
+        /// # Arguments
+        /// * `derivative`: The `ResourceAddress` of the derivative whose redemption rate is updated.
+        ///
+        /// # Returns
+        /// * `Bucket`: The keeper bounty (fUSD), empty if none is paid.
+        ///
+        /// # Panics
+        /// * If the oracle call fails, e.g. because `derivative` isn't registered in the oracle.
+        pub fn update_oracle_redemption_rate(&mut self, derivative: ResourceAddress) -> Bucket {
+            self.oracle
+                .call_raw::<()>("update_redemption_rate", scrypto_args!(derivative));
+
+            self.pay_keeper_bounty(KeeperAction::OracleRateUpdate)
+        }
+
+        /// Pays the keeper bounty for an action performed through another component, such as the PayoutComponent's
+        /// `fetch_rewards_from_stability_pools`.
+        /// Requires OWNER authorization (controller badge).
+        ///
+        /// # Arguments
+        /// * `action`: The `KeeperAction` that was performed.
+        ///
+        /// # Returns
+        /// * `Bucket`: The keeper bounty (fUSD), empty if none is paid.
+        pub fn claim_keeper_bounty(&mut self, action: KeeperAction) -> Bucket {
+            self.pay_keeper_bounty(action)
+        }
+
+        /// Sets the bounty paid to keepers for an action.
+        /// Requires OWNER authorization (controller badge).
+        ///
+        /// # Arguments
+        /// * `action`: The `KeeperAction` to set the bounty for.
+        /// * `amount`: The fUSD bounty paid per call. 0 disables the bounty.
+        /// * `cooldown`: The duration (minutes) after a paid bounty during which no bounty is paid for this action.
+        ///
+        /// # Panics
+        /// * If `amount` or `cooldown` is negative.
+        pub fn set_keeper_bounty(&mut self, action: KeeperAction, amount: Decimal, cooldown: i64) {
+            assert!(amount >= Decimal::ZERO, "Bounty can't be negative.");
+            assert!(cooldown >= 0, "Cooldown can't be negative.");
+
+            let last_paid = self
+                .keeper_rewards
+                .bounties
+                .get(&action)
+                .and_then(|bounty| bounty.last_paid);
+            self.keeper_rewards.bounties.insert(
+                action,
+                KeeperBounty {
+                    amount,
+                    cooldown,
+                    last_paid,
+                },
+            );
+        }
+
+        /// Sets the share of the payout split that is set aside to fund keeper bounties.
+        /// Requires OWNER authorization (controller badge).
+        ///
+        /// # Arguments
+        /// * `funding_share`: The fraction of the payout split set aside for keeper bounties.
+        ///
+        /// # Panics
+        /// * If `funding_share` is not between 0 and 1.
+        pub fn set_keeper_funding_share(&mut self, funding_share: Decimal) {
+            assert!(
+                funding_share >= Decimal::ZERO && funding_share <= Decimal::ONE,
+                "Funding share must be between 0 and 1."
+            );
+
+            self.keeper_rewards.funding_share = funding_share;
+        }
+
+        /// Retrieves the keeper reward configuration and the funds available for bounties.
+        ///
+        /// # Returns
+        /// * `KeeperRewardsInfoReturn`: The available funds, the funding share and the bounty per action.
+        pub fn get_keeper_rewards_info(&self) -> KeeperRewardsInfoReturn {
+            KeeperRewardsInfoReturn {
+                funds: self.keeper_rewards.funds.amount(),
+                funding_share: self.keeper_rewards.funding_share,
+                bounties: self
+                    .keeper_rewards
+                    .bounties
+                    .iter()
+                    .map(|(action, bounty)| (*action, bounty.clone()))
+                    .collect(),
+            }
+        }
+
         /// Sets the default parameters for stability pools.
         ///
         /// These parameters are used for new pools unless explicitly overridden during pool creation
@@ -1483,14 +4155,205 @@ pub struct StabilityPoolInfo {
     pub pool_buy_price_modifier: Option<Decimal>,
     /// Vault holding accumulated fUSD rewards designated for liquidity providers in this pool.
     pub liquidity_rewards: Vault,
-    /// Global reference to the underlying `TwoResourcePool` component managing this pool's liquidity.
-    pub pool: Global<TwoResourcePool>,
+    /// Global reference to the underlying pool component managing this pool's liquidity.
+    pub pool: StabilityPoolComponent,
+    /// The locked pool units and lock rewards of this pool. `None` for product-sum pools, which don't support locks.
+    pub locks: Option<PoolLocks>,
+    /// Vault holding the pool units (or deposit receipts) of requested withdrawals until they are claimed.
+    pub pending_withdrawals: Vault,
+    /// The custody of the pool units handed out as custody receipts. `None` for product-sum pools, whose deposit
+    /// receipts can only be redeemed through this component.
+    pub pool_unit_custody: Option<PoolUnitCustody>,
+    /// The configuration and auction state for converting collateral back to fUSD. `None` if disabled.
+    pub conversion: Option<CollateralConversion>,
+    /// Whether liquidations fall back to the shared stability pool if this pool can't cover them.
+    pub use_shared_pool: bool,
+    /// The maximum amount of fUSD the pool accepts through contributions. `None` for no limit.
+    pub deposit_cap: Option<Decimal>,
+    /// A badge required (in the auth zone) to contribute to the pool. `None` if anyone can contribute.
+    pub contributor_badge: Option<ResourceAddress>,
+    /// The guarded-launch settings, tying the collateral's debt ceiling to the pool's depth. `None` if disabled.
+    pub guarded_launch: Option<GuardedLaunch>,
     /// A recent history of the lowest active interest rates observed for CDPs of this collateral type.
     pub latest_lowest_interests: Vec<Decimal>,
     /// Timestamp of the last time the `latest_lowest_interests` history was updated.
     pub last_lowest_interests_update: Instant,
 }
 
+/// The lock state of a stability pool.
+#[derive(ScryptoSbor)]
+pub struct PoolLocks {
+    /// Vault holding the locked pool units.
+    pub locked_units: Vault,
+    /// The total boosted weight of all locks.
+    pub total_weight: Decimal,
+    /// The fUSD rewards paid per unit of lock weight since the pool was created.
+    pub reward_per_weight: PreciseDecimal,
+    /// Vault holding the fUSD rewards not yet claimed by lockers.
+    pub rewards: Vault,
+}
+
+/// The custody of the pool units of a Radix pool.
+///
+/// The `redeem` method of the Radix pools is public, so users only ever hold custody receipts, which can only be
+/// redeemed through this component and are therefore subject to the withdrawal delay.
+#[derive(ScryptoSbor)]
+pub struct PoolUnitCustody {
+    /// Vault holding the pool units backing the custody receipts.
+    pub pool_units: Vault,
+    /// The resource manager of the custody receipts, minted and burned 1:1 with the pool units in custody.
+    pub receipt_manager: ResourceManager,
+}
+
+/// The non-fungible data of a lock receipt, received when locking pool units.
+#[derive(ScryptoSbor, NonFungibleData)]
+pub struct StabilityPoolLock {
+    /// The `ResourceAddress` of the collateral whose pool units are locked.
+    pub collateral: ResourceAddress,
+    /// The amount of locked pool units.
+    pub pool_units: Decimal,
+    /// The boosted weight of the lock.
+    pub weight: Decimal,
+    /// Time at which the pool units were locked.
+    pub lock_start: Instant,
+    /// Time after which the pool units can be unlocked without penalty.
+    pub unlock_time: Instant,
+    /// The pool's `reward_per_weight` at the last reward claim.
+    #[mutable]
+    pub reward_per_weight_snapshot: PreciseDecimal,
+}
+
+/// The non-fungible data of a withdrawal request, received when requesting a withdrawal from a stability pool.
+#[derive(ScryptoSbor, NonFungibleData)]
+pub struct WithdrawalRequest {
+    /// The `ResourceAddress` of the collateral whose pool is withdrawn from, `None` for the shared stability pool.
+    pub collateral: Option<ResourceAddress>,
+    /// The amount of pool units (or deposit receipts) to withdraw.
+    pub pool_units: Decimal,
+    /// The IDs of the deposit receipts to withdraw, for product-sum pools.
+    pub deposit_ids: Vec<NonFungibleLocalId>,
+    /// Time at which the withdrawal was requested.
+    pub requested_at: Instant,
+    /// Time after which the withdrawal can be claimed.
+    pub claimable_at: Instant,
+}
+
+/// The configuration and state of a stability pool's collateral conversion.
+#[derive(ScryptoSbor, Clone)]
+pub struct CollateralConversion {
+    /// How the collateral is converted.
+    pub method: ConversionMethod,
+    /// The share of the pool's value held in collateral above which a conversion can be triggered.
+    pub threshold: Decimal,
+    /// The maximum fraction below the oracle price collateral can be sold for.
+    pub max_slippage: Decimal,
+    /// Optional cap on the amount of collateral sold (or auctioned) per conversion.
+    pub max_sale_amount: Option<Decimal>,
+    /// Start time of the latest auction, `None` if no auction was started since the last one ended.
+    /// An auction past its duration has expired, even if this is still set.
+    pub auction_start: Option<Instant>,
+    /// The amount of collateral left for sale in the running auction.
+    pub auction_lot: Decimal,
+    /// The time of the latest DEX adapter sale, `None` if none was made since the conversion was set.
+    pub last_sale: Option<Instant>,
+}
+
+/// The funds and configuration used to reward keepers for housekeeping calls.
+#[derive(ScryptoSbor)]
+pub struct KeeperRewards {
+    /// Vault holding the fUSD set aside for keeper bounties.
+    pub funds: Vault,
+    /// The fraction of the payout split set aside for keeper bounties.
+    pub funding_share: Decimal,
+    /// The bounty configuration per action.
+    pub bounties: HashMap<KeeperAction, KeeperBounty>,
+}
+
+/// The bounty paid to keepers for a single action.
+#[derive(ScryptoSbor, Clone)]
+pub struct KeeperBounty {
+    /// The fUSD bounty paid per call.
+    pub amount: Decimal,
+    /// The duration (minutes) after a paid bounty during which no bounty is paid.
+    pub cooldown: i64,
+    /// The time the bounty was last paid.
+    pub last_paid: Option<Instant>,
+}
+
+/// A structure for returning the keeper reward configuration.
+#[derive(ScryptoSbor, Clone)]
+pub struct KeeperRewardsInfoReturn {
+    /// The amount of fUSD available for keeper bounties.
+    pub funds: Decimal,
+    /// The fraction of the payout split set aside for keeper bounties.
+    pub funding_share: Decimal,
+    /// The bounty configuration per action.
+    pub bounties: Vec<(KeeperAction, KeeperBounty)>,
+}
+
+/// The position where `charge_all_interest` resumes charging interest.
+#[derive(ScryptoSbor, Clone)]
+pub struct InterestChargeCursor {
+    /// The collateral to resume at.
+    pub collateral: ResourceAddress,
+    /// The interest tier to resume at, `None` to start at the collateral's lowest tier.
+    pub interest: Option<Decimal>,
+}
+
+/// A summary of the interest charged for a collateral by `charge_all_interest`.
+#[derive(ScryptoSbor, Clone)]
+pub struct InterestChargeSummary {
+    /// The collateral interest was charged for.
+    pub collateral: ResourceAddress,
+    /// The start of the charged interest range (inclusive), `None` for the lowest tier.
+    pub start_interest: Option<Decimal>,
+    /// The end of the charged interest range (exclusive), `None` for all remaining tiers.
+    pub end_interest: Option<Decimal>,
+    /// The number of interest tiers charged.
+    pub tiers_charged: u64,
+    /// The amount of fUSD interest charged, before being split.
+    pub fusd_charged: Decimal,
+}
+
+/// The shared stability pool, covering liquidations of all opted-in collaterals.
+#[derive(ScryptoSbor)]
+pub struct SharedStabilityPool {
+    /// The Radix `MultiResourcePool`, holding fUSD and the seized collaterals.
+    pub pool: Global<MultiResourcePool>,
+    /// The collaterals the shared pool can hold, fixed at creation.
+    pub collaterals: Vec<ResourceAddress>,
+    /// Vault holding the pool units of the seed contribution, which are never redeemed.
+    pub seed_units: Vault,
+    /// Vault holding the pool units of requested withdrawals, until they are claimed or cancelled.
+    pub pending_withdrawals: Vault,
+    /// The custody of the pool units handed out to contributors as custody receipts.
+    pub custody: PoolUnitCustody,
+}
+
+/// A structure for returning information about the shared stability pool.
+#[derive(ScryptoSbor, Clone)]
+pub struct SharedStabilityPoolInfoReturn {
+    /// Global reference to the underlying `MultiResourcePool`.
+    pub pool: Global<MultiResourcePool>,
+    /// The collaterals the shared pool can hold.
+    pub collaterals: Vec<ResourceAddress>,
+    /// The collaterals whose liquidations currently fall back to the shared pool.
+    pub opted_in: Vec<ResourceAddress>,
+    /// The current amounts of fUSD and each collateral held by the shared pool.
+    pub vault_amounts: IndexMap<ResourceAddress, Decimal>,
+    /// The `ResourceAddress` of the shared pool unit custody receipt.
+    pub custody_receipt: ResourceAddress,
+}
+
+/// The component managing a stability pool's liquidity, depending on the pool's `StabilityPoolType`.
+#[derive(ScryptoSbor, Clone, Copy)]
+pub enum StabilityPoolComponent {
+    /// A Radix `TwoResourcePool`, holding the collateral and fUSD of the pool.
+    TwoResource(Global<TwoResourcePool>),
+    /// A `ProductSumPool`, tracking deposits, collateral gains and fUSD yield per depositor.
+    ProductSum(Global<ProductSumPool>),
+}
+
 /// A structure for returning stability pool information, including current asset amounts.
 #[derive(ScryptoSbor, Clone)]
 pub struct StabilityPoolInfoReturn {
@@ -1508,12 +4371,28 @@ pub struct StabilityPoolInfoReturn {
     pub pool_buy_price_modifier: Option<Decimal>,
     /// The current amount of accumulated fUSD liquidity rewards.
     pub liquidity_rewards: Decimal,
-    /// Global reference to the underlying `TwoResourcePool` component.
-    pub pool: Global<TwoResourcePool>,
+    /// Global reference to the underlying pool component.
+    pub pool: StabilityPoolComponent,
     /// The current amount of collateral held within the pool.
     pub collateral_amount: Decimal,
     /// The current amount of fUSD held within the pool.
     pub fusd_amount: Decimal,
+    /// The amount of pool units currently locked.
+    pub locked_pool_units: Decimal,
+    /// The total boosted weight of the locked pool units.
+    pub lock_weight: Decimal,
+    /// The `ResourceAddress` of the pool unit custody receipt. `None` for product-sum pools.
+    pub custody_receipt: Option<ResourceAddress>,
+    /// The collateral conversion configuration and auction state, if enabled.
+    pub conversion: Option<CollateralConversion>,
+    /// Whether liquidations fall back to the shared stability pool.
+    pub use_shared_pool: bool,
+    /// The maximum amount of fUSD the pool accepts through contributions, if any.
+    pub deposit_cap: Option<Decimal>,
+    /// The badge required to contribute to the pool, if any.
+    pub contributor_badge: Option<ResourceAddress>,
+    /// The guarded-launch settings, if enabled.
+    pub guarded_launch: Option<GuardedLaunch>,
     /// The recent history of lowest active interest rates.
     pub latest_lowest_interests: Vec<Decimal>,
     /// Timestamp of the last update to the interest history.
@@ -1547,19 +4426,76 @@ pub struct StabilityPoolParameters {
     pub panic_mode_wait_period: i64,
     /// The cooldown period (minutes) after the last panic mode liquidation before panic mode deactivates.
     pub panic_mode_cooldown_period: i64,
+    /// The maximum duration (minutes) pool units can be locked for, receiving the full lock boost.
+    pub max_lock_duration: i64,
+    /// The extra weight of pool units locked for the maximum duration.
+    pub max_lock_boost: Decimal,
+    /// The fraction of locked pool units lost when unlocking right after locking, decreasing linearly to zero at the unlock time.
+    pub early_unlock_penalty: Decimal,
+    /// The delay (minutes) between requesting and claiming a withdrawal. 0 allows direct withdrawals.
+    pub withdrawal_delay: i64,
+    /// The duration (minutes) after the withdrawal delay in which a withdrawal can be claimed.
+    pub withdrawal_claim_window: i64,
 }
 
 /// Holds the state related to the panic mode functionality.
 #[derive(ScryptoSbor)]
 pub struct PanicModeInfo {
-    /// Flag indicating if panic mode is currently active.
+    /// The panic mode state of each collateral for which panic mode has been initiated at least once.
+    pub collaterals: HashMap<ResourceAddress, CollateralPanicMode>,
+    /// Vaults holding different types of centralized stablecoins, keyed by their `ResourceAddress`.
+    pub centralized_stablecoin_vaults: KeyValueStore<ResourceAddress, Vault>,
+    /// The stablecoins accepted for panic mode liquidations.
+    pub stablecoins: Vec<PanicModeStablecoin>,
+    /// The order in which redemptions drain the stablecoin vaults.
+    pub redemption_order: Vec<ResourceAddress>,
+    /// The last configured depeg tolerance of every stablecoin that has been a panic mode stablecoin, used to skip
+    /// depegged reserves in redemptions, also after a stablecoin is no longer accepted.
+    pub depeg_tolerances: HashMap<ResourceAddress, Decimal>,
+    /// How the stablecoin reserves are unwound back into fUSD, `None` if unwinding is disabled.
+    pub unwind: Option<StablecoinUnwind>,
+}
+
+/// The configuration and rate limit state of the unwinding of panic mode stablecoin reserves.
+#[derive(ScryptoSbor, Clone)]
+pub struct StablecoinUnwind {
+    /// How the reserves are unwound.
+    pub method: StablecoinUnwindMethod,
+    /// The maximum fraction below the oracle value the reserves can be sold for through a DEX adapter.
+    pub max_slippage: Decimal,
+    /// The maximum amount of fUSD that can be bought back and burned per period.
+    pub max_fusd_per_period: Decimal,
+    /// The length of a rate limit period, in minutes.
+    pub period: i64,
+    /// The minimum fUSD in the stability pool of every collateral that has been (pending) panic mode, as a fraction
+    /// of the collateral's total debt, before reserves can be unwound.
+    pub min_pool_coverage: Decimal,
+    /// Start time of the current rate limit period, `None` if nothing has been unwound yet.
+    pub period_start: Option<Instant>,
+    /// The amount of fUSD bought back and burned in the current period.
+    pub unwound_in_period: Decimal,
+}
+
+/// The panic mode state of a single collateral.
+#[derive(ScryptoSbor)]
+pub struct CollateralPanicMode {
+    /// Flag indicating if panic mode is currently active for the collateral.
     pub is_active: bool,
-    /// Timestamp of the last panic mode liquidation.
+    /// Timestamp of the last panic mode liquidation of the collateral.
     pub last_liquidation_time: Option<Instant>,
-    /// Stores CDPs that are pending potential panic mode activation, mapped to their activation eligibility time.
+    /// Stores the collateral's CDPs that are pending potential panic mode activation, mapped to their activation
+    /// eligibility time.
     pub pending_cdps: KeyValueStore<NonFungibleLocalId, Instant>,
-    /// Vaults holding different types of centralized stablecoins, keyed by their `ResourceAddress`.
-    pub centralized_stablecoin_vaults: KeyValueStore<ResourceAddress, Vault>,
-    /// The `ResourceAddress` of the currently designated stablecoin for panic mode operations.
-    pub current_centralized_stablecoin: ResourceAddress,
+}
+
+/// A structure for returning a panic mode stablecoin and its reserves.
+#[derive(ScryptoSbor, Clone)]
+pub struct PanicModeStablecoinInfoReturn {
+    /// The `ResourceAddress` of the stablecoin.
+    pub address: ResourceAddress,
+    /// The maximum deviation of the stablecoin's oracle price from 1 USD for it to be accepted.
+    /// `None` if the stablecoin is no longer accepted, but still holds reserves to be redeemed.
+    pub depeg_tolerance: Option<Decimal>,
+    /// The amount of the stablecoin held by the protocol.
+    pub reserves: Decimal,
 }
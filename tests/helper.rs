@@ -6,6 +6,7 @@ use flux_protocol::stability_pools::stability_pools_test::*;
 use flux_protocol::proxy::proxy_test::*;
 use flux_protocol::shared_structs::*;
 use dummy_oracle_component::oracle_test::*;
+use dummy_oracle_component::Interpolation;
 use scrypto_test::prelude::*;
 use scrypto::prelude::Url;
 use flux_protocol::payout_component::payout_component_test::*;
@@ -99,6 +100,7 @@ impl Helper {
             None,
             true,
             None,
+            StabilityPoolType::TwoResource,
             "XRD".to_string(),
             "XRD".to_string(),
             Url::of("https://ilikeitstable.com"),
@@ -115,6 +117,7 @@ impl Helper {
             None,
             true,
             None,
+            StabilityPoolType::TwoResource,
             "LSULP".to_string(),
             "LSULP".to_string(),
             Url::of("https://ilikeitstable.com"),
@@ -130,6 +133,8 @@ impl Helper {
             &mut env
         );
 
+        dummy_oracle.add_morpher_identifier(usdc_address, "USDC".to_string(), &mut env)?;
+
         env.enable_auth_module();
 
         Ok(Self {
@@ -210,6 +215,70 @@ impl Helper {
         Ok(())
     }
 
+    pub fn set_price_path(
+        &mut self,
+        collateral_identifier: String,
+        points: Vec<(i64, Decimal)>,
+        interpolation: Interpolation,
+    ) -> Result<(), RuntimeError> {
+        self.env.disable_auth_module();
+        self.dummy_oracle.set_price_path(collateral_identifier, points, interpolation, &mut self.env)?;
+        self.env.enable_auth_module();
+
+        Ok(())
+    }
+
+    pub fn set_crash_path(
+        &mut self,
+        collateral_identifier: String,
+        start_price: Decimal,
+        crash_time: i64,
+        crash_price: Decimal,
+        duration_seconds: i64,
+    ) -> Result<(), RuntimeError> {
+        self.env.disable_auth_module();
+        self.dummy_oracle.set_crash_path(
+            collateral_identifier,
+            start_price,
+            crash_time,
+            crash_price,
+            duration_seconds,
+            &mut self.env,
+        )?;
+        self.env.enable_auth_module();
+
+        Ok(())
+    }
+
+    pub fn add_product_sum_collateral(&mut self, market_id: String) -> Result<(Bucket, ResourceAddress), RuntimeError> {
+        let collateral = ResourceBuilder::new_fungible(OwnerRole::None)
+            .divisibility(18)
+            .mint_initial_supply(1000000, &mut self.env)?;
+        let collateral_address = collateral.resource_address(&mut self.env)?;
+
+        self.env.disable_auth_module();
+        self.dummy_oracle.add_morpher_identifier(collateral_address, market_id.clone(), &mut self.env)?;
+        self.proxy.new_collateral(
+            collateral_address,
+            dec!(2),
+            dec!(1),
+            None,
+            None,
+            None,
+            false,
+            None,
+            StabilityPoolType::ProductSum,
+            market_id.clone(),
+            market_id.clone(),
+            Url::of("https://ilikeitstable.com"),
+            format!("{}fUSD", market_id),
+            &mut self.env
+        )?;
+        self.env.enable_auth_module();
+
+        Ok((collateral.into(), collateral_address))
+    }
+
     pub fn create_account(&mut self) -> Result<Reference, RuntimeError> {
         let account = self
             .env
@@ -0,0 +1,128 @@
+mod helper;
+use helper::Helper;
+use flux_protocol::liquidity_rewards_distributor::liquidity_rewards_distributor_test::*;
+
+use scrypto_test::prelude::*;
+
+#[test]
+fn test_liquidity_rewards_distributor_streams_rewards() -> Result<(), RuntimeError> {
+    // Initialize helper with an XRD CDP and an LP token to stake
+    let mut helper = Helper::new().unwrap();
+    helper.set_allow_multiple_actions(true)?;
+
+    let bucket = helper.xrd.take(dec!(2000), &mut helper.env)?;
+    let _ = helper.proxy_open_cdp(None, bucket, dec!(500), dec!(0.05))?;
+
+    let lp_tokens = ResourceBuilder::new_fungible(OwnerRole::None)
+        .mint_initial_supply(1000, &mut helper.env)?;
+    let lp_address = lp_tokens.resource_address(&mut helper.env)?;
+
+    // Create a distributor for the XRD pool's liquidity rewards, streaming over an hour
+    helper.env.disable_auth_module();
+    let distributor = helper.proxy.new_liquidity_rewards_distributor(
+        lp_address,
+        vec![helper.xrd_address],
+        3600,
+        &mut helper.env
+    )?;
+    helper.env.enable_auth_module();
+    let mut distributor = LiquidityRewardsDistributor(*distributor.as_node_id());
+
+    // Two stakers, the second with three times the stake
+    let stake_1 = distributor.stake(lp_tokens.take(dec!(100), &mut helper.env)?, &mut helper.env)?;
+    let stake_2 = distributor.stake(lp_tokens.take(dec!(300), &mut helper.env)?, &mut helper.env)?;
+
+    // Charging interest accrues liquidity rewards in the XRD pool
+    let new_time = helper.env.get_current_time().add_days(30).unwrap();
+    helper.env.set_current_time(new_time);
+    let _ = helper.stability_pools.charge_interest(helper.xrd_address, None, None, &mut helper.env)?;
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0];
+    let liquidity_rewards = pool_info.liquidity_rewards;
+    assert!(liquidity_rewards > Decimal::ZERO);
+
+    // Anyone can fetch the rewards, which start streaming
+    distributor.fetch_rewards(&mut helper.env)?;
+    let info = distributor.get_distributor_info(&mut helper.env)?;
+    assert_eq!(info.rewards, liquidity_rewards);
+    assert_eq!(info.total_staked, dec!(400));
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0];
+    assert_eq!(pool_info.liquidity_rewards, Decimal::ZERO);
+
+    // Halfway through the stream, half of the rewards are claimable, split by stake
+    let new_time = helper.env.get_current_time().add_minutes(30).unwrap();
+    helper.env.set_current_time(new_time);
+    let stake_1_proof = stake_1.create_proof_of_all(&mut helper.env)?;
+    let rewards_1 = distributor.claim_rewards(stake_1_proof, &mut helper.env)?;
+    let expected_1 = liquidity_rewards / dec!(2) / dec!(4);
+    assert!((rewards_1.amount(&mut helper.env)? - expected_1).checked_abs().unwrap() < dec!("0.000001"));
+
+    // After the stream ends, unstaking pays out the rest of the second stake's rewards
+    let new_time = helper.env.get_current_time().add_minutes(60).unwrap();
+    helper.env.set_current_time(new_time);
+    let (returned_lp, rewards_2) = distributor.unstake(stake_2, &mut helper.env)?;
+    assert_eq!(returned_lp.amount(&mut helper.env)?, dec!(300));
+    let expected_2 = liquidity_rewards * dec!(3) / dec!(4);
+    assert!((rewards_2.amount(&mut helper.env)? - expected_2).checked_abs().unwrap() < dec!("0.000001"));
+
+    Ok(())
+}
+
+#[test]
+fn test_liquidity_rewards_distributor_small_fetch_keeps_stream() -> Result<(), RuntimeError> {
+    // Initialize helper with an XRD CDP and an LP token to stake
+    let mut helper = Helper::new().unwrap();
+    helper.set_allow_multiple_actions(true)?;
+
+    let bucket = helper.xrd.take(dec!(2000), &mut helper.env)?;
+    let _ = helper.proxy_open_cdp(None, bucket, dec!(500), dec!(0.05))?;
+
+    let lp_tokens = ResourceBuilder::new_fungible(OwnerRole::None)
+        .mint_initial_supply(1000, &mut helper.env)?;
+    let lp_address = lp_tokens.resource_address(&mut helper.env)?;
+
+    // Create a distributor for the XRD pool's liquidity rewards, streaming over an hour
+    helper.env.disable_auth_module();
+    let distributor = helper.proxy.new_liquidity_rewards_distributor(
+        lp_address,
+        vec![helper.xrd_address],
+        3600,
+        &mut helper.env
+    )?;
+    helper.env.enable_auth_module();
+    let mut distributor = LiquidityRewardsDistributor(*distributor.as_node_id());
+    let stake = distributor.stake(lp_tokens.take(dec!(100), &mut helper.env)?, &mut helper.env)?;
+
+    // Start a stream with 30 days of liquidity rewards
+    let new_time = helper.env.get_current_time().add_days(30).unwrap();
+    helper.env.set_current_time(new_time);
+    let _ = helper.stability_pools.charge_interest(helper.xrd_address, None, None, &mut helper.env)?;
+    distributor.fetch_rewards(&mut helper.env)?;
+    let info = distributor.get_distributor_info(&mut helper.env)?;
+    assert!(info.reward_rate > Decimal::ZERO);
+
+    // Fetching nothing leaves the stream untouched
+    let new_time = helper.env.get_current_time().add_minutes(10).unwrap();
+    helper.env.set_current_time(new_time);
+    distributor.fetch_rewards(&mut helper.env)?;
+    let info_after_empty_fetch = distributor.get_distributor_info(&mut helper.env)?;
+    assert_eq!(info_after_empty_fetch.reward_rate, info.reward_rate);
+    assert_eq!(info_after_empty_fetch.stream_end, info.stream_end);
+
+    // Fetching a few minutes of rewards keeps the stream end and raises the rate
+    let new_time = helper.env.get_current_time().add_minutes(20).unwrap();
+    helper.env.set_current_time(new_time);
+    let _ = helper.stability_pools.charge_interest(helper.xrd_address, None, None, &mut helper.env)?;
+    distributor.fetch_rewards(&mut helper.env)?;
+    let info_after_small_fetch = distributor.get_distributor_info(&mut helper.env)?;
+    assert_eq!(info_after_small_fetch.stream_end, info.stream_end);
+    assert!(info_after_small_fetch.reward_rate > info.reward_rate);
+
+    // After the stream ends, all fetched rewards are claimable
+    let new_time = helper.env.get_current_time().add_minutes(60).unwrap();
+    helper.env.set_current_time(new_time);
+    let (_, rewards) = distributor.unstake(stake, &mut helper.env)?;
+    let rewards = rewards.amount(&mut helper.env)?;
+    assert!((rewards - info_after_small_fetch.rewards).checked_abs().unwrap() < dec!("0.000001"));
+
+    Ok(())
+}
@@ -0,0 +1,1192 @@
+use dummy_dex_pool_component::dummy_dex_pool_component_test::*;
+use dummy_lsulp_component::dummy_lsulp_component_test::*;
+use dummy_morpher_component::dummy_morpher_component_test::*;
+use oracle_component::lp_pricing::*;
+use oracle_component::oracle_test::Oracle;
+use oracle_component::verifier::*;
+use oracle_component::{MessageOrdering, PriceMessage, PriceSource, RedemptionRateSource};
+use scrypto_test::prelude::*;
+
+struct OracleSetup {
+    env: TestEnvironment<InMemorySubstateDatabase>,
+    oracle: Oracle,
+    lsu_pool: LsuPool,
+    lsulp_address: ResourceAddress,
+    token_a: Bucket,
+    token_b: Bucket,
+}
+
+fn setup_oracle() -> Result<OracleSetup, RuntimeError> {
+    let mut env = TestEnvironmentBuilder::new().build();
+
+    let lsulp = ResourceBuilder::new_fungible(OwnerRole::None)
+        .divisibility(18)
+        .mint_initial_supply(10000, &mut env)?;
+    let token_a = ResourceBuilder::new_fungible(OwnerRole::None)
+        .divisibility(18)
+        .mint_initial_supply(1000000, &mut env)?;
+    let token_b = ResourceBuilder::new_fungible(OwnerRole::None)
+        .divisibility(18)
+        .mint_initial_supply(1000000, &mut env)?;
+
+    let lsulp_address = lsulp.resource_address(&mut env)?;
+
+    let lsu_pool_package_address = PackageFactory::compile_and_publish(
+        "./dummy_lsulp_component",
+        &mut env,
+        CompileProfile::Standard,
+    )?;
+    let lsu_pool = LsuPool::instantiate_token_pool(lsu_pool_package_address, &mut env)?;
+    let lsu_pool_address = ComponentAddress::try_from(lsu_pool.0.clone()).unwrap();
+
+    let oracle_package_address = PackageFactory::compile_and_publish(
+        "./oracle_component",
+        &mut env,
+        CompileProfile::Standard,
+    )?;
+    let oracle = Oracle::instantiate_oracle_with_lsu_pool(
+        OwnerRole::None,
+        lsu_pool_address,
+        lsu_pool_address.into(),
+        lsulp_address,
+        lsu_pool_address,
+        oracle_package_address,
+        &mut env,
+    )?;
+
+    Ok(OracleSetup {
+        env,
+        oracle,
+        lsu_pool,
+        lsulp_address,
+        token_a: token_a.into(),
+        token_b: token_b.into(),
+    })
+}
+
+fn signed_xrd_message(
+    env: &mut TestEnvironment<InMemorySubstateDatabase>,
+    price: Decimal,
+    nonce: u64,
+) -> String {
+    PriceMessage {
+        market_id: "GATEIO:XRD_USDT".to_string(),
+        price,
+        nonce,
+        created_at: env.get_current_time().seconds_since_unix_epoch as u64,
+    }
+    .encode()
+}
+
+fn check_signed_xrd_price(
+    oracle: &mut Oracle,
+    env: &mut TestEnvironment<InMemorySubstateDatabase>,
+    private_key: &Ed25519PrivateKey,
+    price: Decimal,
+    nonce: u64,
+) -> Result<Decimal, RuntimeError> {
+    let message = signed_xrd_message(env, price, nonce);
+    let signature = private_key
+        .sign(&keccak256_hash(message.as_bytes()))
+        .to_string();
+    oracle.check_price_input(XRD, message, signature, env)
+}
+
+fn advance_seconds(env: &mut TestEnvironment<InMemorySubstateDatabase>, seconds: i64) {
+    let new_time = env.get_current_time().add_seconds(seconds).unwrap();
+    env.set_current_time(new_time);
+}
+
+#[test]
+fn test_signed_price_ed25519() -> Result<(), RuntimeError> {
+    // Initialize oracle with a locally generated Ed25519 signer
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let private_key = Ed25519PrivateKey::from_u64(1).unwrap();
+    setup.oracle.set_price_verifier(
+        PriceVerifier::Signers {
+            signers: vec![SignerKey::Ed25519(private_key.public_key())],
+        },
+        env,
+    )?;
+
+    // Sign a price message for XRD
+    let message = signed_xrd_message(env, dec!("0.05"), 1);
+    let signature = private_key
+        .sign(&keccak256_hash(message.as_bytes()))
+        .to_string();
+
+    // The verified price is returned
+    let price = setup.oracle.check_price_input(XRD, message, signature, env)?;
+    assert_eq!(price, dec!("0.05"));
+
+    Ok(())
+}
+
+#[test]
+fn test_signed_price_secp256k1() -> Result<(), RuntimeError> {
+    // Initialize oracle with a locally generated Secp256k1 signer
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let private_key = Secp256k1PrivateKey::from_u64(1).unwrap();
+    setup.oracle.set_price_verifier(
+        PriceVerifier::Signers {
+            signers: vec![SignerKey::Secp256k1(private_key.public_key())],
+        },
+        env,
+    )?;
+
+    // Sign a price message for XRD
+    let message = signed_xrd_message(env, dec!("0.05"), 1);
+    let signature = private_key
+        .sign(&keccak256_hash(message.as_bytes()))
+        .to_string();
+
+    // The verified price is returned
+    let price = setup.oracle.check_price_input(XRD, message, signature, env)?;
+    assert_eq!(price, dec!("0.05"));
+
+    Ok(())
+}
+
+#[test]
+fn test_signed_price_rejects_invalid_signatures() -> Result<(), RuntimeError> {
+    // Initialize oracle with a registered signer
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let private_key = Ed25519PrivateKey::from_u64(1).unwrap();
+    let unknown_key = Ed25519PrivateKey::from_u64(2).unwrap();
+    setup.oracle.set_price_verifier(
+        PriceVerifier::Signers {
+            signers: vec![SignerKey::Ed25519(private_key.public_key())],
+        },
+        env,
+    )?;
+
+    let message = signed_xrd_message(env, dec!("0.05"), 1);
+    let signature = private_key
+        .sign(&keccak256_hash(message.as_bytes()))
+        .to_string();
+
+    // A tampered price fails verification
+    let tampered = signed_xrd_message(env, dec!("0.10"), 1);
+    let result = setup
+        .oracle
+        .check_price_input(XRD, tampered, signature.clone(), env);
+    assert!(result.is_err());
+
+    // A message signed by an unregistered key fails verification
+    let unknown_signature = unknown_key
+        .sign(&keccak256_hash(message.as_bytes()))
+        .to_string();
+    let result = setup
+        .oracle
+        .check_price_input(XRD, message.clone(), unknown_signature, env);
+    assert!(result.is_err());
+
+    // A removed signer is no longer trusted
+    setup
+        .oracle
+        .remove_signer(SignerKey::Ed25519(private_key.public_key()), env)?;
+    let result = setup.oracle.check_price_input(XRD, message, signature, env);
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_add_signer_requires_local_verifier() -> Result<(), RuntimeError> {
+    // Initialize oracle, which starts with the Morpher verifier
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let private_key = Ed25519PrivateKey::from_u64(1).unwrap();
+    let second_key = Ed25519PrivateKey::from_u64(2).unwrap();
+
+    // Adding a signer doesn't silently replace the Morpher verifier
+    let result = setup
+        .oracle
+        .add_signer(SignerKey::Ed25519(private_key.public_key()), env);
+    assert!(result.is_err());
+    let result = setup
+        .oracle
+        .remove_signer(SignerKey::Ed25519(private_key.public_key()), env);
+    assert!(result.is_err());
+
+    // After explicitly switching verifiers, more signers can be added
+    setup.oracle.set_price_verifier(
+        PriceVerifier::Signers {
+            signers: vec![SignerKey::Ed25519(private_key.public_key())],
+        },
+        env,
+    )?;
+    setup
+        .oracle
+        .add_signer(SignerKey::Ed25519(second_key.public_key()), env)?;
+    assert_eq!(
+        setup.oracle.get_price_verifier(env)?,
+        PriceVerifier::Signers {
+            signers: vec![
+                SignerKey::Ed25519(private_key.public_key()),
+                SignerKey::Ed25519(second_key.public_key()),
+            ],
+        }
+    );
+
+    // A message signed by the second key is accepted
+    let message = signed_xrd_message(env, dec!("0.05"), 1);
+    let signature = second_key
+        .sign(&keccak256_hash(message.as_bytes()))
+        .to_string();
+    let price = setup.oracle.check_price_input(XRD, message, signature, env)?;
+    assert_eq!(price, dec!("0.05"));
+
+    Ok(())
+}
+
+#[test]
+fn test_lp_token_price_resists_spot_manipulation() -> Result<(), RuntimeError> {
+    // Initialize oracle with a pushed price of 2 for token A, token B is fixed at 1
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let token_a_address = setup.token_a.resource_address(env)?;
+    let token_b_address = setup.token_b.resource_address(env)?;
+
+    setup.oracle.set_price_sources(
+        token_a_address,
+        vec![PriceSource::PushFeed { feed_id: "TOKEN_A".to_string() }],
+        env,
+    )?;
+    setup.oracle.push_price("TOKEN_A".to_string(), dec!(2), env)?;
+
+    // Create a balanced pool: 1000 A (worth 2000) and 2000 B (worth 2000)
+    let dex_package_address = PackageFactory::compile_and_publish(
+        "./dummy_dex_pool_component",
+        env,
+        CompileProfile::Standard,
+    )?;
+    let (mut pool, lp_tokens) = DexPool::instantiate_dex_pool(
+        setup.token_a.take(dec!(1000), env)?,
+        setup.token_b.take(dec!(2000), env)?,
+        dex_package_address,
+        env,
+    )?;
+    let lp_address = lp_tokens.resource_address(env)?;
+    let lp_supply = lp_tokens.amount(env)?;
+
+    setup.oracle.register_lp_token(
+        lp_address,
+        LpTokenInfo {
+            pool: ComponentAddress::try_from(pool.0.clone()).unwrap(),
+            reserves_method: "get_vault_amounts".to_string(),
+            underlying_a: LpUnderlying {
+                resource: token_a_address,
+                fixed_price: None,
+            },
+            underlying_b: LpUnderlying {
+                resource: token_b_address,
+                fixed_price: Some(dec!(1)),
+            },
+        },
+        env,
+    )?;
+
+    // Fair price equals the spot value per LP token in a balanced pool
+    let price_before = setup
+        .oracle
+        .check_price_input(lp_address, "".to_string(), "".to_string(), env)?;
+    let expected = dec!(4000) / lp_supply;
+    assert!((price_before - expected).checked_abs().unwrap() < dec!("0.000001"));
+
+    // Skew the pool with a large swap, which inflates the naive spot valuation
+    let _ = pool.swap(setup.token_a.take(dec!(1000), env)?, env)?;
+    let price_after = setup
+        .oracle
+        .check_price_input(lp_address, "".to_string(), "".to_string(), env)?;
+
+    // The fair price is unaffected since the invariant did not change
+    assert!((price_after - price_before).checked_abs().unwrap() < dec!("0.000001"));
+
+    // A drop in the oracle price of token A lowers the LP price
+    setup.oracle.push_price("TOKEN_A".to_string(), dec!(1), env)?;
+    let price_dropped = setup
+        .oracle
+        .check_price_input(lp_address, "".to_string(), "".to_string(), env)?;
+    assert!(price_dropped < price_before);
+
+    Ok(())
+}
+
+#[test]
+fn test_lp_token_requires_distinct_underlyings() -> Result<(), RuntimeError> {
+    // Initialize oracle
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let token_a_address = setup.token_a.resource_address(env)?;
+
+    // Registering an LP token with the same underlying twice fails
+    let result = setup.oracle.register_lp_token(
+        token_a_address,
+        LpTokenInfo {
+            pool: ComponentAddress::try_from(setup.oracle.0.clone()).unwrap(),
+            reserves_method: "get_vault_amounts".to_string(),
+            underlying_a: LpUnderlying {
+                resource: token_a_address,
+                fixed_price: None,
+            },
+            underlying_b: LpUnderlying {
+                resource: token_a_address,
+                fixed_price: None,
+            },
+        },
+        env,
+    );
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_lp_token_requires_single_signed_market() -> Result<(), RuntimeError> {
+    // Initialize oracle with two tokens priced from different signed markets
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let token_a_address = setup.token_a.resource_address(env)?;
+    let token_b_address = setup.token_b.resource_address(env)?;
+    setup
+        .oracle
+        .add_morpher_identifier(token_a_address, "GATEIO:XRD_USDT".to_string(), env)?;
+    setup
+        .oracle
+        .add_morpher_identifier(token_b_address, "GATEIO:BTC_USDT".to_string(), env)?;
+
+    let pool_address = ComponentAddress::try_from(setup.oracle.0.clone()).unwrap();
+    let lp_info = |fixed_price_b: Option<Decimal>| LpTokenInfo {
+        pool: pool_address,
+        reserves_method: "get_vault_amounts".to_string(),
+        underlying_a: LpUnderlying {
+            resource: token_a_address,
+            fixed_price: None,
+        },
+        underlying_b: LpUnderlying {
+            resource: token_b_address,
+            fixed_price: fixed_price_b,
+        },
+    };
+
+    // A single message can't price both underlyings
+    let result = setup
+        .oracle
+        .register_lp_token(token_a_address, lp_info(None), env);
+    assert!(result.is_err());
+
+    // With token B at a fixed price only one signed market is needed
+    setup
+        .oracle
+        .register_lp_token(token_a_address, lp_info(Some(dec!(1))), env)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_lp_token_markets_are_rechecked_on_underlying_changes() -> Result<(), RuntimeError> {
+    // Initialize oracle with two tokens priced from the same signed market
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let token_a_address = setup.token_a.resource_address(env)?;
+    let token_b_address = setup.token_b.resource_address(env)?;
+    setup
+        .oracle
+        .add_morpher_identifier(token_a_address, "GATEIO:XRD_USDT".to_string(), env)?;
+    setup
+        .oracle
+        .add_morpher_identifier(token_b_address, "GATEIO:XRD_USDT".to_string(), env)?;
+
+    let pool_address = ComponentAddress::try_from(setup.oracle.0.clone()).unwrap();
+    setup.oracle.register_lp_token(
+        token_a_address,
+        LpTokenInfo {
+            pool: pool_address,
+            reserves_method: "get_vault_amounts".to_string(),
+            underlying_a: LpUnderlying {
+                resource: token_a_address,
+                fixed_price: None,
+            },
+            underlying_b: LpUnderlying {
+                resource: token_b_address,
+                fixed_price: None,
+            },
+        },
+        env,
+    )?;
+
+    // Token B can't be moved to another signed market
+    let result = setup
+        .oracle
+        .add_morpher_identifier(token_b_address, "GATEIO:BTC_USDT".to_string(), env);
+    assert!(result.is_err());
+
+    // Token B can move to another market if it isn't priced from signed messages there
+    setup.oracle.set_price_sources(
+        token_b_address,
+        vec![PriceSource::PushFeed {
+            feed_id: "TOKEN_B".to_string(),
+        }],
+        env,
+    )?;
+    setup
+        .oracle
+        .add_morpher_identifier(token_b_address, "GATEIO:BTC_USDT".to_string(), env)?;
+
+    // But then it can't go back to signed messages
+    let result = setup
+        .oracle
+        .set_price_sources(token_b_address, vec![PriceSource::SignedMessage], env);
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_median_price_of_odd_number_of_sources() -> Result<(), RuntimeError> {
+    // Initialize oracle with a signed message and two pushed feeds for XRD
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let private_key = Ed25519PrivateKey::from_u64(1).unwrap();
+    setup.oracle.set_price_verifier(
+        PriceVerifier::Signers {
+            signers: vec![SignerKey::Ed25519(private_key.public_key())],
+        },
+        env,
+    )?;
+    setup.oracle.set_price_sources(
+        XRD,
+        vec![
+            PriceSource::SignedMessage,
+            PriceSource::PushFeed { feed_id: "XRD_A".to_string() },
+            PriceSource::PushFeed { feed_id: "XRD_B".to_string() },
+        ],
+        env,
+    )?;
+    setup.oracle.push_price("XRD_A".to_string(), dec!("0.052"), env)?;
+    setup.oracle.push_price("XRD_B".to_string(), dec!("0.051"), env)?;
+
+    let message = signed_xrd_message(env, dec!("0.05"), 1);
+    let signature = private_key
+        .sign(&keccak256_hash(message.as_bytes()))
+        .to_string();
+
+    // The middle price is returned
+    let price = setup.oracle.check_price_input(XRD, message, signature, env)?;
+    assert_eq!(price, dec!("0.051"));
+
+    Ok(())
+}
+
+#[test]
+fn test_median_price_of_even_number_of_sources() -> Result<(), RuntimeError> {
+    // Initialize oracle with two pushed feeds for token A
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let token_a_address = setup.token_a.resource_address(env)?;
+    setup.oracle.set_price_sources(
+        token_a_address,
+        vec![
+            PriceSource::PushFeed { feed_id: "TOKEN_A_1".to_string() },
+            PriceSource::PushFeed { feed_id: "TOKEN_A_2".to_string() },
+        ],
+        env,
+    )?;
+    setup.oracle.push_price("TOKEN_A_1".to_string(), dec!(2), env)?;
+    setup.oracle.push_price("TOKEN_A_2".to_string(), dec!("2.04"), env)?;
+
+    // The average of the two middle prices is returned
+    let price = setup
+        .oracle
+        .check_price_input(token_a_address, "".to_string(), "".to_string(), env)?;
+    assert_eq!(price, dec!("2.02"));
+
+    Ok(())
+}
+
+#[test]
+fn test_median_price_rejects_large_spread() -> Result<(), RuntimeError> {
+    // Initialize oracle with two pushed feeds for token A that are 10% apart
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let token_a_address = setup.token_a.resource_address(env)?;
+    setup.oracle.set_price_sources(
+        token_a_address,
+        vec![
+            PriceSource::PushFeed { feed_id: "TOKEN_A_1".to_string() },
+            PriceSource::PushFeed { feed_id: "TOKEN_A_2".to_string() },
+        ],
+        env,
+    )?;
+    setup.oracle.push_price("TOKEN_A_1".to_string(), dec!(2), env)?;
+    setup.oracle.push_price("TOKEN_A_2".to_string(), dec!("2.2"), env)?;
+
+    // The default maximum spread of 5% is exceeded
+    let result = setup
+        .oracle
+        .check_price_input(token_a_address, "".to_string(), "".to_string(), env);
+    assert!(result.is_err());
+
+    // After raising the maximum spread, the median is accepted
+    setup.oracle.set_max_price_spread(dec!("0.15"), env)?;
+    let price = setup
+        .oracle
+        .check_price_input(token_a_address, "".to_string(), "".to_string(), env)?;
+    assert_eq!(price, dec!("2.1"));
+
+    Ok(())
+}
+
+#[test]
+fn test_batch_prices_of_collaterals_sharing_a_market() -> Result<(), RuntimeError> {
+    // Initialize oracle with tokens A and B on one market, each priced by its own pushed feed
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let token_a_address = setup.token_a.resource_address(env)?;
+    let token_b_address = setup.token_b.resource_address(env)?;
+    setup.oracle.add_morpher_identifier(token_a_address, "SHARED".to_string(), env)?;
+    setup.oracle.add_morpher_identifier(token_b_address, "SHARED".to_string(), env)?;
+    setup.oracle.set_price_sources(
+        token_a_address,
+        vec![PriceSource::PushFeed { feed_id: "TOKEN_A".to_string() }],
+        env,
+    )?;
+    setup.oracle.set_price_sources(
+        token_b_address,
+        vec![PriceSource::PushFeed { feed_id: "TOKEN_B".to_string() }],
+        env,
+    )?;
+    setup.oracle.push_price("TOKEN_A".to_string(), dec!(2), env)?;
+    setup.oracle.push_price("TOKEN_B".to_string(), dec!(3), env)?;
+
+    // Each collateral is priced from its own sources, not from the first price of the market
+    let prices = setup.oracle.check_price_inputs(
+        vec![
+            (token_a_address, "".to_string(), "".to_string()),
+            (token_b_address, "".to_string(), "".to_string()),
+        ],
+        env,
+    )?;
+    assert_eq!(prices, vec![(token_a_address, dec!(2)), (token_b_address, dec!(3))]);
+
+    Ok(())
+}
+
+#[test]
+fn test_median_price_with_dex_pool_source() -> Result<(), RuntimeError> {
+    // Initialize oracle and a pool of 1000 A and 2000 B, so token A trades at 2
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let token_a_address = setup.token_a.resource_address(env)?;
+    let dex_package_address = PackageFactory::compile_and_publish(
+        "./dummy_dex_pool_component",
+        env,
+        CompileProfile::Standard,
+    )?;
+    let (mut pool, _lp_tokens) = DexPool::instantiate_dex_pool(
+        setup.token_a.take(dec!(1000), env)?,
+        setup.token_b.take(dec!(2000), env)?,
+        dex_package_address,
+        env,
+    )?;
+
+    // Price token A from the pool reserves and a pushed feed
+    setup.oracle.set_price_sources(
+        token_a_address,
+        vec![
+            PriceSource::DexPool {
+                component: ComponentAddress::try_from(pool.0.clone()).unwrap(),
+                method_name: "get_spot_price".to_string(),
+            },
+            PriceSource::PushFeed { feed_id: "TOKEN_A".to_string() },
+        ],
+        env,
+    )?;
+    setup.oracle.push_price("TOKEN_A".to_string(), dec!("2.02"), env)?;
+
+    let price = setup
+        .oracle
+        .check_price_input(token_a_address, "".to_string(), "".to_string(), env)?;
+    assert_eq!(price, dec!("2.01"));
+
+    // Moving the pool price far away from the pushed feed makes the sources disagree
+    let _ = pool.swap(setup.token_a.take(dec!(500), env)?, env)?;
+    let result = setup
+        .oracle
+        .check_price_input(token_a_address, "".to_string(), "".to_string(), env);
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_single_morpher_source_is_unchanged() -> Result<(), RuntimeError> {
+    // Initialize oracle with a Morpher verifier and no extra price sources
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let morpher_package_address = PackageFactory::compile_and_publish(
+        "./dummy_morpher_component",
+        env,
+        CompileProfile::Standard,
+    )?;
+    let morpher = MorpherOracle::instantiate_morpher_oracle(morpher_package_address, env)?;
+    setup.oracle.set_price_verifier(
+        PriceVerifier::Morpher {
+            oracle_address: ComponentAddress::try_from(morpher.0.clone()).unwrap(),
+        },
+        env,
+    )?;
+    assert_eq!(
+        setup.oracle.get_price_sources(XRD, env)?,
+        vec![PriceSource::SignedMessage]
+    );
+
+    let message = signed_xrd_message(env, dec!("0.05"), 1);
+
+    // A single collateral is priced at the signed price
+    let price = setup
+        .oracle
+        .check_price_input(XRD, message.clone(), "signature".to_string(), env)?;
+    assert_eq!(price, dec!("0.05"));
+
+    // A batch prices the LSULP at the same market times its redemption rate of 1.1
+    let prices = setup.oracle.check_price_inputs(
+        vec![
+            (XRD, message.clone(), "signature".to_string()),
+            (setup.lsulp_address, message.clone(), "signature".to_string()),
+        ],
+        env,
+    )?;
+    assert_eq!(
+        prices,
+        vec![(XRD, dec!("0.05")), (setup.lsulp_address, dec!("0.055"))]
+    );
+
+    // A signature rejected by Morpher is rejected by the oracle
+    let result = setup
+        .oracle
+        .check_price_input(XRD, message, "invalid".to_string(), env);
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_price_history_ring_buffer() -> Result<(), RuntimeError> {
+    // Initialize oracle with a signer and a history of 3 observations
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let private_key = Ed25519PrivateKey::from_u64(1).unwrap();
+    setup.oracle.set_price_verifier(
+        PriceVerifier::Signers {
+            signers: vec![SignerKey::Ed25519(private_key.public_key())],
+        },
+        env,
+    )?;
+    setup.oracle.set_twap_parameters(3, 3600, 3600, env)?;
+
+    // Verify 4 prices, one minute apart
+    for (nonce, price) in [dec!("0.05"), dec!("0.06"), dec!("0.07"), dec!("0.08")]
+        .into_iter()
+        .enumerate()
+    {
+        check_signed_xrd_price(&mut setup.oracle, env, &private_key, price, nonce as u64)?;
+        advance_seconds(env, 60);
+    }
+
+    // Only the newest 3 are kept, oldest first
+    let history = setup
+        .oracle
+        .get_price_history("GATEIO:XRD_USDT".to_string(), env)?;
+    let prices: Vec<Decimal> = history.iter().map(|observation| observation.price).collect();
+    assert_eq!(prices, vec![dec!("0.06"), dec!("0.07"), dec!("0.08")]);
+
+    // Shrinking the buffer keeps the newest observations
+    setup.oracle.set_twap_parameters(2, 3600, 3600, env)?;
+    let history = setup
+        .oracle
+        .get_price_history("GATEIO:XRD_USDT".to_string(), env)?;
+    let prices: Vec<Decimal> = history.iter().map(|observation| observation.price).collect();
+    assert_eq!(prices, vec![dec!("0.07"), dec!("0.08")]);
+
+    // A message that isn't newer than the newest observation is not recorded again
+    let message = signed_xrd_message(env, dec!("0.09"), 10);
+    let signature = private_key
+        .sign(&keccak256_hash(message.as_bytes()))
+        .to_string();
+    setup
+        .oracle
+        .check_price_input(XRD, message.clone(), signature.clone(), env)?;
+    setup.oracle.check_price_input(XRD, message, signature, env)?;
+    let history = setup
+        .oracle
+        .get_price_history("GATEIO:XRD_USDT".to_string(), env)?;
+    let prices: Vec<Decimal> = history.iter().map(|observation| observation.price).collect();
+    assert_eq!(prices, vec![dec!("0.08"), dec!("0.09")]);
+
+    Ok(())
+}
+
+#[test]
+fn test_twap_is_time_weighted() -> Result<(), RuntimeError> {
+    // Initialize oracle with a signer
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let private_key = Ed25519PrivateKey::from_u64(1).unwrap();
+    setup.oracle.set_price_verifier(
+        PriceVerifier::Signers {
+            signers: vec![SignerKey::Ed25519(private_key.public_key())],
+        },
+        env,
+    )?;
+
+    // 0.05 holds for 1200 seconds, then 0.08 holds for 300 seconds
+    check_signed_xrd_price(&mut setup.oracle, env, &private_key, dec!("0.05"), 1)?;
+    advance_seconds(env, 1200);
+    check_signed_xrd_price(&mut setup.oracle, env, &private_key, dec!("0.08"), 2)?;
+    advance_seconds(env, 300);
+
+    // Each price is weighted by how long it held: (0.05 * 1200 + 0.08 * 300) / 1500
+    let twap = setup.oracle.get_twap_price(XRD, env)?;
+    assert_eq!(twap, dec!("0.056"));
+
+    // With a 600 second window, only the last 300 seconds of the first price count
+    setup.oracle.set_twap_parameters(60, 600, 3600, env)?;
+    let twap = setup.oracle.get_twap_price(XRD, env)?;
+    assert_eq!(twap, dec!("0.065"));
+
+    Ok(())
+}
+
+#[test]
+fn test_twap_staleness_limit() -> Result<(), RuntimeError> {
+    // Initialize oracle with a signer and a staleness limit of 600 seconds
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let private_key = Ed25519PrivateKey::from_u64(1).unwrap();
+    setup.oracle.set_price_verifier(
+        PriceVerifier::Signers {
+            signers: vec![SignerKey::Ed25519(private_key.public_key())],
+        },
+        env,
+    )?;
+    setup.oracle.set_twap_parameters(60, 3600, 600, env)?;
+
+    // Without any history there is no TWAP
+    let result = setup.oracle.get_twap_price(XRD, env);
+    assert!(result.is_err());
+
+    // Just within the staleness limit the TWAP is available
+    check_signed_xrd_price(&mut setup.oracle, env, &private_key, dec!("0.05"), 1)?;
+    advance_seconds(env, 599);
+    let twap = setup.oracle.get_twap_price(XRD, env)?;
+    assert_eq!(twap, dec!("0.05"));
+
+    // Once the newest observation is too old, the TWAP is rejected
+    advance_seconds(env, 1);
+    let result = setup.oracle.get_twap_price(XRD, env);
+    assert!(result.is_err());
+
+    // A signed price is never silently replaced by the TWAP
+    let result = setup
+        .oracle
+        .check_price_input(XRD, "".to_string(), "".to_string(), env);
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_unordered_ordering_accepts_older_messages() -> Result<(), RuntimeError> {
+    // Initialize oracle with a signer and unordered messages for the XRD market
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let private_key = Ed25519PrivateKey::from_u64(1).unwrap();
+    setup.oracle.set_price_verifier(
+        PriceVerifier::Signers {
+            signers: vec![SignerKey::Ed25519(private_key.public_key())],
+        },
+        env,
+    )?;
+    setup.oracle.set_message_ordering(
+        Some("GATEIO:XRD_USDT".to_string()),
+        MessageOrdering::Unordered,
+        env,
+    )?;
+
+    let old_message = signed_xrd_message(env, dec!("0.05"), 1);
+    let old_signature = private_key
+        .sign(&keccak256_hash(old_message.as_bytes()))
+        .to_string();
+    advance_seconds(env, 10);
+    check_signed_xrd_price(&mut setup.oracle, env, &private_key, dec!("0.06"), 2)?;
+
+    // An older message and a reused message are both accepted
+    let price = setup
+        .oracle
+        .check_price_input(XRD, old_message.clone(), old_signature.clone(), env)?;
+    assert_eq!(price, dec!("0.05"));
+    let price = setup
+        .oracle
+        .check_price_input(XRD, old_message, old_signature, env)?;
+    assert_eq!(price, dec!("0.05"));
+
+    // The newest message stays the latest accepted price
+    let latest = setup
+        .oracle
+        .get_latest_price("GATEIO:XRD_USDT".to_string(), env)?
+        .unwrap();
+    assert_eq!(latest.price, dec!("0.06"));
+    assert_eq!(latest.nonce, 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_older_messages_are_rejected_by_default() -> Result<(), RuntimeError> {
+    // Initialize oracle with a signer, leaving the default monotonic ordering
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let private_key = Ed25519PrivateKey::from_u64(1).unwrap();
+    setup.oracle.set_price_verifier(
+        PriceVerifier::Signers {
+            signers: vec![SignerKey::Ed25519(private_key.public_key())],
+        },
+        env,
+    )?;
+
+    let old_message = signed_xrd_message(env, dec!("0.05"), 1);
+    let old_signature = private_key
+        .sign(&keccak256_hash(old_message.as_bytes()))
+        .to_string();
+    advance_seconds(env, 10);
+    let new_message = signed_xrd_message(env, dec!("0.06"), 2);
+    let new_signature = private_key
+        .sign(&keccak256_hash(new_message.as_bytes()))
+        .to_string();
+
+    // The newest message can be used more than once
+    setup
+        .oracle
+        .check_price_input(XRD, new_message.clone(), new_signature.clone(), env)?;
+    setup
+        .oracle
+        .check_price_input(XRD, new_message, new_signature, env)?;
+
+    // An older message is rejected
+    let result = setup
+        .oracle
+        .check_price_input(XRD, old_message, old_signature, env);
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_single_use_ordering_rejects_reused_messages() -> Result<(), RuntimeError> {
+    // Initialize oracle with a signer and single use ordering for the XRD market
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let private_key = Ed25519PrivateKey::from_u64(1).unwrap();
+    setup.oracle.set_price_verifier(
+        PriceVerifier::Signers {
+            signers: vec![SignerKey::Ed25519(private_key.public_key())],
+        },
+        env,
+    )?;
+    setup.oracle.set_message_ordering(
+        Some("GATEIO:XRD_USDT".to_string()),
+        MessageOrdering::SingleUse,
+        env,
+    )?;
+
+    let message = signed_xrd_message(env, dec!("0.05"), 1);
+    let signature = private_key
+        .sign(&keccak256_hash(message.as_bytes()))
+        .to_string();
+
+    // The message can be used once
+    setup
+        .oracle
+        .check_price_input(XRD, message.clone(), signature.clone(), env)?;
+    let result = setup.oracle.check_price_input(XRD, message, signature, env);
+    assert!(result.is_err());
+
+    // A new message with the same timestamp but another nonce is accepted
+    check_signed_xrd_price(&mut setup.oracle, env, &private_key, dec!("0.05"), 2)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_single_use_ordering_prices_lp_token_with_one_message() -> Result<(), RuntimeError> {
+    // Initialize oracle with single use ordering, and two tokens priced from the XRD market
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let private_key = Ed25519PrivateKey::from_u64(1).unwrap();
+    setup.oracle.set_price_verifier(
+        PriceVerifier::Signers {
+            signers: vec![SignerKey::Ed25519(private_key.public_key())],
+        },
+        env,
+    )?;
+    setup.oracle.set_message_ordering(
+        Some("GATEIO:XRD_USDT".to_string()),
+        MessageOrdering::SingleUse,
+        env,
+    )?;
+
+    let token_a_address = setup.token_a.resource_address(env)?;
+    let token_b_address = setup.token_b.resource_address(env)?;
+    setup
+        .oracle
+        .add_morpher_identifier(token_a_address, "GATEIO:XRD_USDT".to_string(), env)?;
+    setup
+        .oracle
+        .add_morpher_identifier(token_b_address, "GATEIO:XRD_USDT".to_string(), env)?;
+
+    // Create a pool of 1000 A and 1000 B, both signed-priced
+    let dex_package_address = PackageFactory::compile_and_publish(
+        "./dummy_dex_pool_component",
+        env,
+        CompileProfile::Standard,
+    )?;
+    let (pool, lp_tokens) = DexPool::instantiate_dex_pool(
+        setup.token_a.take(dec!(1000), env)?,
+        setup.token_b.take(dec!(1000), env)?,
+        dex_package_address,
+        env,
+    )?;
+    let lp_address = lp_tokens.resource_address(env)?;
+    let lp_supply = lp_tokens.amount(env)?;
+
+    setup.oracle.register_lp_token(
+        lp_address,
+        LpTokenInfo {
+            pool: ComponentAddress::try_from(pool.0.clone()).unwrap(),
+            reserves_method: "get_vault_amounts".to_string(),
+            underlying_a: LpUnderlying {
+                resource: token_a_address,
+                fixed_price: None,
+            },
+            underlying_b: LpUnderlying {
+                resource: token_b_address,
+                fixed_price: None,
+            },
+        },
+        env,
+    )?;
+
+    // Both underlyings are priced from a single verification of the message
+    let message = signed_xrd_message(env, dec!("0.05"), 1);
+    let signature = private_key
+        .sign(&keccak256_hash(message.as_bytes()))
+        .to_string();
+    let price = setup
+        .oracle
+        .check_price_input(lp_address, message.clone(), signature.clone(), env)?;
+    let expected = dec!(100) / lp_supply;
+    assert!((price - expected).checked_abs().unwrap() < dec!("0.000001"));
+
+    // The message is used up afterwards
+    let result = setup
+        .oracle
+        .check_price_input(lp_address, message, signature, env);
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_stale_redemption_rate_is_updated() -> Result<(), RuntimeError> {
+    // Initialize oracle with a signer, the LSULP rate starts at 11000 / 10000 = 1.1
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let private_key = Ed25519PrivateKey::from_u64(1).unwrap();
+    setup.oracle.set_price_verifier(
+        PriceVerifier::Signers {
+            signers: vec![SignerKey::Ed25519(private_key.public_key())],
+        },
+        env,
+    )?;
+
+    // The LSU pool valuation rises, but the cached rate is still fresh
+    setup.lsu_pool.set_dex_valuation_xrd(dec!(12000), env)?;
+    let message = signed_xrd_message(env, dec!("0.05"), 1);
+    let signature = private_key
+        .sign(&keccak256_hash(message.as_bytes()))
+        .to_string();
+    let price = setup
+        .oracle
+        .check_price_input(setup.lsulp_address, message, signature, env)?;
+    assert_eq!(price, dec!("0.055"));
+
+    // Once the rate is older than its maximum age of one day, pricing fetches a new one
+    advance_seconds(env, 86401);
+    let message = signed_xrd_message(env, dec!("0.05"), 2);
+    let signature = private_key
+        .sign(&keccak256_hash(message.as_bytes()))
+        .to_string();
+    let price = setup
+        .oracle
+        .check_price_input(setup.lsulp_address, message, signature, env)?;
+    assert_eq!(price, dec!("0.06"));
+
+    // The LSU multiplier methods act on the LSULP registry entry
+    setup.lsu_pool.set_dex_valuation_xrd(dec!(13000), env)?;
+    setup.oracle.update_lsu_multiplier(env)?;
+    setup.oracle.set_max_lsu_multiplier_age(2, env)?;
+    let derivatives = setup.oracle.get_derivatives(env)?;
+    let (_, lsulp_info) = derivatives
+        .iter()
+        .find(|(address, _)| *address == setup.lsulp_address)
+        .unwrap();
+    assert_eq!(lsulp_info.redemption_rate, dec!("1.3"));
+    assert_eq!(lsulp_info.max_rate_age, 172800);
+
+    Ok(())
+}
+
+#[test]
+fn test_redemption_rate_age_is_per_derivative() -> Result<(), RuntimeError> {
+    // Initialize oracle with a signer and a second derivative with a maximum rate age of one minute
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+    env.disable_auth_module();
+
+    let private_key = Ed25519PrivateKey::from_u64(1).unwrap();
+    setup.oracle.set_price_verifier(
+        PriceVerifier::Signers {
+            signers: vec![SignerKey::Ed25519(private_key.public_key())],
+        },
+        env,
+    )?;
+
+    // Token A has a supply of 1000000, so its rate starts at 11000 / 1000000 = 0.011
+    let token_a_address = setup.token_a.resource_address(env)?;
+    setup.oracle.register_derivative(
+        token_a_address,
+        "GATEIO:XRD_USDT".to_string(),
+        RedemptionRateSource::ValuationPerSupply {
+            component: ComponentAddress::try_from(setup.lsu_pool.0.clone()).unwrap(),
+            valuation_method: "get_dex_valuation_xrd".to_string(),
+        },
+        60,
+        env,
+    )?;
+
+    // After two minutes, only token A's rate is stale
+    setup.lsu_pool.set_dex_valuation_xrd(dec!(22000), env)?;
+    advance_seconds(env, 120);
+
+    let message = signed_xrd_message(env, dec!("0.05"), 1);
+    let signature = private_key
+        .sign(&keccak256_hash(message.as_bytes()))
+        .to_string();
+    let prices = setup.oracle.check_price_inputs(
+        vec![
+            (setup.lsulp_address, message.clone(), signature.clone()),
+            (token_a_address, message, signature),
+        ],
+        env,
+    )?;
+
+    // The LSULP keeps its cached rate of 1.1, token A is updated to 0.022
+    assert_eq!(
+        prices,
+        vec![
+            (setup.lsulp_address, dec!("0.055")),
+            (token_a_address, dec!("0.0011")),
+        ]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_derivative_registration_is_owner_only() -> Result<(), RuntimeError> {
+    // Initialize oracle, keeping the auth module enabled
+    let mut setup = setup_oracle()?;
+    let env = &mut setup.env;
+
+    let token_a_address = setup.token_a.resource_address(env)?;
+    let lsu_pool_address = ComponentAddress::try_from(setup.lsu_pool.0.clone()).unwrap();
+
+    // Registering, removing and configuring derivatives requires the owner
+    let result = setup.oracle.register_derivative(
+        token_a_address,
+        "GATEIO:XRD_USDT".to_string(),
+        RedemptionRateSource::ValuationPerSupply {
+            component: lsu_pool_address,
+            valuation_method: "get_dex_valuation_xrd".to_string(),
+        },
+        60,
+        env,
+    );
+    assert!(result.is_err());
+
+    let result = setup
+        .oracle
+        .set_max_redemption_rate_age(setup.lsulp_address, 60, env);
+    assert!(result.is_err());
+
+    let result = setup.oracle.set_max_lsu_multiplier_age(2, env);
+    assert!(result.is_err());
+
+    let result = setup.oracle.remove_derivative(setup.lsulp_address, env);
+    assert!(result.is_err());
+
+    // Updating a rate from its registered source is public
+    setup.oracle.update_redemption_rate(setup.lsulp_address, env)?;
+    setup.oracle.update_lsu_multiplier(env)?;
+
+    Ok(())
+}
@@ -1,6 +1,8 @@
 mod helper;
 use helper::Helper;
 use flux_protocol::shared_structs::*;
+use flux_protocol::flash_loans::FlashLoanFeeDestination;
+use dummy_flash_borrower_component::dummy_flash_borrower_component_test::*;
 
 use scrypto_test::prelude::*;
 use scrypto::prelude::Url;
@@ -532,6 +534,105 @@ fn test_close_cdp_with_wrong_token() -> Result<(), RuntimeError> {
 
 
 
+#[test]
+fn test_top_up_cdp_with_twap() -> Result<(), RuntimeError> {
+    // Initialize helper and open a CDP
+    let mut helper = Helper::new().unwrap();
+
+    let bucket = helper.xrd.take(dec!(1000), &mut helper.env)?;
+    let top_up_bucket = helper.xrd.take(dec!(500), &mut helper.env)?;
+    let (_fusd, cdp_receipt) = helper.proxy_open_cdp(None, bucket, dec!(50), dec!(0.01))?;
+    let receipt_id = NonFungibleLocalId::from(1);
+    let (_, initial_cdp_info, _) = helper.get_cdp_info(receipt_id.clone())?;
+
+    // Top up without a signed price, using the oracle's time-weighted average price instead
+    let receipt_proof = NonFungibleProof(cdp_receipt.create_proof_of_all(&mut helper.env)?);
+    helper
+        .proxy
+        .top_up_cdp_with_twap(receipt_proof, top_up_bucket, &mut helper.env)?;
+
+    // Collateral increased, debt is unchanged
+    let (_, cdp_info, _) = helper.get_cdp_info(receipt_id)?;
+    assert_eq!(cdp_info.collateral_amount, dec!(1500));
+    assert_eq!(cdp_info.pool_debt, initial_cdp_info.pool_debt);
+
+    Ok(())
+}
+
+#[test]
+fn test_top_up_marked_cdp_with_twap_fails() -> Result<(), RuntimeError> {
+    // Initialize helper
+    let mut helper = Helper::new().unwrap();
+    helper.env.disable_auth_module();
+
+    // Create a privileged borrower with a liquidation notice, so a liquidation attempt marks the CDP
+    let privileged_borrower_data = PrivilegedBorrowerData {
+        redemption_opt_out: true,
+        liquidation_notice: Some(1),
+        max_coupled_loans: 10,
+        coupled_loans: vec![],
+        key_image_url: Url::of("https://flux.ilikeitstable.com/flux-generator.png"),
+    };
+    let privileged_borrower = helper.proxy.create_privileged_borrower(
+        privileged_borrower_data,
+        &mut helper.env
+    )?;
+    let privileged_borrower_proof = NonFungibleProof(privileged_borrower.create_proof_of_all(&mut helper.env)?);
+
+    // Open a CDP as the privileged borrower
+    let bucket = helper.xrd.take(dec!(1000), &mut helper.env)?;
+    let top_up_bucket = helper.xrd.take(dec!(500), &mut helper.env)?;
+    let (_fusd, cdp_receipt) = helper.proxy.open_cdp(
+        Some(privileged_borrower_proof),
+        bucket,
+        dec!(400),
+        dec!(0.01),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    let receipt_id = NonFungibleLocalId::from(1);
+
+    // Fill the stability pool and drop the price, so the liquidation attempt marks the CDP
+    let pool_fusd = helper.free_fusd(dec!(500))?;
+    helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        pool_fusd,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    helper.change_collateral_price("XRD".to_string(), dec!(0.4))?;
+    helper.stability_pools.liquidate(
+        receipt_id.clone(),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    let (_, cdp_info, _) = helper.get_cdp_info(receipt_id.clone())?;
+    assert_eq!(cdp_info.status, CdpStatus::Marked);
+
+    // The time-weighted average price lags behind and is still well above the spot price
+    helper.change_collateral_price("XRD".to_string(), dec!(1))?;
+
+    // Topping up the marked CDP without a signed price is refused
+    let receipt_proof = NonFungibleProof(cdp_receipt.create_proof_of_all(&mut helper.env)?);
+    let result = helper
+        .proxy
+        .top_up_cdp_with_twap(receipt_proof, top_up_bucket, &mut helper.env);
+    assert!(result.is_err(), "Marked CDP should not be topped up with the TWAP");
+
+    // The stored price is untouched and the CDP is still marked
+    let collateral_info = &helper.flux.get_collateral_infos(vec![helper.xrd_address], &mut helper.env)?[0];
+    assert_eq!(collateral_info.usd_price, dec!(0.4));
+    let (_, cdp_info, _) = helper.get_cdp_info(receipt_id)?;
+    assert_eq!(cdp_info.status, CdpStatus::Marked);
+    assert_eq!(cdp_info.collateral_amount, dec!(1000));
+
+    Ok(())
+}
+
 #[test]
 fn test_top_up_cdp_with_wrong_resource() -> Result<(), RuntimeError> {
     // Initialize helper and create buckets
@@ -1319,3 +1420,186 @@ fn test_compound_realistic_user_operations() -> Result<(), RuntimeError> {
     
     Ok(())
 }
+
+#[test]
+fn test_flash_loan_collateral() -> Result<(), RuntimeError> {
+    // Initialize helper and open a CDP so the Flux vault holds XRD
+    let mut helper = Helper::new().unwrap();
+    let bucket = helper.xrd.take(dec!(1000), &mut helper.env)?;
+    let (_fusd, _cdp_receipt) = helper.proxy_open_cdp(None, bucket, dec!(100), dec!(0.01))?;
+
+    // Fill the XRD stability pool and set a 1% collateral flash loan fee
+    helper.env.disable_auth_module();
+    let free_fusd = helper.free_fusd(dec!(500))?;
+    helper.flash_loans.collateral_settings(dec!(0.01), true, &mut helper.env)?;
+    helper.env.enable_auth_module();
+
+    helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        free_fusd,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    let pool_xrd_before = helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0].collateral_amount;
+
+    // Borrow XRD from the Flux vault
+    let (loan, receipt) = helper.proxy.flash_borrow_collateral(helper.xrd_address, dec!(500), &mut helper.env)?;
+    assert_eq!(loan.amount(&mut helper.env)?, dec!(500));
+
+    // Pay back the loan plus the 1% fee
+    loan.put(helper.xrd.take(dec!(5), &mut helper.env)?, &mut helper.env)?;
+    let leftover = helper.proxy.flash_pay_back_collateral(receipt, loan, &mut helper.env)?;
+    assert_eq!(leftover.amount(&mut helper.env)?, Decimal::ZERO);
+
+    // The fee went to the XRD stability pool
+    let pool_xrd_after = helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0].collateral_amount;
+    assert_eq!(pool_xrd_after - pool_xrd_before, dec!(5));
+
+    // The Flux vault and CDP accounting are untouched
+    let collateral_info = &helper.flux.get_collateral_infos(vec![helper.xrd_address], &mut helper.env)?[0];
+    assert_eq!(collateral_info.vault, dec!(1000));
+    assert_eq!(collateral_info.collateral_amount, dec!(1000));
+
+    let (_, cdp_info, _) = helper.get_cdp_info(NonFungibleLocalId::from(1))?;
+    assert_eq!(cdp_info.collateral_amount, dec!(1000));
+
+    Ok(())
+}
+
+#[test]
+fn test_flash_loan_collateral_requires_fee() -> Result<(), RuntimeError> {
+    // Initialize helper and open a CDP so the Flux vault holds XRD
+    let mut helper = Helper::new().unwrap();
+    let bucket = helper.xrd.take(dec!(1000), &mut helper.env)?;
+    let (_fusd, _cdp_receipt) = helper.proxy_open_cdp(None, bucket, dec!(100), dec!(0.01))?;
+
+    // Set a 1% collateral flash loan fee
+    helper.env.disable_auth_module();
+    helper.flash_loans.collateral_settings(dec!(0.01), true, &mut helper.env)?;
+    helper.env.enable_auth_module();
+
+    // Paying back only the principal fails
+    let (loan, receipt) = helper.proxy.flash_borrow_collateral(helper.xrd_address, dec!(500), &mut helper.env)?;
+    let result = helper.proxy.flash_pay_back_collateral(receipt, loan, &mut helper.env);
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_flash_loan_with_callback() -> Result<(), RuntimeError> {
+    // Initialize helper and a flash borrower holding 10 fUSD for fees
+    let mut helper = Helper::new().unwrap();
+
+    helper.env.disable_auth_module();
+    let fee_funds = helper.free_fusd(dec!(10))?;
+    helper.flash_loans.settings(dec!(0.01), true, &mut helper.env)?;
+    helper.env.enable_auth_module();
+
+    let borrower_package_address = PackageFactory::compile_and_publish(
+        "./dummy_flash_borrower_component",
+        &mut helper.env,
+        CompileProfile::Standard,
+    )?;
+    let borrower = FlashBorrower::instantiate_flash_borrower(fee_funds, borrower_package_address, &mut helper.env)?;
+    let borrower_address = ComponentAddress::try_from(borrower.0.clone()).unwrap();
+
+    // Borrow 100 fUSD, the callback returns the loan plus 2 fUSD
+    let excess = helper.proxy.flash_loan_with_callback(
+        dec!(100),
+        borrower_address,
+        "on_flash_loan".to_string(),
+        scrypto_encode(&dec!(2)).unwrap(),
+        None,
+        &mut helper.env
+    )?;
+
+    // 1 fUSD is interest, the other 1 fUSD is returned
+    assert_eq!(excess.amount(&mut helper.env)?, dec!(1));
+
+    // A callback not returning the interest fails
+    let result = helper.proxy.flash_loan_with_callback(
+        dec!(100),
+        borrower_address,
+        "on_flash_loan".to_string(),
+        scrypto_encode(&dec!(0)).unwrap(),
+        None,
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_flash_loan_caps() -> Result<(), RuntimeError> {
+    // Initialize helper with a cap of 100 fUSD per loan and 150 fUSD per transaction
+    let mut helper = Helper::new().unwrap();
+
+    helper.env.disable_auth_module();
+    helper.flash_loans.set_caps(Some(dec!(100)), Some(dec!(150)), &mut helper.env)?;
+    helper.env.enable_auth_module();
+
+    // A loan above the per loan cap fails
+    let result = helper.proxy.flash_borrow(dec!(101), &mut helper.env);
+    assert!(result.is_err());
+
+    // A loan within the caps succeeds
+    let (loan, receipt) = helper.proxy.flash_borrow(dec!(100), &mut helper.env)?;
+    helper.proxy.flash_pay_back(receipt, loan, &mut helper.env)?;
+
+    // Another loan in the same transaction exceeding the aggregate cap fails
+    let result = helper.proxy.flash_borrow(dec!(60), &mut helper.env);
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_flash_loan_fee_tier_and_routing() -> Result<(), RuntimeError> {
+    // Initialize helper with 1% interest, 0.1% for admin badge holders, routed to the XRD stability pool
+    let mut helper = Helper::new().unwrap();
+    let stability_pools_address = ComponentAddress::try_from(helper.stability_pools.0.clone()).unwrap();
+
+    helper.env.disable_auth_module();
+    let free_fusd = helper.free_fusd(dec!(500))?;
+    helper.flash_loans.settings(dec!(0.01), true, &mut helper.env)?;
+    helper.flash_loans.set_fee_tier(helper.admin_address, Some(dec!(0.001)), &mut helper.env)?;
+    helper.flash_loans.set_fee_destinations(
+        vec![(
+            FlashLoanFeeDestination::StabilityPool {
+                stability_pools: stability_pools_address,
+                collateral: helper.xrd_address,
+            },
+            dec!(1),
+        )],
+        &mut helper.env,
+    )?;
+    helper.env.enable_auth_module();
+
+    helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        free_fusd.take(dec!(400), &mut helper.env)?,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    let pool_fusd_before = helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0].fusd_amount;
+
+    // Borrow 100 fUSD with a proof of the admin badge and pay back 100.1 fUSD
+    let badge_proof = helper.admin.create_proof_of_amount(dec!(1), &mut helper.env)?;
+    let (loan, receipt) = helper.proxy.flash_borrow_with_tier(dec!(100), badge_proof, &mut helper.env)?;
+    loan.put(free_fusd.take(dec!(0.1), &mut helper.env)?, &mut helper.env)?;
+    let leftover = helper.proxy.flash_pay_back(receipt, loan, &mut helper.env)?;
+    assert_eq!(leftover.amount(&mut helper.env)?, Decimal::ZERO);
+
+    // The discounted interest went to the XRD stability pool
+    let pool_fusd_after = helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0].fusd_amount;
+    assert_eq!(pool_fusd_after - pool_fusd_before, dec!(0.1));
+
+    Ok(())
+}
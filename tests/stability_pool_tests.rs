@@ -1,6 +1,10 @@
 mod helper;
 use helper::Helper;
 use flux_protocol::shared_structs::*;
+use dummy_oracle_component::Interpolation;
+use dummy_dex_pool_component::dummy_dex_pool_component_test::*;
+use flux_protocol::product_sum_pool::product_sum_pool_test::*;
+use flux_protocol::stability_pools::StabilityPoolComponent;
 
 use scrypto_test::prelude::*;
 use scrypto::prelude::Url;
@@ -214,6 +218,9 @@ fn test_buy_collateral_from_pool() -> Result<(), RuntimeError> {
         None, 
         true, // allow_pool_buys = true
         Some(dec!(0.99)), // 99% of market price
+        None,
+        None,
+        None,
         &mut helper.env
     )?;
     
@@ -295,6 +302,9 @@ fn test_buy_collateral_from_pool_too_much() -> Result<(), RuntimeError> {
         None, 
         true, // allow_pool_buys = true
         Some(dec!(0.99)), // 99% of market price
+        None,
+        None,
+        None,
         &mut helper.env
     )?;
     
@@ -453,6 +463,163 @@ fn test_liquidate_normal() -> Result<(), RuntimeError> {
     Ok(())
 }
 
+#[test]
+fn test_price_paths() -> Result<(), RuntimeError> {
+    // Initialize helper
+    let mut helper = Helper::new().unwrap();
+    let now = helper.env.get_current_time().seconds_since_unix_epoch;
+
+    // Schedule a linear path for XRD and a stepped path for LSULP
+    helper.set_price_path(
+        "XRD".to_string(),
+        vec![(now + 100, dec!(1)), (now + 200, dec!(2))],
+        Interpolation::Linear,
+    )?;
+    helper.set_price_path(
+        "LSULP".to_string(),
+        vec![(now + 100, dec!(1)), (now + 200, dec!(2))],
+        Interpolation::Stepped,
+    )?;
+
+    // Before the first point the first price holds
+    assert_eq!(helper.dummy_oracle.get_price("XRD".to_string(), &mut helper.env)?, dec!(1));
+
+    // Halfway the linear path is interpolated, the stepped path isn't
+    helper.env.set_current_time(helper.env.get_current_time().add_seconds(150).unwrap());
+    assert_eq!(helper.dummy_oracle.get_price("XRD".to_string(), &mut helper.env)?, dec!("1.5"));
+    assert_eq!(helper.dummy_oracle.get_price("LSULP".to_string(), &mut helper.env)?, dec!(1));
+
+    // After the last point the last price holds
+    helper.env.set_current_time(helper.env.get_current_time().add_seconds(100).unwrap());
+    assert_eq!(helper.dummy_oracle.get_price("XRD".to_string(), &mut helper.env)?, dec!(2));
+    assert_eq!(helper.dummy_oracle.get_price("LSULP".to_string(), &mut helper.env)?, dec!(2));
+
+    // A random walk with seed 42 follows a fixed path of 3 steps, one minute apart
+    let now = helper.env.get_current_time().seconds_since_unix_epoch;
+    helper.env.disable_auth_module();
+    for (market_id, seed) in [("XRD", 42), ("LSULP", 43)] {
+        helper.dummy_oracle.set_random_walk_path(
+            market_id.to_string(),
+            now,
+            dec!(1),
+            60,
+            3,
+            dec!("0.05"),
+            seed,
+            &mut helper.env,
+        )?;
+    }
+    helper.env.enable_auth_module();
+
+    let expected_xrd_prices = [
+        dec!(1),
+        dec!("1.0315"),
+        dec!("1.062290275"),
+        dec!("1.09330915103"),
+    ];
+    let expected_lsulp_prices = [
+        dec!(1),
+        dec!("0.9763"),
+        dec!("0.995484295"),
+        dec!("0.964026991278"),
+    ];
+    for (xrd_price, lsulp_price) in expected_xrd_prices.into_iter().zip(expected_lsulp_prices) {
+        assert_eq!(
+            helper.dummy_oracle.get_price("XRD".to_string(), &mut helper.env)?,
+            xrd_price
+        );
+        // A different seed produces a different path
+        assert_eq!(
+            helper.dummy_oracle.get_price("LSULP".to_string(), &mut helper.env)?,
+            lsulp_price
+        );
+        helper.env.set_current_time(helper.env.get_current_time().add_seconds(60).unwrap());
+    }
+
+    // After the last step the last price holds
+    helper.env.set_current_time(helper.env.get_current_time().add_hours(1).unwrap());
+    assert_eq!(
+        helper.dummy_oracle.get_price("XRD".to_string(), &mut helper.env)?,
+        dec!("1.09330915103")
+    );
+
+    // Scheduling the same seed again reproduces the path
+    let now = helper.env.get_current_time().seconds_since_unix_epoch;
+    helper.env.disable_auth_module();
+    helper.dummy_oracle.set_random_walk_path(
+        "LSULP".to_string(),
+        now - 120,
+        dec!(1),
+        60,
+        3,
+        dec!("0.05"),
+        42,
+        &mut helper.env,
+    )?;
+    helper.env.enable_auth_module();
+    assert_eq!(
+        helper.dummy_oracle.get_price("LSULP".to_string(), &mut helper.env)?,
+        expected_xrd_prices[2]
+    );
+
+    // Setting a static price removes the path
+    helper.change_collateral_price("XRD".to_string(), dec!(3))?;
+    assert_eq!(helper.dummy_oracle.get_price("XRD".to_string(), &mut helper.env)?, dec!(3));
+
+    Ok(())
+}
+
+#[test]
+fn test_liquidate_after_scheduled_crash() -> Result<(), RuntimeError> {
+    // Initialize helper
+    let mut helper = Helper::new().unwrap();
+
+    // Create a CDP and fill the stability pool
+    let bucket = helper.xrd.take(dec!(1000), &mut helper.env)?;
+    let (_fusd, _cdp_receipt) = helper.proxy_open_cdp(None, bucket, dec!(400), dec!(0.01))?;
+    let receipt_id = NonFungibleLocalId::from(1);
+
+    helper.env.disable_auth_module();
+    let free_fusd = helper.free_fusd(dec!(500))?;
+    helper.env.enable_auth_module();
+
+    helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        free_fusd,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    // Schedule a crash of XRD from 1 to 0.5, starting in an hour and lasting an hour
+    let crash_time = helper.env.get_current_time().seconds_since_unix_epoch + 3600;
+    helper.set_crash_path("XRD".to_string(), dec!(1), crash_time, dec!("0.5"), 3600)?;
+
+    // Before the crash the CDP can't be liquidated
+    let result = helper.stability_pools.liquidate(
+        receipt_id.clone(),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // After the crash it can
+    helper.env.set_current_time(helper.env.get_current_time().add_hours(3).unwrap());
+    helper.stability_pools.liquidate(
+        receipt_id.clone(),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    let (_, cdp_info, _) = helper.get_cdp_info(receipt_id)?;
+    assert_eq!(cdp_info.status, CdpStatus::Liquidated);
+
+    Ok(())
+}
+
 #[test]
 fn test_basic_redemption() -> Result<(), RuntimeError> {
     // Initialize helper
@@ -558,7 +725,7 @@ fn test_check_and_initiate_panic_mode() -> Result<(), RuntimeError> {
     )?;
     
     // Panic mode should be active
-    let panic_mode_active = helper.stability_pools.check_panic_mode_status(&mut helper.env)?;
+    let panic_mode_active = helper.stability_pools.check_panic_mode_status(helper.xrd_address, &mut helper.env)?;
     assert!(panic_mode_active);
     
     Ok(())
@@ -1424,7 +1591,7 @@ fn test_panic_mode_initiate_after_waiting() -> Result<(), RuntimeError> {
     )?;
     
     // Panic mode should be active
-    let panic_mode_active = helper.stability_pools.check_panic_mode_status(&mut helper.env)?;
+    let panic_mode_active = helper.stability_pools.check_panic_mode_status(helper.xrd_address, &mut helper.env)?;
     assert!(panic_mode_active);
     
     Ok(())
@@ -1470,7 +1637,7 @@ fn test_panic_mode_initiate_too_late() -> Result<(), RuntimeError> {
     );
     
     // Panic mode should not be active
-    let panic_mode_active = helper.stability_pools.check_panic_mode_status(&mut helper.env)?;
+    let panic_mode_active = helper.stability_pools.check_panic_mode_status(helper.xrd_address, &mut helper.env)?;
     assert!(!panic_mode_active);
     
     Ok(())
@@ -1527,6 +1694,8 @@ fn test_panic_mode_liquidate_too_late() -> Result<(), RuntimeError> {
         usdc_payment,
         "".to_string(),
         "".to_string(),
+        "".to_string(),
+        "".to_string(),
         &mut helper.env
     );
     
@@ -1653,6 +1822,8 @@ fn test_redemption_with_stablecoin_and_collateral() -> Result<(), RuntimeError>
         usdc_payment,
         "".to_string(),
         "".to_string(),
+        "".to_string(),
+        "".to_string(),
         &mut helper.env
     )?;
     
@@ -1673,7 +1844,7 @@ fn test_redemption_with_stablecoin_and_collateral() -> Result<(), RuntimeError>
     helper.env.enable_auth_module();
     
     // Get fUSD for redemption
-    let redemption_amount = dec!(650);
+    let redemption_amount = dec!(200);
     let redeem_fusd = fusd_for_redemption.take(redemption_amount, &mut helper.env)?;
     
     // Create oracle info for both collaterals
@@ -1683,25 +1854,37 @@ fn test_redemption_with_stablecoin_and_collateral() -> Result<(), RuntimeError>
     ];
     
     // Perform redemption
-    let (redeemed_collateral, leftover_fusd) = helper.stability_pools.redemptions(
+    let (redeemed_collateral, _leftover_fusd) = helper.stability_pools.redemptions(
         redeem_fusd,
-        oracle_info,
+        oracle_info.clone(),
         10, // max_redemptions
         &mut helper.env
     )?;
     
-    // Verify we received both USDC and collateral
+    // Without a supplied price the USDC reserves are skipped and only normal collateral is redeemed
     assert!(!redeemed_collateral.is_empty());
-    
-    // Should include USDC (from panic mode liquidation)
     let has_usdc = redeemed_collateral.iter().any(|(addr, _)| *addr == helper.usdc_address);
-    // Should include normal collateral
     let has_collateral = redeemed_collateral.iter().any(|(addr, _)| 
         *addr == helper.xrd_address || *addr == helper.lsulp_address);
     
-    assert!(has_usdc, "Should have received USDC");
+    assert!(!has_usdc, "Should not have received USDC without its price");
     assert!(has_collateral, "Should have received normal collateral");
     
+    // With the USDC price supplied, the USDC reserves are redeemed first
+    let redeem_fusd = fusd_for_redemption.take(dec!(100), &mut helper.env)?;
+    let mut oracle_info = oracle_info;
+    oracle_info.push((helper.usdc_address, "".to_string(), "".to_string()));
+    
+    let (redeemed_collateral, _leftover_fusd) = helper.stability_pools.redemptions(
+        redeem_fusd,
+        oracle_info,
+        10, // max_redemptions
+        &mut helper.env
+    )?;
+    
+    let has_usdc = redeemed_collateral.iter().any(|(addr, _)| *addr == helper.usdc_address);
+    assert!(has_usdc, "Should have received USDC");
+    
     Ok(())
 }
 
@@ -1969,6 +2152,8 @@ fn test_panic_mode_liquidate() -> Result<(), RuntimeError> {
         usdc_payment,
         "".to_string(),
         "".to_string(),
+        "".to_string(),
+        "".to_string(),
         &mut helper.env
     )?;
     
@@ -1983,6 +2168,300 @@ fn test_panic_mode_liquidate() -> Result<(), RuntimeError> {
     Ok(())
 }
 
+#[test]
+fn test_panic_mode_is_scoped_per_collateral() -> Result<(), RuntimeError> {
+    // Initialize helper
+    let mut helper = Helper::new().unwrap();
+
+    // Create an LSULP CDP and an XRD CDP, and make both liquidatable
+    let lsulp_bucket = helper.lsulp.take(dec!(500), &mut helper.env)?;
+    let (_, _lsulp_receipt) = helper.proxy_open_cdp(None, lsulp_bucket, dec!(400), dec!(0.01))?;
+    let lsulp_receipt_id = NonFungibleLocalId::from(1);
+    let xrd_bucket = helper.xrd.take(dec!(1000), &mut helper.env)?;
+    let (_, _xrd_receipt) = helper.proxy_open_cdp(None, xrd_bucket, dec!(400), dec!(0.01))?;
+    let xrd_receipt_id = NonFungibleLocalId::from(2);
+    helper.change_collateral_price("LSULP".to_string(), dec!(0.8))?;
+    helper.change_collateral_price("XRD".to_string(), dec!(0.4))?;
+
+    // Activate panic mode for LSULP only
+    helper.stability_pools.check_and_initiate_panic_mode(lsulp_receipt_id.clone(), "".to_string(), "".to_string(), &mut helper.env)?;
+    let new_time = helper.env.get_current_time().add_hours(25).unwrap();
+    helper.env.set_current_time(new_time);
+    helper.stability_pools.check_and_initiate_panic_mode(lsulp_receipt_id.clone(), "".to_string(), "".to_string(), &mut helper.env)?;
+
+    assert!(helper.stability_pools.check_panic_mode_status(helper.lsulp_address, &mut helper.env)?);
+    assert!(!helper.stability_pools.check_panic_mode_status(helper.xrd_address, &mut helper.env)?);
+
+    // The XRD CDP can't be liquidated in panic mode
+    let usdc_payment = helper.usdc.take(dec!(500), &mut helper.env)?;
+    let result = helper.stability_pools.panic_mode_liquidate(
+        xrd_receipt_id.clone(),
+        usdc_payment,
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // The LSULP CDP can
+    let usdc_payment = helper.usdc.take(dec!(500), &mut helper.env)?;
+    let (collateral, _) = helper.stability_pools.panic_mode_liquidate(
+        lsulp_receipt_id.clone(),
+        usdc_payment,
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    assert_eq!(collateral.resource_address(&mut helper.env)?, helper.lsulp_address);
+
+    let (_, lsulp_cdp_info, _) = helper.get_cdp_info(lsulp_receipt_id)?;
+    assert_eq!(lsulp_cdp_info.status, CdpStatus::Liquidated);
+    let (_, xrd_cdp_info, _) = helper.get_cdp_info(xrd_receipt_id)?;
+    assert_ne!(xrd_cdp_info.status, CdpStatus::Liquidated);
+
+    Ok(())
+}
+
+#[test]
+fn test_panic_mode_liquidate_with_multiple_stablecoins() -> Result<(), RuntimeError> {
+    // Initialize helper
+    let mut helper = Helper::new().unwrap();
+    let usdt = ResourceBuilder::new_fungible(OwnerRole::None)
+        .divisibility(18)
+        .mint_initial_supply(1000000, &mut helper.env)?;
+    let usdt_address = usdt.resource_address(&mut helper.env)?;
+
+    // Accept USDC with a 2% and USDT with a 5% depeg tolerance, redeeming USDT reserves first
+    helper.env.disable_auth_module();
+    helper.dummy_oracle.add_morpher_identifier(usdt_address, "USDT".to_string(), &mut helper.env)?;
+    helper.proxy.set_panic_mode_stablecoins(
+        vec![
+            PanicModeStablecoin { address: helper.usdc_address, depeg_tolerance: dec!("0.02") },
+            PanicModeStablecoin { address: usdt_address, depeg_tolerance: dec!("0.05") },
+        ],
+        vec![usdt_address, helper.usdc_address],
+        &mut helper.env
+    )?;
+    helper.env.enable_auth_module();
+
+    // Create a CDP and make it liquidatable
+    let bucket = helper.xrd.take(dec!(1000), &mut helper.env)?;
+    let (_, _cdp_receipt) = helper.proxy_open_cdp(None, bucket, dec!(400), dec!(0.01))?;
+    let receipt_id = NonFungibleLocalId::from(1);
+    helper.change_collateral_price("XRD".to_string(), dec!(0.4))?;
+
+    // Activate panic mode
+    helper.stability_pools.check_and_initiate_panic_mode(receipt_id.clone(), "".to_string(), "".to_string(), &mut helper.env)?;
+    let new_time = helper.env.get_current_time().add_hours(25).unwrap();
+    helper.env.set_current_time(new_time);
+    helper.stability_pools.check_and_initiate_panic_mode(receipt_id.clone(), "".to_string(), "".to_string(), &mut helper.env)?;
+
+    // USDC has depegged beyond its tolerance and is refused
+    helper.change_collateral_price("USDC".to_string(), dec!("0.97"))?;
+    let usdc_payment = helper.usdc.take(dec!(500), &mut helper.env)?;
+    let result = helper.stability_pools.panic_mode_liquidate(
+        receipt_id.clone(),
+        usdc_payment,
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // USDT is within its tolerance, and is valued at its oracle price
+    helper.change_collateral_price("USDT".to_string(), dec!("0.97"))?;
+    let usdt_payment = usdt.take(dec!(500), &mut helper.env)?;
+    let (collateral, leftover) = helper.stability_pools.panic_mode_liquidate(
+        receipt_id.clone(),
+        usdt_payment,
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    assert_eq!(collateral.resource_address(&mut helper.env)?, helper.xrd_address);
+    assert_eq!(leftover.resource_address(&mut helper.env)?, usdt_address);
+
+    let (_, cdp_info, _) = helper.get_cdp_info(receipt_id)?;
+    assert_eq!(cdp_info.status, CdpStatus::Liquidated);
+
+    // More than one USDT was needed per fUSD of debt
+    let stablecoins = helper.stability_pools.get_panic_mode_stablecoins(&mut helper.env)?;
+    assert_eq!(stablecoins.len(), 2);
+    assert_eq!(stablecoins[0].reserves, Decimal::ZERO);
+    let usdt_reserves = stablecoins[1].reserves;
+    assert_eq!(usdt_reserves + leftover.amount(&mut helper.env)?, dec!(500));
+    assert!(usdt_reserves * dec!("0.97") >= dec!(400));
+
+    // Without the USDT price its reserves are skipped, leaving nothing to redeem against
+    helper.env.disable_auth_module();
+    let fusd = helper.free_fusd(dec!(200))?;
+    helper.env.enable_auth_module();
+    let result = helper.stability_pools.redemptions(
+        fusd.take(dec!(97), &mut helper.env)?,
+        vec![(helper.xrd_address, "".to_string(), "".to_string())],
+        10,
+        &mut helper.env
+    );
+    assert!(result.is_err());
+    let stablecoins = helper.stability_pools.get_panic_mode_stablecoins(&mut helper.env)?;
+    assert_eq!(stablecoins[1].reserves, usdt_reserves);
+
+    // USDT reserves are paid out at their oracle value
+    let oracle_info = vec![
+        (helper.xrd_address, "".to_string(), "".to_string()),
+        (helper.usdc_address, "".to_string(), "".to_string()),
+        (usdt_address, "".to_string(), "".to_string())
+    ];
+    let (redeemed, leftover_fusd) = helper.stability_pools.redemptions(
+        fusd.take(dec!(97), &mut helper.env)?,
+        oracle_info.clone(),
+        10,
+        &mut helper.env
+    )?;
+    assert_eq!(redeemed.len(), 1);
+    assert_eq!(redeemed[0].0, usdt_address);
+    assert_eq!(redeemed[0].1.amount(&mut helper.env)?, dec!(100));
+    assert!(leftover_fusd.amount(&mut helper.env)?.is_zero());
+
+    // Once USDT depegs beyond its tolerance its reserves are skipped, leaving nothing to redeem against
+    helper.change_collateral_price("USDT".to_string(), dec!("0.9"))?;
+    let result = helper.stability_pools.redemptions(
+        fusd.take(dec!(90), &mut helper.env)?,
+        oracle_info,
+        10,
+        &mut helper.env
+    );
+    assert!(result.is_err());
+    let stablecoins = helper.stability_pools.get_panic_mode_stablecoins(&mut helper.env)?;
+    assert_eq!(stablecoins[1].reserves, usdt_reserves - dec!(100));
+
+    Ok(())
+}
+
+#[test]
+fn test_unwind_stablecoin_reserves_through_buy_back_window() -> Result<(), RuntimeError> {
+    // Initialize helper
+    let mut helper = Helper::new().unwrap();
+
+    // Create a CDP and make it liquidatable
+    let bucket = helper.xrd.take(dec!(1000), &mut helper.env)?;
+    let (_, _cdp_receipt) = helper.proxy_open_cdp(None, bucket, dec!(400), dec!(0.01))?;
+    let receipt_id = NonFungibleLocalId::from(1);
+
+    // A second, healthy CDP keeps debt outstanding after the liquidation
+    let bucket = helper.xrd.take(dec!(2000), &mut helper.env)?;
+    let (_, _) = helper.proxy_open_cdp(None, bucket, dec!(100), dec!(0.01))?;
+    helper.change_collateral_price("XRD".to_string(), dec!(0.4))?;
+
+    // Activate panic mode and liquidate with USDC
+    helper.stability_pools.check_and_initiate_panic_mode(receipt_id.clone(), "".to_string(), "".to_string(), &mut helper.env)?;
+    let new_time = helper.env.get_current_time().add_hours(25).unwrap();
+    helper.env.set_current_time(new_time);
+    helper.stability_pools.check_and_initiate_panic_mode(receipt_id.clone(), "".to_string(), "".to_string(), &mut helper.env)?;
+    let usdc_payment = helper.usdc.take(dec!(500), &mut helper.env)?;
+    let (_, _) = helper.stability_pools.panic_mode_liquidate(
+        receipt_id,
+        usdc_payment,
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    // Open a buy-back window at a 1% discount, buying back at most 100 fUSD per day once the XRD pool holds half
+    // of the XRD debt
+    helper.env.disable_auth_module();
+    helper.proxy.set_stablecoin_unwind(
+        Some(StablecoinUnwindMethod::BuyBackWindow { discount: dec!("0.01") }),
+        dec!("0.01"),
+        dec!(100),
+        1440,
+        dec!("0.5"),
+        &mut helper.env
+    )?;
+    let fusd = helper.free_fusd(dec!(300))?;
+    helper.env.enable_auth_module();
+
+    // Reserves can't be unwound while panic mode is active
+    let result = helper.stability_pools.sell_fusd_for_stablecoin(
+        helper.usdc_address,
+        fusd.take(dec!(10), &mut helper.env)?,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // Panic mode has ended, but the XRD pool doesn't cover its debt yet
+    let new_time = helper.env.get_current_time().add_days(2).unwrap();
+    helper.env.set_current_time(new_time);
+    let result = helper.stability_pools.sell_fusd_for_stablecoin(
+        helper.usdc_address,
+        fusd.take(dec!(10), &mut helper.env)?,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // Once the pool has recovered, fUSD is bought back up to the rate limit
+    let _ = helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        fusd.take(dec!(80), &mut helper.env)?,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    let (usdc, leftover_fusd) = helper.stability_pools.sell_fusd_for_stablecoin(
+        helper.usdc_address,
+        fusd.take(dec!(150), &mut helper.env)?,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    assert_eq!(usdc.resource_address(&mut helper.env)?, helper.usdc_address);
+    assert_eq!(usdc.amount(&mut helper.env)?, dec!(99));
+    assert_eq!(leftover_fusd.amount(&mut helper.env)?, dec!(50));
+
+    // The rate limit is reached for this period
+    let result = helper.stability_pools.sell_fusd_for_stablecoin(
+        helper.usdc_address,
+        leftover_fusd.take(dec!(10), &mut helper.env)?,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // A new period starts after a day
+    let new_time = helper.env.get_current_time().add_days(1).unwrap();
+    helper.env.set_current_time(new_time);
+    let (usdc, leftover_fusd) = helper.stability_pools.sell_fusd_for_stablecoin(
+        helper.usdc_address,
+        leftover_fusd,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    assert_eq!(usdc.amount(&mut helper.env)?, dec!("39.6"));
+    assert_eq!(leftover_fusd.amount(&mut helper.env)?, Decimal::ZERO);
+
+    let unwind = helper.stability_pools.get_stablecoin_unwind(&mut helper.env)?.unwrap();
+    assert_eq!(unwind.unwound_in_period, dec!(40));
+
+    Ok(())
+}
+
 #[test]
 fn test_system_recovery_from_panic_mode() -> Result<(), RuntimeError> {
     // Initialize helper
@@ -2030,7 +2509,7 @@ fn test_system_recovery_from_panic_mode() -> Result<(), RuntimeError> {
     )?;
     
     // Verify panic mode is active
-    let panic_mode_active = helper.stability_pools.check_panic_mode_status(&mut helper.env)?;
+    let panic_mode_active = helper.stability_pools.check_panic_mode_status(helper.xrd_address, &mut helper.env)?;
     assert!(panic_mode_active);
     
     // PHASE 2: Liquidate the problematic CDP in panic mode
@@ -2042,6 +2521,8 @@ fn test_system_recovery_from_panic_mode() -> Result<(), RuntimeError> {
         usdc_payment,
         "".to_string(),
         "".to_string(),
+        "".to_string(),
+        "".to_string(),
         &mut helper.env
     )?;
     
@@ -2085,7 +2566,7 @@ fn test_system_recovery_from_panic_mode() -> Result<(), RuntimeError> {
     assert_eq!(cdp_info3.status, CdpStatus::Healthy);
     
     // Check if panic mode is still active
-    let panic_mode_still_active = helper.stability_pools.check_panic_mode_status(&mut helper.env)?;
+    let panic_mode_still_active = helper.stability_pools.check_panic_mode_status(helper.xrd_address, &mut helper.env)?;
     
     // If panic mode is still active after all these steps, then there may not be
     // an automatic way to deactivate it, or our recovery steps were insufficient
@@ -2107,4 +2588,1483 @@ fn test_system_recovery_from_panic_mode() -> Result<(), RuntimeError> {
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+#[test]
+fn test_product_sum_pool_gains() -> Result<(), RuntimeError> {
+    // Initialize helper with a collateral using a product-sum stability pool
+    let mut helper = Helper::new().unwrap();
+    let (mut collateral, collateral_address) = helper.add_product_sum_collateral("PSC".to_string())?;
+    let fusd_address = helper.flux.get_fusd_address(&mut helper.env)?;
+
+    // Create a CDP that can be liquidated after a price drop
+    let bucket = collateral.take(dec!(1000), &mut helper.env)?;
+    let (_fusd, _cdp_receipt) = helper.proxy_open_cdp(None, bucket, dec!(400), dec!(0.01))?;
+    let receipt_id = NonFungibleLocalId::from(1);
+
+    // Two depositors, depositing 600 and 200 fUSD
+    helper.env.disable_auth_module();
+    let fusd_a = helper.free_fusd(dec!(600))?;
+    let fusd_b = helper.free_fusd(dec!(200))?;
+    helper.env.enable_auth_module();
+
+    let (receipt_a, _, _) = helper.stability_pools.contribute_to_pool(
+        collateral_address,
+        fusd_a,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    let (receipt_b, _, _) = helper.stability_pools.contribute_to_pool(
+        collateral_address,
+        fusd_b,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    assert_eq!(receipt_a.amount(&mut helper.env)?, dec!(1));
+
+    // fUSD paid to the pool is tracked as yield, not added to the deposits
+    helper.env.disable_auth_module();
+    let fee = helper.free_fusd(dec!(40))?;
+    helper.stability_pools.receive_fusd_fee(collateral_address, fee, &mut helper.env)?;
+    helper.env.enable_auth_module();
+
+    // Liquidate the CDP, consuming part of the deposits
+    helper.change_collateral_price("PSC".to_string(), dec!(0.5))?;
+    helper.stability_pools.liquidate(
+        receipt_id.clone(),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    let (_, cdp_info, _) = helper.get_cdp_info(receipt_id)?;
+    assert_eq!(cdp_info.status, CdpStatus::Liquidated);
+
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![collateral_address]), &mut helper.env)?[0];
+    let remaining_deposits = pool_info.fusd_amount;
+    let collateral_gains = pool_info.collateral_amount;
+    assert!(remaining_deposits < dec!(800));
+    assert!(collateral_gains > Decimal::ZERO);
+
+    // Depositor A claims its gains without withdrawing, receiving 3/4 of the collateral and yield
+    let proof_a = receipt_a.create_proof_of_all(&mut helper.env)?;
+    let (collateral_a, yield_a) = helper.stability_pools.claim_pool_gains(
+        collateral_address,
+        proof_a,
+        &mut helper.env
+    )?;
+    assert!((collateral_a.amount(&mut helper.env)? - collateral_gains * dec!("0.75")).checked_abs().unwrap() < dec!("0.000001"));
+    assert_eq!(yield_a.resource_address(&mut helper.env)?, fusd_address);
+    assert!((yield_a.amount(&mut helper.env)? - dec!(30)).checked_abs().unwrap() < dec!("0.000001"));
+
+    // Depositor B withdraws, receiving 1/4 of the remaining deposits, collateral and yield
+    let (collateral_b, fusd_b) = helper.stability_pools.withdraw_from_pool(
+        collateral_address,
+        receipt_b,
+        &mut helper.env
+    )?;
+    assert!((collateral_b.amount(&mut helper.env)? - collateral_gains * dec!("0.25")).checked_abs().unwrap() < dec!("0.000001"));
+    assert!((fusd_b.amount(&mut helper.env)? - remaining_deposits * dec!("0.25") - dec!(10)).checked_abs().unwrap() < dec!("0.000001"));
+
+    // Depositor A withdraws afterwards, its gains were already claimed
+    let (collateral_a, fusd_a) = helper.stability_pools.withdraw_from_pool(
+        collateral_address,
+        receipt_a,
+        &mut helper.env
+    )?;
+    assert!(collateral_a.amount(&mut helper.env)? < dec!("0.000001"));
+    assert!((fusd_a.amount(&mut helper.env)? - remaining_deposits * dec!("0.75")).checked_abs().unwrap() < dec!("0.000001"));
+
+    Ok(())
+}
+
+#[test]
+fn test_product_sum_pool_rejects_pool_buys() -> Result<(), RuntimeError> {
+    // Initialize helper with a collateral using a product-sum stability pool
+    let mut helper = Helper::new().unwrap();
+    let (_collateral, collateral_address) = helper.add_product_sum_collateral("PSC".to_string())?;
+
+    // Pool buys can't be enabled for product-sum pools
+    helper.env.disable_auth_module();
+    let result = helper.stability_pools.edit_pool(
+        collateral_address,
+        None,
+        None,
+        None,
+        true,
+        None,
+        None,
+        None,
+        None,
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_product_sum_pool_repeated_near_total_offsets() -> Result<(), RuntimeError> {
+    // Instantiate a product-sum pool directly, with a plain token standing in for fUSD
+    let mut helper = Helper::new().unwrap();
+    let env = &mut helper.env;
+    let mut fusd: Bucket = ResourceBuilder::new_fungible(OwnerRole::None)
+        .divisibility(18)
+        .mint_initial_supply(100000, env)?
+        .into();
+    let fusd_address = fusd.resource_address(env)?;
+    let mut pool = ProductSumPool::instantiate(
+        OwnerRole::None,
+        rule!(allow_all),
+        helper.xrd_address,
+        fusd_address,
+        "Stability Deposit".to_string(),
+        "A stability pool deposit".to_string(),
+        Url::of("https://ilikeitstable.com"),
+        "SD".to_string(),
+        GlobalAddress::from(helper.package_address),
+        helper.package_address,
+        env,
+    )?;
+
+    // 15 rounds of a new 1000 fUSD deposit, followed by a liquidation using 99.9% of the pool.
+    // Without scaling, P would shrink by a factor 1000 every round and reach zero after 12 rounds.
+    for _ in 0..15 {
+        let _receipt = pool.deposit(fusd.take(dec!(1000), env)?, env)?;
+        let total_deposits = pool.get_total_deposits(env)?;
+        let used_fusd = pool.withdraw_for_offset(total_deposits * dec!("0.999"), env)?;
+        pool.offset(fusd.take(dec!(0), env)?, helper.xrd.take(dec!(1), env)?, env)?;
+        fusd.put(used_fusd, env)?;
+    }
+
+    // A new deposit still compounds correctly through a liquidation of 500 fUSD
+    let receipt = pool.deposit(fusd.take(dec!(1000), env)?, env)?;
+    let total_deposits = pool.get_total_deposits(env)?;
+    let used_fusd = pool.withdraw_for_offset(dec!(500), env)?;
+    pool.offset(fusd.take(dec!(0), env)?, helper.xrd.take(dec!(10), env)?, env)?;
+    fusd.put(used_fusd, env)?;
+
+    let expected_deposit = dec!(1000) * (total_deposits - dec!(500)) / total_deposits;
+    let expected_gain = dec!(10) * dec!(1000) / total_deposits;
+    let info = pool.get_deposit_info(NonFungibleLocalId::integer(16), env)?;
+    assert!((info.compounded_deposit - expected_deposit).checked_abs().unwrap() < dec!("0.000001"));
+    assert!((info.collateral_gain - expected_gain).checked_abs().unwrap() < dec!("0.000001"));
+
+    // The first deposit was used up many scales ago and is worth nothing
+    let first_info = pool.get_deposit_info(NonFungibleLocalId::integer(1), env)?;
+    assert_eq!(first_info.compounded_deposit, Decimal::ZERO);
+
+    // Withdrawing pays out the compounded deposit and the collateral gain
+    let (collateral, withdrawn_fusd) = pool.withdraw(receipt, env)?;
+    assert!((withdrawn_fusd.amount(env)? - expected_deposit).checked_abs().unwrap() < dec!("0.000001"));
+    assert!((collateral.amount(env)? - expected_gain).checked_abs().unwrap() < dec!("0.000001"));
+
+    Ok(())
+}
+
+#[test]
+fn test_lock_pool_units_boosted_rewards() -> Result<(), RuntimeError> {
+    // Initialize helper
+    let mut helper = Helper::new().unwrap();
+    helper.set_allow_multiple_actions(true)?;
+
+    // Create a CDP to generate interest
+    let bucket = helper.xrd.take(dec!(2000), &mut helper.env)?;
+    let (fusd, _) = helper.proxy_open_cdp(None, bucket, dec!(500), dec!(0.05))?;
+
+    // Contribute to the pool and lock half of the pool units for the maximum duration (2x weight)
+    let deposit_fusd = fusd.take(dec!(200), &mut helper.env)?;
+    let (pool_units, _, _) = helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        deposit_fusd,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    let pool_unit_amount = pool_units.amount(&mut helper.env)?;
+    let units_to_lock = pool_units.take(pool_unit_amount / dec!(2), &mut helper.env)?;
+    let locked_amount = units_to_lock.amount(&mut helper.env)?;
+
+    let lock_receipt = helper.stability_pools.lock_pool_units(
+        helper.xrd_address,
+        units_to_lock,
+        525600,
+        &mut helper.env
+    )?;
+
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0];
+    assert_eq!(pool_info.locked_pool_units, locked_amount);
+    assert_eq!(pool_info.lock_weight, locked_amount * dec!(2));
+
+    // Fast forward time and charge interest
+    let new_time = helper.env.get_current_time().add_days(30).unwrap();
+    helper.env.set_current_time(new_time);
+    helper.stability_pools.charge_interest(
+        helper.xrd_address,
+        None,
+        None,
+        &mut helper.env
+    )?;
+
+    // Lockers hold 2/3 of the weight: they get twice the liquidity rewards left for unlocked units,
+    // plus a boost from the stability pool split
+    let lock_proof = lock_receipt.create_proof_of_all(&mut helper.env)?;
+    let lock_rewards = helper.stability_pools.claim_lock_rewards(lock_proof, &mut helper.env)?;
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0];
+    assert!(pool_info.liquidity_rewards > Decimal::ZERO);
+    assert!(lock_rewards.amount(&mut helper.env)? > pool_info.liquidity_rewards * dec!(2));
+
+    // Unlocking early costs a penalty
+    let (unlocked_units, rewards) = helper.stability_pools.unlock_pool_units(lock_receipt, &mut helper.env)?;
+    let unlocked_amount = unlocked_units.amount(&mut helper.env)?;
+    assert!(unlocked_amount < locked_amount);
+    assert!(unlocked_amount > locked_amount * dec!(0.9));
+    assert_eq!(rewards.amount(&mut helper.env)?, Decimal::ZERO);
+
+    Ok(())
+}
+
+#[test]
+fn test_unlock_pool_units_after_lock_period() -> Result<(), RuntimeError> {
+    // Initialize helper
+    let mut helper = Helper::new().unwrap();
+
+    helper.env.disable_auth_module();
+    let fusd = helper.free_fusd(dec!(100))?;
+    helper.env.enable_auth_module();
+
+    let (pool_units, _, _) = helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        fusd,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    let pool_unit_amount = pool_units.amount(&mut helper.env)?;
+
+    // Lock all pool units for one day
+    let lock_receipt = helper.stability_pools.lock_pool_units(
+        helper.xrd_address,
+        pool_units,
+        1440,
+        &mut helper.env
+    )?;
+
+    // After the lock period the full amount is returned
+    let new_time = helper.env.get_current_time().add_days(2).unwrap();
+    helper.env.set_current_time(new_time);
+    let (unlocked_units, _) = helper.stability_pools.unlock_pool_units(lock_receipt, &mut helper.env)?;
+    assert_eq!(unlocked_units.amount(&mut helper.env)?, pool_unit_amount);
+
+    Ok(())
+}
+
+#[test]
+fn test_withdrawal_delay() -> Result<(), RuntimeError> {
+    // Initialize helper with a withdrawal delay of one day, claimable within one day
+    let mut helper = Helper::new().unwrap();
+    helper.env.disable_auth_module();
+    helper.proxy.set_stability_pool_withdrawal_parameters(1440, 1440, &mut helper.env)?;
+    let fusd = helper.free_fusd(dec!(400))?;
+    helper.env.enable_auth_module();
+
+    let (pool_units, _, _) = helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        fusd,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    let quarter = pool_units.amount(&mut helper.env)? / dec!(4);
+
+    // Direct withdrawals are no longer possible
+    let direct_units = pool_units.take(quarter, &mut helper.env)?;
+    let result = helper.stability_pools.withdraw_from_pool(helper.xrd_address, direct_units, &mut helper.env);
+    assert!(result.is_err());
+
+    // Requested withdrawals stay in the pool
+    let early_units = pool_units.take(quarter, &mut helper.env)?;
+    let early_request = helper.stability_pools.request_withdrawal(helper.xrd_address, early_units, &mut helper.env)?;
+    let units = pool_units.take(quarter, &mut helper.env)?;
+    let request = helper.stability_pools.request_withdrawal(helper.xrd_address, units, &mut helper.env)?;
+    let cancelled_request = helper.stability_pools.request_withdrawal(helper.xrd_address, pool_units, &mut helper.env)?;
+
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0];
+    assert_eq!(pool_info.fusd_amount, dec!(400));
+
+    // Claiming before the delay has passed fails
+    let result = helper.stability_pools.claim_withdrawal(early_request, &mut helper.env);
+    assert!(result.is_err());
+
+    // After the delay the withdrawal can be claimed
+    let new_time = helper.env.get_current_time().add_hours(25).unwrap();
+    helper.env.set_current_time(new_time);
+    let (_collateral, fusd) = helper.stability_pools.claim_withdrawal(request, &mut helper.env)?;
+    assert!((fusd.amount(&mut helper.env)? - dec!(100)).checked_abs().unwrap() < dec!("0.000001"));
+
+    // An expired request can still be cancelled to retrieve the pool units
+    let new_time = helper.env.get_current_time().add_days(2).unwrap();
+    helper.env.set_current_time(new_time);
+    let returned_units = helper.stability_pools.cancel_withdrawal(cancelled_request, &mut helper.env)?;
+    assert!((returned_units.amount(&mut helper.env)? - quarter).checked_abs().unwrap() < dec!("0.000001"));
+
+    Ok(())
+}
+
+#[test]
+fn test_withdrawal_delay_cannot_be_bypassed_through_pool() -> Result<(), RuntimeError> {
+    // Initialize helper with a withdrawal delay of one day
+    let mut helper = Helper::new().unwrap();
+    helper.env.disable_auth_module();
+    helper.proxy.set_stability_pool_withdrawal_parameters(1440, 1440, &mut helper.env)?;
+    let fusd = helper.free_fusd(dec!(400))?;
+    helper.env.enable_auth_module();
+
+    let (pool_units, _, _) = helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        fusd,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    // The contributor receives custody receipts, the pool units stay with the stability pools component
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0];
+    assert_eq!(pool_units.resource_address(&mut helper.env)?, pool_info.custody_receipt.unwrap());
+    let pool_address = match pool_info.pool {
+        StabilityPoolComponent::TwoResource(pool) => pool.address(),
+        StabilityPoolComponent::ProductSum(_) => panic!("Expected a two-resource pool."),
+    };
+
+    // Redeeming directly at the pool fails
+    let result = helper.env.call_method_typed::<_, _, TwoResourcePoolRedeemOutput>(
+        pool_address.as_node_id().clone(),
+        TWO_RESOURCE_POOL_REDEEM_IDENT,
+        &TwoResourcePoolRedeemInput { bucket: pool_units },
+    );
+    assert!(result.is_err());
+
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0];
+    assert_eq!(pool_info.fusd_amount, dec!(400));
+
+    Ok(())
+}
+
+#[test]
+fn test_convert_pool_collateral_through_dex() -> Result<(), RuntimeError> {
+    // Initialize helper and liquidate a CDP into the XRD pool
+    let mut helper = Helper::new().unwrap();
+    let bucket = helper.xrd.take(dec!(1000), &mut helper.env)?;
+    let (_fusd, _cdp_receipt) = helper.proxy_open_cdp(None, bucket, dec!(400), dec!(0.01))?;
+
+    helper.env.disable_auth_module();
+    let free_fusd = helper.free_fusd(dec!(500))?;
+    let dex_fusd = helper.free_fusd(dec!(1000))?;
+    helper.env.enable_auth_module();
+
+    helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        free_fusd,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    helper.env.disable_auth_module();
+    helper.change_collateral_price("XRD".to_string(), dec!(0.5))?;
+    helper.env.enable_auth_module();
+
+    helper.stability_pools.liquidate(
+        NonFungibleLocalId::from(1),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    // Create an XRD/fUSD DEX pool priced at the oracle price
+    let dex_package_address = PackageFactory::compile_and_publish(
+        "./dummy_dex_pool_component",
+        &mut helper.env,
+        CompileProfile::Standard,
+    )?;
+    let (dex_pool, _lp_tokens) = DexPool::instantiate_dex_pool(
+        helper.xrd.take(dec!(2000), &mut helper.env)?,
+        dex_fusd,
+        dex_package_address,
+        &mut helper.env,
+    )?;
+
+    // Selling 100 XRD costs about 5% slippage, which exceeds a 1% limit
+    helper.env.disable_auth_module();
+    helper.proxy.set_stability_pool_conversion(
+        helper.xrd_address,
+        Some(ConversionMethod::DexAdapter {
+            component: ComponentAddress::try_from(dex_pool.0.clone()).unwrap(),
+            method_name: "swap".to_string(),
+            cooldown: 60,
+        }),
+        dec!("0.5"),
+        dec!("0.01"),
+        Some(dec!(100)),
+        &mut helper.env
+    )?;
+    helper.env.enable_auth_module();
+
+    let result = helper.stability_pools.convert_pool_collateral(
+        helper.xrd_address,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // With a 10% limit the sale goes through
+    helper.env.disable_auth_module();
+    helper.proxy.set_stability_pool_conversion(
+        helper.xrd_address,
+        Some(ConversionMethod::DexAdapter {
+            component: ComponentAddress::try_from(dex_pool.0.clone()).unwrap(),
+            method_name: "swap".to_string(),
+            cooldown: 60,
+        }),
+        dec!("0.5"),
+        dec!("0.1"),
+        Some(dec!(100)),
+        &mut helper.env
+    )?;
+    helper.env.enable_auth_module();
+
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0];
+    let (collateral_before, fusd_before) = (pool_info.collateral_amount, pool_info.fusd_amount);
+
+    helper.stability_pools.convert_pool_collateral(
+        helper.xrd_address,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0];
+    assert_eq!(pool_info.collateral_amount, collateral_before - dec!(100));
+    assert!(pool_info.fusd_amount > fusd_before + dec!(45));
+
+    // A new sale has to wait for the cooldown
+    let result = helper.stability_pools.convert_pool_collateral(
+        helper.xrd_address,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // Without a sale cap, no more is sold than keeps XRD at half of the pool's value at the oracle price
+    let new_time = helper.env.get_current_time().add_minutes(60).unwrap();
+    helper.env.set_current_time(new_time);
+    helper.env.disable_auth_module();
+    helper.proxy.set_stability_pool_conversion(
+        helper.xrd_address,
+        Some(ConversionMethod::DexAdapter {
+            component: ComponentAddress::try_from(dex_pool.0.clone()).unwrap(),
+            method_name: "swap".to_string(),
+            cooldown: 60,
+        }),
+        dec!("0.5"),
+        dec!("0.3"),
+        None,
+        &mut helper.env
+    )?;
+    helper.env.enable_auth_module();
+
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0];
+    let (collateral_before, fusd_before) = (pool_info.collateral_amount, pool_info.fusd_amount);
+
+    helper.stability_pools.convert_pool_collateral(
+        helper.xrd_address,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0];
+    let expected_sale = collateral_before / dec!(2) - fusd_before;
+    assert!((collateral_before - pool_info.collateral_amount - expected_sale).checked_abs().unwrap() < dec!("0.000001"));
+    assert!(pool_info.collateral_amount * dec!("0.5") > pool_info.fusd_amount);
+
+    Ok(())
+}
+
+#[test]
+fn test_convert_pool_collateral_through_auction() -> Result<(), RuntimeError> {
+    // Initialize helper and liquidate a CDP into the XRD pool
+    let mut helper = Helper::new().unwrap();
+    let bucket = helper.xrd.take(dec!(1000), &mut helper.env)?;
+    let (_fusd, _cdp_receipt) = helper.proxy_open_cdp(None, bucket, dec!(400), dec!(0.01))?;
+
+    helper.env.disable_auth_module();
+    let free_fusd = helper.free_fusd(dec!(500))?;
+    let payment = helper.free_fusd(dec!(200))?;
+    helper.env.enable_auth_module();
+
+    helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        free_fusd,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    helper.env.disable_auth_module();
+    helper.change_collateral_price("XRD".to_string(), dec!(0.5))?;
+    helper.env.enable_auth_module();
+
+    helper.stability_pools.liquidate(
+        NonFungibleLocalId::from(1),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    // Auction 100 XRD for a day, starting 10% above the oracle price and decaying 0.1% per minute, down to 5% below it
+    helper.env.disable_auth_module();
+    helper.proxy.set_stability_pool_conversion(
+        helper.xrd_address,
+        Some(ConversionMethod::Auction {
+            start_premium: dec!("0.1"),
+            decay_per_minute: dec!("0.001"),
+            duration: 1440,
+        }),
+        dec!("0.5"),
+        dec!("0.05"),
+        Some(dec!(100)),
+        &mut helper.env
+    )?;
+    helper.env.enable_auth_module();
+
+    helper.stability_pools.convert_pool_collateral(
+        helper.xrd_address,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    // An auction can't be started twice
+    let result = helper.stability_pools.convert_pool_collateral(
+        helper.xrd_address,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // At the start, collateral costs 0.55 fUSD
+    let first_payment = payment.take(dec!(11), &mut helper.env)?;
+    let (collateral, leftover) = helper.stability_pools.buy_from_conversion_auction(
+        helper.xrd_address,
+        first_payment,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    assert_eq!(collateral.amount(&mut helper.env)?, dec!(20));
+    assert_eq!(leftover.amount(&mut helper.env)?, Decimal::ZERO);
+
+    // After 200 minutes the price has hit the floor of 0.475 fUSD, the rest of the lot is bought
+    let new_time = helper.env.get_current_time().add_minutes(200).unwrap();
+    helper.env.set_current_time(new_time);
+    let (collateral, leftover) = helper.stability_pools.buy_from_conversion_auction(
+        helper.xrd_address,
+        payment,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    assert_eq!(collateral.amount(&mut helper.env)?, dec!(80));
+    assert_eq!(leftover.amount(&mut helper.env)?, dec!(151));
+
+    // The auction has ended
+    helper.env.disable_auth_module();
+    let late_payment = helper.free_fusd(dec!(10))?;
+    helper.env.enable_auth_module();
+    let result = helper.stability_pools.buy_from_conversion_auction(
+        helper.xrd_address,
+        late_payment,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_conversion_auction_threshold_and_expiry() -> Result<(), RuntimeError> {
+    // Initialize helper and liquidate a CDP into the XRD pool
+    let mut helper = Helper::new().unwrap();
+    let bucket = helper.xrd.take(dec!(1000), &mut helper.env)?;
+    let (_fusd, _cdp_receipt) = helper.proxy_open_cdp(None, bucket, dec!(400), dec!(0.01))?;
+
+    helper.env.disable_auth_module();
+    let free_fusd = helper.free_fusd(dec!(500))?;
+    let payment = helper.free_fusd(dec!(1000))?;
+    helper.env.enable_auth_module();
+
+    helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        free_fusd,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    helper.env.disable_auth_module();
+    helper.change_collateral_price("XRD".to_string(), dec!(0.5))?;
+    helper.env.enable_auth_module();
+
+    helper.stability_pools.liquidate(
+        NonFungibleLocalId::from(1),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    // A maximum slippage of 100% would let the price decay to zero
+    let auction = ConversionMethod::Auction {
+        start_premium: dec!("0.1"),
+        decay_per_minute: dec!("0.001"),
+        duration: 60,
+    };
+    helper.env.disable_auth_module();
+    let result = helper.proxy.set_stability_pool_conversion(
+        helper.xrd_address,
+        Some(auction.clone()),
+        dec!("0.5"),
+        dec!(1),
+        None,
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // Auction all XRD for an hour, as long as XRD makes up more than half of the pool's value
+    helper.proxy.set_stability_pool_conversion(
+        helper.xrd_address,
+        Some(auction),
+        dec!("0.5"),
+        dec!("0.05"),
+        None,
+        &mut helper.env
+    )?;
+    helper.env.enable_auth_module();
+
+    helper.stability_pools.convert_pool_collateral(
+        helper.xrd_address,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    // Only the collateral above the threshold is sold, and paid for at the auction price
+    let first_payment = payment.take(dec!(500), &mut helper.env)?;
+    let (collateral, leftover) = helper.stability_pools.buy_from_conversion_auction(
+        helper.xrd_address,
+        first_payment,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    let bought_amount = collateral.amount(&mut helper.env)?;
+    assert!(bought_amount > Decimal::ZERO);
+    assert_eq!(leftover.amount(&mut helper.env)?, dec!(500) - bought_amount * dec!("0.55"));
+
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0];
+    let collateral_value = pool_info.collateral_amount * dec!("0.5");
+    let share = collateral_value / (collateral_value + pool_info.fusd_amount);
+    assert!((share - dec!("0.5")).checked_abs().unwrap() < dec!("0.000001"));
+
+    // Once the threshold is reached, nothing more is sold
+    let second_payment = payment.take(dec!(10), &mut helper.env)?;
+    let result = helper.stability_pools.buy_from_conversion_auction(
+        helper.xrd_address,
+        second_payment,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // Raise the collateral's share above the threshold again, the auction expires after an hour
+    helper.env.disable_auth_module();
+    helper.change_collateral_price("XRD".to_string(), dec!(1))?;
+    helper.env.enable_auth_module();
+
+    let new_time = helper.env.get_current_time().add_minutes(61).unwrap();
+    helper.env.set_current_time(new_time);
+    let result = helper.stability_pools.buy_from_conversion_auction(
+        helper.xrd_address,
+        payment,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // An expired auction can be replaced by a new one
+    helper.stability_pools.convert_pool_collateral(
+        helper.xrd_address,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0];
+    assert_eq!(pool_info.conversion.as_ref().unwrap().auction_start, Some(new_time));
+
+    Ok(())
+}
+
+#[test]
+fn test_liquidate_from_shared_pool() -> Result<(), RuntimeError> {
+    // Initialize helper and open a CDP, leaving the XRD pool empty
+    let mut helper = Helper::new().unwrap();
+    let bucket = helper.xrd.take(dec!(1000), &mut helper.env)?;
+    let (fusd, _cdp_receipt) = helper.proxy_open_cdp(None, bucket, dec!(400), dec!(0.01))?;
+    let fusd_address = fusd.resource_address(&mut helper.env)?;
+
+    // Create a shared pool for XRD, seeded with a small amount of fUSD and XRD
+    helper.env.disable_auth_module();
+    let seed_fusd = helper.free_fusd(dec!(1))?;
+    let seed_xrd = helper.xrd.take(dec!(1), &mut helper.env)?;
+    let free_fusd = helper.free_fusd(dec!(500))?;
+    helper.proxy.new_shared_stability_pool(
+        vec![helper.xrd_address],
+        vec![seed_fusd, seed_xrd],
+        "Shared".to_string(),
+        "Shared".to_string(),
+        Url::of("https://ilikeitstable.com"),
+        "SHAREDfUSD".to_string(),
+        &mut helper.env
+    )?;
+    helper.env.enable_auth_module();
+
+    let (shared_units, _change) = helper.stability_pools.contribute_to_shared_pool(
+        free_fusd,
+        vec![(helper.xrd_address, "".to_string(), "".to_string())],
+        &mut helper.env
+    )?;
+    assert!(shared_units.amount(&mut helper.env)? > Decimal::ZERO);
+
+    helper.env.disable_auth_module();
+    helper.change_collateral_price("XRD".to_string(), dec!(0.5))?;
+    helper.env.enable_auth_module();
+
+    // Without opting in, the empty XRD pool can't cover the liquidation
+    let result = helper.stability_pools.liquidate(
+        NonFungibleLocalId::from(1),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // After opting in, the liquidation falls back to the shared pool
+    helper.env.disable_auth_module();
+    helper.proxy.set_shared_stability_pool_opt_in(helper.xrd_address, true, &mut helper.env)?;
+    helper.env.enable_auth_module();
+
+    let liquidator_fee = helper.stability_pools.liquidate(
+        NonFungibleLocalId::from(1),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    assert!(liquidator_fee.amount(&mut helper.env)? > Decimal::ZERO);
+
+    let (_, cdp_info, _) = helper.get_cdp_info(NonFungibleLocalId::from(1))?;
+    assert_eq!(cdp_info.status, CdpStatus::Liquidated);
+
+    // The seized collateral went to the shared pool, the XRD pool is untouched
+    let shared_info = helper.stability_pools.get_shared_pool_info(&mut helper.env)?.unwrap();
+    assert!(*shared_info.vault_amounts.get(&helper.xrd_address).unwrap() > dec!(500));
+    assert!(*shared_info.vault_amounts.get(&fusd_address).unwrap() < dec!(150));
+
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0];
+    assert_eq!(pool_info.collateral_amount, Decimal::ZERO);
+
+    Ok(())
+}
+
+#[test]
+fn test_shared_pool_withdrawal_delay() -> Result<(), RuntimeError> {
+    // Initialize helper with a shared pool for XRD and a withdrawal delay of one day, claimable within one day
+    let mut helper = Helper::new().unwrap();
+
+    helper.env.disable_auth_module();
+    let seed_fusd = helper.free_fusd(dec!(1))?;
+    let seed_xrd = helper.xrd.take(dec!(1), &mut helper.env)?;
+    let free_fusd = helper.free_fusd(dec!(400))?;
+    let fusd_address = free_fusd.resource_address(&mut helper.env)?;
+    helper.proxy.new_shared_stability_pool(
+        vec![helper.xrd_address],
+        vec![seed_fusd, seed_xrd],
+        "Shared".to_string(),
+        "Shared".to_string(),
+        Url::of("https://ilikeitstable.com"),
+        "SHAREDfUSD".to_string(),
+        &mut helper.env
+    )?;
+    helper.proxy.set_stability_pool_withdrawal_parameters(1440, 1440, &mut helper.env)?;
+    helper.env.enable_auth_module();
+
+    let (shared_units, _change) = helper.stability_pools.contribute_to_shared_pool(
+        free_fusd,
+        vec![(helper.xrd_address, "".to_string(), "".to_string())],
+        &mut helper.env
+    )?;
+    let quarter = shared_units.amount(&mut helper.env)? / dec!(4);
+
+    // Direct withdrawals are no longer possible
+    let direct_units = shared_units.take(quarter, &mut helper.env)?;
+    let result = helper.stability_pools.withdraw_from_shared_pool(direct_units, &mut helper.env);
+    assert!(result.is_err());
+
+    // Requested withdrawals stay in the shared pool
+    let fusd_before = *helper.stability_pools.get_shared_pool_info(&mut helper.env)?.unwrap().vault_amounts.get(&fusd_address).unwrap();
+    let units = shared_units.take(quarter, &mut helper.env)?;
+    let request = helper.stability_pools.request_shared_pool_withdrawal(units, &mut helper.env)?;
+    let units = shared_units.take(quarter, &mut helper.env)?;
+    let cancelled_request = helper.stability_pools.request_shared_pool_withdrawal(units, &mut helper.env)?;
+
+    let shared_info = helper.stability_pools.get_shared_pool_info(&mut helper.env)?.unwrap();
+    assert_eq!(*shared_info.vault_amounts.get(&fusd_address).unwrap(), fusd_before);
+
+    let new_time = helper.env.get_current_time().add_hours(25).unwrap();
+    helper.env.set_current_time(new_time);
+
+    // After the delay the withdrawal is redeemed at the shared pool
+    let withdrawn = helper.stability_pools.claim_shared_pool_withdrawal(request, &mut helper.env)?;
+    let mut fusd_withdrawn = Decimal::ZERO;
+    for bucket in withdrawn {
+        if bucket.resource_address(&mut helper.env)? == fusd_address {
+            fusd_withdrawn += bucket.amount(&mut helper.env)?;
+        }
+    }
+    assert!(fusd_withdrawn > dec!(99) && fusd_withdrawn <= dec!(100));
+
+    // Cancelling returns the shared pool unit custody receipts
+    let returned_units = helper.stability_pools.cancel_withdrawal(cancelled_request, &mut helper.env)?;
+    assert_eq!(returned_units.resource_address(&mut helper.env)?, shared_info.custody_receipt);
+    assert!((returned_units.amount(&mut helper.env)? - quarter).checked_abs().unwrap() < dec!("0.000001"));
+
+    // Claiming before the delay has passed fails, as does claiming a shared pool request as a regular one
+    let early_units = returned_units.take(quarter / dec!(2), &mut helper.env)?;
+    let early_request = helper.stability_pools.request_shared_pool_withdrawal(early_units, &mut helper.env)?;
+    let result = helper.stability_pools.claim_shared_pool_withdrawal(early_request, &mut helper.env);
+    assert!(result.is_err());
+
+    let request = helper.stability_pools.request_shared_pool_withdrawal(returned_units, &mut helper.env)?;
+    let new_time = helper.env.get_current_time().add_hours(25).unwrap();
+    helper.env.set_current_time(new_time);
+    let result = helper.stability_pools.claim_withdrawal(request, &mut helper.env);
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_liquidate_split_with_shared_pool() -> Result<(), RuntimeError> {
+    // Initialize helper and open a CDP
+    let mut helper = Helper::new().unwrap();
+    let bucket = helper.xrd.take(dec!(1000), &mut helper.env)?;
+    let (fusd, _cdp_receipt) = helper.proxy_open_cdp(None, bucket, dec!(400), dec!(0.01))?;
+    let fusd_address = fusd.resource_address(&mut helper.env)?;
+
+    // Create a shared pool for XRD and opt in, with a delay on withdrawals
+    helper.env.disable_auth_module();
+    let seed_fusd = helper.free_fusd(dec!(1))?;
+    let seed_xrd = helper.xrd.take(dec!(1), &mut helper.env)?;
+    let shared_fusd = helper.free_fusd(dec!(500))?;
+    let own_fusd = helper.free_fusd(dec!(100))?;
+    helper.proxy.new_shared_stability_pool(
+        vec![helper.xrd_address],
+        vec![seed_fusd, seed_xrd],
+        "Shared".to_string(),
+        "Shared".to_string(),
+        Url::of("https://ilikeitstable.com"),
+        "SHAREDfUSD".to_string(),
+        &mut helper.env
+    )?;
+    helper.proxy.set_shared_stability_pool_opt_in(helper.xrd_address, true, &mut helper.env)?;
+    helper.proxy.set_stability_pool_withdrawal_parameters(1440, 1440, &mut helper.env)?;
+    helper.env.enable_auth_module();
+
+    let (shared_units, _change) = helper.stability_pools.contribute_to_shared_pool(
+        shared_fusd,
+        vec![(helper.xrd_address, "".to_string(), "".to_string())],
+        &mut helper.env
+    )?;
+    helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        own_fusd,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    // Contributors hold custody receipts, which can't be redeemed at the shared pool directly
+    let shared_info = helper.stability_pools.get_shared_pool_info(&mut helper.env)?.unwrap();
+    assert_eq!(shared_units.resource_address(&mut helper.env)?, shared_info.custody_receipt);
+    let result = helper.env.call_method_typed::<_, _, MultiResourcePoolRedeemOutput>(
+        shared_info.pool.address().as_node_id().clone(),
+        MULTI_RESOURCE_POOL_REDEEM_IDENT,
+        &MultiResourcePoolRedeemInput { bucket: shared_units },
+    );
+    assert!(result.is_err());
+
+    helper.env.disable_auth_module();
+    helper.change_collateral_price("XRD".to_string(), dec!(0.5))?;
+    helper.env.enable_auth_module();
+
+    let shared_info = helper.stability_pools.get_shared_pool_info(&mut helper.env)?.unwrap();
+    let shared_fusd_before = *shared_info.vault_amounts.get(&fusd_address).unwrap();
+    let shared_xrd_before = *shared_info.vault_amounts.get(&helper.xrd_address).unwrap();
+
+    // The XRD pool's 100 fUSD are used first, the shared pool pays the rest
+    helper.stability_pools.liquidate(
+        NonFungibleLocalId::from(1),
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    let (_, cdp_info, _) = helper.get_cdp_info(NonFungibleLocalId::from(1))?;
+    assert_eq!(cdp_info.status, CdpStatus::Liquidated);
+
+    let pool_info = &helper.stability_pools.get_stability_pool_infos(Some(vec![helper.xrd_address]), &mut helper.env)?[0];
+    let shared_info = helper.stability_pools.get_shared_pool_info(&mut helper.env)?.unwrap();
+    let shared_fusd_paid = shared_fusd_before - *shared_info.vault_amounts.get(&fusd_address).unwrap();
+    let shared_xrd_received = *shared_info.vault_amounts.get(&helper.xrd_address).unwrap() - shared_xrd_before;
+    assert!(pool_info.fusd_amount < dec!("0.000001"));
+    assert!(shared_fusd_paid > Decimal::ZERO);
+
+    // The seized XRD is split pro rata to the fUSD each pool paid
+    let own_share = pool_info.collateral_amount / (pool_info.collateral_amount + shared_xrd_received);
+    let expected_share = (dec!(100) - pool_info.fusd_amount) / (dec!(100) - pool_info.fusd_amount + shared_fusd_paid);
+    assert!((own_share - expected_share).checked_abs().unwrap() < dec!("0.000001"));
+
+    Ok(())
+}
+
+#[test]
+fn test_batch_liquidate() -> Result<(), RuntimeError> {
+    // Initialize helper and open three CDPs close to the liquidation threshold, and one safe CDP
+    let mut helper = Helper::new().unwrap();
+    for _ in 0..3 {
+        let bucket = helper.xrd.take(dec!(1000), &mut helper.env)?;
+        let _ = helper.proxy_open_cdp(None, bucket, dec!(400), dec!(0.01))?;
+    }
+    let bucket = helper.xrd.take(dec!(1000), &mut helper.env)?;
+    let _ = helper.proxy_open_cdp(None, bucket, dec!(100), dec!(0.01))?;
+
+    helper.env.disable_auth_module();
+    let free_fusd = helper.free_fusd(dec!(2000))?;
+    helper.env.enable_auth_module();
+
+    helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        free_fusd,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    helper.env.disable_auth_module();
+    helper.change_collateral_price("XRD".to_string(), dec!(0.5))?;
+    helper.env.enable_auth_module();
+
+    // Inspecting a single CDP liquidates at most one, even if more are requested
+    helper.stability_pools.batch_liquidate(
+        helper.xrd_address,
+        10,
+        1,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    let ids: Vec<NonFungibleLocalId> = (1..=4u64).map(NonFungibleLocalId::from).collect();
+    let liquidated = helper
+        .get_cdps_info(ids.clone())?
+        .into_iter()
+        .filter(|(_, cdp, _)| cdp.status == CdpStatus::Liquidated)
+        .count();
+    assert_eq!(liquidated, 1);
+
+    // Liquidate at most one more CDP
+    let liquidator_fee = helper.stability_pools.batch_liquidate(
+        helper.xrd_address,
+        1,
+        10,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    assert!(liquidator_fee.amount(&mut helper.env)? > Decimal::ZERO);
+
+    let liquidated = helper
+        .get_cdps_info(ids.clone())?
+        .into_iter()
+        .filter(|(_, cdp, _)| cdp.status == CdpStatus::Liquidated)
+        .count();
+    assert_eq!(liquidated, 2);
+
+    // The next batch liquidates the remaining unhealthy CDP, the safe CDP stays open
+    helper.stability_pools.batch_liquidate(
+        helper.xrd_address,
+        10,
+        10,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    let cdps = helper.get_cdps_info(ids)?;
+    assert!(cdps[..3].iter().all(|(_, cdp, _)| cdp.status == CdpStatus::Liquidated));
+    assert_eq!(cdps[3].1.status, CdpStatus::Healthy);
+
+    Ok(())
+}
+
+#[test]
+fn test_charge_all_interest_resumes_from_cursor() -> Result<(), RuntimeError> {
+    // Initialize helper with XRD CDPs in three interest tiers
+    let mut helper = Helper::new().unwrap();
+    helper.set_allow_multiple_actions(true)?;
+
+    for interest in [dec!(0.01), dec!(0.02), dec!(0.03)] {
+        let bucket = helper.xrd.take(dec!(2000), &mut helper.env)?;
+        let _ = helper.proxy_open_cdp(None, bucket, dec!(500), interest)?;
+    }
+
+    let new_time = helper.env.get_current_time().add_days(30).unwrap();
+    helper.env.set_current_time(new_time);
+
+    // Interest tiers can be loaded in bounded batches
+    let tiers = helper.flux.get_interest_infos_limited(helper.xrd_address, None, None, 2, &mut helper.env)?;
+    assert_eq!(tiers.iter().map(|tier| tier.interest).collect::<Vec<Decimal>>(), vec![dec!(0.01), dec!(0.02)]);
+
+    // With a cap of two tiers, charging stops in the middle of XRD
+    let (summaries, _) = helper.stability_pools.charge_all_interest(2, &mut helper.env)?;
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].collateral, helper.xrd_address);
+    assert_eq!(summaries[0].tiers_charged, 2);
+    assert_eq!(summaries[0].end_interest, Some(dec!(0.03)));
+    assert!(summaries[0].fusd_charged > Decimal::ZERO);
+
+    // The next call resumes at the last XRD tier and continues with LSULP
+    let (summaries, _) = helper.stability_pools.charge_all_interest(10, &mut helper.env)?;
+    assert_eq!(summaries.len(), 2);
+    assert_eq!(summaries[0].collateral, helper.xrd_address);
+    assert_eq!(summaries[0].start_interest, Some(dec!(0.03)));
+    assert_eq!(summaries[0].tiers_charged, 1);
+    assert_eq!(summaries[1].collateral, helper.lsulp_address);
+
+    // After a full round, charging starts at the first XRD tier again
+    let (summaries, _) = helper.stability_pools.charge_all_interest(10, &mut helper.env)?;
+    assert_eq!(summaries[0].collateral, helper.xrd_address);
+    assert_eq!(summaries[0].start_interest, None);
+    assert_eq!(summaries[0].tiers_charged, 3);
+
+    Ok(())
+}
+
+#[test]
+fn test_keeper_bounty_for_charge_interest() -> Result<(), RuntimeError> {
+    // Initialize helper with an XRD CDP
+    let mut helper = Helper::new().unwrap();
+    helper.set_allow_multiple_actions(true)?;
+
+    let bucket = helper.xrd.take(dec!(2000), &mut helper.env)?;
+    let _ = helper.proxy_open_cdp(None, bucket, dec!(500), dec!(0.05))?;
+
+    // Set aside half of the payout split for keepers, paying 0.1 fUSD per interest charge with a 1 hour cooldown
+    helper.env.disable_auth_module();
+    helper.proxy.set_keeper_funding_share(dec!("0.5"), &mut helper.env)?;
+    helper.proxy.set_keeper_bounty(KeeperAction::ChargeInterest, dec!("0.1"), 60, &mut helper.env)?;
+    helper.env.enable_auth_module();
+
+    let new_time = helper.env.get_current_time().add_days(30).unwrap();
+    helper.env.set_current_time(new_time);
+
+    // Charging interest funds the keeper vault and pays the bounty
+    let bounty = helper.stability_pools.charge_interest(helper.xrd_address, None, None, &mut helper.env)?;
+    assert_eq!(bounty.amount(&mut helper.env)?, dec!("0.1"));
+    let info = helper.stability_pools.get_keeper_rewards_info(&mut helper.env)?;
+    assert!(info.funds > Decimal::ZERO);
+
+    // A second call within the cooldown pays nothing
+    let bounty = helper.stability_pools.charge_interest(helper.xrd_address, None, None, &mut helper.env)?;
+    assert_eq!(bounty.amount(&mut helper.env)?, Decimal::ZERO);
+
+    // After the cooldown, a call charging an empty range of tiers pays nothing
+    let new_time = helper.env.get_current_time().add_minutes(61).unwrap();
+    helper.env.set_current_time(new_time);
+    let bounty = helper.stability_pools.charge_interest(
+        helper.xrd_address,
+        Some(dec!(0.05)),
+        Some(dec!(0.05)),
+        &mut helper.env
+    )?;
+    assert_eq!(bounty.amount(&mut helper.env)?, Decimal::ZERO);
+
+    // Neither does fetching rewards for the payout component once they have been fetched
+    helper.env.disable_auth_module();
+    helper.proxy.set_keeper_bounty(KeeperAction::PayoutFetch, dec!("0.0001"), 0, &mut helper.env)?;
+    helper.env.enable_auth_module();
+    let _ = helper.payout_component.fetch_rewards_from_stability_pools(&mut helper.env)?;
+    let info = helper.stability_pools.get_keeper_rewards_info(&mut helper.env)?;
+    assert!(info.funds > dec!("0.0001"));
+    let bounty = helper.payout_component.fetch_rewards_from_stability_pools(&mut helper.env)?;
+    assert_eq!(bounty.amount(&mut helper.env)?, Decimal::ZERO);
+
+    // Charging all tiers after the cooldown pays the bounty again
+    let bounty = helper.stability_pools.charge_interest(helper.xrd_address, None, None, &mut helper.env)?;
+    assert_eq!(bounty.amount(&mut helper.env)?, dec!("0.1"));
+
+    Ok(())
+}
+
+#[test]
+fn test_guarded_launch_claimed_withdrawal_lowers_debt_ceiling() -> Result<(), RuntimeError> {
+    // Initialize helper with a withdrawal delay of one day, allowing 200 + 2 fUSD of debt per pool fUSD
+    let mut helper = Helper::new().unwrap();
+    helper.env.disable_auth_module();
+    helper.proxy.set_stability_pool_withdrawal_parameters(1440, 1440, &mut helper.env)?;
+    helper.proxy.edit_stability_pool(
+        helper.xrd_address,
+        None,
+        None,
+        None,
+        false,
+        None,
+        None,
+        None,
+        Some(GuardedLaunch {
+            base_debt_ceiling: dec!(200),
+            debt_per_pool_fusd: dec!(2),
+        }),
+        &mut helper.env
+    )?;
+    let fusd = helper.free_fusd(dec!(400))?;
+    helper.env.enable_auth_module();
+
+    let (pool_units, _, _) = helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        fusd,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    let collateral_info = &helper.flux.get_collateral_infos(vec![helper.xrd_address], &mut helper.env)?[0];
+    assert_eq!(collateral_info.debt_ceiling, Some(dec!(1000)));
+
+    // Requested withdrawals stay in the pool, so the debt ceiling is unchanged
+    let half = pool_units.amount(&mut helper.env)? / dec!(2);
+    let request = helper.stability_pools.request_withdrawal(
+        helper.xrd_address,
+        pool_units.take(half, &mut helper.env)?,
+        &mut helper.env
+    )?;
+    let collateral_info = &helper.flux.get_collateral_infos(vec![helper.xrd_address], &mut helper.env)?[0];
+    assert_eq!(collateral_info.debt_ceiling, Some(dec!(1000)));
+
+    // Claiming the withdrawal takes the fUSD out of the pool and lowers the debt ceiling
+    let new_time = helper.env.get_current_time().add_hours(25).unwrap();
+    helper.env.set_current_time(new_time);
+    let _ = helper.stability_pools.claim_withdrawal(request, &mut helper.env)?;
+
+    let collateral_info = &helper.flux.get_collateral_infos(vec![helper.xrd_address], &mut helper.env)?[0];
+    let debt_ceiling = collateral_info.debt_ceiling.unwrap();
+    assert!((debt_ceiling - dec!(600)).checked_abs().unwrap() < dec!("0.000001"));
+
+    Ok(())
+}
+
+#[test]
+fn test_guarded_launch_pool() -> Result<(), RuntimeError> {
+    // Initialize helper, leaving the XRD pool empty
+    let mut helper = Helper::new().unwrap();
+    let contributor_badge = ResourceBuilder::new_fungible(OwnerRole::None)
+        .mint_initial_supply(1, &mut helper.env)?;
+    let contributor_badge_address = contributor_badge.resource_address(&mut helper.env)?;
+
+    // Cap the XRD pool at 1000 fUSD, require a badge to contribute and allow 200 + 2 fUSD of debt per pool fUSD
+    helper.env.disable_auth_module();
+    helper.proxy.edit_stability_pool(
+        helper.xrd_address,
+        None,
+        None,
+        None,
+        false,
+        None,
+        Some(dec!(1000)),
+        Some(contributor_badge_address),
+        Some(GuardedLaunch {
+            base_debt_ceiling: dec!(200),
+            debt_per_pool_fusd: dec!(2),
+        }),
+        &mut helper.env
+    )?;
+    helper.env.enable_auth_module();
+
+    let collateral_info = &helper.flux.get_collateral_infos(vec![helper.xrd_address], &mut helper.env)?[0];
+    assert_eq!(collateral_info.debt_ceiling, Some(dec!(200)));
+
+    // Borrowing above the debt ceiling fails
+    let bucket = helper.xrd.take(dec!(2000), &mut helper.env)?;
+    let result = helper.proxy_open_cdp(None, bucket, dec!(300), dec!(0.01));
+    assert!(result.is_err());
+
+    // Contributing without the badge fails
+    helper.env.disable_auth_module();
+    let fusd = helper.free_fusd(dec!(2000))?;
+    helper.env.enable_auth_module();
+    let result = helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        fusd.take(dec!(100), &mut helper.env)?,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // With the badge, the contribution raises the debt ceiling
+    let proof = contributor_badge.create_proof_of_all(&mut helper.env)?;
+    LocalAuthZone::push(proof, &mut helper.env)?;
+    let _ = helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        fusd.take(dec!(100), &mut helper.env)?,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+
+    let collateral_info = &helper.flux.get_collateral_infos(vec![helper.xrd_address], &mut helper.env)?[0];
+    assert_eq!(collateral_info.debt_ceiling, Some(dec!(400)));
+
+    let bucket = helper.xrd.take(dec!(2000), &mut helper.env)?;
+    let _ = helper.proxy_open_cdp(None, bucket, dec!(300), dec!(0.01))?;
+
+    // Contributions above the deposit cap fail
+    let result = helper.stability_pools.contribute_to_pool(
+        helper.xrd_address,
+        fusd.take(dec!(950), &mut helper.env)?,
+        false,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_payout_rewards_descending_price_auction() -> Result<(), RuntimeError> {
+    // Initialize helper
+    let mut helper = Helper::new().unwrap();
+
+    // Start 10% above a reference price of 10 XRD per fUSD, decaying 0.1% per minute to at most 20% below it,
+    // and never lower the reference price below 9 XRD per fUSD
+    helper.env.disable_auth_module();
+    helper.proxy.payout_set_parameters(dec!("0.1"), dec!("0.001"), dec!("0.2"), Some(dec!(10)), Some(dec!(9)), false, &mut helper.env)?;
+    let fusd = helper.free_fusd(dec!(15))?;
+    helper.env.enable_auth_module();
+
+    // The first epoch auctions the 10 fUSD available when it starts
+    helper.payout_component.receive_rewards(fusd.take(dec!(10), &mut helper.env)?, &mut helper.env)?;
+    let _ = helper.payout_component.fetch_rewards_from_stability_pools(&mut helper.env)?;
+    helper.payout_component.receive_rewards(fusd, &mut helper.env)?;
+
+    let info = helper.payout_component.get_auction_info(&mut helper.env)?;
+    let epoch = info.epoch.unwrap();
+    assert_eq!(epoch.number, 1);
+    assert_eq!(epoch.lot, dec!(10));
+    assert_eq!(epoch.start_price, dec!(11));
+    assert_eq!(info.required_payment_amount, Some(dec!(110)));
+    assert_eq!(info.pending_rewards, dec!(5));
+
+    // After 50 minutes the price has decayed by 5% of the reference price
+    let new_time = helper.env.get_current_time().add_minutes(50).unwrap();
+    helper.env.set_current_time(new_time);
+    let info = helper.payout_component.get_auction_info(&mut helper.env)?;
+    assert_eq!(info.current_price, Some(dec!("10.5")));
+    assert_eq!(info.required_payment_amount, Some(dec!(105)));
+
+    // Paying less than the required amount fails
+    let payment = helper.xrd.take(dec!(100), &mut helper.env)?;
+    let result = helper.payout_component.claim_rewards(payment, &mut helper.env);
+    assert!(result.is_err());
+
+    // Claiming takes the lot only, and starts the next epoch from the price paid
+    let payment = helper.xrd.take(dec!(200), &mut helper.env)?;
+    let (rewards, change) = helper.payout_component.claim_rewards(payment, &mut helper.env)?;
+    assert_eq!(rewards.amount(&mut helper.env)?, dec!(10));
+    assert_eq!(change.amount(&mut helper.env)?, dec!(95));
+
+    let info = helper.payout_component.get_auction_info(&mut helper.env)?;
+    let epoch = info.epoch.unwrap();
+    assert_eq!(epoch.number, 2);
+    assert_eq!(epoch.lot, dec!(5));
+    assert_eq!(epoch.start_price, dec!("11.55"));
+    assert_eq!(info.reference_price, dec!("10.5"));
+    assert_eq!(info.pending_rewards, Decimal::ZERO);
+
+    // The price doesn't decay below the maximum discount
+    let new_time = helper.env.get_current_time().add_days(1).unwrap();
+    helper.env.set_current_time(new_time);
+    let info = helper.payout_component.get_auction_info(&mut helper.env)?;
+    assert_eq!(info.current_price, Some(dec!("8.4")));
+
+    // Claiming at the maximum discount doesn't lower the reference price below its minimum
+    let payment = helper.xrd.take(dec!(42), &mut helper.env)?;
+    let (rewards, _change) = helper.payout_component.claim_rewards(payment, &mut helper.env)?;
+    assert_eq!(rewards.amount(&mut helper.env)?, dec!(5));
+
+    let info = helper.payout_component.get_auction_info(&mut helper.env)?;
+    assert_eq!(info.reference_price, dec!(9));
+    assert_eq!(info.min_reference_price, dec!(9));
+
+    Ok(())
+}
+
+#[test]
+fn test_payout_revenue_split() -> Result<(), RuntimeError> {
+    // Initialize helper with an XRD CDP
+    let mut helper = Helper::new().unwrap();
+    helper.set_allow_multiple_actions(true)?;
+
+    let bucket = helper.xrd.take(dec!(2000), &mut helper.env)?;
+    let _ = helper.proxy_open_cdp(None, bucket, dec!(500), dec!(0.05))?;
+
+    // Shares must add up to 1
+    helper.env.disable_auth_module();
+    let result = helper.proxy.payout_set_revenue_split(
+        vec![
+            (RevenueDestination::BuyBackAuction, dec!("0.5")),
+            (RevenueDestination::StabilityPoolDeposit { collateral: helper.xrd_address }, dec!("0.4")),
+        ],
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // Auction half of the revenue and deposit the other half into the XRD stability pool
+    helper.proxy.payout_set_revenue_split(
+        vec![
+            (RevenueDestination::BuyBackAuction, dec!("0.5")),
+            (RevenueDestination::StabilityPoolDeposit { collateral: helper.xrd_address }, dec!("0.5")),
+        ],
+        &mut helper.env
+    )?;
+    helper.env.enable_auth_module();
+
+    // Charge interest to accumulate revenue for the payout component
+    let new_time = helper.env.get_current_time().add_days(30).unwrap();
+    helper.env.set_current_time(new_time);
+    let _ = helper.stability_pools.charge_interest(helper.xrd_address, None, None, &mut helper.env)?;
+
+    // Fetching splits the revenue between the auction lot and the stability pool deposit
+    let _ = helper.payout_component.fetch_rewards_from_stability_pools(&mut helper.env)?;
+    let lot = helper.payout_component.get_auction_info(&mut helper.env)?.epoch.unwrap().lot;
+    let (pending_fusd, pool_units) = helper.payout_component.get_protocol_owned_liquidity(helper.xrd_address, &mut helper.env)?;
+    assert!(pending_fusd > Decimal::ZERO);
+    assert_eq!(pool_units, Decimal::ZERO);
+    assert!(lot >= pending_fusd && lot - pending_fusd <= dec!("0.000000000000000001"));
+
+    // Only the owner can deposit, as the caller chooses the oracle data
+    let result = helper.payout_component.deposit_protocol_owned_liquidity(
+        helper.xrd_address,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    );
+    assert!(result.is_err());
+
+    // The deposit share is contributed to the stability pool as protocol-owned liquidity
+    helper.env.disable_auth_module();
+    helper.proxy.payout_deposit_protocol_owned_liquidity(
+        helper.xrd_address,
+        "".to_string(),
+        "".to_string(),
+        &mut helper.env
+    )?;
+    helper.env.enable_auth_module();
+    let (pending_fusd, pool_units) = helper.payout_component.get_protocol_owned_liquidity(helper.xrd_address, &mut helper.env)?;
+    assert_eq!(pending_fusd, Decimal::ZERO);
+    assert!(pool_units > Decimal::ZERO);
+
+    Ok(())
+}
+
+#[test]
+fn test_payout_failing_revenue_destination() -> Result<(), RuntimeError> {
+    // Initialize helper with an XRD CDP
+    let mut helper = Helper::new().unwrap();
+    helper.set_allow_multiple_actions(true)?;
+
+    let bucket = helper.xrd.take(dec!(2000), &mut helper.env)?;
+    let _ = helper.proxy_open_cdp(None, bucket, dec!(500), dec!(0.05))?;
+
+    // Send half of the revenue to a savings rate method that doesn't exist
+    let failing_destination = RevenueDestination::SavingsRate {
+        component: ComponentAddress::try_from(helper.payout_component.0.clone()).unwrap(),
+        method_name: "missing_method".to_string(),
+    };
+    helper.env.disable_auth_module();
+    helper.proxy.payout_set_revenue_split(
+        vec![
+            (RevenueDestination::BuyBackAuction, dec!("0.5")),
+            (failing_destination.clone(), dec!("0.5")),
+        ],
+        &mut helper.env
+    )?;
+    helper.env.enable_auth_module();
+
+    // Fetching and claiming don't call the destinations
+    let new_time = helper.env.get_current_time().add_days(30).unwrap();
+    helper.env.set_current_time(new_time);
+    let _ = helper.stability_pools.charge_interest(helper.xrd_address, None, None, &mut helper.env)?;
+    let _ = helper.payout_component.fetch_rewards_from_stability_pools(&mut helper.env)?;
+    let payment = helper.xrd.take(dec!(10000), &mut helper.env)?;
+    let (rewards, _) = helper.payout_component.claim_rewards(payment, &mut helper.env)?;
+    assert!(rewards.amount(&mut helper.env)? > Decimal::ZERO);
+
+    let failing_pending = helper.payout_component.get_pending_revenue(failing_destination.clone(), &mut helper.env)?;
+    assert!(failing_pending > Decimal::ZERO);
+
+    // Fetching again still works, adding to the pending revenue
+    let new_time = helper.env.get_current_time().add_days(30).unwrap();
+    helper.env.set_current_time(new_time);
+    let _ = helper.stability_pools.charge_interest(helper.xrd_address, None, None, &mut helper.env)?;
+    let _ = helper.payout_component.fetch_rewards_from_stability_pools(&mut helper.env)?;
+    assert!(helper.payout_component.get_pending_revenue(failing_destination.clone(), &mut helper.env)? > failing_pending);
+
+    // Only pushing to the failing destination fails
+    let result = helper.payout_component.push_revenue(failing_destination, &mut helper.env);
+    assert!(result.is_err());
+
+    Ok(())
+}